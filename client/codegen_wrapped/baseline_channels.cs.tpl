@@ -0,0 +1,40 @@
+using ffi = tp_client.generated.__Internal;
+using Channels = Teleportal.Client.Contract.Properties.Channels;
+using ObjectHandle = Teleportal.Client.Object.ObjectHandle;
+using RSharp;
+
+namespace Teleportal.Client
+{
+    partial class Baseline
+    {
+{{#each types}}
+        public Channels.ChannelHandle_{{this}} BindChannel{{this}}(Channels.ChannelId_{{this}} id, ObjectHandle obj)
+        {
+            var p = new Ptr<Channels.ChannelHandle_{{this}}>(ffi.TpClientBaselineBaselineBindChannel{{this}}(this.Inner.Value.p, id.Inner.Value.p, obj.Inner.Value.p));
+            return new Channels.ChannelHandle_{{this}}(p);
+        }
+
+        public Channels.Channel_{{this}} Channel(Channels.ChannelHandle_{{this}} channel_handle)
+        {
+            var p = new Ptr<Channels.Channel_{{this}}>(
+                ffi.TpClientBaselineBaselineChannel{{this}}(this.Inner.Value.p, channel_handle.Inner.Value.p)
+            );
+            return new Channels.Channel_{{this}}(p, OwnershipSemantics.SharedRef);
+        }
+
+        public Channels.Channel_{{this}} ChannelMut(Channels.ChannelHandle_{{this}} channel_handle)
+        {
+            if (this.OwnershipSemantics == OwnershipSemantics.SharedRef)
+            {
+                throw new MutabilityException("`this` must be mutable!");
+            }
+
+            var p = new Ptr<Channels.Channel_{{this}}>(
+                ffi.TpClientBaselineBaselineChannelMut{{this}}(this.Inner.Value.p, channel_handle.Inner.Value.p)
+            );
+            return new Channels.Channel_{{this}}(p, OwnershipSemantics.MutRef);
+        }
+
+{{/each}}
+    }
+}
@@ -0,0 +1,41 @@
+public int Count
+{
+    get => (int) generated.__Internal.TpClientContractPropertiesChannelsChannel{{value_mangled_name}}Len(this.Inner.Value.p);
+}
+
+/// The keyframe at `index`. Keyframes are sorted by time.
+public unsafe Keyframe_{{value_mangled_name}} this[int index]
+{
+    get
+    {
+        if (index < 0 || index >= this.Count)
+        {
+            throw new System.IndexOutOfRangeException();
+        }
+        var raw_ptr = generated.__Internal.TpClientContractPropertiesChannelsChannel{{value_mangled_name}}Keyframe(this.Inner.Value.p, (ulong) index);
+        var ptr = new Ptr<Keyframe_{{value_mangled_name}}>(raw_ptr);
+        return new Keyframe_{{value_mangled_name}}(ptr, OwnershipSemantics.SharedRef);
+    }
+}
+
+/// Inserts `keyframe`, keeping the keyframes sorted by time. Takes ownership of `keyframe`.
+public unsafe void Insert(Keyframe_{{value_mangled_name}} keyframe)
+{
+    if (this.OwnershipSemantics == OwnershipSemantics.SharedRef)
+    {
+        throw new MutabilityException("`this` must be mutable!");
+    }
+    if (keyframe.OwnershipSemantics != OwnershipSemantics.Owned)
+    {
+        throw new OwnershipException("`keyframe` must be owned");
+    }
+    generated.__Internal.TpClientContractPropertiesChannelsChannel{{value_mangled_name}}Insert(this.Inner.Value.p, keyframe.StealInner().p);
+}
+
+public RListEnumerator<Channel_{{value_mangled_name}}, Keyframe_{{value_mangled_name}}> GetEnumerator()
+{
+    return new RListEnumerator<Channel_{{value_mangled_name}}, Keyframe_{{value_mangled_name}}>(this);
+}
+
+[System.Runtime.InteropServices.DllImport(Metadata.LIBRARY_NAME)]
+private static extern IntPtr tp_client__contract__properties__channels__Channel_{{value_mangled_name}}__new(RawVec keyframes);
@@ -0,0 +1,164 @@
+// This file was generated by `cs_codegen`. Do not edit it by hand.
+using IntPtr = System.IntPtr;
+using InvalidOperationException = System.InvalidOperationException;
+using System.Runtime.InteropServices;
+using RSharp;
+using Baseline = Teleportal.Client.Baseline;
+using ContractDataHandle = Teleportal.Client.Contract.ContractDataHandle;
+using ObjectHandle = Teleportal.Client.Object.ObjectHandle;
+
+namespace {{namespace}}
+{
+{{#if contract}}
+    public sealed class {{contract.class_ident}} : OpaqueWrapper<{{contract.class_ident}}>
+    {
+#if UNITY_IOS && !UNITY_EDITOR
+        private const string LIBRARY_NAME = "__Internal";
+#else
+        private const string LIBRARY_NAME = "{{library}}";
+#endif
+
+        [DllImport(LIBRARY_NAME, CallingConvention = CallingConvention.Cdecl)]
+        private static extern void {{contract.symbol_prefix}}__drop(IntPtr c);
+
+        [DllImport(LIBRARY_NAME, CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr {{contract.symbol_prefix}}__handle(IntPtr c);
+
+        [DllImport(LIBRARY_NAME, CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr {{contract.symbol_prefix}}__states(IntPtr c);
+
+        [DllImport(LIBRARY_NAME, CallingConvention = CallingConvention.Cdecl)]
+        private static extern RResult_Ptr {{contract.symbol_prefix}}__register(IntPtr baseline);
+
+        [DllImport(LIBRARY_NAME, CallingConvention = CallingConvention.Cdecl)]
+        private static extern RResult_Ptr {{contract.symbol_prefix}}__object_create(
+            IntPtr contract,
+            IntPtr baseline{{#each fields}},
+            {{#if is_bool}}[MarshalAs(UnmanagedType.U1)] {{/if}}{{arg_raw}} {{rust_ident}}{{/each}}
+        );
+
+        [DllImport(LIBRARY_NAME, CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr {{contract.symbol_prefix}}__object_remove(IntPtr baseline, IntPtr obj);
+
+        public {{contract.class_ident}}(Ptr<{{contract.class_ident}}> inner, OwnershipSemantics ownershipSemantics)
+        : base(inner, ownershipSemantics)
+        { }
+
+        override protected void NativeDrop(Ptr<{{contract.class_ident}}> inner)
+        {
+            {{contract.symbol_prefix}}__drop(inner.p);
+        }
+
+        /// Throws `InvalidOperationException` if the contract was already registered.
+        public static {{contract.class_ident}} register(Baseline baseline)
+        {
+            if (baseline.OwnershipSemantics == OwnershipSemantics.SharedRef)
+            {
+                throw new MutabilityException("`baseline` was not mutable!");
+            }
+
+            var raw = {{contract.symbol_prefix}}__register(baseline.Inner.Value.p).Unwrap();
+            return new {{contract.class_ident}}(new Ptr<{{contract.class_ident}}>(raw), OwnershipSemantics.Owned);
+        }
+
+        public ContractDataHandle Handle
+        {
+            get
+            {
+                var p = new Ptr<ContractDataHandle>({{contract.symbol_prefix}}__handle(this.Inner.Value.p));
+                return new ContractDataHandle(p, OwnershipSemantics.Owned);
+            }
+        }
+
+        public {{class_ident}} States
+        {
+            get
+            {
+                var p = new Ptr<{{class_ident}}>({{contract.symbol_prefix}}__states(this.Inner.Value.p));
+                return new {{class_ident}}(p);
+            }
+        }
+
+        /// Null arguments use the `#[default]` of their field. Takes ownership
+        /// of all non-null arguments. Throws `ArgumentNullException` if an
+        /// argument is null and its field has no default.
+        public ObjectHandle ObjectCreate(
+            Baseline baseline{{#each fields}},
+            {{arg_type}}{{#if is_opaque}}?{{/if}} {{rust_ident}}{{#if optional}} = null{{/if}}{{/each}}
+        )
+        {
+            if (baseline.OwnershipSemantics == OwnershipSemantics.SharedRef)
+            {
+                throw new MutabilityException("`baseline` was not mutable!");
+            }
+{{#each fields}}
+{{#if is_opaque}}
+            if ({{rust_ident}} != null && {{rust_ident}}.OwnershipSemantics != OwnershipSemantics.Owned)
+            {
+                throw new OwnershipException("`{{rust_ident}}` must be owned");
+            }
+{{/if}}
+{{/each}}
+
+            var raw = {{contract.symbol_prefix}}__object_create(
+                this.Inner.Value.p,
+                baseline.Inner.Value.p{{#each fields}},
+                {{#if is_opaque}}{{rust_ident}}?.StealInner().p ?? IntPtr.Zero{{else}}{{rust_ident}}{{/if}}{{/each}}
+            ).Unwrap();
+            return new ObjectHandle(new Ptr<ObjectHandle>(raw), OwnershipSemantics.Owned);
+        }
+
+        /// Takes ownership of `obj`.
+        public void ObjectRemove(Baseline baseline, ObjectHandle obj)
+        {
+            if (baseline.OwnershipSemantics == OwnershipSemantics.SharedRef)
+            {
+                throw new MutabilityException("`baseline` was not mutable!");
+            }
+            if (obj.OwnershipSemantics != OwnershipSemantics.Owned)
+            {
+                throw new OwnershipException("`obj` must be owned");
+            }
+            RError.Check({{contract.symbol_prefix}}__object_remove(baseline.Inner.Value.p, obj.StealInner().p));
+        }
+    }
+
+{{/if}}
+    public sealed class {{class_ident}} : OpaqueWrapper<{{class_ident}}>
+    {
+#if UNITY_IOS && !UNITY_EDITOR
+        private const string LIBRARY_NAME = "__Internal";
+#else
+        private const string LIBRARY_NAME = "{{library}}";
+#endif
+
+{{#each fields}}
+        [DllImport(LIBRARY_NAME, CallingConvention = CallingConvention.Cdecl)]
+        private static extern IntPtr {{id_symbol}}(IntPtr s);
+
+{{/each}}
+        /// The {{#if is_states}}states{{else}}channels{{/if}} are always borrowed from their contract.
+        public {{class_ident}}(Ptr<{{class_ident}}> inner)
+        : base(inner, OwnershipSemantics.SharedRef)
+        { }
+
+        override protected void NativeDrop(Ptr<{{class_ident}}> inner)
+        {
+            throw new InvalidOperationException("Unreachable code reached");
+        }
+{{#each fields}}
+
+{{#each doc}}
+        /// {{this}}
+{{/each}}
+        public {{id_class}} {{cs_ident}}
+        {
+            get
+            {
+                var p = new Ptr<{{id_class}}>({{id_symbol}}(this.Inner.Value.p));
+                return new {{id_class}}(p, OwnershipSemantics.Owned);
+            }
+        }
+{{/each}}
+    }
+}
@@ -22,7 +22,7 @@ namespace Teleportal.Client.{{namespace_super}}
 
 namespace Teleportal.Client.{{namespace_super}}.{{namespace_sub}}
 {
-    public sealed class {{class_ident}} : OpaqueWrapper<{{class_ident}}>
+    public sealed class {{class_ident}} : OpaqueWrapper<{{class_ident}}>{{#each interfaces}}, {{this}}{{/each}}
     {
 {{#if only_owned}}
         public unsafe {{class_ident}}(Ptr<{{class_ident}}> inner) : base(inner, OwnershipSemantics.Owned) { }
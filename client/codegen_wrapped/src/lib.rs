@@ -1,9 +1,17 @@
+mod template_channel;
+mod template_channel_handle;
+mod template_channel_id;
+mod template_contract;
 mod template_keyframe;
 mod template_state;
 mod template_state_handle;
 mod template_state_id;
 pub mod type_info;
 
+pub use self::template_channel::{BaselineChannelsData, CDChannel};
+pub use self::template_channel_handle::CDChannelHandle;
+pub use self::template_channel_id::CDChannelId;
+pub use self::template_contract::ContractData;
 pub use self::template_keyframe::CDKeyframe;
 pub use self::template_state::CDState;
 pub use self::template_state_handle::CDStateHandle;
@@ -43,6 +51,9 @@ pub struct ClassData<M: Serialize = ()> {
     /// C symbol name for the `drop` function for this type.
     pub drop_ident: Option<String>,
 
+    /// C# interfaces implemented by this class, in addition to `OpaqueWrapper`.
+    pub interfaces: Vec<String>,
+
     /// Injects functionality beyond the scope of `ClassData<M>`.
     #[serde(flatten)]
     pub additional_methods: Option<M>,
@@ -54,12 +65,34 @@ pub struct Codegen {
 }
 impl Codegen {
     pub fn new(partial_tpl_filename: &str, overwrite: bool) -> Result<Self> {
-        let tpl_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("opaque_template.cs.tpl");
         let partial_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(partial_tpl_filename);
-        let output_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+
+        let mut result =
+            Self::with_template("opaque_template.cs.tpl", Self::wrapped_dir(), overwrite)?;
+
+        let partial = std::fs::read_to_string(&partial_path)
+            .into_diagnostic()
+            .wrap_err("Faild to read partial template file")?;
+        result
+            .reg
+            .register_partial("additional_methods", &partial)
+            .into_diagnostic()?;
+
+        Ok(result)
+    }
+
+    /// The directory that the classes of `tp_client` are generated in.
+    pub fn wrapped_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
             .parent()
             .unwrap()
-            .join("cs/src/generated/wrapped");
+            .join("cs/src/generated/wrapped")
+    }
+
+    /// Like [`Codegen::new`], but renders `tpl_filename` directly into
+    /// `output_dir`, without any partial template.
+    pub fn with_template(tpl_filename: &str, output_dir: PathBuf, overwrite: bool) -> Result<Self> {
+        let tpl_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(tpl_filename);
 
         if !overwrite
             && output_dir.exists()
@@ -86,22 +119,20 @@ impl Codegen {
         reg.register_template_file(TPL_NAME, &tpl_path)
             .into_diagnostic()?;
 
-        let partial = std::fs::read_to_string(&partial_path)
-            .into_diagnostic()
-            .wrap_err("Faild to read partial template file")?;
-        reg.register_partial("additional_methods", &partial)
-            .into_diagnostic()?;
-
         Ok(Self { reg, output_dir })
     }
 
     pub fn render_to_file<M: Serialize>(&self, data: &ClassData<M>) -> Result<()> {
-        let class_ident = &data.class_ident;
-        let output_path = self.output_dir.join(format!("{class_ident}.cs"));
+        self.render(&data.class_ident, data)
+    }
+
+    /// Renders `data` to `{file_stem}.cs` in the output directory.
+    pub fn render(&self, file_stem: &str, data: &impl Serialize) -> Result<()> {
+        let output_path = self.output_dir.join(format!("{file_stem}.cs"));
         let output_file = File::create(&output_path)
             .into_diagnostic()
             .wrap_err_with(|| {
-                format!("Failed to create output file for class {class_ident} at {output_path:?}")
+                format!("Failed to create output file for class {file_stem} at {output_path:?}")
             })?;
 
         self.reg
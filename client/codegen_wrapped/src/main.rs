@@ -1,17 +1,42 @@
-use cs_codegen::{CDKeyframe, CDState, CDStateHandle, CDStateId, ClassData, Codegen};
+use cs_codegen::{
+    BaselineChannelsData, CDChannel, CDChannelHandle, CDChannelId, CDKeyframe, CDState,
+    CDStateHandle, CDStateId, ClassData, Codegen, ContractData,
+};
 
 use clap::Parser;
-use miette::{Result, WrapErr};
+use miette::{miette, Result, WrapErr};
+use std::path::PathBuf;
 
 #[derive(Parser)]
 struct Args {
     #[clap(short)]
     force: bool,
+
+    /// Rust files with `#[states]` or `#[channels]` structs to generate
+    /// contract classes for. If given, only these classes are generated.
+    #[clap(long = "contract")]
+    contracts: Vec<PathBuf>,
+
+    /// The directory to write the contract classes to.
+    #[clap(long)]
+    out: Option<PathBuf>,
+
+    /// The C# namespace of the contract classes.
+    #[clap(long, default_value = "Teleportal.Contract")]
+    namespace: String,
+
+    /// The native library that exports the C API of the contracts.
+    #[clap(long, default_value = "unity_states")]
+    library: String,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
+    if !args.contracts.is_empty() {
+        return generate_contracts(args);
+    }
+
     let codegen_keyframe =
         Codegen::new("keyframe.cs.tpl", args.force).wrap_err("Failed to create `Codegen`")?;
     let codegen_state =
@@ -20,6 +45,18 @@ fn main() -> Result<()> {
         Codegen::new("state_id.cs.tpl", args.force).wrap_err("Failed to create `Codegen`")?;
     let codegen_state_handle =
         Codegen::new("state_handle.cs.tpl", args.force).wrap_err("msFailed to create `Codegen`")?;
+    let codegen_channel =
+        Codegen::new("channel.cs.tpl", args.force).wrap_err("Failed to create `Codegen`")?;
+    let codegen_channel_id =
+        Codegen::new("channel_id.cs.tpl", args.force).wrap_err("Failed to create `Codegen`")?;
+    let codegen_channel_handle =
+        Codegen::new("channel_handle.cs.tpl", args.force).wrap_err("Failed to create `Codegen`")?;
+    let codegen_baseline_channels = Codegen::with_template(
+        "baseline_channels.cs.tpl",
+        Codegen::wrapped_dir(),
+        args.force,
+    )
+    .wrap_err("Failed to create `Codegen`")?;
 
     let cd_keyframe = ClassData::<CDKeyframe>::generate_class_data();
     let result_keyframe: Result<()> = cd_keyframe
@@ -45,12 +82,52 @@ fn main() -> Result<()> {
         .map(|d| codegen_state_handle.render_to_file(d))
         .collect();
 
+    let cd_channel = ClassData::<CDChannel>::generate_class_data();
+    let result_channel: Result<()> = cd_channel
+        .iter()
+        .map(|d| codegen_channel.render_to_file(d))
+        .collect();
+
+    let cd_channel_id = ClassData::<CDChannelId>::generate_class_data();
+    let result_channel_id: Result<()> = cd_channel_id
+        .iter()
+        .map(|d| codegen_channel_id.render_to_file(d))
+        .collect();
+
+    let cd_channel_handle = ClassData::<CDChannelHandle>::generate_class_data();
+    let result_channel_handle: Result<()> = cd_channel_handle
+        .iter()
+        .map(|d| codegen_channel_handle.render_to_file(d))
+        .collect();
+
+    let result_baseline_channels =
+        codegen_baseline_channels.render("Baseline_Channels", &BaselineChannelsData::generate());
+
     let results = vec![
         result_keyframe,
         result_state,
         result_state_id,
         result_state_handle,
+        result_channel,
+        result_channel_id,
+        result_channel_handle,
+        result_baseline_channels,
     ];
 
     results.into_iter().collect()
 }
+
+fn generate_contracts(args: Args) -> Result<()> {
+    let out = args
+        .out
+        .ok_or_else(|| miette!("`--out` is required when using `--contract`"))?;
+    let codegen = Codegen::with_template("contract.cs.tpl", out, args.force)
+        .wrap_err("Failed to create `Codegen`")?;
+
+    for path in &args.contracts {
+        for data in ContractData::from_file(path, &args.namespace, &args.library)? {
+            codegen.render(&data.class_ident, &data)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use crate::{
+    type_info::{PrimitiveType, TypeInfo},
+    ClassData,
+};
+
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct CDChannel {
+    /// The mangled name of values stored in the Channel in C# (`F32`, `ObjectHandle`)
+    value_mangled_name: String,
+}
+impl ClassData<CDChannel> {
+    fn new(type_info: &PrimitiveType) -> Self {
+        ClassData {
+            namespace_super: "Contract.Properties".to_string(),
+            namespace_sub: "Channels".to_string(),
+            class_ident: format!("Channel_{}", type_info.mangled_name()),
+            only_owned: false,
+            new_args: format!("RVec_Keyframe_{} keyframes", type_info.mangled_name()),
+            new_expr: Some(format!(
+                "tp_client__contract__properties__channels__Channel_{}__new(
+                    keyframes.StealInner()
+                )",
+                type_info.mangled_name(),
+            )),
+            drop_ident: Some(format!(
+                "generated.__Internal.TpClientContractPropertiesChannelsChannel{}Drop",
+                type_info.mangled_name()
+            )),
+            interfaces: vec![format!("IRList<Keyframe_{}>", type_info.mangled_name())],
+            additional_methods: Some(CDChannel {
+                value_mangled_name: type_info.mangled_name().to_owned(),
+            }),
+        }
+    }
+
+    pub fn generate_class_data() -> Vec<Self> {
+        PrimitiveType::types()
+            .iter()
+            .map(ClassData::<CDChannel>::new)
+            .collect()
+    }
+}
+
+/// The data for the channel accessors on `Baseline`.
+#[derive(Serialize)]
+pub struct BaselineChannelsData {
+    /// The mangled names of every type (`F32`, `ObjectHandle`)
+    pub types: Vec<&'static str>,
+}
+impl BaselineChannelsData {
+    pub fn generate() -> Self {
+        Self {
+            types: PrimitiveType::types()
+                .iter()
+                .map(|t| t.mangled_name())
+                .collect(),
+        }
+    }
+}
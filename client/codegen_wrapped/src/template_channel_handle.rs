@@ -0,0 +1,34 @@
+use crate::{
+    type_info::{PrimitiveType, TypeInfo},
+    ClassData,
+};
+
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct CDChannelHandle;
+impl ClassData<CDChannelHandle> {
+    fn new(type_info: &PrimitiveType) -> Self {
+        ClassData {
+            namespace_super: "Contract.Properties".to_string(),
+            namespace_sub: "Channels".to_string(),
+            class_ident: format!("ChannelHandle_{}", type_info.mangled_name()),
+            only_owned: true,
+            new_args: "".to_owned(),
+            new_expr: None,
+            drop_ident: Some(format!(
+                "generated.__Internal.TpClientContractPropertiesChannelsChannelHandle{}Drop",
+                type_info.mangled_name()
+            )),
+            interfaces: vec![],
+            additional_methods: None,
+        }
+    }
+
+    pub fn generate_class_data() -> Vec<Self> {
+        PrimitiveType::types()
+            .iter()
+            .map(ClassData::<CDChannelHandle>::new)
+            .collect()
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{
+    type_info::{PrimitiveType, TypeInfo},
+    ClassData,
+};
+
+use serde::Serialize;
+
+#[derive(Serialize)]
+pub struct CDChannelId {
+    /// The mangled name of the inner type (`F32`, `ObjectHandle`)
+    inner_mangled_name: String,
+}
+impl ClassData<CDChannelId> {
+    fn new(type_info: &PrimitiveType) -> Self {
+        ClassData {
+            namespace_super: "Contract.Properties".to_string(),
+            namespace_sub: "Channels".to_string(),
+            class_ident: format!("ChannelId_{}", type_info.mangled_name()),
+            only_owned: false,
+            new_args: "".to_owned(),
+            new_expr: None,
+            drop_ident: Some(format!(
+                "generated.__Internal.TpClientContractPropertiesChannelsChannelId{}Drop",
+                type_info.mangled_name()
+            )),
+            interfaces: vec![],
+            additional_methods: Some(CDChannelId {
+                inner_mangled_name: type_info.mangled_name().to_owned(),
+            }),
+        }
+    }
+
+    pub fn generate_class_data() -> Vec<Self> {
+        PrimitiveType::types()
+            .iter()
+            .map(ClassData::<CDChannelId>::new)
+            .collect()
+    }
+}
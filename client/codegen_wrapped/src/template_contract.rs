@@ -0,0 +1,277 @@
+//! Generates C# classes for the `#[states]` and `#[channels]` structs of a
+//! contract. Only structs that pass `c_api = "..."` to the attribute have a C
+//! API, so all others are skipped.
+
+use crate::type_info::{PrimitiveType, TypeInfo};
+
+use miette::{miette, IntoDiagnostic, Result, WrapErr};
+use serde::Serialize;
+use std::path::Path;
+use syn::punctuated::Punctuated;
+
+/// The data for a single `#[states]` or `#[channels]` struct, and its contract
+/// if it has one.
+#[derive(Serialize)]
+pub struct ContractData {
+    /// The namespace of the generated classes.
+    pub namespace: String,
+
+    /// The native library that contains the C API.
+    pub library: String,
+
+    /// C# name for the struct (e.g. `ExampleStates`).
+    pub class_ident: String,
+
+    /// Whether the struct holds states rather than channels.
+    pub is_states: bool,
+
+    pub fields: Vec<FieldData>,
+
+    /// Only present for `#[states]` structs that set `contract = "..."`.
+    pub contract: Option<ContractClassData>,
+}
+
+#[derive(Serialize)]
+pub struct ContractClassData {
+    /// C# name for the contract (e.g. `ExampleContract`).
+    pub class_ident: String,
+
+    /// C symbol prefix for the functions of the contract.
+    pub symbol_prefix: String,
+}
+
+#[derive(Serialize)]
+pub struct FieldData {
+    /// The name of the property in C# (e.g. `U8_0`).
+    pub cs_ident: String,
+
+    /// The name of the field in rust (e.g. `u8_0`).
+    pub rust_ident: String,
+
+    /// The doc comment lines of the field.
+    pub doc: Vec<String>,
+
+    /// C symbol name for the function that returns the id of the field.
+    pub id_symbol: String,
+
+    /// The fully qualified C# class of the id of the field.
+    pub id_class: String,
+
+    /// The C# type passed to `ObjectCreate` for the field.
+    pub arg_type: String,
+
+    /// The type of the argument in the PInvoke FFI.
+    pub arg_raw: String,
+
+    /// Whether the argument is an opaque pointer, and therefore nullable.
+    pub is_opaque: bool,
+
+    /// Whether the argument is a `bool`, which must be marshalled as one byte.
+    pub is_bool: bool,
+
+    /// Whether the argument may be omitted in `ObjectCreate`. This is only the
+    /// case for opaque arguments that are not followed by any value arguments.
+    pub optional: bool,
+}
+
+impl ContractData {
+    /// Parses the rust file at `path`, and returns the data for every
+    /// `#[states]` or `#[channels]` struct in it that has a C API.
+    pub fn from_file(path: &Path, namespace: &str, library: &str) -> Result<Vec<Self>> {
+        let src = std::fs::read_to_string(path)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to read {path:?}"))?;
+        let file = syn::parse_file(&src)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("Failed to parse {path:?}"))?;
+
+        let mut result = Vec::new();
+        for item in file.items {
+            let s = match item {
+                syn::Item::Struct(s) => s,
+                _ => continue,
+            };
+            for attr in &s.attrs {
+                let attr_ident = &attr.path.segments.last().unwrap().ident;
+                let is_states = if attr_ident == "states" {
+                    true
+                } else if attr_ident == "channels" {
+                    false
+                } else {
+                    continue;
+                };
+                let data = Self::new(&s, attr, is_states, namespace, library)
+                    .wrap_err_with(|| format!("Invalid struct `{}` in {path:?}", s.ident))?;
+                result.extend(data);
+            }
+        }
+        Ok(result)
+    }
+
+    fn new(
+        s: &syn::ItemStruct,
+        attr: &syn::Attribute,
+        is_states: bool,
+        namespace: &str,
+        library: &str,
+    ) -> Result<Option<Self>> {
+        let mut c_api = None;
+        let mut contract = None;
+        if !attr.tokens.is_empty() {
+            let args = attr
+                .parse_args_with(Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated)
+                .into_diagnostic()?;
+            for arg in args {
+                let value = match arg.lit {
+                    syn::Lit::Str(s) => s.value(),
+                    _ => return Err(miette!("Expected a string literal")),
+                };
+                if arg.path.is_ident("c_api") {
+                    c_api = Some(value);
+                } else if arg.path.is_ident("contract") {
+                    contract = Some(value);
+                }
+            }
+        }
+        let c_api = match c_api {
+            Some(c_api) => c_api.replace("::", "__"),
+            None => return Ok(None),
+        };
+
+        let class_ident = s.ident.to_string();
+        let named = match &s.fields {
+            syn::Fields::Named(named) => named,
+            _ => return Err(miette!("Only structs with named fields are supported")),
+        };
+        let mut fields = named
+            .named
+            .iter()
+            .map(|f| FieldData::new(f, &c_api, &class_ident, is_states))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Optional arguments must come last in C#
+        let mut all_opaque = true;
+        for f in fields.iter_mut().rev() {
+            all_opaque &= f.is_opaque;
+            f.optional = all_opaque;
+        }
+
+        let contract = contract.map(|path| {
+            let c_ident = path.rsplit("::").next().unwrap().to_owned();
+            ContractClassData {
+                symbol_prefix: format!("{c_api}__{c_ident}"),
+                class_ident: c_ident,
+            }
+        });
+
+        Ok(Some(Self {
+            namespace: namespace.to_owned(),
+            library: library.to_owned(),
+            class_ident,
+            is_states,
+            fields,
+            contract,
+        }))
+    }
+}
+
+impl FieldData {
+    fn new(f: &syn::Field, c_api: &str, s_ident: &str, is_states: bool) -> Result<Self> {
+        let rust_ident = f.ident.as_ref().unwrap().to_string();
+        let t_ident = match &f.ty {
+            syn::Type::Path(p) => p.path.segments.last().unwrap().ident.to_string(),
+            _ => return Err(miette!("Unsupported type for field `{rust_ident}`")),
+        };
+        let t = PrimitiveType::from_rust_ident(&t_ident)
+            .ok_or_else(|| miette!("Unsupported type `{t_ident}` for field `{rust_ident}`"))?;
+
+        let doc = f
+            .attrs
+            .iter()
+            .filter(|a| a.path.is_ident("doc"))
+            .filter_map(|a| match a.parse_meta() {
+                Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                    lit: syn::Lit::Str(s),
+                    ..
+                })) => Some(s.value().trim().to_owned()),
+                _ => None,
+            })
+            .collect();
+
+        let mut cs_ident = rust_ident.clone();
+        cs_ident[..1].make_ascii_uppercase();
+
+        let (id_namespace, id_prefix) = if is_states {
+            ("States", "StateId")
+        } else {
+            ("Channels", "ChannelId")
+        };
+        let is_opaque = t.ptr_raw() == "IntPtr";
+        Ok(Self {
+            cs_ident,
+            id_symbol: format!("{c_api}__{s_ident}__{rust_ident}"),
+            id_class: format!(
+                "Teleportal.Client.Contract.Properties.{id_namespace}.{id_prefix}_{}",
+                t.mangled_name()
+            ),
+            arg_type: if is_opaque {
+                t.owned_ident().to_owned()
+            } else {
+                t.ptr_inner().to_owned()
+            },
+            arg_raw: if is_opaque {
+                "IntPtr".to_owned()
+            } else {
+                t.ptr_inner().to_owned()
+            },
+            is_opaque,
+            is_bool: matches!(t, PrimitiveType::Bool(_)),
+            optional: false,
+            rust_ident,
+            doc,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_example_states() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../contract_example/rust/src/states.rs");
+        let data =
+            ContractData::from_file(&path, "Teleportal.Example.Contract", "unity_states").unwrap();
+        assert_eq!(data.len(), 1);
+
+        let states = &data[0];
+        assert_eq!(states.class_ident, "ExampleStates");
+        assert!(states.is_states);
+        let contract = states.contract.as_ref().unwrap();
+        assert_eq!(contract.class_ident, "ExampleContract");
+        assert_eq!(
+            contract.symbol_prefix,
+            "tp_contract_example__ExampleContract"
+        );
+
+        let u8_0 = &states.fields[0];
+        assert_eq!(u8_0.cs_ident, "U8_0");
+        assert_eq!(u8_0.id_symbol, "tp_contract_example__ExampleStates__u8_0");
+        assert_eq!(
+            u8_0.id_class,
+            "Teleportal.Client.Contract.Properties.States.StateId_U8"
+        );
+        assert_eq!(u8_0.arg_type, "byte");
+        assert!(!u8_0.is_opaque && !u8_0.optional);
+
+        let str_0 = states
+            .fields
+            .iter()
+            .find(|f| f.rust_ident == "str_0")
+            .unwrap();
+        assert_eq!(str_0.cs_ident, "Str_0");
+        assert_eq!(str_0.arg_type, "RString");
+        assert!(str_0.is_opaque && str_0.optional);
+    }
+}
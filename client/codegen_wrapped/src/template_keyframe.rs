@@ -35,6 +35,7 @@ impl ClassData<CDKeyframe> {
                 "generated.__Internal.TpClientContractPropertiesChannelsKeyframe{}Drop",
                 type_info.mangled_name()
             )),
+            interfaces: vec![],
             additional_methods: Some(CDKeyframe {
                 value_owned_ident: type_info.owned_ident().to_owned(),
                 value_mangled_name: type_info.mangled_name().to_owned(),
@@ -37,6 +37,7 @@ impl ClassData<CDState> {
                 "generated.__Internal.TpClientContractPropertiesStatesState{}Drop",
                 type_info.mangled_name()
             )),
+            interfaces: vec![],
             additional_methods: Some(CDState {
                 value_owned_ident: type_info.owned_ident().to_owned(),
                 value_mangled_name: type_info.mangled_name().to_owned(),
@@ -20,6 +20,7 @@ impl ClassData<CDStateHandle> {
                 "generated.__Internal.TpClientContractPropertiesStatesStateHandle{}Drop",
                 type_info.mangled_name()
             )),
+            interfaces: vec![],
             additional_methods: None,
         }
     }
@@ -23,6 +23,7 @@ impl ClassData<CDStateId> {
                 "generated.__Internal.TpClientContractPropertiesStatesStateId{}Drop",
                 type_info.mangled_name()
             )),
+            interfaces: vec![],
             additional_methods: Some(CDStateId {
                 inner_mangled_name: type_info.mangled_name().to_owned(),
             }),
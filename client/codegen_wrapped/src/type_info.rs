@@ -52,6 +52,12 @@ pub enum PrimitiveType {
     String(String),
     ObjectHandle(ObjectHandle),
     ContractDataHandle(ContractDataHandle),
+    Vec2(Vec2),
+    Vec3(Vec3),
+    Quat(Quat),
+    Color(Color),
+    Transform(Transform),
+    Bytes(Bytes),
 }
 impl PrimitiveType {
     pub fn new<T: TypeInfoConcrete>() -> Self {
@@ -60,7 +66,7 @@ impl PrimitiveType {
     pub fn types() -> &'static [PrimitiveType] {
         use PrimitiveType as P;
         lazy_static! {
-            static ref RESULT: [PrimitiveType; 14] = [
+            static ref RESULT: [PrimitiveType; 20] = [
                 P::new::<U8>(),
                 P::new::<U16>(),
                 P::new::<U32>(),
@@ -75,10 +81,25 @@ impl PrimitiveType {
                 P::new::<String>(),
                 P::new::<ObjectHandle>(),
                 P::new::<ContractDataHandle>(),
+                P::new::<Vec2>(),
+                P::new::<Vec3>(),
+                P::new::<Quat>(),
+                P::new::<Color>(),
+                P::new::<Transform>(),
+                P::new::<Bytes>(),
             ];
         }
         &*RESULT
     }
+
+    /// Looks up the type from the name it has in rust (`u8`, `String`,
+    /// `ObjectHandle`).
+    pub fn from_rust_ident(ident: &str) -> Option<Self> {
+        Self::types()
+            .iter()
+            .copied()
+            .find(|t| t.mangled_name().eq_ignore_ascii_case(ident))
+    }
 }
 
 macro_rules! type_info {
@@ -167,3 +188,57 @@ type_info!(
     "Teleportal.Client.Contract.ContractDataHandle",
     O::Both,
 );
+type_info!(
+    Vec2,
+    "Vec2",
+    "IntPtr",
+    "Teleportal.Client.Contract.Properties.Vec2",
+    false,
+    "Teleportal.Client.Contract.Properties.Vec2",
+    O::Both,
+);
+type_info!(
+    Vec3,
+    "Vec3",
+    "IntPtr",
+    "Teleportal.Client.Contract.Properties.Vec3",
+    false,
+    "Teleportal.Client.Contract.Properties.Vec3",
+    O::Both,
+);
+type_info!(
+    Quat,
+    "Quat",
+    "IntPtr",
+    "Teleportal.Client.Contract.Properties.Quat",
+    false,
+    "Teleportal.Client.Contract.Properties.Quat",
+    O::Both,
+);
+type_info!(
+    Color,
+    "Color",
+    "IntPtr",
+    "Teleportal.Client.Contract.Properties.Color",
+    false,
+    "Teleportal.Client.Contract.Properties.Color",
+    O::Both,
+);
+type_info!(
+    Transform,
+    "Transform",
+    "IntPtr",
+    "Teleportal.Client.Contract.Properties.Transform",
+    false,
+    "Teleportal.Client.Contract.Properties.Transform",
+    O::Both,
+);
+type_info!(
+    Bytes,
+    "Bytes",
+    "IntPtr",
+    "Teleportal.Client.Contract.Properties.Bytes",
+    false,
+    "Teleportal.Client.Contract.Properties.Bytes",
+    O::Both,
+);
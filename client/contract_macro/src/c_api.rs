@@ -0,0 +1,261 @@
+//! Generates the C API for `#[states(c_api = "...")]` and
+//! `#[channels(c_api = "...")]`.
+//!
+//! The generated functions are remangled with the given path, just like the
+//! hand-written C API of `tp_client`. Crates that use this must depend on
+//! `rsharp` and `safer-ffi`.
+//!
+//! `#[flatten]` fields return the ids of the composite, so the composite must
+//! have a C API too, see `#[derive(Composite)]`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Error, Result};
+
+pub struct CApiArgs {
+    /// The path used to remangle the generated symbols.
+    pub path: syn::LitStr,
+    /// The contract whose functions should be generated. Only for states.
+    pub contract: Option<syn::Path>,
+}
+
+/// A field of the struct.
+pub struct Field {
+    pub name: syn::Ident,
+    /// The type of the field, before it was wrapped in an id.
+    pub ty: syn::Type,
+    /// Whether the field is a `Composite` that gets flattened.
+    pub flatten: bool,
+    /// The index of the first property of the field.
+    pub idx: TokenStream,
+}
+
+/// Parses the arguments of the attribute. Returns `None` if no C API was
+/// requested.
+pub fn parse_args(attr: TokenStream, is_states: bool) -> Result<Option<CApiArgs>> {
+    let args = Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated.parse2(attr)?;
+
+    let mut path = None;
+    let mut contract = None;
+    for arg in args {
+        let value = match &arg.lit {
+            syn::Lit::Str(s) => s,
+            lit => return Err(Error::new(lit.span(), "expected a string literal")),
+        };
+        if arg.path.is_ident("c_api") {
+            path = Some(value.clone());
+        } else if arg.path.is_ident("contract") && is_states {
+            contract = Some(value.parse::<syn::Path>()?);
+        } else {
+            return Err(Error::new(arg.path.span(), "unknown argument"));
+        }
+    }
+
+    match (path, contract) {
+        (Some(path), contract) => Ok(Some(CApiArgs { path, contract })),
+        (None, Some(contract)) => Err(Error::new(
+            contract.span(),
+            "`contract` requires `c_api` to also be set",
+        )),
+        (None, None) => Ok(None),
+    }
+}
+
+/// The attributes to add to the struct, so that it can be passed through the C
+/// API.
+pub fn struct_attrs(args: &CApiArgs) -> Result<Vec<syn::Attribute>> {
+    let path = &args.path;
+    let attrs = quote! {
+        #[::rsharp::remangle(#path)]
+        #[::safer_ffi::derive_ReprC]
+        #[ReprC::opaque]
+    };
+    syn::Attribute::parse_outer.parse2(attrs)
+}
+
+/// Generates the C API functions for the struct `s_name` with `fields`.
+///
+/// `id_type` is the associated type of `CProperty` for the ids of the fields,
+/// and `ids_type` the associated type of `IComposite` for flattened fields.
+pub fn generate(
+    args: &CApiArgs,
+    s_name: &syn::Ident,
+    fields: &[Field],
+    id_type: &syn::Ident,
+    ids_type: &syn::Ident,
+) -> TokenStream {
+    let path = &args.path;
+    let c_property = quote! { ::tp_client::contract::properties::c_api::CProperty };
+    let composite = quote! { ::tp_client::contract::properties::composite::IComposite };
+
+    let mut fns = TokenStream::new();
+    for Field {
+        name: f_name,
+        ty: inner_t,
+        flatten,
+        ..
+    } in fields
+    {
+        let fn_name = format_ident!("{}__{}", s_name, f_name);
+        if *flatten {
+            fns.extend(quote! {
+                #[::rsharp::remangle(#path)]
+                #[::safer_ffi::ffi_export]
+                pub fn #fn_name(s: &#s_name) -> &<#inner_t as #composite>::#ids_type {
+                    &s.#f_name
+                }
+            });
+            continue;
+        }
+        fns.extend(quote! {
+            #[::rsharp::remangle(#path)]
+            #[::safer_ffi::ffi_export]
+            pub fn #fn_name(
+                s: &#s_name,
+            ) -> ::safer_ffi::boxed::Box<<#inner_t as #c_property>::#id_type> {
+                let id = <<#inner_t as #c_property>::#id_type>::from(s.#f_name());
+                ::safer_ffi::boxed::Box::new(id)
+            }
+        });
+    }
+
+    if let Some(contract) = &args.contract {
+        fns.extend(contract_fns(path, contract, s_name, fields));
+    }
+
+    let mod_name = format_ident!("__{}_c_api", s_name);
+    quote! {
+        #[allow(non_snake_case)]
+        mod #mod_name {
+            use super::*;
+
+            #fns
+        }
+    }
+}
+
+/// Generates the functions for the contract that uses the states `s_name`.
+fn contract_fns(
+    path: &syn::LitStr,
+    contract: &syn::Path,
+    s_name: &syn::Ident,
+    fields: &[Field],
+) -> TokenStream {
+    let c_name = &contract
+        .segments
+        .last()
+        .expect("paths have at least one segment")
+        .ident;
+    let drop_fn = format_ident!("{}__drop", c_name);
+    let handle_fn = format_ident!("{}__handle", c_name);
+    let states_fn = format_ident!("{}__states", c_name);
+    let register_fn = format_ident!("{}__register", c_name);
+    let object_create_fn = format_ident!("{}__object_create", c_name);
+    let object_remove_fn = format_ident!("{}__object_remove", c_name);
+
+    let c_property = quote! { ::tp_client::contract::properties::c_api::CProperty };
+    let c_composite = quote! { ::tp_client::contract::properties::c_api::CComposite };
+    let composite = quote! { ::tp_client::contract::properties::composite::IComposite };
+    let args = fields.iter().map(|f| {
+        let (f_name, inner_t) = (&f.name, &f.ty);
+        if f.flatten {
+            quote! { #f_name: <#inner_t as #c_composite>::Arg }
+        } else {
+            quote! { #f_name: <#inner_t as #c_property>::Arg }
+        }
+    });
+    // Pushes the properties of each field onto `states`
+    let states = fields.iter().map(|f| {
+        let (f_name, inner_t, idx) = (&f.name, &f.ty, &f.idx);
+        if f.flatten {
+            let msg = format!("`{}` was null", f_name);
+            return quote! {
+                let v = <#inner_t as #c_composite>::from_arg(#f_name).ok_or_else(|| {
+                    ::rsharp::result::RError::new(::rsharp::result::RErrorKind::NullPointer, #msg)
+                })?;
+                states.extend(<#inner_t as #composite>::into_properties(v));
+            };
+        }
+        let msg = format!("`{}` was null, and has no default", f_name);
+        quote! {
+            states.push(
+                <#inner_t as #c_property>::from_arg(#f_name)
+                    .map(::tp_client::contract::properties::dynamic::DynTpProperty::from)
+                    .or_else(|| field_info[#idx].default.clone())
+                    .ok_or_else(|| ::rsharp::result::RError::new(
+                        ::rsharp::result::RErrorKind::NullPointer,
+                        #msg,
+                    ))?,
+            );
+        }
+    });
+
+    quote! {
+        #[::rsharp::remangle(#path)]
+        #[::safer_ffi::ffi_export]
+        pub fn #drop_fn(c: ::safer_ffi::boxed::Box<#contract>) {
+            drop(c)
+        }
+
+        #[::rsharp::remangle(#path)]
+        #[::safer_ffi::ffi_export]
+        pub fn #handle_fn(
+            c: &#contract,
+        ) -> ::safer_ffi::boxed::Box<::tp_client::contract::c_api::ContractDataHandle> {
+            let handle = <#contract as ::tp_client::contract::Contract>::handle(c);
+            ::safer_ffi::boxed::Box::new(handle.into())
+        }
+
+        #[::rsharp::remangle(#path)]
+        #[::safer_ffi::ffi_export]
+        pub fn #states_fn(c: &#contract) -> &#s_name {
+            <#contract as ::tp_client::contract::Contract>::states(c)
+        }
+
+        /// Errors if the contract was already registered.
+        #[::rsharp::remangle(#path)]
+        #[::safer_ffi::ffi_export]
+        pub fn #register_fn(
+            baseline: &mut ::tp_client::baseline::Baseline,
+        ) -> ::rsharp::result::RResult<Option<::safer_ffi::boxed::Box<#contract>>> {
+            let c: #contract = baseline.register_contract().map_err(|e| {
+                ::rsharp::result::RError::new(
+                    ::rsharp::result::RErrorKind::InvalidOperation,
+                    e.to_string(),
+                )
+            })?;
+            Ok(Some(::safer_ffi::boxed::Box::new(c)))
+        }
+
+        /// Null arguments use the `#[default]` of their field. `#[flatten]` fields
+        /// can't be null.
+        #[::rsharp::remangle(#path)]
+        #[::safer_ffi::ffi_export]
+        pub fn #object_create_fn(
+            contract: &#contract,
+            baseline: &mut ::tp_client::baseline::Baseline,
+            #(#args),*
+        ) -> ::rsharp::result::RResult<
+            Option<::safer_ffi::boxed::Box<::tp_client::object::c_api::ObjectHandle>>,
+        > {
+            let field_info = <#s_name as ::tp_client::contract::properties::states::IStates>::field_info();
+            let mut states = ::std::vec::Vec::new();
+            #(#states)*
+            // TODO: Support contracts with channels
+            let obj = baseline.object_create(contract, states.into_iter(), ::std::iter::empty())?;
+            Ok(Some(::safer_ffi::boxed::Box::new(obj.into())))
+        }
+
+        #[::rsharp::remangle(#path)]
+        #[::safer_ffi::ffi_export]
+        pub fn #object_remove_fn(
+            baseline: &mut ::tp_client::baseline::Baseline,
+            obj: ::safer_ffi::boxed::Box<::tp_client::object::c_api::ObjectHandle>,
+        ) -> ::rsharp::result::RStatus {
+            baseline.object_remove::<#contract>(obj.inner)
+        }
+    }
+}
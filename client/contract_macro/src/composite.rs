@@ -0,0 +1,268 @@
+//! Implements `#[derive(Composite)]`.
+//!
+//! The struct can be annotated with `#[composite(c_api = "...")]` to generate a
+//! C API for it, which is needed to flatten it into `#[states]` and
+//! `#[channels]` that have a C API. It makes the `StateIds` and `ChannelIds`
+//! opaque C types with a function to get the id of each field, and wraps the
+//! struct in an opaque C type of the same name, which is made with `__new`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Error, Result};
+
+/// Parses the path given by `#[composite(c_api = "...")]`, if any.
+fn parse_c_api(attrs: &[syn::Attribute]) -> Result<Option<syn::LitStr>> {
+    let mut path = None;
+    for attr in attrs.iter().filter(|a| a.path.is_ident("composite")) {
+        let args = attr
+            .parse_args_with(Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated)?;
+        for arg in args {
+            let value = match &arg.lit {
+                syn::Lit::Str(s) => s,
+                lit => return Err(Error::new(lit.span(), "expected a string literal")),
+            };
+            if !arg.path.is_ident("c_api") {
+                return Err(Error::new(arg.path.span(), "unknown argument"));
+            }
+            if path.is_some() {
+                return Err(Error::new(arg.span(), "duplicate argument"));
+            }
+            path = Some(value.clone());
+        }
+    }
+    Ok(path)
+}
+
+pub fn derive(item: syn::DeriveInput) -> Result<TokenStream> {
+    if !item.generics.params.is_empty() {
+        return Err(Error::new(
+            item.generics.span(),
+            "Generic structs are not supported",
+        ));
+    }
+    let fields = match &item.data {
+        syn::Data::Struct(syn::DataStruct {
+            fields: syn::Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        syn::Data::Struct(s) => {
+            return Err(Error::new(
+                s.fields.span(),
+                "Only named structs are supported",
+            ))
+        }
+        syn::Data::Enum(e) => {
+            return Err(Error::new(
+                e.enum_token.span(),
+                "Only structs are supported",
+            ))
+        }
+        syn::Data::Union(u) => {
+            return Err(Error::new(
+                u.union_token.span(),
+                "Only structs are supported",
+            ))
+        }
+    };
+
+    let c_api_path = parse_c_api(&item.attrs)?;
+    let vis = &item.vis;
+    let s_name = &item.ident;
+    let state_ids = format_ident!("{}StateIds", s_name);
+    let channel_ids = format_ident!("{}ChannelIds", s_name);
+    // won't panic because we already checked that the fields were named
+    let f_names: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let f_types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let idxs: Vec<_> = (0..fields.len()).collect();
+    let len = fields.len();
+
+    let state_id = quote! { ::tp_client::contract::properties::states::StateId };
+    let channel_id = quote! { ::tp_client::contract::properties::channels::ChannelId };
+    let reads = fields.iter().map(|f| {
+        let f_name = f.ident.as_ref().unwrap();
+        quote_spanned! {f.ty.span()=>
+            #f_name: baseline
+                .state(baseline.bind_state(self.#f_name, obj)?)?
+                .value
+                .clone()
+        }
+    });
+
+    let state_doc = format!("The `StateId` of each field of [`{}`].", s_name);
+    let channel_doc = format!("The `ChannelId` of each field of [`{}`].", s_name);
+    let ids_attrs = match &c_api_path {
+        Some(path) => quote! {
+            #[::rsharp::remangle(#path)]
+            #[::safer_ffi::derive_ReprC]
+            #[ReprC::opaque]
+        },
+        None => TokenStream::new(),
+    };
+    let c_api = match &c_api_path {
+        Some(path) => c_api(path, s_name, &f_names, &f_types),
+        None => TokenStream::new(),
+    };
+    Ok(quote! {
+        #ids_attrs
+        #[doc = #state_doc]
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #vis struct #state_ids {
+            #(pub #f_names: #state_id<#f_types>,)*
+        }
+        impl #state_ids {
+            /// Reads the states of `obj` back into the struct.
+            pub fn get(
+                &self,
+                baseline: &::tp_client::baseline::Baseline,
+                obj: ::tp_client::object::ObjectHandle,
+            ) -> ::tp_client::eyre::Result<#s_name> {
+                Ok(#s_name {
+                    #(#reads,)*
+                })
+            }
+        }
+
+        #ids_attrs
+        #[doc = #channel_doc]
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        #vis struct #channel_ids {
+            #(pub #f_names: #channel_id<#f_types>,)*
+        }
+
+        impl ::tp_client::contract::properties::composite::IComposite for #s_name {
+            const LEN: usize = #len;
+            type StateIds = #state_ids;
+            type ChannelIds = #channel_ids;
+
+            fn type_ids() -> Vec<::std::any::TypeId> {
+                vec![#(::std::any::TypeId::of::<#f_types>()),*]
+            }
+
+            fn enumerate_types() -> Vec<::tp_client::contract::properties::dynamic::TpPropertyType> {
+                vec![#(<#f_types as ::tp_client::contract::properties::traits::ITpPropertyStatic>::PROPERTY_TYPE),*]
+            }
+
+            fn field_names() -> &'static [&'static str] {
+                &[#(std::stringify!(#f_names)),*]
+            }
+
+            fn state_ids(
+                first: usize,
+                contract: ::tp_client::contract::ContractDataHandle,
+            ) -> Self::StateIds {
+                #state_ids {
+                    #(#f_names: #state_id::new(first + #idxs, contract),)*
+                }
+            }
+
+            fn channel_ids(
+                first: usize,
+                contract: ::tp_client::contract::ContractDataHandle,
+            ) -> Self::ChannelIds {
+                #channel_ids {
+                    #(#f_names: #channel_id::new(first + #idxs, contract),)*
+                }
+            }
+
+            fn into_properties(self) -> Vec<::tp_client::contract::properties::dynamic::DynTpProperty> {
+                vec![#(::tp_client::contract::properties::dynamic::DynTpProperty::from(self.#f_names)),*]
+            }
+        }
+
+        #c_api
+    })
+}
+
+/// Generates the C API of the composite `s_name`, remangled with `path`.
+fn c_api(
+    path: &syn::LitStr,
+    s_name: &syn::Ident,
+    f_names: &[&syn::Ident],
+    f_types: &[&syn::Type],
+) -> TokenStream {
+    let c_property = quote! { ::tp_client::contract::properties::c_api::CProperty };
+    let state_ids = format_ident!("{}StateIds", s_name);
+    let channel_ids = format_ident!("{}ChannelIds", s_name);
+    let new_fn = format_ident!("{}__new", s_name);
+    let drop_fn = format_ident!("{}__drop", s_name);
+
+    let mut fns = TokenStream::new();
+    for (f_name, f_t) in std::iter::zip(f_names, f_types) {
+        let state_fn = format_ident!("{}__{}", state_ids, f_name);
+        let channel_fn = format_ident!("{}__{}", channel_ids, f_name);
+        fns.extend(quote! {
+            #[::rsharp::remangle(#path)]
+            #[::safer_ffi::ffi_export]
+            pub fn #state_fn(
+                ids: &#state_ids,
+            ) -> ::safer_ffi::boxed::Box<<#f_t as #c_property>::StateId> {
+                ::safer_ffi::boxed::Box::new(ids.#f_name.into())
+            }
+
+            #[::rsharp::remangle(#path)]
+            #[::safer_ffi::ffi_export]
+            pub fn #channel_fn(
+                ids: &#channel_ids,
+            ) -> ::safer_ffi::boxed::Box<<#f_t as #c_property>::ChannelId> {
+                ::safer_ffi::boxed::Box::new(ids.#f_name.into())
+            }
+        });
+    }
+    let fields = std::iter::zip(f_names, f_types).map(|(f_name, f_t)| {
+        let msg = format!("`{}` was null", f_name);
+        quote! {
+            #f_name: <#f_t as #c_property>::from_arg(#f_name).ok_or_else(|| {
+                ::rsharp::result::RError::new(::rsharp::result::RErrorKind::NullPointer, #msg)
+            })?
+        }
+    });
+
+    let mod_name = format_ident!("__{}_c_api", s_name);
+    quote! {
+        #[allow(non_snake_case)]
+        mod #mod_name {
+            use super::*;
+
+            #[::rsharp::remangle(#path)]
+            #[::safer_ffi::derive_ReprC]
+            #[ReprC::opaque]
+            pub struct #s_name {
+                inner: super::#s_name,
+            }
+
+            impl ::tp_client::contract::properties::c_api::CComposite for super::#s_name {
+                type Arg = ::std::option::Option<::safer_ffi::boxed::Box<#s_name>>;
+
+                fn from_arg(arg: Self::Arg) -> ::std::option::Option<Self> {
+                    arg.map(|v| {
+                        let v: ::std::boxed::Box<#s_name> = v.into();
+                        v.inner
+                    })
+                }
+            }
+
+            /// Errors if any of the arguments were null.
+            #[::rsharp::remangle(#path)]
+            #[::safer_ffi::ffi_export]
+            pub fn #new_fn(
+                #(#f_names: <#f_types as #c_property>::Arg),*
+            ) -> ::rsharp::result::RResult<::std::option::Option<::safer_ffi::boxed::Box<#s_name>>> {
+                let inner = super::#s_name {
+                    #(#fields,)*
+                };
+                Ok(Some(::safer_ffi::boxed::Box::new(#s_name { inner })))
+            }
+
+            #[::rsharp::remangle(#path)]
+            #[::safer_ffi::ffi_export]
+            pub fn #drop_fn(v: ::safer_ffi::boxed::Box<#s_name>) {
+                drop(v)
+            }
+
+            #fns
+        }
+    }
+}
@@ -1,3 +1,6 @@
+mod c_api;
+mod composite;
+
 use proc_macro2::TokenStream;
 use quote::ToTokens;
 use quote::{quote, quote_spanned};
@@ -14,25 +17,215 @@ macro_rules! template {
     ($macro_name:ident) => {
         #[proc_macro_attribute]
         pub fn $macro_name(
-            _attr: proc_macro::TokenStream,
+            attr: proc_macro::TokenStream,
             item: proc_macro::TokenStream,
         ) -> proc_macro::TokenStream {
             // Get the AST of anything that can be derived (in this case, the struct)
             let item = parse_macro_input!(item as syn::DeriveInput);
 
-            imp::$macro_name(item)
+            imp::$macro_name(attr.into(), item)
                 .unwrap_or_else(|e| e.into_compile_error())
                 .into()
         }
     };
 }
+
+/// Flattens a struct of properties into one property per field, so that it can
+/// be used in `#[states]` and `#[channels]` with `#[flatten]`.
+///
+/// `#[composite(c_api = "...")]` generates a C API for the struct, which is
+/// needed to flatten it into structs that have a C API.
+#[proc_macro_derive(Composite, attributes(composite))]
+pub fn composite(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let item = parse_macro_input!(item as syn::DeriveInput);
+
+    composite::derive(item)
+        .unwrap_or_else(|e| e.into_compile_error())
+        .into()
+}
+
+/// The attributes of a field that get recorded in its `FieldInfo`.
+#[derive(Default)]
+struct FieldAttrs {
+    default: Option<syn::Expr>,
+    range: Option<syn::ExprRange>,
+    readonly: bool,
+    /// Whether the field is a `Composite` that gets flattened.
+    flatten: Option<proc_macro2::Span>,
+    doc: Vec<String>,
+}
+
+/// Whether `ty` is one of the numeric primitives, which are the only types that
+/// can have a range. Paths like `std::primitive::f32` are matched by their last
+/// segment.
+fn is_numeric(ty: &syn::Type) -> bool {
+    const NUMERIC: [&str; 10] = [
+        "u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "f32", "f64",
+    ];
+    match ty {
+        syn::Type::Path(syn::TypePath { qself: None, path }) => {
+            path.segments.last().map_or(false, |segment| {
+                segment.arguments.is_empty() && NUMERIC.iter().any(|n| segment.ident == n)
+            })
+        }
+        syn::Type::Group(g) => is_numeric(&g.elem),
+        syn::Type::Paren(p) => is_numeric(&p.elem),
+        _ => false,
+    }
+}
+
+/// Parses and strips the field attributes that we handle. Doc comments are
+/// recorded but left on the field.
+///
+/// Defaults, ranges, and `readonly` are only supported on states.
+fn parse_field_attrs(f: &mut syn::Field, is_states: bool) -> Result<FieldAttrs> {
+    let mut result = FieldAttrs::default();
+    for attr in std::mem::take(&mut f.attrs) {
+        let duplicate = || Error::new(attr.span(), "duplicate attribute");
+        if attr.path.is_ident("doc") {
+            if let Ok(syn::Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(s),
+                ..
+            })) = attr.parse_meta()
+            {
+                let line = s.value();
+                result
+                    .doc
+                    .push(line.strip_prefix(' ').unwrap_or(&line).to_string());
+            }
+            f.attrs.push(attr);
+        } else if attr.path.is_ident("default") {
+            if !is_states {
+                return Err(Error::new(
+                    attr.span(),
+                    "defaults are only supported on states",
+                ));
+            }
+            if result.default.is_some() {
+                return Err(duplicate());
+            }
+            result.default = Some(attr.parse_args()?);
+        } else if attr.path.is_ident("range") {
+            if !is_states {
+                return Err(Error::new(
+                    attr.span(),
+                    "ranges are only supported on states",
+                ));
+            }
+            if !is_numeric(&f.ty) {
+                return Err(Error::new(
+                    f.ty.span(),
+                    "ranges are only supported on numeric primitives",
+                ));
+            }
+            if result.range.is_some() {
+                return Err(duplicate());
+            }
+            let range: syn::ExprRange = attr.parse_args()?;
+            if range.from.is_none() && range.to.is_none() {
+                return Err(Error::new(
+                    range.span(),
+                    "the range must have at least one bound",
+                ));
+            }
+            result.range = Some(range);
+        } else if attr.path.is_ident("readonly") {
+            if !is_states {
+                return Err(Error::new(
+                    attr.span(),
+                    "`readonly` is only supported on states",
+                ));
+            }
+            if !attr.tokens.is_empty() {
+                return Err(Error::new(
+                    attr.tokens.span(),
+                    "`readonly` does not take any arguments",
+                ));
+            }
+            if result.readonly {
+                return Err(duplicate());
+            }
+            result.readonly = true;
+        } else if attr.path.is_ident("flatten") {
+            if !attr.tokens.is_empty() {
+                return Err(Error::new(
+                    attr.tokens.span(),
+                    "`flatten` does not take any arguments",
+                ));
+            }
+            if result.flatten.is_some() {
+                return Err(duplicate());
+            }
+            result.flatten = Some(attr.span());
+        } else {
+            f.attrs.push(attr);
+        }
+    }
+    Ok(result)
+}
+
+/// Generates the `FieldInfo` expression for a field of type `inner_t`.
+fn field_info_tokens(attrs: &FieldAttrs, inner_t: &syn::Type) -> TokenStream {
+    let default = match &attrs.default {
+        Some(expr) => quote_spanned! {expr.span()=>
+            ::std::option::Option::Some(
+                ::tp_client::contract::properties::dynamic::DynTpProperty::from({
+                    let v: #inner_t = #expr;
+                    v
+                })
+            )
+        },
+        None => quote! { ::std::option::Option::None },
+    };
+
+    let bound = |e: &Option<Box<syn::Expr>>, inclusive: bool| match e {
+        Some(e) if inclusive => quote_spanned! {e.span()=>
+            ::std::ops::Bound::Included((#e) as f64)
+        },
+        Some(e) => quote_spanned! {e.span()=>
+            ::std::ops::Bound::Excluded((#e) as f64)
+        },
+        None => quote! { ::std::ops::Bound::Unbounded },
+    };
+    let range = match &attrs.range {
+        Some(r) => {
+            let start = bound(&r.from, true);
+            let end = bound(&r.to, matches!(r.limits, syn::RangeLimits::Closed(_)));
+            quote! {
+                ::std::option::Option::Some(
+                    ::tp_client::contract::properties::field::FieldRange {
+                        start: #start,
+                        end: #end,
+                    }
+                )
+            }
+        }
+        None => quote! { ::std::option::Option::None },
+    };
+
+    let readonly = attrs.readonly;
+    let doc = attrs.doc.join("\n");
+    let doc = doc.trim();
+    quote! {
+        ::tp_client::contract::properties::field::FieldInfo {
+            default: #default,
+            range: #range,
+            readonly: #readonly,
+            doc: #doc,
+        }
+    }
+}
+
 macro_rules! template_impl {
-    ($macro_name:ident, $handle_type:ty, $trait_name:ty,) => {
-        pub fn $macro_name(mut item: syn::DeriveInput) -> Result<TokenStream> {
+    ($macro_name:ident, $handle_type:ty, $trait_name:ty, $id_type:ident, $ids_type:ident, $ids_fn:ident, $is_states:literal,) => {
+        pub fn $macro_name(attr: TokenStream, mut item: syn::DeriveInput) -> Result<TokenStream> {
+            let c_api_args = c_api::parse_args(attr, $is_states)?;
+
             // ---- Field Names ----
             let type_ids_ident = quote::format_ident!("type_ids");
             let enumerate_types_ident = quote::format_ident!("enumerate_types");
             let field_names_ident = quote::format_ident!("field_names");
+            let field_info_ident = quote::format_ident!("field_info");
 
             // Parse struct
             let s: &mut syn::DataStruct = match item.data {
@@ -66,28 +259,126 @@ macro_rules! template_impl {
 
             // Holds the contents of the field initializer
             let mut field_init_ts = TokenStream::new();
-            // Holds the typeids of each field as the contents of vec![...]
+            // Holds the statements that push the typeids of each field
             let mut typeids = Vec::new();
-            // Holds the TpPropertyTypes of each field;
+            // Holds the statements that push the TpPropertyTypes of each field;
             let mut prop_types = Vec::new();
-            // Holds the names of the fields
+            // Holds the statements that push the names of the fields
             let mut field_names = Vec::new();
+            // Holds the statements that push the `FieldInfo` of each field
+            let mut field_infos = Vec::new();
+            // Holds each field, for the C API
+            let mut c_api_fields = Vec::new();
+            // The index of a field is the number of plain fields before it, plus
+            // the length of each flattened field before it
+            let mut n_plain = 0usize;
+            let mut flattened = Vec::new();
+            let composite = quote! { ::tp_client::contract::properties::composite::IComposite };
             let s_name = &item.ident;
-            for (i, f) in fields.iter_mut().enumerate() {
+            for f in fields.iter_mut() {
                 let inner_t = f.ty.clone();
                 // won't panic because we already checked that the fields were named
                 let f_name = f.ident.as_ref().unwrap();
-                if [&type_ids_ident, &enumerate_types_ident, &field_names_ident].contains(&f_name) {
+                if [
+                    &type_ids_ident,
+                    &enumerate_types_ident,
+                    &field_names_ident,
+                    &field_info_ident,
+                ]
+                .contains(&f_name)
+                {
                     return Err(Error::new(
                         f_name.span(),
                         "this field identifier is reserved",
                     ));
                 }
-                // Wrap field with handle type
-                f.ty = parse2(quote_spanned! {inner_t.span()=>
-                    $handle_type<#inner_t>
-                })
-                .unwrap();
+                let attrs = parse_field_attrs(f, $is_states)?;
+                // Get the name again, since parsing the attributes borrowed `f` mutably
+                let f_name = f.ident.as_ref().unwrap();
+                let idx = quote! { #n_plain #(+ <#flattened as #composite>::LEN)* };
+                let info = field_info_tokens(&attrs, &inner_t);
+
+                if let Some(span) = attrs.flatten {
+                    if attrs.default.is_some() || attrs.range.is_some() || attrs.readonly {
+                        return Err(Error::new(
+                            span,
+                            "`flatten` can't be combined with `default`, `range`, or `readonly`",
+                        ));
+                    }
+                    c_api_fields.push(c_api::Field {
+                        name: f_name.clone(),
+                        ty: inner_t.clone(),
+                        flatten: true,
+                        idx: idx.clone(),
+                    });
+
+                    // Wrap field with the ids of the composite's fields
+                    f.ty = parse2(quote_spanned! {inner_t.span()=>
+                        <#inner_t as #composite>::$ids_type
+                    })
+                    .unwrap();
+                    field_init_ts.extend(quote_spanned! {inner_t.span()=>
+                        #f_name: <#inner_t as #composite>::$ids_fn(#idx, id),
+                    });
+                    typeids.push(quote_spanned! {inner_t.span()=>
+                        v.extend(<#inner_t as #composite>::type_ids());
+                    });
+                    prop_types.push(quote_spanned! {inner_t.span()=>
+                        v.extend(<#inner_t as #composite>::enumerate_types());
+                    });
+                    // The names are only built once, so leaking them is fine
+                    field_names.push(quote_spanned! {f_name.span()=>
+                        v.extend(<#inner_t as #composite>::field_names().iter().map(|name| {
+                            let name = ::std::format!("{}.{}", std::stringify!(#f_name), name);
+                            &*::std::boxed::Box::leak(name.into_boxed_str())
+                        }));
+                    });
+                    field_infos.push(quote! {
+                        v.extend(::std::iter::repeat(#info).take(<#inner_t as #composite>::LEN));
+                    });
+                    flattened.push(inner_t.clone());
+                } else {
+                    c_api_fields.push(c_api::Field {
+                        name: f_name.clone(),
+                        ty: inner_t.clone(),
+                        flatten: false,
+                        idx: idx.clone(),
+                    });
+                    // Wrap field with handle type
+                    f.ty = parse2(quote_spanned! {inner_t.span()=>
+                        $handle_type<#inner_t>
+                    })
+                    .unwrap();
+
+                    // Add field initialization to the list
+                    field_init_ts.extend(quote_spanned! {inner_t.span()=>
+                        #f_name: $handle_type::new(#idx, id),
+                    });
+
+                    // Add typeid of inner type
+                    typeids.push({
+                        quote_spanned! {inner_t.span()=>
+                            v.push(::std::any::TypeId::of::<#inner_t>());
+                        }
+                    });
+
+                    // Add `TpPropertyType`
+                    prop_types.push({
+                        quote_spanned! {inner_t.span()=>
+                            v.push(<#inner_t as ::tp_client::contract::properties::traits::ITpPropertyStatic>::PROPERTY_TYPE);
+                        }
+                    });
+
+                    // Add the field name
+                    field_names.push({
+                        quote_spanned! {f_name.span()=>
+                            v.push(std::stringify!(#f_name));
+                        }
+                    });
+
+                    field_infos.push(quote! { v.push(#info); });
+                    n_plain += 1;
+                }
                 let wrapped_ty = &f.ty;
 
                 // Add getter method
@@ -98,32 +389,6 @@ macro_rules! template_impl {
                         }
                     }
                 });
-
-                // Add field initialization to the list
-                field_init_ts.extend(quote_spanned! {inner_t.span()=>
-                    #f_name: $handle_type::new(#i, id),
-                });
-
-                // Add typeid of inner type
-                typeids.push({
-                    quote_spanned! {inner_t.span()=>
-                        ::std::any::TypeId::of::<#inner_t>()
-                    }
-                });
-
-                // Add `TpPropertyType`
-                prop_types.push({
-                    quote_spanned! {inner_t.span()=>
-                        <#inner_t as ::tp_client::contract::properties::traits::ITpPropertyStatic>::PROPERTY_TYPE
-                    }
-                });
-
-                // Add the field name
-                field_names.push({
-                    quote_spanned! {f_name.span()=>
-                        std::stringify!(#f_name)
-                    }
-                });
             }
 
             // Field-agnostic impl blocks
@@ -139,29 +404,73 @@ macro_rules! template_impl {
                 impl $trait_name for #s_name {
                     fn #type_ids_ident() -> &'static [::std::any::TypeId] {
                         ::tp_client::lazy_static::lazy_static! {
-                            static ref TYPE_IDS: Vec<::std::any::TypeId> = vec![#(#typeids),*];
+                            static ref TYPE_IDS: Vec<::std::any::TypeId> = {
+                                #[allow(unused_mut)]
+                                let mut v = Vec::new();
+                                #(#typeids)*
+                                v
+                            };
                         }
                         TYPE_IDS.as_slice()
                     }
 
                     fn #enumerate_types_ident() -> &'static [::tp_client::contract::properties::dynamic::TpPropertyType] {
                         ::tp_client::lazy_static::lazy_static! {
-                            static ref PROP_TYPES: Vec<::tp_client::contract::properties::dynamic::TpPropertyType> = vec![#(#prop_types),*];
+                            static ref PROP_TYPES: Vec<::tp_client::contract::properties::dynamic::TpPropertyType> = {
+                                #[allow(unused_mut)]
+                                let mut v = Vec::new();
+                                #(#prop_types)*
+                                v
+                            };
                         }
                         PROP_TYPES.as_slice()
                     }
 
                     fn #field_names_ident() -> &'static [&'static str] {
                         ::tp_client::lazy_static::lazy_static! {
-                            static ref FIELD_NAMES: Vec<&'static str> = vec![#(#field_names),*];
+                            static ref FIELD_NAMES: Vec<&'static str> = {
+                                #[allow(unused_mut)]
+                                let mut v = Vec::new();
+                                #(#field_names)*
+                                v
+                            };
                         }
                         FIELD_NAMES.as_slice()
                     }
+
+                    fn #field_info_ident() -> &'static [::tp_client::contract::properties::field::FieldInfo] {
+                        ::tp_client::lazy_static::lazy_static! {
+                            static ref FIELD_INFO: Vec<::tp_client::contract::properties::field::FieldInfo> = {
+                                #[allow(unused_mut)]
+                                let mut v = Vec::new();
+                                #(#field_infos)*
+                                v
+                            };
+                        }
+                        FIELD_INFO.as_slice()
+                    }
                 }
 
             });
 
+            if let Some(args) = &c_api_args {
+                let id_type = quote::format_ident!("{}", std::stringify!($id_type));
+                let ids_type = quote::format_ident!("{}", std::stringify!($ids_type));
+                impl_ts.extend(c_api::generate(
+                    args,
+                    s_name,
+                    &c_api_fields,
+                    &id_type,
+                    &ids_type,
+                ));
+            }
+
             // Concatenate and return item tokens and impl tokens
+            if let Some(args) = &c_api_args {
+                let mut attrs = c_api::struct_attrs(args)?;
+                attrs.append(&mut item.attrs);
+                item.attrs = attrs;
+            }
             let mut result = item.into_token_stream();
             result.extend(impl_ts);
             Ok(result)
@@ -177,10 +486,18 @@ pub(crate) mod imp {
         states,
         ::tp_client::contract::properties::states::StateId,
         ::tp_client::contract::properties::states::IStates,
+        StateId,
+        StateIds,
+        state_ids,
+        true,
     );
     template_impl!(
         channels,
         ::tp_client::contract::properties::channels::ChannelId,
         ::tp_client::contract::properties::channels::IChannels,
+        ChannelId,
+        ChannelIds,
+        channel_ids,
+        false,
     );
 }
@@ -29,3 +29,175 @@ fn test_macros() {
         &[TypeId::of::<f32>(), TypeId::of::<u8>()],
     );
 }
+
+#[test]
+fn test_field_attributes() {
+    #![allow(unused)]
+    use std::ops::Bound;
+    use tp_client::contract::properties::dynamic::DynTpProperty;
+    use tp_client::contract::properties::field::FieldRange;
+    use tp_client::contract::properties::states::IStates;
+
+    #[states]
+    pub struct MyStates {
+        /// How opaque the object is.
+        ///
+        /// 0 is fully transparent.
+        #[default(1.0)]
+        #[range(0.0..=1.0)]
+        opacity: f32,
+        #[readonly]
+        #[default(String::from("player"))]
+        kind: String,
+        #[range(..10)]
+        lives: i8,
+        name: String,
+        #[range(1..)]
+        level: std::primitive::u16,
+    }
+
+    let info = MyStates::field_info();
+    assert_eq!(info.len(), MyStates::field_names().len());
+
+    assert_eq!(info[0].default, Some(DynTpProperty::from(1.0f32)));
+    assert_eq!(
+        info[0].range,
+        Some(FieldRange {
+            start: Bound::Included(0.0),
+            end: Bound::Included(1.0),
+        })
+    );
+    assert!(!info[0].readonly);
+    assert_eq!(
+        info[0].doc,
+        "How opaque the object is.\n\n0 is fully transparent."
+    );
+
+    assert_eq!(
+        info[1].default,
+        Some(DynTpProperty::from(String::from("player")))
+    );
+    assert_eq!(info[1].range, None);
+    assert!(info[1].readonly);
+    assert_eq!(info[1].doc, "");
+
+    assert_eq!(info[2].default, None);
+    assert_eq!(
+        info[2].range,
+        Some(FieldRange {
+            start: Bound::Unbounded,
+            end: Bound::Excluded(10.0),
+        })
+    );
+
+    assert!(!info[3].is_constrained());
+
+    assert_eq!(
+        info[4].range,
+        Some(FieldRange {
+            start: Bound::Included(1.0),
+            end: Bound::Unbounded,
+        })
+    );
+}
+
+#[test]
+fn test_composite() {
+    #![allow(unused)]
+    use tp_client::baseline::{Baseline, BaselineKind};
+    use tp_client::contract::properties::channels::IChannels;
+    use tp_client::contract::properties::composite::{IComposite, Vec3};
+    use tp_client::contract::properties::dynamic::DynTpProperty;
+    use tp_client::contract::properties::states::IStates;
+    use tp_client::contract::{Composite, Contract, ContractDataHandle, ContractId};
+
+    #[derive(Composite, Clone, Debug, PartialEq)]
+    pub struct Pose {
+        pos: Vec3,
+        scale: f32,
+    }
+
+    #[states]
+    pub struct MyStates {
+        id: u32,
+        /// Where the object is.
+        #[flatten]
+        pose: Pose,
+        name: String,
+    }
+
+    #[channels]
+    pub struct MyChannels {
+        #[flatten]
+        pose: Pose,
+    }
+
+    struct MyContract {
+        handle: ContractDataHandle,
+        states: MyStates,
+    }
+    impl Contract for MyContract {
+        type States = MyStates;
+        type Channels = ();
+
+        const ID: ContractId = ContractId {
+            name: "teleportal.test.composite",
+            version: (0, 0, 0),
+        };
+
+        fn new(handle: ContractDataHandle) -> Self {
+            Self {
+                handle,
+                states: MyStates::new(handle),
+            }
+        }
+
+        fn states(&self) -> &Self::States {
+            &self.states
+        }
+
+        fn channels(&self) -> &Self::Channels {
+            &()
+        }
+
+        fn handle(&self) -> ContractDataHandle {
+            self.handle
+        }
+    }
+
+    assert_eq!(
+        MyStates::field_names(),
+        &["id", "pose.pos", "pose.scale", "name"],
+    );
+    assert_eq!(
+        MyStates::type_ids(),
+        &[
+            TypeId::of::<u32>(),
+            TypeId::of::<Vec3>(),
+            TypeId::of::<f32>(),
+            TypeId::of::<String>(),
+        ],
+    );
+    assert_eq!(MyStates::field_info()[1].doc, "Where the object is.");
+    assert_eq!(MyChannels::field_names(), &["pose.pos", "pose.scale"]);
+
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract: MyContract = baseline.register_contract().unwrap();
+    let pose = Pose {
+        pos: Vec3::new(1.0, 2.0, 3.0),
+        scale: 0.5,
+    };
+    let states = std::iter::once(DynTpProperty::from(7u32))
+        .chain(pose.clone().into_properties())
+        .chain(std::iter::once(DynTpProperty::from(String::from("box"))));
+    let obj = baseline
+        .object_create(&contract, states, std::iter::empty())
+        .unwrap();
+
+    let s = contract.states();
+    assert_eq!(s.pose().get(&baseline, obj).unwrap(), pose);
+    let scale = baseline.bind_state(s.pose().scale, obj).unwrap();
+    assert_eq!(baseline.state(scale).unwrap().value, 0.5);
+    let name = baseline.bind_state(s.name(), obj).unwrap();
+    assert_eq!(baseline.state(name).unwrap().value, "box");
+}
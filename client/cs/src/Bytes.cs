@@ -0,0 +1,69 @@
+using generated = tp_client.generated;
+using IntPtr = System.IntPtr;
+using System.Runtime.InteropServices;
+using RSharp;
+
+// This file is manually implemented for now but will be autogenerated eventually
+
+namespace Teleportal.Client.Contract.Properties
+{
+    /// An opaque binary blob. Cloning on the rust side is cheap, so prefer passing
+    /// `Bytes` around over copying it into a `byte[]`.
+    public sealed class Bytes : OpaqueWrapper<Bytes>
+    {
+        public Bytes(Ptr<Bytes> inner, OwnershipSemantics semantics) : base(inner, semantics) { }
+
+        /// Copies `data` into a new `Bytes`.
+        public Bytes(byte[] data) : base(NewHelper(data), OwnershipSemantics.Owned) { }
+
+        private static unsafe Ptr<Bytes> NewHelper(byte[] data)
+        {
+            fixed (byte* first_element = data)
+            {
+                SliceU8 slice = new SliceU8(first_element, (ulong)data.Length);
+                return new Ptr<Bytes>(tp_client__contract__properties__bytes__Bytes__copy(slice));
+            }
+        }
+
+        public unsafe ulong Length
+        {
+            get => generated.__Internal.TpClientContractPropertiesBytesBytesLen(this.Inner.Value.p);
+        }
+
+        /// Copies the data into a managed array.
+        public byte[] ToArray()
+        {
+            unsafe
+            {
+                SliceU8 slice = tp_client__contract__properties__bytes__Bytes__value(this.Inner.Value.p);
+                byte[] result = new byte[slice.len];
+                Marshal.Copy((IntPtr)slice.ptr, result, 0, (int)slice.len);
+                return result;
+            }
+        }
+
+        override protected void NativeDrop(Ptr<Bytes> inner)
+        {
+            generated.__Internal.TpClientContractPropertiesBytesBytesDrop(inner.p);
+        }
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern unsafe IntPtr tp_client__contract__properties__bytes__Bytes__copy(SliceU8 slice);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern unsafe SliceU8 tp_client__contract__properties__bytes__Bytes__value(IntPtr b);
+
+        [StructLayout(LayoutKind.Sequential)]
+        private unsafe struct SliceU8
+        {
+            public byte* ptr;
+            public ulong len;
+
+            public SliceU8(byte* ptr, ulong len)
+            {
+                this.ptr = ptr;
+                this.len = len;
+            }
+        }
+    }
+}
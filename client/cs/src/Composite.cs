@@ -0,0 +1,184 @@
+using generated = tp_client.generated;
+using IntPtr = System.IntPtr;
+using RSharp;
+
+// This file is manually implemented for now but will be autogenerated eventually
+
+namespace Teleportal.Client.Contract.Properties
+{
+    public sealed class Vec2 : OpaqueWrapper<Vec2>
+    {
+        public Vec2(Ptr<Vec2> inner, OwnershipSemantics semantics) : base(inner, semantics) { }
+
+        public unsafe Vec2(float x, float y) : base(
+            new Ptr<Vec2>((IntPtr)generated.__Internal.TpClientContractPropertiesCompositeVec2New(x, y)),
+            OwnershipSemantics.Owned
+        )
+        { }
+
+        public unsafe float X
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeVec2X(this.Inner.Value.p);
+        }
+
+        public unsafe float Y
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeVec2Y(this.Inner.Value.p);
+        }
+
+        override protected void NativeDrop(Ptr<Vec2> inner)
+        {
+            generated.__Internal.TpClientContractPropertiesCompositeVec2Drop(inner.p);
+        }
+    }
+
+    public sealed class Vec3 : OpaqueWrapper<Vec3>
+    {
+        public Vec3(Ptr<Vec3> inner, OwnershipSemantics semantics) : base(inner, semantics) { }
+
+        public unsafe Vec3(float x, float y, float z) : base(
+            new Ptr<Vec3>((IntPtr)generated.__Internal.TpClientContractPropertiesCompositeVec3New(x, y, z)),
+            OwnershipSemantics.Owned
+        )
+        { }
+
+        public unsafe float X
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeVec3X(this.Inner.Value.p);
+        }
+
+        public unsafe float Y
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeVec3Y(this.Inner.Value.p);
+        }
+
+        public unsafe float Z
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeVec3Z(this.Inner.Value.p);
+        }
+
+        override protected void NativeDrop(Ptr<Vec3> inner)
+        {
+            generated.__Internal.TpClientContractPropertiesCompositeVec3Drop(inner.p);
+        }
+    }
+
+    public sealed class Quat : OpaqueWrapper<Quat>
+    {
+        public Quat(Ptr<Quat> inner, OwnershipSemantics semantics) : base(inner, semantics) { }
+
+        public unsafe Quat(float x, float y, float z, float w) : base(
+            new Ptr<Quat>((IntPtr)generated.__Internal.TpClientContractPropertiesCompositeQuatNew(x, y, z, w)),
+            OwnershipSemantics.Owned
+        )
+        { }
+
+        public unsafe float X
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeQuatX(this.Inner.Value.p);
+        }
+
+        public unsafe float Y
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeQuatY(this.Inner.Value.p);
+        }
+
+        public unsafe float Z
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeQuatZ(this.Inner.Value.p);
+        }
+
+        public unsafe float W
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeQuatW(this.Inner.Value.p);
+        }
+
+        override protected void NativeDrop(Ptr<Quat> inner)
+        {
+            generated.__Internal.TpClientContractPropertiesCompositeQuatDrop(inner.p);
+        }
+    }
+
+    public sealed class Color : OpaqueWrapper<Color>
+    {
+        public Color(Ptr<Color> inner, OwnershipSemantics semantics) : base(inner, semantics) { }
+
+        public unsafe Color(float r, float g, float b, float a) : base(
+            new Ptr<Color>((IntPtr)generated.__Internal.TpClientContractPropertiesCompositeColorNew(r, g, b, a)),
+            OwnershipSemantics.Owned
+        )
+        { }
+
+        public unsafe float R
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeColorR(this.Inner.Value.p);
+        }
+
+        public unsafe float G
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeColorG(this.Inner.Value.p);
+        }
+
+        public unsafe float B
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeColorB(this.Inner.Value.p);
+        }
+
+        public unsafe float A
+        {
+            get => generated.__Internal.TpClientContractPropertiesCompositeColorA(this.Inner.Value.p);
+        }
+
+        override protected void NativeDrop(Ptr<Color> inner)
+        {
+            generated.__Internal.TpClientContractPropertiesCompositeColorDrop(inner.p);
+        }
+    }
+
+    public sealed class Transform : OpaqueWrapper<Transform>
+    {
+        public Transform(Ptr<Transform> inner, OwnershipSemantics semantics) : base(inner, semantics) { }
+
+        public unsafe Transform(Vec3 translation, Quat rotation, Vec3 scale) : base(
+            new Ptr<Transform>((IntPtr)generated.__Internal.TpClientContractPropertiesCompositeTransformNew(
+                translation.Inner.Value.p,
+                rotation.Inner.Value.p,
+                scale.Inner.Value.p
+            )),
+            OwnershipSemantics.Owned
+        )
+        { }
+
+        public unsafe Vec3 Translation
+        {
+            get
+            {
+                var raw_ptr = generated.__Internal.TpClientContractPropertiesCompositeTransformTranslation(this.Inner.Value.p);
+                return new Vec3(new Ptr<Vec3>((IntPtr)raw_ptr), OwnershipSemantics.SharedRef);
+            }
+        }
+
+        public unsafe Quat Rotation
+        {
+            get
+            {
+                var raw_ptr = generated.__Internal.TpClientContractPropertiesCompositeTransformRotation(this.Inner.Value.p);
+                return new Quat(new Ptr<Quat>((IntPtr)raw_ptr), OwnershipSemantics.SharedRef);
+            }
+        }
+
+        public unsafe Vec3 Scale
+        {
+            get
+            {
+                var raw_ptr = generated.__Internal.TpClientContractPropertiesCompositeTransformScale(this.Inner.Value.p);
+                return new Vec3(new Ptr<Vec3>((IntPtr)raw_ptr), OwnershipSemantics.SharedRef);
+            }
+        }
+
+        override protected void NativeDrop(Ptr<Transform> inner)
+        {
+            generated.__Internal.TpClientContractPropertiesCompositeTransformDrop(inner.p);
+        }
+    }
+}
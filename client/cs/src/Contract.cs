@@ -34,9 +34,35 @@ namespace Teleportal.Client.Contract
             }
         }
 
+        public int ObjectsCount
+        {
+            get => (int)generated.__Internal.TpClientContractContractDataObjectsLen(this.Inner.Value.p);
+        }
+
+        /// Iterates over the objects without copying them. The objects are
+        /// borrowed from this `ContractData`.
         public IEnumerable<ObjectHandle> Objects
         {
-            get => throw new System.Exception("todo");
+            get
+            {
+                var iter = generated.__Internal.TpClientContractContractDataObjectsIter(this.Inner.Value.p);
+                try
+                {
+                    while (true)
+                    {
+                        var p = generated.__Internal.TpClientContractContractDataObjectsIterNext(iter);
+                        if (p == System.IntPtr.Zero)
+                        {
+                            yield break;
+                        }
+                        yield return new ObjectHandle(new Ptr<ObjectHandle>(p), OwnershipSemantics.SharedRef);
+                    }
+                }
+                finally
+                {
+                    generated.__Internal.TpClientContractContractDataObjectsIterDrop(iter);
+                }
+            }
         }
     }
 
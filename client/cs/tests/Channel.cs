@@ -0,0 +1,39 @@
+using Channels = Teleportal.Client.Contract.Properties.Channels;
+using Xunit;
+using RSharp;
+using InvalidOperationException = System.InvalidOperationException;
+using RBox_U8 = RSharp.RBox_U8;
+
+public class TestChannel
+{
+    private readonly Xunit.Abstractions.ITestOutputHelper output;
+
+    public TestChannel(Xunit.Abstractions.ITestOutputHelper output)
+    {
+        this.output = output;
+    }
+
+    [Fact]
+    public void TestInsert()
+    {
+        var c = new Channels.Channel_U8(new Channels.RVec_Keyframe_U8());
+        Assert.Equal(0, c.Count);
+
+        c.Insert(new Channels.Keyframe_U8(new RBox_U8(1), 1.0));
+        c.Insert(new Channels.Keyframe_U8(new RBox_U8(0), 0.0));
+        var kf = new Channels.Keyframe_U8(new RBox_U8(2), 2.0);
+        c.Insert(kf);
+        Assert.Throws<InvalidOperationException>(() => kf.Value);
+
+        Assert.Equal(3, c.Count);
+        for (int i = 0; i < c.Count; i++)
+        {
+            Assert.Equal(i, c[i].Value.Value);
+            Assert.Equal((double)i, c[i].Time);
+        }
+        Assert.Throws<System.IndexOutOfRangeException>(() => c[3]);
+
+        c.Dispose();
+        Assert.Throws<InvalidOperationException>(() => c.Count);
+    }
+}
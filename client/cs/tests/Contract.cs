@@ -42,4 +42,32 @@ public class TestContract
         b.Dispose();
         Assert.Throws<InvalidOperationException>(() => b.IsMain);
     }
+
+    [Fact]
+    public void TestObjects()
+    {
+        var b = new Baseline(true);
+        var c = ExampleContract.register(b);
+        var c_data = b.ContractData(c.Handle);
+        Assert.Equal(0, c_data.ObjectsCount);
+        Assert.Empty(c_data.Objects);
+
+        c_data.Dispose();
+        Assert.Throws<InvalidOperationException>(() => c_data.ObjectsCount);
+    }
+
+    [Fact]
+    public void TestErrorsAreThrown()
+    {
+        var b = new Baseline(true);
+        var c = ExampleContract.register(b);
+
+        // Errors from rust are thrown as exceptions instead of aborting.
+        var e = Assert.Throws<InvalidOperationException>(() => ExampleContract.register(b));
+        Assert.Contains("already", e.Message);
+
+        // `oh_0` and `ch_0` have no default.
+        Assert.Throws<System.ArgumentNullException>(() => c.ObjectCreate(b, 0, 1, -1, -2, 0.0f, 1.0f));
+        Assert.Equal(0, b.ContractData(c.Handle).ObjectsCount);
+    }
 }
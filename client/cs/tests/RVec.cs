@@ -0,0 +1,47 @@
+using Channels = Teleportal.Client.Contract.Properties.Channels;
+using Xunit;
+using RSharp;
+using InvalidOperationException = System.InvalidOperationException;
+using RBox_U8 = RSharp.RBox_U8;
+
+public class TestRVec
+{
+    private readonly Xunit.Abstractions.ITestOutputHelper output;
+
+    public TestRVec(Xunit.Abstractions.ITestOutputHelper output)
+    {
+        this.output = output;
+    }
+
+    [Fact]
+    public void TestPushAndIndex()
+    {
+        var v = new Channels.RVec_Keyframe_U8();
+        Assert.Equal(0, v.Count);
+
+        for (byte i = 0; i < 4; i++)
+        {
+            v.push(new Channels.Keyframe_U8(new RBox_U8(i), i * 0.5));
+        }
+        Assert.Equal(4, v.Count);
+
+        int count = 0;
+        foreach (var kf in v)
+        {
+            Assert.Equal(count, kf.Value.Value);
+            Assert.Equal(count * 0.5, kf.Time);
+            count++;
+        }
+        Assert.Equal(4, count);
+        Assert.Throws<System.IndexOutOfRangeException>(() => v[4]);
+
+        v[0] = new Channels.Keyframe_U8(new RBox_U8(10), 0.0);
+        Assert.Equal(10, v[0].Value.Value);
+
+        var borrowed = v[1];
+        Assert.Throws<OwnershipException>(() => v.push(borrowed));
+
+        v.Dispose();
+        Assert.Throws<InvalidOperationException>(() => v.Count);
+    }
+}
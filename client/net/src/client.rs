@@ -0,0 +1,323 @@
+use crate::protocol::{self, ClientId, ClientMessage, EnteredObject, NetAction, ServerMessage};
+use crate::snapshot;
+use crate::transport::Connection;
+
+use eyre::{eyre, Result, WrapErr};
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use tp_client::apply_to_state_handle;
+use tp_client::baseline::BaselineKind;
+use tp_client::contract::properties::bytes::Bytes;
+use tp_client::contract::properties::dynamic::{DynTpProperty, TpPrimitiveType};
+use tp_client::contract::properties::states::StateHandle;
+use tp_client::contract::properties::traits::TpMap;
+use tp_client::engine::ActionSender;
+use tp_client::object::ObjectHandle;
+use tp_client::realm::Realm;
+use tp_client::Engine;
+
+/// Something that happened during [`Client::poll`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientEvent {
+    /// The realm was replaced with a snapshot from the server. Any handles into
+    /// the old realm are no longer valid.
+    Snapshot,
+    /// An object is now sent to the client, because it entered the client's
+    /// interest. Comes after the `Snapshot` that added it, if it came with
+    /// one, and holds the server's handle. Objects that enter or leave on
+    /// their own don't change the handles of other objects.
+    ObjectEntered(ObjectHandle),
+    /// An object is no longer sent to the client, because it left the client's
+    /// interest or was removed. Holds the server's handle.
+    ObjectLeft(ObjectHandle),
+    /// A collaction was applied by the server, and then to the local realm.
+    Applied { origin: ClientId, seq: u64 },
+    /// A collaction from this client was rejected by the server.
+    Rejected { seq: u64, reason: String },
+}
+
+/// Keeps a copy of a [`Server`](crate::Server)'s realm in sync.
+///
+/// Collactions submitted by the client are only applied locally once the
+/// server approves them, so the realm always matches the server's as of the
+/// last message received. The realm only holds the objects that the server
+/// decided are relevant to the client, see [`Interest`](crate::Interest).
+pub struct Client<C: Connection> {
+    conn: C,
+    engine: Engine,
+    sender: ActionSender,
+    id: Option<ClientId>,
+    next_seq: u64,
+    /// The seqs of merges that were applied locally, but not by the server yet
+    merges: HashSet<u64>,
+    /// The server's handles, to the local ones
+    to_local: HashMap<ObjectHandle, ObjectHandle>,
+    /// The local handles, to the server's ones
+    to_remote: HashMap<ObjectHandle, ObjectHandle>,
+}
+impl<C: Connection> Client<C> {
+    /// `realm` is replaced by the server's snapshot, once it arrives.
+    pub fn new(realm: Realm, conn: C) -> Self {
+        let (mut engine, sender) = Engine::new(realm, None);
+        // The server enforces locks, and the local realm only applies what it
+        // approved.
+        engine.set_lock_checks(false);
+        Self {
+            conn,
+            engine,
+            sender,
+            id: None,
+            next_seq: 0,
+            merges: HashSet::new(),
+            to_local: HashMap::new(),
+            to_remote: HashMap::new(),
+        }
+    }
+
+    /// `None` until the server has welcomed the client.
+    pub fn id(&self) -> Option<ClientId> {
+        self.id
+    }
+
+    /// Changes should be made with [`Client::submit`], since any other changes
+    /// are overwritten by the server.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// The local handle of an object, from the server's handle.
+    pub fn local_object(&self, remote: ObjectHandle) -> Option<ObjectHandle> {
+        self.to_local.get(&remote).copied()
+    }
+
+    /// The server's handle of an object, from the local handle.
+    pub fn remote_object(&self, local: ObjectHandle) -> Option<ObjectHandle> {
+        self.to_remote.get(&local).copied()
+    }
+
+    /// Asks the server to apply `actions`, which use local handles. Returns the
+    /// seq that the server's reply will have.
+    pub fn submit(&mut self, actions: Vec<NetAction>) -> Result<u64> {
+        let seq = self.next_seq;
+        let to_remote = |local: ObjectHandle| {
+            self.remote_object(local)
+                .ok_or_else(|| eyre!("{:?} isn't known to the server", local))
+        };
+        let msg = ClientMessage::Submit { seq, actions }.encode(&to_remote)?;
+        self.conn.send(&msg)?;
+        self.next_seq += 1;
+        Ok(seq)
+    }
+
+    /// Merges the CRDT `delta` into a `Bytes` state, see
+    /// [`crdt`](tp_client::contract::properties::crdt). Unlike
+    /// [`Client::submit`], the merge is applied locally right away, since
+    /// merges don't conflict with each other. Merging the server's copy again
+    /// when it arrives has no effect. If the server rejects the merge, such as
+    /// when someone else holds a lock on the state, the client resyncs. Returns
+    /// the seq that the server's reply will have.
+    ///
+    /// # Errors
+    /// Will error if the state isn't `Bytes`, or `delta` can't be merged into
+    /// it.
+    pub fn merge(&mut self, object: ObjectHandle, state: usize, delta: Bytes) -> Result<u64> {
+        let action = NetAction::Merge {
+            object,
+            state,
+            delta,
+        };
+        let origin = self.id.unwrap_or(ClientId::SERVER);
+        self.apply(origin, std::slice::from_ref(&action))?;
+        let seq = self.submit(vec![action])?;
+        self.merges.insert(seq);
+        Ok(seq)
+    }
+
+    /// Handles everything that the server has sent.
+    ///
+    /// # Errors
+    /// Will error if the connection was closed, or the server sent something
+    /// that can't be decoded.
+    pub fn poll(&mut self) -> Result<Vec<ClientEvent>> {
+        let mut events = Vec::new();
+        while let Some(bytes) = self.conn.try_recv()? {
+            let to_local = |remote: ObjectHandle| {
+                self.local_object(remote)
+                    .ok_or_else(|| eyre!("{:?} isn't in the local realm", remote))
+            };
+            let msg = match ServerMessage::decode(&bytes, &to_local) {
+                Ok(msg) => msg,
+                Err(err) => {
+                    // Most likely an object that the local realm doesn't have
+                    tracing::warn!("Failed to decode message, resyncing: {:#}", err);
+                    self.request_snapshot()?;
+                    continue;
+                }
+            };
+            match msg {
+                ServerMessage::Welcome { client } => self.id = Some(client),
+                ServerMessage::Snapshot { data, objects } => {
+                    let before: HashSet<ObjectHandle> = self.to_local.keys().copied().collect();
+                    self.load_snapshot(&data, &objects)
+                        .wrap_err("Failed to load snapshot")?;
+                    events.push(ClientEvent::Snapshot);
+                    events.extend(
+                        objects
+                            .iter()
+                            .filter(|o| !before.contains(o))
+                            .map(|o| ClientEvent::ObjectEntered(*o)),
+                    );
+                    events.extend(
+                        before
+                            .into_iter()
+                            .filter(|o| !self.to_local.contains_key(o))
+                            .map(ClientEvent::ObjectLeft),
+                    );
+                }
+                ServerMessage::Update { entered, left } => {
+                    let objects: Vec<ObjectHandle> = entered.iter().map(|o| o.object).collect();
+                    if let Err(err) = self.load_update(entered, &left) {
+                        tracing::warn!("Failed to update objects, resyncing: {:#}", err);
+                        self.request_snapshot()?;
+                        continue;
+                    }
+                    events.extend(objects.into_iter().map(ClientEvent::ObjectEntered));
+                    events.extend(left.into_iter().map(ClientEvent::ObjectLeft));
+                }
+                ServerMessage::Applied {
+                    origin,
+                    seq,
+                    actions,
+                } => {
+                    if Some(origin) == self.id {
+                        self.merges.remove(&seq);
+                    }
+                    if let Err(err) = self.apply(origin, &actions) {
+                        tracing::warn!("Failed to apply collaction, resyncing: {:#}", err);
+                        self.request_snapshot()?;
+                        continue;
+                    }
+                    events.push(ClientEvent::Applied { origin, seq });
+                }
+                ServerMessage::Rejected { seq, reason } => {
+                    // The local realm has a merge that the server's doesn't
+                    if self.merges.remove(&seq) {
+                        self.request_snapshot()?;
+                    }
+                    events.push(ClientEvent::Rejected { seq, reason })
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    fn apply(&mut self, origin: ClientId, actions: &[NetAction]) -> Result<()> {
+        // Replies to the client's own collactions may have nothing left in them
+        if actions.is_empty() {
+            return Ok(());
+        }
+        let baseline = self.engine.realm().baseline(BaselineKind::Fork);
+        let collaction = protocol::to_collaction(actions, baseline)?.with_owner(origin.into());
+        self.sender
+            .send(collaction)
+            .expect("The engine holds a receiver");
+        match self.engine.try_apply() {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(_)) => Err(eyre!("The collaction was rejected by the local engine")),
+            Err(_) => unreachable!("The collaction was just sent"),
+        }
+    }
+
+    fn request_snapshot(&mut self) -> Result<()> {
+        let msg = ClientMessage::RequestSnapshot.encode(&crate::codec::same_handle)?;
+        self.conn.send(&msg)
+    }
+
+    /// Replaces the fork baseline with the snapshot.
+    fn load_snapshot(&mut self, data: &[u8], objects: &[ObjectHandle]) -> Result<()> {
+        let (baseline, to_local) = snapshot::load(data, objects, BaselineKind::Fork)?;
+        *self.engine.realm_mut().baseline_mut(BaselineKind::Fork) = baseline;
+        self.to_remote = to_local.iter().map(|(r, l)| (*l, *r)).collect();
+        self.to_local = to_local;
+        Ok(())
+    }
+
+    /// Removes the objects that `left`, and creates the ones that `entered`.
+    fn load_update(&mut self, entered: Vec<EnteredObject>, left: &[ObjectHandle]) -> Result<()> {
+        let baseline = self.engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        for remote in left {
+            let Some(local) = self.to_local.remove(remote) else {
+                continue;
+            };
+            self.to_remote.remove(&local);
+            // Already removed along with its parent
+            if baseline.object(local).is_ok() {
+                baseline.object_remove_dyn(local)?;
+            }
+        }
+
+        // The states still hold the server's handles, which are only
+        // translated once every object has been created, since they can
+        // reference each other.
+        let mut created = Vec::with_capacity(entered.len());
+        for o in &entered {
+            let (name, version) = &o.contract;
+            let contract = baseline
+                .iter_contracts()
+                .find(|c| c.id().name == name && c.id().version == *version)
+                .ok_or_else(|| eyre!("There is no contract {} {:?}", name, version))?;
+            let local =
+                baseline.object_create(&contract, o.states.iter().cloned(), [].into_iter())?;
+            self.to_local.insert(o.object, local);
+            self.to_remote.insert(local, o.object);
+            created.push(local);
+        }
+
+        let to_local = |remote: ObjectHandle| {
+            self.to_local
+                .get(&remote)
+                .copied()
+                .ok_or_else(|| eyre!("{:?} isn't in the local realm", remote))
+        };
+        for (o, local) in entered.into_iter().zip(created) {
+            if let Some((parent, idx)) = o.parent {
+                baseline.object_reparent(local, Some(to_local(parent)?), Some(idx))?;
+            }
+            for (state, mut value) in o.states.into_iter().enumerate() {
+                if value.prop_type().primitive_type() != TpPrimitiveType::ObjectHandle {
+                    continue;
+                }
+                translate_handles(&mut value, &to_local)?;
+                let handle = protocol::bind_state(baseline, local, state)?;
+                apply_to_state_handle!(handle, |h: StateHandle<_>| -> Result<()> {
+                    let value = value.cast_mut().expect("Types were checked on creation");
+                    mem::swap(&mut baseline.state_mut(h)?.value, value);
+                    Ok(())
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the server's handles in `value` with local ones.
+fn translate_handles(
+    value: &mut DynTpProperty,
+    to_local: impl Fn(ObjectHandle) -> Result<ObjectHandle>,
+) -> Result<()> {
+    let translate = |h: &mut ObjectHandle| -> Result<()> {
+        *h = to_local(*h)?;
+        Ok(())
+    };
+    if let Some(h) = value.cast_mut::<ObjectHandle>() {
+        translate(h)
+    } else if let Some(h) = value.cast_mut::<Option<ObjectHandle>>() {
+        h.iter_mut().try_for_each(translate)
+    } else if let Some(v) = value.cast_mut::<Vec<ObjectHandle>>() {
+        v.iter_mut().try_for_each(translate)
+    } else if let Some(m) = value.cast_mut::<TpMap<ObjectHandle>>() {
+        m.values_mut().try_for_each(translate)
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,451 @@
+//! The binary encoding of the values of properties, as sent over the network.
+//!
+//! Everything is little endian. Lengths and indices are `u32`s, and values of
+//! dynamic type are prefixed with their [`TpPropertyType`] so that the
+//! receiver can check them against its own contracts.
+//!
+//! `ObjectHandle`s are only meaningful to the baseline that they came from, so
+//! [`Writer`] and [`Reader`] translate them to and from the server's handles.
+//! `ContractDataHandle`s can't be sent yet.
+
+use eyre::{eyre, Result, WrapErr};
+use tp_client::apply_to_prop;
+use tp_client::contract::properties::bytes::Bytes;
+use tp_client::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
+use tp_client::contract::properties::dynamic::{
+    DynTpPrimitive, DynTpProperty, TpPrimitiveType, TpPropertyType,
+};
+use tp_client::contract::properties::traits::TpMap;
+use tp_client::contract::ContractDataHandle;
+use tp_client::object::ObjectHandle;
+
+use arena::generational_arena as ga;
+
+/// Translates an `ObjectHandle` between a local baseline and the server's.
+pub(crate) type HandleMap<'a> = &'a dyn Fn(ObjectHandle) -> Result<ObjectHandle>;
+
+/// The identity `HandleMap`, used by the server.
+pub(crate) fn same_handle(handle: ObjectHandle) -> Result<ObjectHandle> {
+    Ok(handle)
+}
+
+pub(crate) struct Writer<'a> {
+    buf: Vec<u8>,
+    to_wire: HandleMap<'a>,
+}
+impl<'a> Writer<'a> {
+    pub fn new(to_wire: HandleMap<'a>) -> Self {
+        Self {
+            buf: Vec::new(),
+            to_wire,
+        }
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes)
+    }
+
+    pub fn write_len(&mut self, len: usize) -> Result<()> {
+        let len = u32::try_from(len).wrap_err("Too many elements to encode")?;
+        len.encode(self)
+    }
+
+    /// Writes the handle as is, without translating it.
+    pub fn write_raw_handle(&mut self, handle: ObjectHandle) -> Result<()> {
+        let (index, generation) = ga::Index::from(handle).into_raw_parts();
+        self.write_len(index)?;
+        generation.encode(self)
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    buf: &'a [u8],
+    from_wire: HandleMap<'a>,
+}
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8], from_wire: HandleMap<'a>) -> Self {
+        Self { buf, from_wire }
+    }
+
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.buf.len() < n {
+            return Err(eyre!("Message ended unexpectedly"));
+        }
+        let (bytes, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Ok(bytes)
+    }
+
+    pub fn read_len(&mut self) -> Result<usize> {
+        Ok(u32::decode(self)? as usize)
+    }
+
+    /// Reads a handle as is, without translating it.
+    pub fn read_raw_handle(&mut self) -> Result<ObjectHandle> {
+        let index = self.read_len()?;
+        let generation = u64::decode(self)?;
+        Ok(ObjectHandle::new(ga::Index::from_raw_parts(
+            index, generation,
+        )))
+    }
+
+    /// Stops translating handles, for the rest of what is read.
+    pub fn untranslated(&mut self) {
+        self.from_wire = &same_handle;
+    }
+
+    /// Errors if there is anything left to read.
+    pub fn finish(self) -> Result<()> {
+        if self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("Message had {} trailing bytes", self.buf.len()))
+        }
+    }
+}
+
+pub(crate) trait Encode {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()>;
+}
+
+pub(crate) trait Decode: Sized {
+    fn decode(r: &mut Reader<'_>) -> Result<Self>;
+}
+
+// ---- Primitives ----
+
+macro_rules! numbers {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl Encode for $t {
+                fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+                    w.write_bytes(&self.to_le_bytes());
+                    Ok(())
+                }
+            }
+            impl Decode for $t {
+                fn decode(r: &mut Reader<'_>) -> Result<Self> {
+                    let bytes = r.read_bytes(std::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_le_bytes(bytes.try_into().expect("Length was checked")))
+                }
+            }
+        )+
+    };
+}
+numbers!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl Encode for bool {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        u8::from(*self).encode(w)
+    }
+}
+impl Decode for bool {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        match u8::decode(r)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(eyre!("{} is not a bool", b)),
+        }
+    }
+}
+
+impl Encode for String {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        w.write_len(self.len())?;
+        w.write_bytes(self.as_bytes());
+        Ok(())
+    }
+}
+impl Decode for String {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let len = r.read_len()?;
+        let bytes = r.read_bytes(len)?;
+        String::from_utf8(bytes.to_vec()).wrap_err("String was not utf-8")
+    }
+}
+
+impl Encode for Bytes {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        w.write_len(self.len())?;
+        w.write_bytes(self.as_slice());
+        Ok(())
+    }
+}
+impl Decode for Bytes {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let len = r.read_len()?;
+        Ok(Bytes::from(r.read_bytes(len)?))
+    }
+}
+
+impl Encode for ObjectHandle {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        let handle = (w.to_wire)(*self)?;
+        w.write_raw_handle(handle)
+    }
+}
+impl Decode for ObjectHandle {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let handle = r.read_raw_handle()?;
+        (r.from_wire)(handle)
+    }
+}
+
+impl Encode for ContractDataHandle {
+    fn encode(&self, _w: &mut Writer<'_>) -> Result<()> {
+        Err(eyre!(
+            "ContractDataHandles can't be sent over the network yet"
+        ))
+    }
+}
+impl Decode for ContractDataHandle {
+    fn decode(_r: &mut Reader<'_>) -> Result<Self> {
+        Err(eyre!(
+            "ContractDataHandles can't be sent over the network yet"
+        ))
+    }
+}
+
+macro_rules! composites {
+    ($($t:ident { $($field:ident),+ $(,)? }),+ $(,)?) => {
+        $(
+            impl Encode for $t {
+                fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+                    $(self.$field.encode(w)?;)+
+                    Ok(())
+                }
+            }
+            impl Decode for $t {
+                fn decode(r: &mut Reader<'_>) -> Result<Self> {
+                    Ok(Self { $($field: Decode::decode(r)?),+ })
+                }
+            }
+        )+
+    };
+}
+composites!(
+    Vec2 { x, y },
+    Vec3 { x, y, z },
+    Quat { x, y, z, w },
+    Color { r, g, b, a },
+    Transform {
+        translation,
+        rotation,
+        scale
+    },
+);
+
+// ---- Containers ----
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        w.write_len(self.len())?;
+        self.iter().try_for_each(|v| v.encode(w))
+    }
+}
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let len = r.read_len()?;
+        (0..len).map(|_| T::decode(r)).collect()
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        self.is_some().encode(w)?;
+        self.as_ref().map_or(Ok(()), |v| v.encode(w))
+    }
+}
+impl<T: Decode> Decode for Option<T> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        bool::decode(r)?.then(|| T::decode(r)).transpose()
+    }
+}
+
+impl<T: Encode> Encode for TpMap<T> {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        w.write_len(self.len())?;
+        self.iter().try_for_each(|(k, v)| {
+            k.encode(w)?;
+            v.encode(w)
+        })
+    }
+}
+impl<T: Decode> Decode for TpMap<T> {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let len = r.read_len()?;
+        (0..len)
+            .map(|_| Ok((String::decode(r)?, T::decode(r)?)))
+            .collect()
+    }
+}
+
+// ---- Dynamic types ----
+
+/// Expands `$body` once per primitive type, with `$t` as an alias of it.
+macro_rules! with_primitive {
+    ($typ:expr, $t:ident => $body:expr) => {
+        with_primitive!(
+            @ $typ, $t, $body;
+            U8 u8, U16 u16, U32 u32, U64 u64, I8 i8, I16 i16, I32 i32, I64 i64,
+            Bool bool, F32 f32, F64 f64, String String, ObjectHandle ObjectHandle,
+            ContractDataHandle ContractDataHandle, Vec2 Vec2, Vec3 Vec3, Quat Quat,
+            Color Color, Transform Transform, Bytes Bytes
+        )
+    };
+    (@ $typ:expr, $t:ident, $body:expr; $($variant:ident $ty:ty),+) => {
+        match $typ {
+            $(TpPrimitiveType::$variant => {
+                #[allow(dead_code)]
+                type $t = $ty;
+                $body
+            })+
+        }
+    };
+}
+
+impl Encode for TpPropertyType {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        let container: u8 = match self {
+            Self::Primitive(_) => 0,
+            Self::Vec(_) => 1,
+            Self::Option(_) => 2,
+            Self::Map(_) => 3,
+        };
+        let primitive = TpPrimitiveType::ALL
+            .iter()
+            .position(|p| *p == self.primitive_type())
+            .expect("`ALL` has every primitive type");
+        container.encode(w)?;
+        (primitive as u8).encode(w)
+    }
+}
+impl Decode for TpPropertyType {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let container = u8::decode(r)?;
+        let primitive = u8::decode(r)?;
+        let primitive = *TpPrimitiveType::ALL
+            .get(primitive as usize)
+            .ok_or_else(|| eyre!("{} is not a primitive type", primitive))?;
+        match container {
+            0 => Ok(Self::Primitive(primitive)),
+            1 => Ok(Self::Vec(primitive)),
+            2 => Ok(Self::Option(primitive)),
+            3 => Ok(Self::Map(primitive)),
+            c => Err(eyre!("{} is not a container type", c)),
+        }
+    }
+}
+
+impl Encode for DynTpProperty {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        self.prop_type().encode(w)?;
+        apply_to_prop!(self, |v| Encode::encode(v, w))
+    }
+}
+impl Decode for DynTpProperty {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let typ = TpPropertyType::decode(r)?;
+        with_primitive!(typ.primitive_type(), T => match typ {
+            TpPropertyType::Primitive(_) => T::decode(r).map(Self::from),
+            TpPropertyType::Vec(_) => Vec::<T>::decode(r).map(Self::from),
+            TpPropertyType::Option(_) => Option::<T>::decode(r).map(Self::from),
+            TpPropertyType::Map(_) => TpMap::<T>::decode(r).map(Self::from),
+        })
+    }
+}
+
+impl Encode for DynTpPrimitive {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        // Encoded the same as a `DynTpProperty`, so that the type is checked.
+        DynTpProperty::from(self.clone()).encode(w)
+    }
+}
+impl Decode for DynTpPrimitive {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        match DynTpProperty::decode(r)? {
+            DynTpProperty::Primitive(p) => Ok(p),
+            other => Err(eyre!("Expected a primitive, got a {}", other.prop_type())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(value: DynTpProperty) {
+        let mut w = Writer::new(&same_handle);
+        value.encode(&mut w).unwrap();
+        let bytes = w.finish();
+        let mut r = Reader::new(&bytes, &same_handle);
+        assert_eq!(DynTpProperty::decode(&mut r).unwrap(), value);
+        r.finish().unwrap();
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        roundtrip(7u8.into());
+        roundtrip((-7i64).into());
+        roundtrip(true.into());
+        roundtrip(String::from("hello").into());
+        roundtrip(Some(Vec3::new(1.0, 2.0, 3.0)).into());
+        roundtrip(None::<Quat>.into());
+        roundtrip(Transform::default().into());
+        roundtrip(Bytes::from(vec![1, 2, 3]).into());
+        roundtrip(vec![Color::new(0.0, 0.5, 1.0, 1.0)].into());
+        let map: TpMap<u32> = [("a".to_string(), 1)].into_iter().collect();
+        roundtrip(map.into());
+        roundtrip(ObjectHandle::new(ga::Index::from_raw_parts(3, 4)).into());
+    }
+
+    #[test]
+    fn test_handles_are_translated() {
+        let local = ObjectHandle::new(ga::Index::from_raw_parts(1, 0));
+        let remote = ObjectHandle::new(ga::Index::from_raw_parts(5, 2));
+
+        let to_wire = |h: ObjectHandle| {
+            (h == local)
+                .then_some(remote)
+                .ok_or_else(|| eyre!("Unknown handle"))
+        };
+        let mut w = Writer::new(&to_wire);
+        DynTpProperty::from(vec![local]).encode(&mut w).unwrap();
+        let bytes = w.finish();
+
+        let mut r = Reader::new(&bytes, &same_handle);
+        assert_eq!(
+            DynTpProperty::decode(&mut r).unwrap(),
+            DynTpProperty::from(vec![remote])
+        );
+
+        let mut w = Writer::new(&to_wire);
+        assert!(DynTpProperty::from(remote).encode(&mut w).is_err());
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mut w = Writer::new(&same_handle);
+        String::from("hello").encode(&mut w).unwrap();
+        let bytes = w.finish();
+
+        // Truncated
+        let mut r = Reader::new(&bytes[..bytes.len() - 1], &same_handle);
+        assert!(String::decode(&mut r).is_err());
+        // Trailing bytes
+        let mut r = Reader::new(&bytes, &same_handle);
+        u32::decode(&mut r).unwrap();
+        assert!(r.finish().is_err());
+        // Not a type
+        let mut r = Reader::new(&[9, 0], &same_handle);
+        assert!(TpPropertyType::decode(&mut r).is_err());
+
+        let mut w = Writer::new(&same_handle);
+        let handle = ContractDataHandle::new(ga::Index::from_raw_parts(0, 0));
+        assert!(DynTpProperty::from(handle).encode(&mut w).is_err());
+    }
+}
@@ -0,0 +1,292 @@
+//! Which objects each client is sent.
+
+use crate::protocol::{self, NetAction};
+
+use std::collections::HashSet;
+use tp_client::baseline::Baseline;
+use tp_client::contract::properties::dynamic::{TpPrimitiveType, TpPropertyType};
+use tp_client::contract::properties::states::dyn_handle::DynStateHandlePrimitive;
+use tp_client::contract::properties::states::{DynStateHandle, StateId};
+use tp_client::contract::ContractId;
+use tp_client::object::ObjectHandle;
+use tp_client::spatial::{Point, SpatialIndex};
+
+/// An area of space that a client is interested in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Proximity {
+    /// Within `radius` of a fixed point.
+    Point { center: Point, radius: f32 },
+    /// Within `radius` of an object, wherever it moves. Usually the object
+    /// that the client controls.
+    Object { object: ObjectHandle, radius: f32 },
+}
+
+/// Describes the objects that a client is interested in. A client is sent
+/// every object that matches any part of its `Interest`, along with every
+/// object that those reference through `ObjectHandle` states. The ancestors and
+/// descendants of all of these are sent too, so that hierarchies arrive whole.
+///
+/// Objects are the server's handles. Proximity is answered by the server's
+/// [`SpatialIndex`], so it only finds objects whose contracts it tracks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Interest {
+    /// Matches every object, regardless of the other fields.
+    pub everything: bool,
+    /// Matches the objects of these contracts.
+    pub contracts: HashSet<ContractId>,
+    /// Matches these objects, as long as they exist.
+    pub objects: HashSet<ObjectHandle>,
+    /// Matches the objects in any of these areas.
+    pub proximity: Vec<Proximity>,
+}
+impl Interest {
+    /// Matches nothing.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Matches every object.
+    pub fn everything() -> Self {
+        Self {
+            everything: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_contract(mut self, contract: ContractId) -> Self {
+        self.contracts.insert(contract);
+        self
+    }
+
+    pub fn with_object(mut self, object: ObjectHandle) -> Self {
+        self.objects.insert(object);
+        self
+    }
+
+    pub fn with_proximity(mut self, proximity: Proximity) -> Self {
+        self.proximity.push(proximity);
+        self
+    }
+
+    /// The objects in `baseline` that a client with this interest is sent.
+    pub fn relevant(&self, baseline: &Baseline, spatial: &SpatialIndex) -> HashSet<ObjectHandle> {
+        let exists = |obj: &ObjectHandle| baseline.object(*obj).is_ok();
+        let mut relevant: HashSet<ObjectHandle> = HashSet::new();
+        if self.everything {
+            relevant.extend(baseline.iter_objects().map(|(obj, _)| obj));
+        } else {
+            for contract in baseline.iter_contracts() {
+                if !self.contracts.contains(&contract.id()) {
+                    continue;
+                }
+                if let Ok(data) = baseline.contract_data(contract.handle()) {
+                    relevant.extend(data.objects());
+                }
+            }
+            relevant.extend(self.objects.iter().copied().filter(exists));
+            for p in &self.proximity {
+                let (center, radius) = match *p {
+                    Proximity::Point { center, radius } => (center, radius),
+                    Proximity::Object { object, radius } => match spatial.position(object) {
+                        Some(center) => (center, radius),
+                        None => continue,
+                    },
+                };
+                relevant.extend(spatial.query_radius(center, radius));
+            }
+        }
+
+        // Clients can't hold handles to objects they weren't sent. Each object is
+        // paired with whether to add its ancestors and descendants, which isn't
+        // needed for objects that were added as one of those.
+        let mut expanded: HashSet<ObjectHandle> = relevant.clone();
+        let mut pending: Vec<(ObjectHandle, bool)> =
+            relevant.iter().map(|obj| (*obj, true)).collect();
+        while let Some((obj, expand)) = pending.pop() {
+            if expand {
+                let hierarchy = baseline
+                    .iter_ancestors(obj)
+                    .chain(baseline.iter_descendants(obj));
+                for related in hierarchy {
+                    if relevant.insert(related) {
+                        pending.push((related, false));
+                    }
+                }
+            }
+            for referenced in references(baseline, obj) {
+                if exists(&referenced) && expanded.insert(referenced) {
+                    relevant.insert(referenced);
+                    pending.push((referenced, true));
+                }
+            }
+        }
+        relevant
+    }
+
+    /// Whether `changes` may have changed what [`Self::relevant()`] returns.
+    /// Objects and contracts aren't created or removed by collactions, so
+    /// only references and positions matter.
+    pub(crate) fn is_affected_by(&self, changes: Changes) -> bool {
+        !self.everything && (changes.references || changes.positions && !self.proximity.is_empty())
+    }
+}
+
+/// What a collaction changed that decides which objects are relevant.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Changes {
+    /// An `ObjectHandle` state was written
+    pub references: bool,
+    /// A state that makes up the position of an object in `spatial` was written
+    pub positions: bool,
+}
+impl Changes {
+    pub fn of(actions: &[NetAction], baseline: &Baseline, spatial: &SpatialIndex) -> Self {
+        let mut changes = Self::default();
+        for action in actions {
+            // Asserts, merges and locks don't change any values that matter
+            let (NetAction::StateWrite { object, state, .. }
+            | NetAction::MapInsert { object, state, .. }
+            | NetAction::MapRemove { object, state, .. }) = action
+            else {
+                continue;
+            };
+            match protocol::bind_state(baseline, *object, *state) {
+                Ok(DynStateHandle::Primitive(DynStateHandlePrimitive::F32(h))) => {
+                    changes.positions |= spatial.is_position(h);
+                }
+                Ok(DynStateHandle::Primitive(DynStateHandlePrimitive::ObjectHandle(_))) => {
+                    changes.references = true;
+                }
+                _ => (),
+            }
+        }
+        changes
+    }
+}
+
+/// The objects that the `ObjectHandle` states of `obj` point to.
+fn references(baseline: &Baseline, obj: ObjectHandle) -> Vec<ObjectHandle> {
+    let Ok(contract) = baseline.object(obj).map(|o| o.contract()) else {
+        return Vec::new();
+    };
+    let Ok(data) = baseline.contract_data(contract) else {
+        return Vec::new();
+    };
+    let handle_type = TpPropertyType::Primitive(TpPrimitiveType::ObjectHandle);
+    data.layout()
+        .state_types
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| **t == handle_type)
+        .filter_map(|(idx, _)| {
+            let state = baseline
+                .bind_state(StateId::<ObjectHandle>::new(idx, contract), obj)
+                .ok()?;
+            baseline.state(state).ok().map(|s| s.value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tp_client::baseline::BaselineKind;
+    use tp_client::contract::properties::dynamic::DynTpProperty;
+    use tp_client::contract::ContractSchema;
+    use tp_client::spatial::PositionStates;
+
+    #[test]
+    fn test_relevant() {
+        let mut b = Baseline::new(BaselineKind::Fork);
+        let schema = ContractSchema::new("teleportal.test.interest", (0, 1, 0))
+            .with_state("x", TpPropertyType::Primitive(TpPrimitiveType::F32))
+            .with_state("y", TpPropertyType::Primitive(TpPrimitiveType::F32))
+            .with_state(
+                "target",
+                TpPropertyType::Primitive(TpPrimitiveType::ObjectHandle),
+            );
+        let c = b.register_dyn_contract(&schema).unwrap();
+        let other = b
+            .register_dyn_contract(&ContractSchema::new("teleportal.test.other", (0, 1, 0)))
+            .unwrap();
+        let lone = b
+            .object_create(&other, [].into_iter(), [].into_iter())
+            .unwrap();
+
+        let mut create = |x: f32, target: ObjectHandle| {
+            let states = [
+                DynTpProperty::from(x),
+                DynTpProperty::from(0.0f32),
+                DynTpProperty::from(target),
+            ];
+            b.object_create(&c, states.into_iter(), [].into_iter())
+                .unwrap()
+        };
+        let near = create(1.0, lone);
+        let far = create(100.0, lone);
+        let farther = create(200.0, far);
+
+        let mut spatial = SpatialIndex::new(10.0);
+        let position =
+            PositionStates::new_2d(StateId::new(0, c.handle()), StateId::new(1, c.handle()));
+        spatial.track_positions(&b, c.handle(), position).unwrap();
+
+        let set = |objs: &[ObjectHandle]| objs.iter().copied().collect::<HashSet<_>>();
+        let relevant = |i: Interest| i.relevant(&b, &spatial);
+        assert!(relevant(Interest::none()).is_empty());
+        assert_eq!(
+            relevant(Interest::everything()),
+            set(&[lone, near, far, farther])
+        );
+        assert_eq!(
+            relevant(Interest::none().with_contract(other.id())),
+            set(&[lone])
+        );
+        // `far` is referenced by `farther`, and `lone` by both of them.
+        assert_eq!(
+            relevant(Interest::none().with_object(farther)),
+            set(&[lone, far, farther])
+        );
+        let around = |center: Point| Proximity::Point {
+            center,
+            radius: 5.0,
+        };
+        assert_eq!(
+            relevant(Interest::none().with_proximity(around([0.0; 3]))),
+            set(&[lone, near])
+        );
+        let around_far = Proximity::Object {
+            object: far,
+            radius: 5.0,
+        };
+        assert_eq!(
+            relevant(Interest::none().with_proximity(around_far)),
+            set(&[lone, far])
+        );
+    }
+
+    #[test]
+    fn test_relevant_hierarchy() {
+        let mut b = Baseline::new(BaselineKind::Fork);
+        let c = b
+            .register_dyn_contract(&ContractSchema::new("teleportal.test.tree", (0, 1, 0)))
+            .unwrap();
+        let mut create = || b.object_create(&c, [].into_iter(), [].into_iter()).unwrap();
+        let [root, mid, leaf, sibling] = [(); 4].map(|_| create());
+        b.object_reparent(mid, Some(root), None).unwrap();
+        b.object_reparent(leaf, Some(mid), None).unwrap();
+        b.object_reparent(sibling, Some(root), None).unwrap();
+
+        let spatial = SpatialIndex::new(10.0);
+        let set = |objs: &[ObjectHandle]| objs.iter().copied().collect::<HashSet<_>>();
+        // Ancestors and descendants, but not siblings
+        assert_eq!(
+            Interest::none().with_object(mid).relevant(&b, &spatial),
+            set(&[root, mid, leaf])
+        );
+        assert_eq!(
+            Interest::none().with_object(root).relevant(&b, &spatial),
+            set(&[root, mid, leaf, sibling])
+        );
+    }
+}
@@ -0,0 +1,671 @@
+//! An append-only journal of approved collactions, and a [`Replayer`] that
+//! rebuilds past states of the realm from it.
+//!
+//! Collactions are only journaled once they are approved, along with the
+//! `RealmTime` that they were applied at. Every so often the journal also
+//! records a checkpoint, which is a snapshot of the whole baseline. Replays
+//! start from the closest checkpoint, and are checked against the later ones.
+//!
+//! Objects and contracts aren't created through collactions, so whenever they
+//! change the journal needs a checkpoint that replays restart from, see
+//! [`Journal::reset`].
+//!
+//! # Format
+//! A journal is a sequence of records, each of which is a `u8` tag followed by
+//! the length of its payload as a little endian `u32`, and then the payload.
+//! Payloads use the same encoding as the network protocol, with the handles of
+//! the journaled baseline.
+
+use crate::codec::{same_handle, Decode, Encode, Reader, Writer};
+use crate::protocol::{self, ClientId, NetAction};
+use crate::snapshot;
+
+use eyre::{eyre, Result, WrapErr};
+use std::collections::HashMap;
+use std::io::{ErrorKind, Read, Write};
+use tp_client::apply_to_state_id;
+use tp_client::baseline::{Baseline, BaselineKind};
+use tp_client::contract::properties::dynamic::{DynTpPrimitive, DynTpProperty};
+use tp_client::contract::properties::states::{DynStateId, StateId};
+use tp_client::contract::ContractId;
+use tp_client::engine::ActionSender;
+use tp_client::object::ObjectHandle;
+use tp_client::realm::{Realm, RealmID};
+use tp_client::time::{RealmTime, Ticks};
+use tp_client::Engine;
+
+const ENTRY_TAG: u8 = 0;
+const CHECKPOINT_TAG: u8 = 1;
+
+/// A collaction that was approved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournalEntry {
+    pub time: RealmTime,
+    pub origin: ClientId,
+    /// The actions, with the handles of the journaled baseline.
+    pub actions: Vec<NetAction>,
+}
+
+/// A snapshot of the whole baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub time: RealmTime,
+    /// The number of entries that were journaled before the checkpoint.
+    pub entry: usize,
+    /// Whether the realm was changed outside of collactions since the
+    /// previous checkpoint, so replays need to restart from this one.
+    pub reset: bool,
+    data: Vec<u8>,
+    objects: Vec<ObjectHandle>,
+}
+impl Checkpoint {
+    /// Deserializes the checkpoint, returning the baseline and a map from the
+    /// journaled handles to its handles.
+    pub fn load(&self) -> Result<(Baseline, HashMap<ObjectHandle, ObjectHandle>)> {
+        snapshot::load(&self.data, &self.objects, BaselineKind::Fork)
+    }
+}
+
+/// Records approved collactions and checkpoints, in memory and optionally to a
+/// writer such as a file.
+///
+/// A [`Server`](crate::Server) journals everything it applies once it is given
+/// a journal with [`Server::set_journal`](crate::Server::set_journal).
+#[derive(Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+    checkpoints: Vec<Checkpoint>,
+    checkpoint_every: Option<usize>,
+    writer: Option<Box<dyn Write + Send>>,
+}
+impl Journal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every record made from now on is also appended to `writer`, and flushed
+    /// before the call that made it returns.
+    pub fn with_writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    /// Takes a checkpoint after every `entries` entries. There are no periodic
+    /// checkpoints otherwise.
+    pub fn with_checkpoint_every(mut self, entries: usize) -> Self {
+        self.checkpoint_every = Some(entries.max(1));
+        self
+    }
+
+    /// Reads the records written by [`Journal::with_writer`]. A record that is
+    /// cut off at the end, such as from a crash while it was written, is
+    /// ignored.
+    pub fn read(mut reader: impl Read) -> Result<Self> {
+        let mut this = Self::new();
+        let mut header = [0; 5];
+        loop {
+            match read_exact_or_eof(&mut reader, &mut header)? {
+                Filled::Eof => break,
+                Filled::Partial => {
+                    tracing::warn!("Ignoring a journal record that was cut off");
+                    break;
+                }
+                Filled::Full => (),
+            }
+            let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]);
+            let mut payload = vec![0; len as usize];
+            if !matches!(read_exact_or_eof(&mut reader, &mut payload)?, Filled::Full) {
+                tracing::warn!("Ignoring a journal record that was cut off");
+                break;
+            }
+
+            let idx = this.entries.len() + this.checkpoints.len();
+            let mut r = Reader::new(&payload, &same_handle);
+            match header[0] {
+                ENTRY_TAG => {
+                    let entry = decode_entry(&mut r)
+                        .wrap_err_with(|| format!("Record {} is an invalid entry", idx))?;
+                    this.entries.push(entry);
+                }
+                CHECKPOINT_TAG => {
+                    let checkpoint = decode_checkpoint(&mut r)
+                        .wrap_err_with(|| format!("Record {} is an invalid checkpoint", idx))?;
+                    this.checkpoints.push(checkpoint);
+                }
+                t => return Err(eyre!("Record {} has unknown tag {}", idx, t)),
+            }
+            r.finish()?;
+        }
+        Ok(this)
+    }
+
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// In the order that they were taken.
+    pub fn checkpoints(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Journals a collaction that was approved, and takes a checkpoint if one
+    /// is due. `baseline` is the baseline that it was applied to, after it was
+    /// applied.
+    pub fn record(
+        &mut self,
+        baseline: &Baseline,
+        time: RealmTime,
+        origin: ClientId,
+        actions: Vec<NetAction>,
+    ) -> Result<()> {
+        let entry = JournalEntry {
+            time,
+            origin,
+            actions,
+        };
+        let mut w = Writer::new(&same_handle);
+        encode_entry(&entry, &mut w)?;
+        self.write(ENTRY_TAG, w.finish())?;
+        self.entries.push(entry);
+
+        let since_checkpoint = self.entries.len() - self.checkpoints.last().map_or(0, |c| c.entry);
+        if matches!(self.checkpoint_every, Some(n) if since_checkpoint >= n) {
+            self.checkpoint(baseline, time)?;
+        }
+        Ok(())
+    }
+
+    /// Takes a checkpoint of `baseline`, which replays are checked against.
+    pub fn checkpoint(&mut self, baseline: &Baseline, time: RealmTime) -> Result<()> {
+        self.push_checkpoint(baseline, time, false)
+    }
+
+    /// Takes a checkpoint of `baseline` that replays restart from. Needed
+    /// whenever objects or contracts were created or removed, and before the
+    /// first entry.
+    pub fn reset(&mut self, baseline: &Baseline, time: RealmTime) -> Result<()> {
+        self.push_checkpoint(baseline, time, true)
+    }
+
+    fn push_checkpoint(&mut self, baseline: &Baseline, time: RealmTime, reset: bool) -> Result<()> {
+        let (data, objects) = snapshot::take(baseline, |_| true)?;
+        let checkpoint = Checkpoint {
+            time,
+            entry: self.entries.len(),
+            reset,
+            data,
+            objects,
+        };
+        let mut w = Writer::new(&same_handle);
+        encode_checkpoint(&checkpoint, &mut w)?;
+        self.write(CHECKPOINT_TAG, w.finish())?;
+        self.checkpoints.push(checkpoint);
+        Ok(())
+    }
+
+    fn write(&mut self, tag: u8, payload: Vec<u8>) -> Result<()> {
+        let Some(writer) = &mut self.writer else {
+            return Ok(());
+        };
+        let len = u32::try_from(payload.len()).wrap_err("Record was too large")?;
+        writer.write_all(&[tag])?;
+        writer.write_all(&len.to_le_bytes())?;
+        writer.write_all(&payload)?;
+        writer.flush().wrap_err("Failed to write to the journal")
+    }
+}
+
+enum Filled {
+    Full,
+    Partial,
+    Eof,
+}
+
+fn read_exact_or_eof(reader: &mut impl Read, buf: &mut [u8]) -> Result<Filled> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(Filled::Eof),
+            Ok(0) => return Ok(Filled::Partial),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(Filled::Full)
+}
+
+impl Encode for RealmTime {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        self.ticks().as_millis().encode(w)
+    }
+}
+impl Decode for RealmTime {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        Ok(RealmTime::from(Ticks::new(i32::decode(r)?)))
+    }
+}
+
+fn encode_entry(entry: &JournalEntry, w: &mut Writer<'_>) -> Result<()> {
+    entry.time.encode(w)?;
+    entry.origin.0.encode(w)?;
+    entry.actions.encode(w)
+}
+
+fn decode_entry(r: &mut Reader<'_>) -> Result<JournalEntry> {
+    Ok(JournalEntry {
+        time: Decode::decode(r)?,
+        origin: ClientId(Decode::decode(r)?),
+        actions: Decode::decode(r)?,
+    })
+}
+
+fn encode_checkpoint(checkpoint: &Checkpoint, w: &mut Writer<'_>) -> Result<()> {
+    checkpoint.time.encode(w)?;
+    w.write_len(checkpoint.entry)?;
+    checkpoint.reset.encode(w)?;
+    w.write_len(checkpoint.data.len())?;
+    w.write_bytes(&checkpoint.data);
+    w.write_len(checkpoint.objects.len())?;
+    for o in &checkpoint.objects {
+        w.write_raw_handle(*o)?;
+    }
+    Ok(())
+}
+
+fn decode_checkpoint(r: &mut Reader<'_>) -> Result<Checkpoint> {
+    let time = Decode::decode(r)?;
+    let entry = r.read_len()?;
+    let reset = Decode::decode(r)?;
+    let len = r.read_len()?;
+    let data = r.read_bytes(len)?.to_vec();
+    let len = r.read_len()?;
+    let objects = (0..len)
+        .map(|_| r.read_raw_handle())
+        .collect::<Result<_>>()?;
+    Ok(Checkpoint {
+        time,
+        entry,
+        reset,
+        data,
+        objects,
+    })
+}
+
+/// Rebuilds past states of a realm from a [`Journal`], by applying its entries
+/// to its checkpoints.
+pub struct Replayer<'a> {
+    journal: &'a Journal,
+    engine: Engine,
+    sender: ActionSender,
+    /// The journaled handles, to the replayed ones
+    to_local: HashMap<ObjectHandle, ObjectHandle>,
+    /// The number of entries that have been replayed
+    position: usize,
+    /// The index of the first checkpoint that hasn't been passed yet
+    next_checkpoint: usize,
+}
+impl<'a> Replayer<'a> {
+    /// Starts at the first checkpoint.
+    ///
+    /// # Errors
+    /// Will error if the journal has no checkpoints to start from.
+    pub fn new(journal: &'a Journal) -> Result<Self> {
+        let (mut engine, sender) = Engine::new(Realm::new(RealmID::new("replay".into())), None);
+        // The journal only has approved collactions, which were already
+        // checked against the locks of the time.
+        engine.set_lock_checks(false);
+        let mut this = Self {
+            journal,
+            engine,
+            sender,
+            to_local: HashMap::new(),
+            position: 0,
+            next_checkpoint: 0,
+        };
+        this.load(0)?;
+        Ok(this)
+    }
+
+    /// The number of entries that have been replayed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn time(&self) -> RealmTime {
+        self.engine.realm().time()
+    }
+
+    /// The replayed realm, in its fork baseline.
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// The replayed handle of an object, from its journaled handle.
+    pub fn local_object(&self, journaled: ObjectHandle) -> Option<ObjectHandle> {
+        self.to_local.get(&journaled).copied()
+    }
+
+    /// Replays the next entry, and returns it. Returns `None` at the end of
+    /// the journal.
+    pub fn step(&mut self) -> Result<Option<&'a JournalEntry>> {
+        self.pass_checkpoints(false)?;
+        let Some(entry) = self.journal.entries.get(self.position) else {
+            return Ok(None);
+        };
+        self.apply(entry)
+            .wrap_err_with(|| format!("Failed to replay entry {}", self.position))?;
+        self.position += 1;
+        Ok(Some(entry))
+    }
+
+    /// Rebuilds the realm as it was after `position` entries, starting from
+    /// the closest checkpoint.
+    pub fn seek(&mut self, position: usize) -> Result<()> {
+        if position > self.journal.entries.len() {
+            return Err(eyre!(
+                "The journal only has {} entries",
+                self.journal.entries.len()
+            ));
+        }
+        let checkpoint = self
+            .journal
+            .checkpoints
+            .iter()
+            .rposition(|c| c.entry <= position)
+            .ok_or_else(|| eyre!("There is no checkpoint before entry {}", position))?;
+        self.load(checkpoint)?;
+        while self.position < position {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the realm as it was at `time`, after every entry up to then.
+    pub fn seek_time(&mut self, time: RealmTime) -> Result<()> {
+        let position = self.journal.entries.partition_point(|e| e.time <= time);
+        self.seek(position)
+    }
+
+    /// Replays the whole journal from the start, and checks that the replayed
+    /// baseline matches every checkpoint that doesn't reset it. Any mismatch
+    /// means that replaying isn't deterministic, or that the realm was changed
+    /// without the journal knowing.
+    pub fn verify(&mut self) -> Result<()> {
+        self.load(0)?;
+        while self.position < self.journal.entries.len() {
+            self.pass_checkpoints(true)?;
+            self.step()?;
+        }
+        self.pass_checkpoints(true)
+    }
+
+    /// Starts over from the checkpoint at `idx`.
+    fn load(&mut self, idx: usize) -> Result<()> {
+        let checkpoint = self
+            .journal
+            .checkpoints
+            .get(idx)
+            .ok_or_else(|| eyre!("The journal has no checkpoint {}", idx))?;
+        let (baseline, to_local) = checkpoint.load()?;
+        let realm = self.engine.realm_mut();
+        *realm.baseline_mut(BaselineKind::Fork) = baseline;
+        *realm.time_mut() = checkpoint.time;
+        self.to_local = to_local;
+        self.position = checkpoint.entry;
+        self.next_checkpoint = idx + 1;
+        Ok(())
+    }
+
+    /// Handles the checkpoints taken before the next entry. Ones that reset are
+    /// loaded, and others are compared against if `verify` is set.
+    fn pass_checkpoints(&mut self, verify: bool) -> Result<()> {
+        while let Some(checkpoint) = self.journal.checkpoints.get(self.next_checkpoint) {
+            if checkpoint.entry > self.position {
+                break;
+            }
+            if checkpoint.reset {
+                self.load(self.next_checkpoint)?;
+                continue;
+            }
+            if verify {
+                self.compare(checkpoint).wrap_err_with(|| {
+                    format!("Replay diverged from checkpoint {}", self.next_checkpoint)
+                })?;
+            }
+            self.next_checkpoint += 1;
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, entry: &JournalEntry) -> Result<()> {
+        // Translate the handles by encoding the actions with the journaled
+        // handles, and decoding them with the replayed ones.
+        let mut w = Writer::new(&same_handle);
+        entry.actions.encode(&mut w)?;
+        let bytes = w.finish();
+        let to_local = |h: ObjectHandle| {
+            self.local_object(h)
+                .ok_or_else(|| eyre!("{:?} is not in the last checkpoint", h))
+        };
+        let mut r = Reader::new(&bytes, &to_local);
+        let actions: Vec<NetAction> = Decode::decode(&mut r)?;
+
+        let baseline = self.engine.realm().baseline(BaselineKind::Fork);
+        let collaction =
+            protocol::to_collaction(&actions, baseline)?.with_owner(entry.origin.into());
+        self.sender
+            .send(collaction)
+            .expect("The engine holds a receiver");
+        let result = self
+            .engine
+            .try_apply()
+            .expect("The collaction was just sent");
+        if result.is_err() {
+            return Err(eyre!("The collaction was rejected when replayed"));
+        }
+        *self.engine.realm_mut().time_mut() = entry.time;
+        Ok(())
+    }
+
+    /// Checks that every object in `checkpoint` has the same states in the
+    /// replayed baseline.
+    fn compare(&self, checkpoint: &Checkpoint) -> Result<()> {
+        let (recorded, recorded_handles) = checkpoint.load()?;
+        let replayed = self.engine.realm().baseline(BaselineKind::Fork);
+        if replayed.iter_objects().count() != recorded_handles.len() {
+            return Err(eyre!(
+                "Replayed {} objects, but {} were recorded",
+                replayed.iter_objects().count(),
+                recorded_handles.len()
+            ));
+        }
+        let invert = |map: &HashMap<ObjectHandle, ObjectHandle>| -> HashMap<_, _> {
+            map.iter().map(|(k, v)| (*v, *k)).collect()
+        };
+        let recorded_to_journal = invert(&recorded_handles);
+        let replayed_to_journal = invert(&self.to_local);
+
+        for (journaled, recorded_obj) in &recorded_handles {
+            let replayed_obj = self
+                .local_object(*journaled)
+                .ok_or_else(|| eyre!("{:?} was not replayed", journaled))?;
+            let expected = object_states(&recorded, *recorded_obj, &recorded_to_journal)?;
+            let actual = object_states(replayed, replayed_obj, &replayed_to_journal)?;
+            if expected.0 != actual.0 {
+                return Err(eyre!("{:?} has a different contract", journaled));
+            }
+            for (idx, (e, a)) in expected.1.iter().zip(&actual.1).enumerate() {
+                if e != a {
+                    return Err(eyre!(
+                        "State {} of {:?} was {:?}, but {:?} was recorded",
+                        idx,
+                        journaled,
+                        a,
+                        e
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The contract and the values of all states of `obj`, with `ObjectHandle`
+/// values translated by `to_journal`.
+fn object_states(
+    baseline: &Baseline,
+    obj: ObjectHandle,
+    to_journal: &HashMap<ObjectHandle, ObjectHandle>,
+) -> Result<(ContractId, Vec<DynTpProperty>)> {
+    let contract = baseline.object(obj)?.contract();
+    let layout = baseline.contract_data(contract)?.layout();
+    let states = layout
+        .state_types
+        .iter()
+        .enumerate()
+        .map(|(idx, prop_type)| {
+            let id = DynStateId::new(contract, idx, *prop_type);
+            let mut value = apply_to_state_id!(id, |id: StateId<_>| -> Result<DynTpProperty> {
+                let handle = baseline.bind_state(id, obj)?;
+                Ok(DynTpProperty::from(baseline.state(handle)?.value.clone()))
+            })?;
+            if let DynTpProperty::Primitive(DynTpPrimitive::ObjectHandle(h)) = &mut value {
+                *h = to_journal.get(h).copied().unwrap_or(*h);
+            }
+            Ok(value)
+        })
+        .collect::<Result<_>>()?;
+    Ok((layout.id, states))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tp_client::contract::properties::dynamic::{TpPrimitiveType, TpPropertyType};
+    use tp_client::contract::ContractSchema;
+
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn time(ms: i32) -> RealmTime {
+        RealmTime::from(Ticks::new(ms))
+    }
+
+    /// Records writing `0..n` to the `hp` of an object, at one tick each.
+    fn record(journal: &mut Journal, n: u8) -> ObjectHandle {
+        let (mut engine, sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let b = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let schema = ContractSchema::new("teleportal.test.journal", (0, 1, 0))
+            .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8));
+        let c = b.register_dyn_contract(&schema).unwrap();
+        let states = [DynTpProperty::from(100u8)];
+        let obj = b
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let b = engine.realm().baseline(BaselineKind::Fork);
+        journal.reset(b, time(0)).unwrap();
+
+        for hp in 0..n {
+            let actions = vec![NetAction::StateWrite {
+                object: obj,
+                state: 0,
+                data: DynTpProperty::from(hp),
+            }];
+            let b = engine.realm().baseline(BaselineKind::Fork);
+            sender
+                .send(protocol::to_collaction(&actions, b).unwrap())
+                .unwrap();
+            assert!(engine.try_apply().unwrap().is_ok());
+            let b = engine.realm().baseline(BaselineKind::Fork);
+            journal
+                .record(b, time(hp as i32 + 1), ClientId::SERVER, actions)
+                .unwrap();
+        }
+        obj
+    }
+
+    fn hp(replayer: &Replayer, obj: ObjectHandle) -> u8 {
+        let b = replayer.engine().realm().baseline(BaselineKind::Fork);
+        let obj = replayer.local_object(obj).unwrap();
+        let contract = b.object(obj).unwrap().contract();
+        let handle = b.bind_state(StateId::<u8>::new(0, contract), obj).unwrap();
+        b.state(handle).unwrap().value
+    }
+
+    #[test]
+    fn test_replay() {
+        let buf = SharedBuf::default();
+        let mut journal = Journal::new()
+            .with_writer(buf.clone())
+            .with_checkpoint_every(3);
+        let obj = record(&mut journal, 10);
+        assert_eq!(journal.entries().len(), 10);
+        // The reset, and one after every 3 entries
+        assert_eq!(journal.checkpoints().len(), 4);
+
+        let mut replayer = Replayer::new(&journal).unwrap();
+        assert_eq!(hp(&replayer, obj), 100);
+        assert_eq!(replayer.step().unwrap(), Some(&journal.entries()[0]));
+        assert_eq!(hp(&replayer, obj), 0);
+        assert_eq!(replayer.time(), time(1));
+
+        replayer.seek(8).unwrap();
+        assert_eq!(replayer.position(), 8);
+        assert_eq!(hp(&replayer, obj), 7);
+        replayer.seek(2).unwrap();
+        assert_eq!(hp(&replayer, obj), 1);
+        replayer.seek_time(time(5)).unwrap();
+        assert_eq!(replayer.position(), 5);
+        assert_eq!(hp(&replayer, obj), 4);
+        assert!(replayer.seek(11).is_err());
+        replayer.verify().unwrap();
+        assert_eq!(hp(&replayer, obj), 9);
+        assert!(replayer.step().unwrap().is_none());
+
+        // The written journal reads back the same, even if it was cut off.
+        let mut bytes = buf.0.lock().unwrap().clone();
+        let read = Journal::read(bytes.as_slice()).unwrap();
+        assert_eq!(read.entries(), journal.entries());
+        assert_eq!(read.checkpoints(), journal.checkpoints());
+        bytes.truncate(bytes.len() - 1);
+        let read = Journal::read(bytes.as_slice()).unwrap();
+        assert_eq!(read.entries(), &journal.entries()[..9]);
+        assert_eq!(read.checkpoints(), journal.checkpoints());
+        Replayer::new(&read).unwrap().verify().unwrap();
+    }
+
+    #[test]
+    fn test_verify_detects_divergence() {
+        let mut journal = Journal::new();
+        let obj = record(&mut journal, 2);
+        let mut replayer = Replayer::new(&journal).unwrap();
+        replayer.verify().unwrap();
+
+        // A checkpoint of a baseline that was changed without the journal
+        let (mut b, handles) = journal.checkpoints()[0].load().unwrap();
+        let local = handles[&obj];
+        let contract = b.object(local).unwrap().contract();
+        let handle = b
+            .bind_state(StateId::<u8>::new(0, contract), local)
+            .unwrap();
+        b.state_mut(handle).unwrap().value = 42;
+        journal.checkpoint(&b, time(3)).unwrap();
+        // The checkpoint has the handles of `b`, rather than the journal's.
+        journal.checkpoints.last_mut().unwrap().objects = vec![obj];
+
+        let err = Replayer::new(&journal).unwrap().verify().unwrap_err();
+        assert!(format!("{:#}", err).contains("diverged"), "{:#}", err);
+    }
+}
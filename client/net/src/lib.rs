@@ -0,0 +1,53 @@
+//! Keeps realms on different machines in sync.
+//!
+//! A [`Server`] owns the authoritative realm. [`Client`]s submit collactions
+//! to it as [`NetAction`]s, which the server validates and applies, and then
+//! sends to every client that they are relevant to. Clients start from a
+//! snapshot of the server's realm, so they don't need to know its contracts
+//! ahead of time.
+//!
+//! Each client is only sent the objects that match its [`Interest`], whether
+//! by contract, by explicit subscription, or by proximity.
+//!
+//! The server can record everything it applies to a [`Journal`], which a
+//! [`Replayer`] uses to rebuild past states of the realm and to check that
+//! replaying them is deterministic.
+//!
+//! Messages are carried by a [`transport`], which is polled rather than
+//! blocking.
+//!
+//! # Limitations
+//! Snapshots are serialized with `tp_serialize`, so they only support the
+//! states that it does. `Vec` states and `ContractDataHandle` values can't be
+//! sent yet.
+
+#![deny(
+    bad_style,
+    improper_ctypes,
+    non_shorthand_field_patterns,
+    no_mangle_generic_items,
+    overflowing_literals,
+    path_statements,
+    patterns_in_fns_without_body,
+    private_in_public,
+    unconditional_recursion,
+    unused_allocation,
+    unused_comparisons,
+    unused_parens,
+    while_true
+)]
+
+mod client;
+mod codec;
+mod interest;
+mod journal;
+mod protocol;
+mod server;
+mod snapshot;
+pub mod transport;
+
+pub use self::client::{Client, ClientEvent};
+pub use self::interest::{Interest, Proximity};
+pub use self::journal::{Checkpoint, Journal, JournalEntry, Replayer};
+pub use self::protocol::{ClientId, NetAction};
+pub use self::server::{Server, ServerEvent, Validator};
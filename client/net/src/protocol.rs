@@ -0,0 +1,631 @@
+//! The messages sent between the server and its clients.
+
+use crate::codec::{Decode, Encode, HandleMap, Reader, Writer};
+
+use eyre::{eyre, Result, WrapErr};
+use tp_client::action::lock::LockAction;
+use tp_client::action::property::{PropertyAction, StateAction};
+use tp_client::action::{Action, Collaction};
+use tp_client::apply_to_state_handle;
+use tp_client::apply_to_state_id;
+use tp_client::baseline::Baseline;
+use tp_client::contract::properties::bytes::Bytes;
+use tp_client::contract::properties::dynamic::{DynTpPrimitive, DynTpProperty};
+use tp_client::contract::properties::states::dyn_handle::DynStateHandlePrimitive;
+use tp_client::contract::properties::states::{DynStateHandle, DynStateId, StateHandle, StateId};
+use tp_client::lock::{LockTarget, OwnerId};
+use tp_client::object::ObjectHandle;
+use tp_client::time::Ticks;
+
+/// Identifies a client of a [`Server`](crate::Server). Only unique for the
+/// lifetime of the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientId(pub(crate) u32);
+impl ClientId {
+    /// The server itself, for collactions that it applied on its own.
+    pub const SERVER: Self = Self(0);
+}
+impl From<ClientId> for OwnerId {
+    /// Clients own the locks that they acquire.
+    fn from(other: ClientId) -> Self {
+        Self(other.0.into())
+    }
+}
+
+/// An action that can be sent over the network. States are addressed by
+/// their object and their index in the object's contract, since the handles
+/// of states are not the same on different machines.
+///
+/// `object`, and any `ObjectHandle`s in the values, are handles in the local
+/// baseline of whoever holds the `NetAction`. They are translated when sent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetAction {
+    StateWrite {
+        object: ObjectHandle,
+        state: usize,
+        data: DynTpProperty,
+    },
+    StateAssert {
+        object: ObjectHandle,
+        state: usize,
+        data: DynTpProperty,
+    },
+    MapInsert {
+        object: ObjectHandle,
+        state: usize,
+        key: String,
+        value: DynTpPrimitive,
+    },
+    MapRemove {
+        object: ObjectHandle,
+        state: usize,
+        key: String,
+    },
+    BytesWrite {
+        object: ObjectHandle,
+        state: usize,
+        offset: usize,
+        chunk: Bytes,
+    },
+    /// Merges a CRDT into a `Bytes` state. Merges don't conflict with each
+    /// other, so clients apply their own without waiting for the server, see
+    /// [`Client::merge`](crate::Client::merge).
+    Merge {
+        object: ObjectHandle,
+        state: usize,
+        delta: Bytes,
+    },
+    /// Locks the object, or only its state at index `state`, for the client
+    /// that submits the action. See [`tp_client::lock`].
+    LockAcquire {
+        object: ObjectHandle,
+        state: Option<usize>,
+        lease: Ticks,
+    },
+    LockRelease {
+        object: ObjectHandle,
+        state: Option<usize>,
+    },
+}
+impl NetAction {
+    /// The object that the action is for, and the index of the state if it
+    /// is only for one of its states.
+    pub fn target(&self) -> (ObjectHandle, Option<usize>) {
+        match self {
+            Self::StateWrite { object, state, .. }
+            | Self::StateAssert { object, state, .. }
+            | Self::MapInsert { object, state, .. }
+            | Self::MapRemove { object, state, .. }
+            | Self::BytesWrite { object, state, .. }
+            | Self::Merge { object, state, .. } => (*object, Some(*state)),
+            Self::LockAcquire { object, state, .. } | Self::LockRelease { object, state } => {
+                (*object, *state)
+            }
+        }
+    }
+
+    /// Converts into an `Action` on the states of `baseline`.
+    ///
+    /// # Errors
+    /// Will error if the state doesn't exist, or if this is a `BytesWrite` or a
+    /// `Merge` to a state that isn't `Bytes`. Other type mismatches are caught by the
+    /// `Engine` when it applies the action.
+    pub fn to_action(&self, baseline: &Baseline) -> Result<Action> {
+        let (object, state) = self.target();
+        let target = match state {
+            Some(state) => LockTarget::State(bind_state(baseline, object, state)?),
+            None => LockTarget::Object(object),
+        };
+        let handle = || match target {
+            LockTarget::State(handle) => Ok(handle),
+            LockTarget::Object(_) => Err(eyre!("The action is not for a state")),
+        };
+        let bytes_handle = || match handle()? {
+            DynStateHandle::Primitive(DynStateHandlePrimitive::Bytes(handle)) => Ok(handle),
+            _ => Err(eyre!("State {:?} is not `Bytes`", state)),
+        };
+        let action = match self.clone() {
+            Self::LockAcquire { lease, .. } => return Ok(LockAction::acquire(target, lease).into()),
+            Self::LockRelease { .. } => return Ok(LockAction::release(target).into()),
+            Self::StateWrite { data, .. } => StateAction::Write {
+                handle: handle()?,
+                data,
+            },
+            Self::StateAssert { data, .. } => StateAction::Assert {
+                handle: handle()?,
+                data,
+            },
+            Self::MapInsert { key, value, .. } => StateAction::map_insert(handle()?, key, value),
+            Self::MapRemove { key, .. } => StateAction::map_remove(handle()?, key),
+            Self::BytesWrite { offset, chunk, .. } => {
+                StateAction::bytes_write(bytes_handle()?, offset, chunk)
+            }
+            Self::Merge { delta, .. } => StateAction::merge(bytes_handle()?, delta),
+        };
+        Ok(PropertyAction::from(action).into())
+    }
+}
+
+/// Finds the handle of the state at index `state` of `object`.
+pub(crate) fn bind_state(
+    baseline: &Baseline,
+    object: ObjectHandle,
+    state: usize,
+) -> Result<DynStateHandle> {
+    let contract = baseline.object(object)?.contract();
+    let prop_type = *baseline
+        .contract_data(contract)?
+        .layout()
+        .state_types
+        .get(state)
+        .ok_or_else(|| eyre!("The object has no state {}", state))?;
+    let id = DynStateId::new(contract, state, prop_type);
+    apply_to_state_id!(id, |id: StateId<_>| baseline
+        .bind_state(id, object)
+        .map(DynStateHandle::from))
+}
+
+/// Converts `actions` into a `Collaction` on the states of `baseline`.
+pub(crate) fn to_collaction(actions: &[NetAction], baseline: &Baseline) -> Result<Collaction> {
+    let actions = actions
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            a.to_action(baseline)
+                .wrap_err_with(|| format!("Action {} was invalid", i))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Collaction::new(actions))
+}
+
+impl Encode for NetAction {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        let tag: u8 = match self {
+            Self::StateWrite { .. } => 0,
+            Self::StateAssert { .. } => 1,
+            Self::MapInsert { .. } => 2,
+            Self::MapRemove { .. } => 3,
+            Self::BytesWrite { .. } => 4,
+            Self::Merge { .. } => 5,
+            Self::LockAcquire { .. } => 6,
+            Self::LockRelease { .. } => 7,
+        };
+        tag.encode(w)?;
+        let (object, state) = self.target();
+        object.encode(w)?;
+        match state {
+            // Only locks can be for a whole object
+            Some(state) if tag < 6 => w.write_len(state)?,
+            state => {
+                state.is_some().encode(w)?;
+                w.write_len(state.unwrap_or_default())?;
+            }
+        }
+        match self {
+            Self::StateWrite { data, .. } | Self::StateAssert { data, .. } => data.encode(w),
+            Self::MapInsert { key, value, .. } => {
+                key.encode(w)?;
+                value.encode(w)
+            }
+            Self::MapRemove { key, .. } => key.encode(w),
+            Self::BytesWrite { offset, chunk, .. } => {
+                w.write_len(*offset)?;
+                chunk.encode(w)
+            }
+            Self::Merge { delta, .. } => delta.encode(w),
+            Self::LockAcquire { lease, .. } => lease.as_millis().encode(w),
+            Self::LockRelease { .. } => Ok(()),
+        }
+    }
+}
+impl Decode for NetAction {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let tag = u8::decode(r)?;
+        let object = ObjectHandle::decode(r)?;
+        if tag >= 6 {
+            let state = match (bool::decode(r)?, r.read_len()?) {
+                (true, state) => Some(state),
+                (false, _) => None,
+            };
+            return Ok(match tag {
+                6 => Self::LockAcquire {
+                    object,
+                    state,
+                    lease: Ticks::new(i32::decode(r)?),
+                },
+                7 => Self::LockRelease { object, state },
+                t => return Err(eyre!("{} is not an action", t)),
+            });
+        }
+        let state = r.read_len()?;
+        Ok(match tag {
+            0 => Self::StateWrite {
+                object,
+                state,
+                data: Decode::decode(r)?,
+            },
+            1 => Self::StateAssert {
+                object,
+                state,
+                data: Decode::decode(r)?,
+            },
+            2 => Self::MapInsert {
+                object,
+                state,
+                key: Decode::decode(r)?,
+                value: Decode::decode(r)?,
+            },
+            3 => Self::MapRemove {
+                object,
+                state,
+                key: Decode::decode(r)?,
+            },
+            4 => Self::BytesWrite {
+                object,
+                state,
+                offset: r.read_len()?,
+                chunk: Decode::decode(r)?,
+            },
+            5 => Self::Merge {
+                object,
+                state,
+                delta: Decode::decode(r)?,
+            },
+            t => return Err(eyre!("{} is not an action", t)),
+        })
+    }
+}
+
+/// An object that entered a client's interest, along with everything the
+/// client needs to create it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EnteredObject {
+    /// The server's handle
+    pub object: ObjectHandle,
+    /// The name and version of the object's contract
+    pub contract: (String, (u16, u16, u16)),
+    /// The parent of the object, and the object's index among its children
+    pub parent: Option<(ObjectHandle, usize)>,
+    pub states: Vec<DynTpProperty>,
+}
+impl EnteredObject {
+    /// Reads `object` and the values of its states from `baseline`.
+    pub fn new(baseline: &Baseline, object: ObjectHandle) -> Result<Self> {
+        let o = baseline.object(object)?;
+        let c_data = baseline.contract_data(o.contract())?;
+        let id = c_data.id();
+        let parent = match o.parent() {
+            Some(parent) => {
+                let idx = baseline
+                    .object(parent)?
+                    .children()
+                    .iter()
+                    .position(|c| *c == object)
+                    .expect("Parents hold their children");
+                Some((parent, idx))
+            }
+            None => None,
+        };
+        let states = (0..c_data.layout().state_types.len())
+            .map(|idx| {
+                let handle = bind_state(baseline, object, idx)?;
+                apply_to_state_handle!(handle, |h: StateHandle<_>| -> Result<DynTpProperty> {
+                    Ok(DynTpProperty::from(baseline.state(h)?.value.clone()))
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            object,
+            contract: (id.name.to_owned(), id.version),
+            parent,
+            states,
+        })
+    }
+}
+impl Encode for EnteredObject {
+    fn encode(&self, w: &mut Writer<'_>) -> Result<()> {
+        w.write_raw_handle(self.object)?;
+        let (name, (major, minor, patch)) = &self.contract;
+        name.encode(w)?;
+        major.encode(w)?;
+        minor.encode(w)?;
+        patch.encode(w)?;
+        self.parent.is_some().encode(w)?;
+        if let Some((parent, idx)) = self.parent {
+            w.write_raw_handle(parent)?;
+            w.write_len(idx)?;
+        }
+        self.states.encode(w)
+    }
+}
+impl Decode for EnteredObject {
+    fn decode(r: &mut Reader<'_>) -> Result<Self> {
+        let object = r.read_raw_handle()?;
+        let name = String::decode(r)?;
+        let version = (u16::decode(r)?, u16::decode(r)?, u16::decode(r)?);
+        let parent = match bool::decode(r)? {
+            true => Some((r.read_raw_handle()?, r.read_len()?)),
+            false => None,
+        };
+        Ok(Self {
+            object,
+            contract: (name, version),
+            parent,
+            states: Decode::decode(r)?,
+        })
+    }
+}
+
+// ---- Messages ----
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ClientMessage {
+    /// Asks the server to apply a collaction. `seq` is chosen by the client, to
+    /// tell the replies apart.
+    Submit { seq: u64, actions: Vec<NetAction> },
+    /// Asks for a new snapshot, after the client fell out of sync.
+    RequestSnapshot,
+}
+impl ClientMessage {
+    /// `to_wire` translates local handles to the server's.
+    pub fn encode(&self, to_wire: HandleMap<'_>) -> Result<Vec<u8>> {
+        let mut w = Writer::new(to_wire);
+        match self {
+            Self::Submit { seq, actions } => {
+                0u8.encode(&mut w)?;
+                seq.encode(&mut w)?;
+                actions.encode(&mut w)?;
+            }
+            Self::RequestSnapshot => 1u8.encode(&mut w)?,
+        }
+        Ok(w.finish())
+    }
+
+    /// Decoding `Submit` stops at the first invalid action, so that the seq can
+    /// still be used to reject it.
+    pub fn decode(bytes: &[u8], from_wire: HandleMap<'_>) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes, from_wire);
+        let msg = match u8::decode(&mut r)? {
+            0 => {
+                let seq = u64::decode(&mut r)?;
+                let actions = Vec::decode(&mut r)
+                    .and_then(|actions| r.finish().map(|_| actions))
+                    .map_err(|err| DecodeError::Submit(seq, err))?;
+                return Ok(Self::Submit { seq, actions });
+            }
+            1 => Self::RequestSnapshot,
+            t => return Err(eyre!("{} is not a client message", t).into()),
+        };
+        r.finish()?;
+        Ok(msg)
+    }
+}
+
+/// Why a `ClientMessage` couldn't be decoded.
+#[derive(Debug)]
+pub(crate) enum DecodeError {
+    /// The message was a `Submit`, with a valid seq.
+    Submit(u64, eyre::Report),
+    Other(eyre::Report),
+}
+impl From<eyre::Report> for DecodeError {
+    fn from(err: eyre::Report) -> Self {
+        Self::Other(err)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ServerMessage {
+    /// The first message that a client receives.
+    Welcome { client: ClientId },
+    /// The server's baseline, serialized with `tp_serialize`. `objects` are the
+    /// server's handles of the serialized objects, in the order that they
+    /// appear in the flatbuffer.
+    Snapshot {
+        data: Vec<u8>,
+        objects: Vec<ObjectHandle>,
+    },
+    /// Objects entered or left the client's interest. `entered` are ordered
+    /// so that parents come before their children, and siblings in order.
+    /// Their states hold the server's handles.
+    Update {
+        entered: Vec<EnteredObject>,
+        left: Vec<ObjectHandle>,
+    },
+    /// A collaction was applied by the server, and should be applied by the
+    /// clients too.
+    Applied {
+        origin: ClientId,
+        seq: u64,
+        actions: Vec<NetAction>,
+    },
+    /// A collaction from this client was rejected.
+    Rejected { seq: u64, reason: String },
+}
+impl ServerMessage {
+    /// Handles are already the server's, so they aren't translated.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut w = Writer::new(&crate::codec::same_handle);
+        match self {
+            Self::Welcome { client } => {
+                0u8.encode(&mut w)?;
+                client.0.encode(&mut w)?;
+            }
+            Self::Snapshot { data, objects } => {
+                1u8.encode(&mut w)?;
+                w.write_len(data.len())?;
+                w.write_bytes(data);
+                w.write_len(objects.len())?;
+                for o in objects {
+                    w.write_raw_handle(*o)?;
+                }
+            }
+            Self::Applied {
+                origin,
+                seq,
+                actions,
+            } => {
+                2u8.encode(&mut w)?;
+                origin.0.encode(&mut w)?;
+                seq.encode(&mut w)?;
+                actions.encode(&mut w)?;
+            }
+            Self::Rejected { seq, reason } => {
+                3u8.encode(&mut w)?;
+                seq.encode(&mut w)?;
+                reason.encode(&mut w)?;
+            }
+            Self::Update { entered, left } => {
+                4u8.encode(&mut w)?;
+                entered.encode(&mut w)?;
+                w.write_len(left.len())?;
+                for o in left {
+                    w.write_raw_handle(*o)?;
+                }
+            }
+        }
+        Ok(w.finish())
+    }
+
+    /// `from_wire` translates the server's handles to local ones. It is not
+    /// used for `Snapshot`s and `Update`s, whose objects don't exist locally
+    /// yet.
+    pub fn decode(bytes: &[u8], from_wire: HandleMap<'_>) -> Result<Self> {
+        let mut r = Reader::new(bytes, from_wire);
+        let msg = match u8::decode(&mut r)? {
+            0 => Self::Welcome {
+                client: ClientId(Decode::decode(&mut r)?),
+            },
+            1 => {
+                let len = r.read_len()?;
+                let data = r.read_bytes(len)?.to_vec();
+                let len = r.read_len()?;
+                let objects = (0..len)
+                    .map(|_| r.read_raw_handle())
+                    .collect::<Result<_>>()?;
+                Self::Snapshot { data, objects }
+            }
+            2 => Self::Applied {
+                origin: ClientId(Decode::decode(&mut r)?),
+                seq: Decode::decode(&mut r)?,
+                actions: Decode::decode(&mut r)?,
+            },
+            3 => Self::Rejected {
+                seq: Decode::decode(&mut r)?,
+                reason: Decode::decode(&mut r)?,
+            },
+            4 => {
+                r.untranslated();
+                let entered = Decode::decode(&mut r)?;
+                let len = r.read_len()?;
+                let left = (0..len)
+                    .map(|_| r.read_raw_handle())
+                    .collect::<Result<_>>()?;
+                Self::Update { entered, left }
+            }
+            t => return Err(eyre!("{} is not a server message", t)),
+        };
+        r.finish()?;
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::same_handle;
+
+    use arena::generational_arena as ga;
+
+    #[test]
+    fn test_roundtrip() {
+        let object = ObjectHandle::new(ga::Index::from_raw_parts(2, 1));
+        let actions = vec![
+            NetAction::StateWrite {
+                object,
+                state: 1,
+                data: 3.0f32.into(),
+            },
+            NetAction::MapInsert {
+                object,
+                state: 2,
+                key: "a".into(),
+                value: 7u32.into(),
+            },
+            NetAction::BytesWrite {
+                object,
+                state: 3,
+                offset: 4,
+                chunk: Bytes::from(vec![1, 2]),
+            },
+            NetAction::Merge {
+                object,
+                state: 3,
+                delta: Bytes::from(vec![3]),
+            },
+            NetAction::LockAcquire {
+                object,
+                state: None,
+                lease: Ticks::new(100),
+            },
+            NetAction::LockRelease {
+                object,
+                state: Some(1),
+            },
+        ];
+
+        let msg = ClientMessage::Submit {
+            seq: 5,
+            actions: actions.clone(),
+        };
+        let bytes = msg.encode(&same_handle).unwrap();
+        assert_eq!(ClientMessage::decode(&bytes, &same_handle).unwrap(), msg);
+
+        for msg in [
+            ServerMessage::Welcome {
+                client: ClientId(3),
+            },
+            ServerMessage::Snapshot {
+                data: vec![1, 2, 3],
+                objects: vec![object],
+            },
+            ServerMessage::Applied {
+                origin: ClientId::SERVER,
+                seq: 5,
+                actions,
+            },
+            ServerMessage::Rejected {
+                seq: 5,
+                reason: "no".into(),
+            },
+            ServerMessage::Update {
+                entered: vec![EnteredObject {
+                    object,
+                    contract: ("teleportal.test".into(), (1, 2, 3)),
+                    parent: Some((object, 4)),
+                    states: vec![7u8.into(), object.into()],
+                }],
+                left: vec![object],
+            },
+        ] {
+            let bytes = msg.encode().unwrap();
+            assert_eq!(ServerMessage::decode(&bytes, &same_handle).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn test_invalid_submit_keeps_seq() {
+        let msg = ClientMessage::Submit {
+            seq: 9,
+            actions: Vec::new(),
+        };
+        let mut bytes = msg.encode(&same_handle).unwrap();
+        // Claim that there is one action, which is missing
+        let len = bytes.len();
+        bytes[len - 4] = 1;
+        assert!(matches!(
+            ClientMessage::decode(&bytes, &same_handle),
+            Err(DecodeError::Submit(9, _))
+        ));
+    }
+}
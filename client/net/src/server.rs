@@ -0,0 +1,399 @@
+use crate::codec::same_handle;
+use crate::interest::{Changes, Interest};
+use crate::journal::Journal;
+use crate::protocol::{
+    self, ClientId, ClientMessage, DecodeError, EnteredObject, NetAction, ServerMessage,
+};
+use crate::snapshot;
+use crate::transport::{Connection, Listener};
+
+use eyre::{eyre, Result, WrapErr};
+use std::collections::HashSet;
+use tp_client::baseline::{Baseline, BaselineKind};
+use tp_client::engine::ActionSender;
+use tp_client::object::ObjectHandle;
+use tp_client::realm::Realm;
+use tp_client::Engine;
+
+/// Decides whether a client may apply a collaction, before it reaches the
+/// `Engine`. Returning an error rejects the collaction, with the error as the
+/// reason.
+pub type Validator = Box<dyn FnMut(ClientId, &[NetAction], &Baseline) -> Result<()> + Send>;
+
+/// Something that happened during [`Server::poll`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerEvent {
+    Connected(ClientId),
+    Disconnected(ClientId),
+    Approved { client: ClientId, seq: u64 },
+    Rejected { client: ClientId, seq: u64 },
+}
+
+struct Peer<C> {
+    id: ClientId,
+    conn: C,
+    interest: Interest,
+    /// The objects that the client was last sent
+    relevant: HashSet<ObjectHandle>,
+}
+
+/// Owns the authoritative copy of a `Realm`. Clients submit collactions to the
+/// server, which applies them to the fork baseline of its `Engine` and, if
+/// they were approved, sends them to every client that they are relevant to.
+///
+/// Each client has an [`Interest`], which decides the objects that it is sent.
+/// New clients receive a snapshot of those objects in the fork baseline. When
+/// a collaction moves objects in or out of a client's interest, by writing to
+/// their positions or references, the client is only sent the objects that
+/// entered and the handles of those that left, so its other handles stay
+/// valid. Objects and contracts aren't created through collactions, so after
+/// creating or removing them on the server, call [`Server::broadcast_snapshot`].
+/// Snapshots are serialized with `tp_serialize`, so they only support what it
+/// does.
+///
+/// Approved collactions are recorded to the server's [`Journal`], if it has
+/// one.
+pub struct Server<L: Listener> {
+    engine: Engine,
+    // The server is the only sender, so that collactions are applied in the
+    // order that they are received.
+    sender: ActionSender,
+    listener: L,
+    peers: Vec<Peer<L::Connection>>,
+    next_id: u32,
+    validators: Vec<Validator>,
+    default_interest: Interest,
+    journal: Option<Journal>,
+}
+impl<L: Listener> Server<L> {
+    pub fn new(realm: Realm, listener: L) -> Self {
+        let (engine, sender) = Engine::new(realm, None);
+        Self {
+            engine,
+            sender,
+            listener,
+            peers: Vec::new(),
+            next_id: ClientId::SERVER.0 + 1,
+            validators: Vec::new(),
+            default_interest: Interest::everything(),
+            journal: None,
+        }
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Changes made directly to the realm are not sent to clients, see
+    /// [`Server::broadcast_snapshot`].
+    pub fn engine_mut(&mut self) -> &mut Engine {
+        &mut self.engine
+    }
+
+    pub fn clients(&self) -> impl Iterator<Item = ClientId> + '_ {
+        self.peers.iter().map(|p| p.id)
+    }
+
+    /// The interest that new clients start with. Clients are sent everything
+    /// unless this is changed.
+    pub fn set_default_interest(&mut self, interest: Interest) {
+        self.default_interest = interest;
+    }
+
+    /// Returns `None` if there is no such client.
+    pub fn interest(&self, client: ClientId) -> Option<&Interest> {
+        self.peers
+            .iter()
+            .find(|p| p.id == client)
+            .map(|p| &p.interest)
+    }
+
+    /// Changes the objects that `client` is sent. It is sent the objects that
+    /// entered its interest, and the handles of those that left.
+    pub fn set_interest(&mut self, client: ClientId, interest: Interest) -> Result<()> {
+        let idx = self
+            .peers
+            .iter()
+            .position(|p| p.id == client)
+            .ok_or_else(|| eyre!("There is no client {:?}", client))?;
+        self.peers[idx].interest = interest;
+        self.resync(idx, false)?;
+        Ok(())
+    }
+
+    /// Starts recording every approved collaction to `journal`, beginning with
+    /// a checkpoint of the fork baseline as it is now.
+    pub fn set_journal(&mut self, mut journal: Journal) -> Result<()> {
+        let realm = self.engine.realm();
+        journal.reset(realm.baseline(BaselineKind::Fork), realm.time())?;
+        self.journal = Some(journal);
+        Ok(())
+    }
+
+    pub fn journal(&self) -> Option<&Journal> {
+        self.journal.as_ref()
+    }
+
+    /// Stops recording, and returns the journal.
+    pub fn take_journal(&mut self) -> Option<Journal> {
+        self.journal.take()
+    }
+
+    /// Registers `validator` to be called with every collaction that a client
+    /// submits, before it is applied.
+    pub fn add_validator(
+        &mut self,
+        validator: impl FnMut(ClientId, &[NetAction], &Baseline) -> Result<()> + Send + 'static,
+    ) {
+        self.validators.push(Box::new(validator));
+    }
+
+    /// Accepts new clients, and handles everything that clients have sent.
+    pub fn poll(&mut self) -> Result<Vec<ServerEvent>> {
+        let mut events = Vec::new();
+
+        while let Some(conn) = self.listener.try_accept()? {
+            let id = ClientId(self.next_id);
+            self.next_id += 1;
+            self.peers.push(Peer {
+                id,
+                conn,
+                interest: self.default_interest.clone(),
+                relevant: HashSet::new(),
+            });
+            let idx = self.peers.len() - 1;
+            let welcome = ServerMessage::Welcome { client: id }.encode()?;
+            let welcomed = self.peers[idx]
+                .conn
+                .send(&welcome)
+                .and_then(|()| self.resync(idx, true));
+            if let Err(e) = welcomed {
+                tracing::warn!("Failed to welcome client {:?}: {:#}", id, e);
+                self.peers.pop();
+                continue;
+            }
+            events.push(ServerEvent::Connected(id));
+        }
+
+        let mut idx = 0;
+        while idx < self.peers.len() {
+            match self.recv_from(idx, &mut events) {
+                Ok(()) => idx += 1,
+                Err(e) => {
+                    let peer = self.peers.remove(idx);
+                    tracing::debug!("Client {:?} disconnected: {:#}", peer.id, e);
+                    events.push(ServerEvent::Disconnected(peer.id));
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// Handles the messages from the peer at `idx`, until there are none left.
+    /// Errors if the peer disconnected.
+    fn recv_from(&mut self, idx: usize, events: &mut Vec<ServerEvent>) -> Result<()> {
+        while let Some(bytes) = self.peers[idx].conn.try_recv()? {
+            let id = self.peers[idx].id;
+            match ClientMessage::decode(&bytes, &same_handle) {
+                Ok(ClientMessage::Submit { seq, actions }) => {
+                    let event = match self.submit(id, seq, actions) {
+                        Ok(()) => ServerEvent::Approved { client: id, seq },
+                        Err(reason) => {
+                            let reason = format!("{:#}", reason);
+                            let msg = ServerMessage::Rejected { seq, reason }.encode()?;
+                            self.peers[idx].conn.send(&msg)?;
+                            ServerEvent::Rejected { client: id, seq }
+                        }
+                    };
+                    events.push(event);
+                }
+                Ok(ClientMessage::RequestSnapshot) => {
+                    self.resync(idx, true)?;
+                }
+                Err(DecodeError::Submit(seq, err)) => {
+                    let reason = format!("{:#}", err.wrap_err("Invalid collaction"));
+                    let msg = ServerMessage::Rejected { seq, reason }.encode()?;
+                    self.peers[idx].conn.send(&msg)?;
+                    events.push(ServerEvent::Rejected { client: id, seq });
+                }
+                Err(DecodeError::Other(err)) => {
+                    tracing::warn!("Ignoring invalid message from {:?}: {:#}", id, err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies a collaction as the server itself, and sends it to the clients
+    /// that it is relevant to if it was approved.
+    ///
+    /// # Errors
+    /// Will error if the collaction was rejected.
+    pub fn apply(&mut self, actions: Vec<NetAction>) -> Result<()> {
+        self.submit(ClientId::SERVER, 0, actions)
+    }
+
+    /// Validates and applies a collaction, and sends it to the clients that it
+    /// is relevant to if it was approved. Errors with the reason if it was
+    /// rejected.
+    fn submit(&mut self, origin: ClientId, seq: u64, actions: Vec<NetAction>) -> Result<()> {
+        let baseline = self.engine.realm().baseline(BaselineKind::Fork);
+        if let Some(peer) = self.peers.iter().find(|p| p.id == origin) {
+            if let Some(a) = actions
+                .iter()
+                .find(|a| !peer.relevant.contains(&a.target().0))
+            {
+                return Err(eyre!("{:?} was not sent to this client", a.target().0));
+            }
+        }
+        if origin != ClientId::SERVER {
+            for validator in &mut self.validators {
+                validator(origin, &actions, baseline)?;
+            }
+        }
+        // The `Engine` swaps the old values into the actions that it applies,
+        // so clients are sent `actions` instead. Collactions are owned by
+        // their origin, so that the engine can enforce the locks of each client.
+        let collaction = protocol::to_collaction(&actions, baseline)?.with_owner(origin.into());
+        self.sender
+            .send(collaction)
+            .expect("The engine holds a receiver");
+        let result = self
+            .engine
+            .try_apply()
+            .expect("The collaction was just sent");
+        if let Err(rejected) = result {
+            let rejection = rejected
+                .rejection()
+                .expect("The engine explains every rejection");
+            return Err(eyre!(
+                "Action {} was rejected by the engine: {}",
+                rejection.action,
+                rejection.reason
+            ));
+        }
+        if let Some(journal) = &mut self.journal {
+            let realm = self.engine.realm();
+            let baseline = realm.baseline(BaselineKind::Fork);
+            if let Err(e) = journal.record(baseline, realm.time(), origin, actions.clone()) {
+                tracing::error!("Failed to journal a collaction: {:#}", e);
+            }
+        }
+
+        // The collaction may have moved objects in or out of the interest of
+        // clients that depend on what it changed. The objects that entered
+        // are sent as they are now, so they already include it.
+        let baseline = self.engine.realm().baseline(BaselineKind::Fork);
+        let changes = Changes::of(&actions, baseline, self.engine.spatial_index());
+        for idx in 0..self.peers.len() {
+            let mut entered = HashSet::new();
+            if self.peers[idx].interest.is_affected_by(changes) {
+                entered = self.resync(idx, false).unwrap_or_else(|e| {
+                    tracing::warn!("Failed to resync {:?}: {:#}", self.peers[idx].id, e);
+                    HashSet::new()
+                });
+            }
+            let peer = &mut self.peers[idx];
+            let actions: Vec<NetAction> = actions
+                .iter()
+                .filter(|a| {
+                    let obj = a.target().0;
+                    peer.relevant.contains(&obj) && !entered.contains(&obj)
+                })
+                .cloned()
+                .collect();
+            // The client that submitted it always hears back.
+            if actions.is_empty() && peer.id != origin {
+                continue;
+            }
+            let msg = ServerMessage::Applied {
+                origin,
+                seq,
+                actions,
+            }
+            .encode()
+            .wrap_err("Failed to encode an approved collaction")?;
+            send(peer, &msg);
+        }
+        Ok(())
+    }
+
+    /// Sends every client a new snapshot of the objects that it is interested
+    /// in. Needed after objects or contracts are created or removed on the
+    /// server, which also checkpoints the journal.
+    pub fn broadcast_snapshot(&mut self) -> Result<()> {
+        if let Some(journal) = &mut self.journal {
+            let realm = self.engine.realm();
+            journal.reset(realm.baseline(BaselineKind::Fork), realm.time())?;
+        }
+        for idx in 0..self.peers.len() {
+            self.resync(idx, true)?;
+        }
+        Ok(())
+    }
+
+    /// Recomputes the objects that the client at `idx` is interested in. If
+    /// `force` is set, it is sent a new snapshot of them. Otherwise it is only
+    /// sent the objects that entered its interest, and the handles of those
+    /// that left, if there are any. Returns the objects that were sent.
+    fn resync(&mut self, idx: usize, force: bool) -> Result<HashSet<ObjectHandle>> {
+        let baseline = self.engine.realm().baseline(BaselineKind::Fork);
+        let peer = &self.peers[idx];
+        let relevant = peer
+            .interest
+            .relevant(baseline, self.engine.spatial_index());
+        if force {
+            let snapshot = self.snapshot(&relevant)?;
+            let peer = &mut self.peers[idx];
+            peer.relevant = relevant.clone();
+            send(peer, &snapshot);
+            return Ok(relevant);
+        }
+
+        let entered: HashSet<ObjectHandle> = relevant.difference(&peer.relevant).copied().collect();
+        let left: Vec<ObjectHandle> = peer.relevant.difference(&relevant).copied().collect();
+        if entered.is_empty() && left.is_empty() {
+            return Ok(entered);
+        }
+        let update = ServerMessage::Update {
+            entered: self.entered_objects(&entered)?,
+            left,
+        }
+        .encode()?;
+        let peer = &mut self.peers[idx];
+        peer.relevant = relevant;
+        send(peer, &update);
+        Ok(entered)
+    }
+
+    /// Reads the objects in `entered`, ordered so that parents come before
+    /// their children, and siblings in order.
+    fn entered_objects(&self, entered: &HashSet<ObjectHandle>) -> Result<Vec<EnteredObject>> {
+        let baseline = self.engine.realm().baseline(BaselineKind::Fork);
+        let mut objects = entered
+            .iter()
+            .map(|obj| {
+                let depth = baseline.iter_ancestors(*obj).count();
+                EnteredObject::new(baseline, *obj).map(|o| (depth, o))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        objects.sort_by_key(|(depth, o)| (*depth, o.parent.map(|(_, idx)| idx)));
+        Ok(objects.into_iter().map(|(_, o)| o).collect())
+    }
+
+    /// Serializes all contracts in the fork baseline, and the objects in
+    /// `relevant`.
+    fn snapshot(&self, relevant: &HashSet<ObjectHandle>) -> Result<Vec<u8>> {
+        let baseline = self.engine.realm().baseline(BaselineKind::Fork);
+        let (data, objects) = snapshot::take(baseline, |obj| relevant.contains(&obj))?;
+        ServerMessage::Snapshot { data, objects }.encode()
+    }
+}
+
+/// Clients that fail to receive `msg` are dropped on their next `poll()`, when
+/// their connection errors again.
+fn send<C: Connection>(peer: &mut Peer<C>, msg: &[u8]) {
+    if let Err(e) = peer.conn.send(msg) {
+        tracing::debug!("Failed to send to {:?}: {:#}", peer.id, e);
+    }
+}
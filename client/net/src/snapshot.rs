@@ -0,0 +1,61 @@
+//! Snapshots of a baseline, serialized with `tp_serialize`.
+//!
+//! Deserializing gives every object a new handle, so snapshots also carry the
+//! handles that their objects had when they were taken. Those are what the
+//! server and the journal refer to objects by.
+
+use eyre::{eyre, Result, WrapErr};
+use flatbuffers::FlatBufferBuilder;
+use std::collections::HashMap;
+use tp_client::baseline::{Baseline, BaselineKind};
+use tp_client::object::ObjectHandle;
+use tp_serialize::{DeserializerBuilder, Serializer};
+
+/// Serializes all contracts in `baseline`, and the objects for which `filter`
+/// returns true. Returns the flatbuffer, and the handles of the objects in the
+/// order that they appear in it.
+pub(crate) fn take(
+    baseline: &Baseline,
+    mut filter: impl FnMut(ObjectHandle) -> bool,
+) -> Result<(Vec<u8>, Vec<ObjectHandle>)> {
+    let mut serializer = Serializer::new(FlatBufferBuilder::new(), baseline);
+    for contract in baseline.iter_contracts() {
+        serializer
+            .serialize_filtered(&contract, &mut filter)
+            .wrap_err_with(|| format!("Failed to serialize {:?}", contract.id()))?;
+    }
+    let objects = serializer.serialized_objects();
+    let data = serializer.finish().finished_data().to_vec();
+    Ok((data, objects))
+}
+
+/// Deserializes a snapshot into a new baseline. Contracts don't need to be
+/// known ahead of time, since they are registered from the snapshot.
+///
+/// Returns the baseline, and a map from the handles in `objects` to the new
+/// ones.
+pub(crate) fn load(
+    data: &[u8],
+    objects: &[ObjectHandle],
+    kind: BaselineKind,
+) -> Result<(Baseline, HashMap<ObjectHandle, ObjectHandle>)> {
+    let mut builder = DeserializerBuilder::new(data, kind)?;
+    let contracts = builder
+        .serialized_schemas()?
+        .iter()
+        .map(|schema| builder.register_dyn_contract(schema))
+        .collect::<Result<Vec<_>>>()?;
+    let mut deserializer = builder.finish();
+    for contract in &contracts {
+        deserializer.deserialize_objects(contract)?;
+    }
+
+    let mut handles = HashMap::new();
+    for (idx, original) in objects.iter().enumerate() {
+        let new = deserializer
+            .deserialized_object(idx)
+            .ok_or_else(|| eyre!("The snapshot is missing object {}", idx))?;
+        handles.insert(*original, new);
+    }
+    Ok((deserializer.finish()?, handles))
+}
@@ -0,0 +1,102 @@
+//! A transport within a single process, mostly for tests.
+
+use super::{Connection, Listener};
+
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
+use eyre::{eyre, Result};
+
+/// Creates a listener, and a connector that makes connections to it.
+pub fn loopback() -> (LoopbackListener, LoopbackConnector) {
+    let (sender, receiver) = crossbeam_channel::unbounded();
+    (
+        LoopbackListener { pending: receiver },
+        LoopbackConnector { listener: sender },
+    )
+}
+
+pub struct LoopbackListener {
+    pending: Receiver<LoopbackConnection>,
+}
+impl Listener for LoopbackListener {
+    type Connection = LoopbackConnection;
+
+    fn try_accept(&mut self) -> Result<Option<Self::Connection>> {
+        match self.pending.try_recv() {
+            Ok(conn) => Ok(Some(conn)),
+            // Connectors may all be gone while the listener is still useful.
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => Ok(None),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LoopbackConnector {
+    listener: Sender<LoopbackConnection>,
+}
+impl LoopbackConnector {
+    /// Connects to the listener.
+    ///
+    /// # Errors
+    /// Will error if the listener was dropped.
+    pub fn connect(&self) -> Result<LoopbackConnection> {
+        let (to_server, from_client) = crossbeam_channel::unbounded();
+        let (to_client, from_server) = crossbeam_channel::unbounded();
+        let server_end = LoopbackConnection {
+            sender: to_client,
+            receiver: from_client,
+        };
+        self.listener
+            .send(server_end)
+            .map_err(|_| eyre!("The listener was dropped"))?;
+        Ok(LoopbackConnection {
+            sender: to_server,
+            receiver: from_server,
+        })
+    }
+}
+
+/// Either end of a loopback connection. Dropping it closes the connection.
+pub struct LoopbackConnection {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+}
+impl Connection for LoopbackConnection {
+    fn send(&mut self, msg: &[u8]) -> Result<()> {
+        self.sender
+            .send(msg.to_vec())
+            .map_err(|_| eyre!("The connection was closed"))
+    }
+
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>> {
+        match self.receiver.try_recv() {
+            Ok(msg) => Ok(Some(msg)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(eyre!("The connection was closed")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loopback() {
+        let (mut listener, connector) = loopback();
+        assert!(listener.try_accept().unwrap().is_none());
+
+        let mut client = connector.connect().unwrap();
+        let mut server = listener.try_accept().unwrap().unwrap();
+        client.send(b"hello").unwrap();
+        client.send(b"").unwrap();
+        assert_eq!(server.try_recv().unwrap().unwrap(), b"hello");
+        assert_eq!(server.try_recv().unwrap().unwrap(), b"");
+        assert!(server.try_recv().unwrap().is_none());
+
+        server.send(b"bye").unwrap();
+        drop(server);
+        assert_eq!(client.try_recv().unwrap().unwrap(), b"bye");
+        assert!(client.try_recv().is_err());
+        assert!(client.send(b"?").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+//! Transports carry whole messages between the server and its clients.
+//!
+//! Everything is polled rather than blocking, so that the server and clients
+//! can be driven from the same loop that ticks their `Engine`.
+
+pub mod loopback;
+pub mod tcp;
+pub mod ws;
+
+pub use self::loopback::{LoopbackConnection, LoopbackConnector, LoopbackListener};
+pub use self::tcp::{TcpConnection, TcpListener};
+pub use self::ws::{WsConnection, WsListener};
+
+use eyre::Result;
+
+/// A connection to a single peer, which preserves the boundaries of messages.
+pub trait Connection: Send {
+    /// Queues `msg` to be sent to the peer.
+    ///
+    /// # Errors
+    /// Will error if the connection was closed.
+    fn send(&mut self, msg: &[u8]) -> Result<()>;
+
+    /// Returns the next message from the peer, or `None` if no whole message
+    /// has arrived yet.
+    ///
+    /// # Errors
+    /// Will error if the connection was closed, after all messages that
+    /// arrived before then were returned.
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>>;
+
+    /// Sends any messages that the transport wasn't ready to send yet. This is
+    /// also done by `try_recv()`.
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Accepts connections from clients.
+pub trait Listener: Send {
+    type Connection: Connection;
+
+    /// Returns the next new connection, or `None` if there are none pending.
+    fn try_accept(&mut self) -> Result<Option<Self::Connection>>;
+}
+
+/// Calls `f` until it returns `Some`, and panics if that takes too long.
+#[cfg(test)]
+pub(crate) fn poll_until<T>(mut f: impl FnMut() -> Option<T>) -> T {
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    loop {
+        if let Some(v) = f() {
+            return v;
+        }
+        assert!(start.elapsed() < Duration::from_secs(5), "Timed out");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,168 @@
+//! A transport over TCP. Each message is prefixed with its length, as a
+//! little endian `u32`.
+
+use super::{Connection, Listener};
+
+use eyre::{eyre, Result, WrapErr};
+use std::io::{ErrorKind, Read, Write};
+use std::net::{self, SocketAddr, TcpStream, ToSocketAddrs};
+
+/// Messages larger than this are treated as a broken connection.
+const MAX_MESSAGE_LEN: usize = 64 * 1024 * 1024;
+
+pub struct TcpListener {
+    inner: net::TcpListener,
+}
+impl TcpListener {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        let inner = net::TcpListener::bind(addr).wrap_err("Failed to bind")?;
+        inner.set_nonblocking(true)?;
+        Ok(Self { inner })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.inner.local_addr()?)
+    }
+}
+impl Listener for TcpListener {
+    type Connection = TcpConnection;
+
+    fn try_accept(&mut self) -> Result<Option<Self::Connection>> {
+        match self.inner.accept() {
+            Ok((stream, _addr)) => TcpConnection::new(stream).map(Some),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+pub struct TcpConnection {
+    stream: TcpStream,
+    /// Bytes received that don't make up a whole message yet
+    incoming: Vec<u8>,
+    /// Bytes that the socket wasn't ready to send yet
+    outgoing: Vec<u8>,
+    closed: bool,
+}
+impl TcpConnection {
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<Self> {
+        let stream = TcpStream::connect(addr).wrap_err("Failed to connect")?;
+        Self::new(stream)
+    }
+
+    fn new(stream: TcpStream) -> Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Reads everything that has arrived, until the socket would block.
+    fn fill(&mut self) -> Result<()> {
+        let mut buf = [0; 4096];
+        while !self.closed {
+            match self.stream.read(&mut buf) {
+                Ok(0) => self.closed = true,
+                Ok(n) => self.incoming.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Takes the first whole message out of `incoming`.
+    fn take_message(&mut self) -> Result<Option<Vec<u8>>> {
+        let Some(len) = self.incoming.get(..4) else {
+            return Ok(None);
+        };
+        let len = u32::from_le_bytes(len.try_into().expect("Length was checked")) as usize;
+        if len > MAX_MESSAGE_LEN {
+            return Err(eyre!("Message of {} bytes was too large", len));
+        }
+        if self.incoming.len() < 4 + len {
+            return Ok(None);
+        }
+        let msg = self.incoming[4..4 + len].to_vec();
+        self.incoming.drain(..4 + len);
+        Ok(Some(msg))
+    }
+}
+impl Connection for TcpConnection {
+    fn send(&mut self, msg: &[u8]) -> Result<()> {
+        if self.closed {
+            return Err(eyre!("The connection was closed"));
+        }
+        let len = u32::try_from(msg.len())
+            .ok()
+            .filter(|len| *len as usize <= MAX_MESSAGE_LEN)
+            .ok_or_else(|| eyre!("Message of {} bytes was too large", msg.len()))?;
+        self.outgoing.extend_from_slice(&len.to_le_bytes());
+        self.outgoing.extend_from_slice(msg);
+        self.flush()
+    }
+
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>> {
+        self.flush()?;
+        if let Some(msg) = self.take_message()? {
+            return Ok(Some(msg));
+        }
+        self.fill()?;
+        match self.take_message()? {
+            Some(msg) => Ok(Some(msg)),
+            None if self.closed => Err(eyre!("The connection was closed")),
+            None => Ok(None),
+        }
+    }
+
+    /// Sends as much of the queued messages as the socket will take.
+    fn flush(&mut self) -> Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(eyre!("The connection was closed")),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::poll_until;
+
+    #[test]
+    fn test_tcp() {
+        let mut listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpConnection::connect(listener.local_addr().unwrap()).unwrap();
+        let mut server = poll_until(|| listener.try_accept().unwrap());
+
+        // Large enough that the socket can't take it all at once
+        let big = vec![7; 1024 * 1024];
+        client.send(b"hello").unwrap();
+        client.send(&big).unwrap();
+        let mut recv = || {
+            client.flush().unwrap();
+            server.try_recv().unwrap()
+        };
+        assert_eq!(poll_until(&mut recv), b"hello");
+        assert_eq!(poll_until(&mut recv), big);
+        assert!(recv().is_none());
+
+        server.send(b"bye").unwrap();
+        drop(server);
+        assert_eq!(poll_until(|| client.try_recv().unwrap()), b"bye");
+        poll_until(|| client.try_recv().is_err().then_some(()));
+    }
+}
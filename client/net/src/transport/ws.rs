@@ -0,0 +1,135 @@
+//! A transport over WebSockets, so that browsers can connect. Each message is
+//! sent as a single binary WebSocket message.
+
+use super::{Connection, Listener};
+
+use eyre::{eyre, Result, WrapErr};
+use std::io::ErrorKind;
+use std::net::{self, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use tungstenite::http::Uri;
+use tungstenite::{Message, WebSocket};
+
+/// How long the handshake of a new connection may take.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Accepting a connection blocks during its WebSocket handshake, for up to
+/// `HANDSHAKE_TIMEOUT`.
+pub struct WsListener {
+    inner: net::TcpListener,
+}
+impl WsListener {
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self> {
+        let inner = net::TcpListener::bind(addr).wrap_err("Failed to bind")?;
+        inner.set_nonblocking(true)?;
+        Ok(Self { inner })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.inner.local_addr()?)
+    }
+}
+impl Listener for WsListener {
+    type Connection = WsConnection;
+
+    fn try_accept(&mut self) -> Result<Option<Self::Connection>> {
+        let stream = match self.inner.accept() {
+            Ok((stream, _addr)) => stream,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let ws = tungstenite::accept(stream).map_err(|e| eyre!("Handshake failed: {}", e))?;
+        WsConnection::new(ws).map(Some)
+    }
+}
+
+pub struct WsConnection {
+    ws: WebSocket<TcpStream>,
+}
+impl WsConnection {
+    /// Connects to `url`, like `ws://localhost:8080`.
+    pub fn connect(url: &str) -> Result<Self> {
+        let uri: Uri = url.parse().wrap_err("Invalid url")?;
+        let host = uri.host().ok_or_else(|| eyre!("The url has no host"))?;
+        let port = uri.port_u16().unwrap_or(80);
+        let stream = TcpStream::connect((host, port)).wrap_err("Failed to connect")?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+        let (ws, _response) =
+            tungstenite::client(url, stream).map_err(|e| eyre!("Handshake failed: {}", e))?;
+        Self::new(ws)
+    }
+
+    fn new(ws: WebSocket<TcpStream>) -> Result<Self> {
+        let stream = ws.get_ref();
+        stream.set_read_timeout(None)?;
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self { ws })
+    }
+}
+impl Connection for WsConnection {
+    fn send(&mut self, msg: &[u8]) -> Result<()> {
+        match self.ws.write_message(Message::Binary(msg.to_vec())) {
+            // The message was queued, and is sent by `flush()`
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            result => result.map_err(|e| eyre!("Failed to send: {}", e)),
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<Option<Vec<u8>>> {
+        self.flush()?;
+        loop {
+            match self.ws.read_message() {
+                Ok(Message::Binary(msg)) => return Ok(Some(msg)),
+                Ok(Message::Close(_)) => return Err(eyre!("The connection was closed")),
+                // Pings are answered by tungstenite, and nothing else is used.
+                Ok(_) => continue,
+                Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => {
+                    return Ok(None)
+                }
+                Err(e) => return Err(eyre!("The connection was closed: {}", e)),
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        match self.ws.write_pending() {
+            Err(tungstenite::Error::Io(e)) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            result => result.map_err(|e| eyre!("Failed to send: {}", e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::poll_until;
+
+    use std::thread;
+
+    #[test]
+    fn test_ws() {
+        let mut listener = WsListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        // The handshakes of both ends block, so they can't be on one thread.
+        let client = thread::spawn(move || WsConnection::connect(&url).unwrap());
+        let mut server = poll_until(|| listener.try_accept().unwrap());
+        let mut client = client.join().unwrap();
+
+        client.send(b"hello").unwrap();
+        client.send(b"").unwrap();
+        let mut recv = || {
+            client.flush().unwrap();
+            server.try_recv().unwrap()
+        };
+        assert_eq!(poll_until(&mut recv), b"hello");
+        assert_eq!(poll_until(&mut recv), b"");
+        assert!(recv().is_none());
+
+        server.send(b"bye").unwrap();
+        server.flush().unwrap();
+        assert_eq!(poll_until(|| client.try_recv().unwrap()), b"bye");
+    }
+}
@@ -0,0 +1,458 @@
+use eyre::{eyre, Result, WrapErr};
+use std::ops::Bound;
+use std::time::{Duration, Instant};
+use tp_client::baseline::{Baseline, BaselineKind};
+use tp_client::contract::properties::dynamic::{DynTpProperty, TpPrimitiveType, TpPropertyType};
+use tp_client::contract::properties::field::FieldRange;
+use tp_client::contract::properties::states::StateId;
+use tp_client::contract::{ContractSchema, DynContract};
+use tp_client::object::ObjectHandle;
+use tp_client::realm::{Realm, RealmID};
+use tp_client::spatial::PositionStates;
+use tp_net::transport::{self, Connection, Listener, TcpConnection, TcpListener};
+use tp_net::{
+    Client, ClientEvent, Interest, Journal, NetAction, Proximity, Replayer, Server, ServerEvent,
+};
+
+const NAME: usize = 0;
+const HP: usize = 1;
+const TARGET: usize = 2;
+
+fn player_schema() -> ContractSchema {
+    let mut schema = ContractSchema::new("teleportal.test.net-player", (0, 1, 0))
+        .with_state("name", TpPropertyType::Primitive(TpPrimitiveType::String))
+        .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8))
+        .with_state(
+            "target",
+            TpPropertyType::Primitive(TpPrimitiveType::ObjectHandle),
+        );
+    schema.states[HP].info.range = Some(FieldRange {
+        start: Bound::Included(0.0),
+        end: Bound::Included(100.0),
+    });
+    schema
+}
+
+/// A realm with two markers, and a player that targets the first one.
+/// Returns the player and the markers.
+fn realm() -> Result<(Realm, ObjectHandle, Vec<ObjectHandle>)> {
+    let mut realm = Realm::new(RealmID::new("test".to_string()));
+    let b = realm.baseline_mut(BaselineKind::Fork);
+    let marker_schema = ContractSchema::new("teleportal.test.net-marker", (0, 1, 0))
+        .with_state("label", TpPropertyType::Primitive(TpPrimitiveType::String));
+    let marker = b.register_dyn_contract(&marker_schema)?;
+    let markers = (0..2)
+        .map(|i| {
+            let label = DynTpProperty::from(format!("marker {}", i));
+            b.object_create(&marker, [label].into_iter(), [].into_iter())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let player = b.register_dyn_contract(&player_schema())?;
+    let states = [
+        DynTpProperty::from(String::from("bob")),
+        DynTpProperty::from(10u8),
+        DynTpProperty::from(markers[0]),
+    ];
+    let obj = b.object_create(&player, states.into_iter(), [].into_iter())?;
+    Ok((realm, obj, markers))
+}
+
+fn state<T>(b: &Baseline, obj: ObjectHandle, idx: usize) -> Result<T>
+where
+    T: tp_client::contract::properties::traits::ITpPropertyStatic + Clone,
+{
+    let contract = b.object(obj)?.contract();
+    let handle = b.bind_state(StateId::<T>::new(idx, contract), obj)?;
+    Ok(b.state(handle)?.value.clone())
+}
+
+fn fork<C: Connection>(client: &Client<C>) -> &Baseline {
+    client.engine().realm().baseline(BaselineKind::Fork)
+}
+
+/// Polls the server and clients until `done` returns true for the events that
+/// the server and each client received.
+fn settle<L: Listener, C: Connection>(
+    server: &mut Server<L>,
+    clients: &mut [Client<C>],
+    done: impl Fn(&[ServerEvent], &[Vec<ClientEvent>]) -> bool,
+) -> Result<(Vec<ServerEvent>, Vec<Vec<ClientEvent>>)> {
+    let start = Instant::now();
+    let mut server_events = Vec::new();
+    let mut events = vec![Vec::new(); clients.len()];
+    while !done(&server_events, &events) {
+        if start.elapsed() > Duration::from_secs(5) {
+            return Err(eyre!("Timed out, got {:?}", events));
+        }
+        server_events.extend(server.poll()?);
+        for (client, events) in clients.iter_mut().zip(&mut events) {
+            events.extend(client.poll()?);
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    Ok((server_events, events))
+}
+
+fn has(events: &[ClientEvent], f: impl Fn(&ClientEvent) -> bool) -> bool {
+    events.iter().any(f)
+}
+
+fn test_sync<L: Listener, C: Connection>(
+    listener: L,
+    mut connect: impl FnMut() -> Result<C>,
+) -> Result<()> {
+    let _ = color_eyre::install();
+
+    let (realm, obj, markers) = realm()?;
+    let mut server = Server::new(realm, listener);
+    server.set_journal(Journal::new().with_checkpoint_every(1))?;
+    // Clients may not rename players.
+    server.add_validator(|_client, actions, _baseline| {
+        match actions.iter().any(|a| a.target().1 == Some(NAME)) {
+            true => Err(eyre!("Players can't be renamed")),
+            false => Ok(()),
+        }
+    });
+
+    let mut clients = vec![
+        Client::new(Realm::new(RealmID::new("a".into())), connect()?),
+        Client::new(Realm::new(RealmID::new("b".into())), connect()?),
+    ];
+    settle(&mut server, &mut clients, |_, events| {
+        events
+            .iter()
+            .all(|e| has(e, |e| *e == ClientEvent::Snapshot))
+    })?;
+    for client in &clients {
+        assert!(client.id().is_some());
+        let local = client.local_object(obj).unwrap();
+        let marker = client.local_object(markers[0]).unwrap();
+        assert_eq!(client.remote_object(local), Some(obj));
+        assert_eq!(state::<String>(fork(client), local, NAME)?, "bob");
+        assert_eq!(state::<u8>(fork(client), local, HP)?, 10);
+        assert_eq!(state::<ObjectHandle>(fork(client), local, TARGET)?, marker);
+        // The contract was registered from the snapshot.
+        let contracts: Vec<DynContract> = fork(client).iter_contracts().collect();
+        assert_eq!(contracts.len(), 2);
+    }
+    assert_ne!(clients[0].id(), clients[1].id());
+
+    // A write, with handles that need translating, reaches every client.
+    let local = clients[0].local_object(obj).unwrap();
+    let marker = clients[0].local_object(markers[1]).unwrap();
+    let seq = clients[0].submit(vec![
+        NetAction::StateWrite {
+            object: local,
+            state: HP,
+            data: DynTpProperty::from(50u8),
+        },
+        NetAction::StateWrite {
+            object: local,
+            state: TARGET,
+            data: DynTpProperty::from(marker),
+        },
+    ])?;
+    let (server_events, _) = settle(&mut server, &mut clients, |_, events| {
+        events.iter().all(|e| {
+            has(
+                e,
+                |e| matches!(e, ClientEvent::Applied { seq: s, .. } if *s == seq),
+            )
+        })
+    })?;
+    let id = clients[0].id().unwrap();
+    assert!(server_events.contains(&ServerEvent::Approved { client: id, seq }));
+    for client in &clients {
+        let local = client.local_object(obj).unwrap();
+        let marker = client.local_object(markers[1]).unwrap();
+        assert_eq!(state::<u8>(fork(client), local, HP)?, 50);
+        assert_eq!(state::<ObjectHandle>(fork(client), local, TARGET)?, marker);
+    }
+
+    // Out of range, so the engine rejects it.
+    let local = clients[1].local_object(obj).unwrap();
+    let out_of_range = clients[1].submit(vec![NetAction::StateWrite {
+        object: local,
+        state: HP,
+        data: DynTpProperty::from(200u8),
+    }])?;
+    // Rejected by the validator.
+    let renamed = clients[1].submit(vec![NetAction::StateWrite {
+        object: local,
+        state: NAME,
+        data: DynTpProperty::from(String::from("eve")),
+    }])?;
+    let (_, events) = settle(&mut server, &mut clients, |_, events| {
+        events[1]
+            .iter()
+            .filter(|e| matches!(e, ClientEvent::Rejected { .. }))
+            .count()
+            == 2
+    })?;
+    assert!(has(&events[1], |e| matches!(
+        e,
+        ClientEvent::Rejected { seq, .. } if *seq == out_of_range
+    )));
+    assert!(has(&events[1], |e| matches!(
+        e,
+        ClientEvent::Rejected { seq, reason } if *seq == renamed && reason.contains("renamed")
+    )));
+    assert!(!has(&events[0], |e| matches!(
+        e,
+        ClientEvent::Applied { .. }
+    )));
+    for client in &clients {
+        let local = client.local_object(obj).unwrap();
+        assert_eq!(state::<String>(fork(client), local, NAME)?, "bob");
+        assert_eq!(state::<u8>(fork(client), local, HP)?, 50);
+    }
+
+    // The server can write anything, and isn't validated.
+    server.apply(vec![NetAction::StateWrite {
+        object: obj,
+        state: NAME,
+        data: DynTpProperty::from(String::from("alice")),
+    }])?;
+
+    // A client that joins late gets the current state.
+    clients.push(Client::new(
+        Realm::new(RealmID::new("c".into())),
+        connect()?,
+    ));
+    settle(&mut server, &mut clients, |_, events| {
+        has(&events[2], |e| *e == ClientEvent::Snapshot)
+            && has(&events[0], |e| matches!(e, ClientEvent::Applied { .. }))
+    })?;
+    for client in &clients {
+        let local = client.local_object(obj).unwrap();
+        assert_eq!(state::<String>(fork(client), local, NAME)?, "alice");
+        assert_eq!(state::<u8>(fork(client), local, HP)?, 50);
+    }
+
+    // Disconnected clients are forgotten.
+    let gone = clients.pop().unwrap().id().unwrap();
+    settle(&mut server, &mut clients, |server_events, _| {
+        server_events.contains(&ServerEvent::Disconnected(gone))
+    })
+    .wrap_err("Server didn't notice the disconnect")?;
+    assert_eq!(server.clients().count(), 2);
+
+    // Only the approved collactions were journaled, and they replay to the
+    // same state.
+    let journal = server.journal().unwrap();
+    assert_eq!(journal.entries().len(), 2);
+    let mut replayer = Replayer::new(journal)?;
+    replayer.verify()?;
+    let local = replayer.local_object(obj).unwrap();
+    let replayed = replayer.engine().realm().baseline(BaselineKind::Fork);
+    assert_eq!(state::<String>(replayed, local, NAME)?, "alice");
+    assert_eq!(state::<u8>(replayed, local, HP)?, 50);
+    Ok(())
+}
+
+#[test]
+fn test_sync_loopback() -> Result<()> {
+    let (listener, connector) = transport::loopback::loopback();
+    test_sync(listener, || connector.connect())
+}
+
+#[test]
+fn test_sync_tcp() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    test_sync(listener, || TcpConnection::connect(addr))
+}
+
+#[test]
+fn test_interest_references() -> Result<()> {
+    let _ = color_eyre::install();
+
+    // Two players that target each other, one the child of the other, which
+    // nothing targets yet.
+    let (mut realm, obj, markers) = realm()?;
+    let b = realm.baseline_mut(BaselineKind::Fork);
+    let player = b
+        .iter_contracts()
+        .find(|c| c.id().name == player_schema().name)
+        .unwrap();
+    let mut create = |name: &str| {
+        let states = [
+            DynTpProperty::from(String::from(name)),
+            DynTpProperty::from(1u8),
+            DynTpProperty::from(obj),
+        ];
+        b.object_create(&player, states.into_iter(), [].into_iter())
+    };
+    let (p, q) = (create("p")?, create("q")?);
+    b.object_reparent(q, Some(p), None)?;
+    for (from, to) in [(p, q), (q, p)] {
+        let handle = b.bind_state(StateId::<ObjectHandle>::new(TARGET, player.handle()), from)?;
+        b.state_mut(handle)?.value = to;
+    }
+
+    let (listener, connector) = transport::loopback::loopback();
+    let mut server = Server::new(realm, listener);
+    server.set_default_interest(Interest::none().with_object(obj));
+    let mut clients = vec![Client::new(
+        Realm::new(RealmID::new("a".into())),
+        connector.connect()?,
+    )];
+    settle(&mut server, &mut clients, |_, events| {
+        has(&events[0], |e| *e == ClientEvent::Snapshot)
+    })?;
+    let local_obj = clients[0].local_object(obj).unwrap();
+    assert!(clients[0].local_object(markers[0]).is_some());
+    assert!(clients[0].local_object(p).is_none());
+
+    // Targeting `p` brings in what it targets and its children, which
+    // reference each other.
+    server.apply(vec![NetAction::StateWrite {
+        object: obj,
+        state: TARGET,
+        data: DynTpProperty::from(p),
+    }])?;
+    let (_, events) = settle(&mut server, &mut clients, |_, events| {
+        has(&events[0], |e| matches!(e, ClientEvent::Applied { .. }))
+    })?;
+    assert!(!has(&events[0], |e| *e == ClientEvent::Snapshot));
+    assert!(has(&events[0], |e| *e == ClientEvent::ObjectEntered(p)));
+    assert!(has(&events[0], |e| *e == ClientEvent::ObjectEntered(q)));
+    assert!(has(&events[0], |e| *e == ClientEvent::ObjectLeft(markers[0])));
+
+    let client = &clients[0];
+    let (local_p, local_q) = (
+        client.local_object(p).unwrap(),
+        client.local_object(q).unwrap(),
+    );
+    assert_eq!(client.local_object(obj), Some(local_obj));
+    assert!(client.local_object(markers[0]).is_none());
+    let b = fork(client);
+    assert_eq!(state::<ObjectHandle>(b, local_obj, TARGET)?, local_p);
+    assert_eq!(state::<ObjectHandle>(b, local_p, TARGET)?, local_q);
+    assert_eq!(state::<ObjectHandle>(b, local_q, TARGET)?, local_p);
+    assert_eq!(state::<String>(b, local_q, NAME)?, "q");
+    assert_eq!(b.object(local_q)?.parent(), Some(local_p));
+    Ok(())
+}
+
+#[test]
+fn test_interest() -> Result<()> {
+    let _ = color_eyre::install();
+
+    let mut realm = Realm::new(RealmID::new("test".to_string()));
+    let b = realm.baseline_mut(BaselineKind::Fork);
+    let unit_schema = ContractSchema::new("teleportal.test.net-unit", (0, 1, 0))
+        .with_state("x", TpPropertyType::Primitive(TpPrimitiveType::F32))
+        .with_state("y", TpPropertyType::Primitive(TpPrimitiveType::F32));
+    let unit = b.register_dyn_contract(&unit_schema)?;
+    let mut create = |x: f32| {
+        let states = [DynTpProperty::from(x), DynTpProperty::from(0.0f32)];
+        b.object_create(&unit, states.into_iter(), [].into_iter())
+    };
+    let (a, near, far) = (create(0.0)?, create(5.0)?, create(50.0)?);
+    let scenery_schema = ContractSchema::new("teleportal.test.net-scenery", (0, 1, 0))
+        .with_state("label", TpPropertyType::Primitive(TpPrimitiveType::String));
+    let scenery = b.register_dyn_contract(&scenery_schema)?;
+    let tree = b.object_create(
+        &scenery,
+        [DynTpProperty::from(String::from("tree"))].into_iter(),
+        [].into_iter(),
+    )?;
+
+    let position = PositionStates::new_2d(
+        StateId::new(0, unit.handle()),
+        StateId::new(1, unit.handle()),
+    );
+    b.spatial_track_positions(unit.handle(), position)?;
+
+    let (listener, connector) = transport::loopback::loopback();
+    let mut server = Server::new(realm, listener);
+    server.set_default_interest(Interest::none().with_contract(scenery.id()));
+
+    let mut clients = vec![
+        Client::new(Realm::new(RealmID::new("a".into())), connector.connect()?),
+        Client::new(Realm::new(RealmID::new("b".into())), connector.connect()?),
+    ];
+    settle(&mut server, &mut clients, |_, events| {
+        events
+            .iter()
+            .all(|e| has(e, |e| *e == ClientEvent::Snapshot))
+    })?;
+    let ids: Vec<_> = clients.iter().map(|c| c.id().unwrap()).collect();
+    for client in &clients {
+        assert!(client.local_object(tree).is_some());
+        assert!(client.local_object(a).is_none());
+    }
+
+    // The first client follows `a`.
+    let around_a = Proximity::Object {
+        object: a,
+        radius: 10.0,
+    };
+    server.set_interest(ids[0], Interest::none().with_proximity(around_a))?;
+    let (_, events) = settle(&mut server, &mut clients, |_, events| {
+        has(&events[0], |e| *e == ClientEvent::ObjectLeft(tree))
+    })?;
+    // Only the objects that changed are sent, rather than a new snapshot.
+    assert!(!has(&events[0], |e| *e == ClientEvent::Snapshot));
+    assert!(has(&events[0], |e| *e == ClientEvent::ObjectEntered(a)));
+    assert!(has(&events[0], |e| *e == ClientEvent::ObjectEntered(near)));
+    assert!(has(&events[0], |e| *e == ClientEvent::ObjectLeft(tree)));
+    assert!(clients[0].local_object(far).is_none());
+    let local_a = clients[0].local_object(a).unwrap();
+
+    // `far` comes into range, which only concerns the first client.
+    server.apply(vec![NetAction::StateWrite {
+        object: far,
+        state: 0,
+        data: DynTpProperty::from(8.0f32),
+    }])?;
+    let (_, events) = settle(&mut server, &mut clients, |_, events| {
+        has(&events[0], |e| *e == ClientEvent::ObjectEntered(far))
+    })?;
+    assert!(events[1].is_empty());
+    let local = clients[0].local_object(far).unwrap();
+    assert_eq!(state::<f32>(fork(&clients[0]), local, 0)?, 8.0);
+    // The objects that were already there keep their handles.
+    assert_eq!(clients[0].local_object(a), Some(local_a));
+    assert_eq!(state::<f32>(fork(&clients[0]), local_a, 0)?, 0.0);
+
+    // The first client moves `near` out of range, and still hears back.
+    let local = clients[0].local_object(near).unwrap();
+    let seq = clients[0].submit(vec![NetAction::StateWrite {
+        object: local,
+        state: 0,
+        data: DynTpProperty::from(100.0f32),
+    }])?;
+    let (_, events) = settle(&mut server, &mut clients, |_, events| {
+        has(&events[0], |e| {
+            *e == ClientEvent::Applied {
+                origin: ids[0],
+                seq,
+            }
+        })
+    })?;
+    assert!(has(&events[0], |e| *e == ClientEvent::ObjectLeft(near)));
+    assert!(clients[0].local_object(near).is_none());
+    assert!(events[1].is_empty());
+
+    // Writes to objects in range are sent as they are.
+    let local = clients[0].local_object(a).unwrap();
+    let seq = clients[0].submit(vec![NetAction::StateWrite {
+        object: local,
+        state: 1,
+        data: DynTpProperty::from(1.0f32),
+    }])?;
+    let (_, events) = settle(&mut server, &mut clients, |_, events| {
+        has(&events[0], |e| {
+            *e == ClientEvent::Applied {
+                origin: ids[0],
+                seq,
+            }
+        })
+    })?;
+    assert!(!has(&events[0], |e| *e == ClientEvent::Snapshot));
+    assert_eq!(state::<f32>(fork(&clients[0]), local, 1)?, 1.0);
+    Ok(())
+}
@@ -0,0 +1,378 @@
+use crate::contract::{self, Contract, ContractSchema};
+use crate::value::{FromPy, ToNumpy, ToPy};
+
+use arena::generational_arena as ga;
+use eyre::eyre;
+use pyo3::basic::CompareOp;
+use pyo3::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use tp_client::contract::properties::channels::{
+    Channel, ChannelHandle as RChannelHandle, ChannelId, DynChannel, DynChannelHandle,
+    IChannelHandle, Keyframe,
+};
+use tp_client::contract::properties::dynamic::{DynTpProperty, DynTpPropertyRef};
+use tp_client::contract::properties::states::{
+    DynStateHandle, IStateHandle, StateHandle as RStateHandle, StateId,
+};
+use tp_client::contract::properties::traits::{ITpProperty, ITpPropertyStatic};
+use tp_client::contract::IContract;
+use tp_client::object::ObjectHandle;
+use tp_client::{apply_to_channel_handle, apply_to_channel_id, apply_to_state_handle};
+use tp_client::{apply_to_state_id, baseline as rs};
+
+#[pyclass(name = "BaselineKind", module = "tp_python")]
+#[derive(Clone, Copy)]
+pub enum PyBaselineKind {
+    Main,
+    Fork,
+}
+impl From<PyBaselineKind> for rs::BaselineKind {
+    fn from(kind: PyBaselineKind) -> Self {
+        match kind {
+            PyBaselineKind::Main => Self::Main,
+            PyBaselineKind::Fork => Self::Fork,
+        }
+    }
+}
+impl From<rs::BaselineKind> for PyBaselineKind {
+    fn from(kind: rs::BaselineKind) -> Self {
+        match kind {
+            rs::BaselineKind::Main => Self::Main,
+            rs::BaselineKind::Fork => Self::Fork,
+        }
+    }
+}
+
+// ---- Handles ----
+
+/// An `ObjectHandle` or `ContractDataHandle`.
+#[pyclass(module = "tp_python")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Handle {
+    #[pyo3(get)]
+    index: usize,
+    #[pyo3(get)]
+    generation: u64,
+}
+#[pymethods]
+impl Handle {
+    #[new]
+    fn new(index: usize, generation: u64) -> Self {
+        Self { index, generation }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Handle(index={}, generation={})",
+            self.index, self.generation
+        )
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp, py: Python<'_>) -> PyObject {
+        match op {
+            CompareOp::Eq => (self == other).into_py(py),
+            CompareOp::Ne => (self != other).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __hash__(&self) -> isize {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish() as isize
+    }
+}
+impl<T> From<arena::Index<T>> for Handle {
+    fn from(h: arena::Index<T>) -> Self {
+        let (index, generation) = ga::Index::from(h).into_raw_parts();
+        Self { index, generation }
+    }
+}
+impl<T> From<Handle> for arena::Index<T> {
+    fn from(h: Handle) -> Self {
+        arena::Index::new(ga::Index::from_raw_parts(h.index, h.generation))
+    }
+}
+
+/// A handle to a state, of any type.
+#[pyclass(module = "tp_python")]
+#[derive(Clone, Copy)]
+pub struct StateHandle {
+    inner: DynStateHandle,
+}
+#[pymethods]
+impl StateHandle {
+    /// The rust name of the type of the state, like `"f32"` or `"Vec<Vec3>"`.
+    #[getter]
+    fn prop_type(&self) -> String {
+        self.inner.prop_type().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("StateHandle({})", self.prop_type())
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp, py: Python<'_>) -> PyObject {
+        match op {
+            CompareOp::Eq => (self.inner == other.inner).into_py(py),
+            CompareOp::Ne => (self.inner != other.inner).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+
+    fn __hash__(&self) -> isize {
+        let mut hasher = DefaultHasher::new();
+        self.inner.hash(&mut hasher);
+        hasher.finish() as isize
+    }
+}
+
+/// A handle to a channel, of any type.
+#[pyclass(module = "tp_python")]
+#[derive(Clone, Copy)]
+pub struct ChannelHandle {
+    inner: DynChannelHandle,
+}
+#[pymethods]
+impl ChannelHandle {
+    /// The rust name of the type of the channel, like `"f32"` or `"Vec3"`.
+    #[getter]
+    fn prop_type(&self) -> String {
+        self.inner.prop_type().to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ChannelHandle({})", self.prop_type())
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp, py: Python<'_>) -> PyObject {
+        match op {
+            CompareOp::Eq => (self.inner == other.inner).into_py(py),
+            CompareOp::Ne => (self.inner != other.inner).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+}
+
+// ---- Baseline ----
+
+#[pyclass(module = "tp_python")]
+pub struct Baseline {
+    pub(crate) inner: rs::Baseline,
+}
+#[pymethods]
+impl Baseline {
+    #[new]
+    fn new(kind: PyBaselineKind) -> Self {
+        Self {
+            inner: rs::Baseline::new(kind.into()),
+        }
+    }
+
+    #[getter]
+    fn kind(&self) -> PyBaselineKind {
+        self.inner.kind().into()
+    }
+
+    /// Registers the contract named `name`, which must be one of
+    /// `known_contracts()`.
+    fn register_contract(&mut self, name: &str) -> PyResult<Contract> {
+        let known = contract::find(name)?;
+        Ok((known.register)(&mut self.inner)?.into())
+    }
+
+    /// Registers a contract declared at runtime.
+    fn register_dyn_contract(&mut self, schema: &ContractSchema) -> PyResult<Contract> {
+        Ok(self.inner.register_dyn_contract(&schema.inner)?.into())
+    }
+
+    /// Also removes all objects of the contract.
+    fn unregister_contract(&mut self, contract: &Contract) -> PyResult<()> {
+        Ok(self.inner.unregister_dyn_contract(&contract.inner)?)
+    }
+
+    /// The handles of all objects in the baseline.
+    fn objects(&self) -> Vec<Handle> {
+        self.inner
+            .iter_objects()
+            .map(|(h, _)| Handle::from(h))
+            .collect()
+    }
+
+    /// The handle of the contract that `obj` was created from.
+    fn object_contract(&self, obj: Handle) -> PyResult<Handle> {
+        let obj = self.inner.object(obj.into())?;
+        Ok(obj.contract().into())
+    }
+
+    /// Creates an object of `contract`. `states` holds the values of the
+    /// contract's states, in order. Trailing states that have a `#[default]`
+    /// may be omitted. All channels start out empty.
+    #[args(states = "Vec::new()")]
+    fn object_create(&mut self, contract: &Contract, states: Vec<&PyAny>) -> PyResult<Handle> {
+        let inner = &contract.inner;
+        let states = states
+            .into_iter()
+            .enumerate()
+            .map(|(i, value)| -> PyResult<DynTpProperty> {
+                let id = inner
+                    .state_id(i)
+                    .ok_or_else(|| eyre!("The contract only has {} states", i))?;
+                apply_to_state_id!(id, |id: StateId<_>| prop_from_py(id, value))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        let channels = (0..inner.layout().channel_types.len())
+            .map(|i| {
+                let id = inner.chan_id(i).expect("`i` is in bounds");
+                apply_to_channel_id!(id, |id: ChannelId<_>| empty_channel(id))
+            })
+            .collect::<Vec<_>>();
+        let obj = self
+            .inner
+            .object_create(inner, states.into_iter(), channels.into_iter())?;
+        Ok(obj.into())
+    }
+
+    fn object_remove(&mut self, obj: Handle) -> PyResult<()> {
+        Ok(self.inner.object_remove_dyn(obj.into())?)
+    }
+
+    // ---- States ----
+
+    /// Binds the state `field` of `obj`. `field` is either the name or the
+    /// index of a state of `contract`.
+    fn bind_state(&self, contract: &Contract, obj: Handle, field: &PyAny) -> PyResult<StateHandle> {
+        let idx = contract.field_index(field, contract.inner.layout().state_names)?;
+        let id = contract.inner.state_id(idx).expect("`idx` is in bounds");
+        let obj: ObjectHandle = obj.into();
+        let baseline = &self.inner;
+        let inner = apply_to_state_id!(id, |id: StateId<_>| baseline
+            .bind_state(id, obj)
+            .map(DynStateHandle::from))?;
+        Ok(StateHandle { inner })
+    }
+
+    /// Reads the value of `state`.
+    fn state(&self, state: &StateHandle, py: Python<'_>) -> PyResult<PyObject> {
+        let baseline = &self.inner;
+        let value = apply_to_state_handle!(state.inner, |h: RStateHandle<_>| baseline
+            .state(h)
+            .map(|s| s.value.to_py(py)))?;
+        Ok(value)
+    }
+
+    /// Writes `value` to `state`. Throws if `value` doesn't have the type of
+    /// the state, or is outside the range of its field.
+    fn set_state(&mut self, state: &StateHandle, value: &PyAny) -> PyResult<()> {
+        let baseline = &mut self.inner;
+        apply_to_state_handle!(state.inner, |h: RStateHandle<_>| set_state(
+            baseline, h, value
+        ))
+    }
+
+    // ---- Channels ----
+
+    /// Binds the channel `field` of `obj`. `field` is either the name or the
+    /// index of a channel of `contract`.
+    fn bind_channel(
+        &self,
+        contract: &Contract,
+        obj: Handle,
+        field: &PyAny,
+    ) -> PyResult<ChannelHandle> {
+        let idx = contract.field_index(field, contract.inner.layout().channel_names)?;
+        let id = contract.inner.chan_id(idx).expect("`idx` is in bounds");
+        let obj: ObjectHandle = obj.into();
+        let baseline = &self.inner;
+        let inner = apply_to_channel_id!(id, |id: ChannelId<_>| baseline
+            .bind_channel(id, obj)
+            .map(DynChannelHandle::from))?;
+        Ok(ChannelHandle { inner })
+    }
+
+    /// The keyframes of `channel`, as a list of `(time, value)` tuples.
+    fn keyframes(&self, channel: &ChannelHandle, py: Python<'_>) -> PyResult<Vec<PyObject>> {
+        let baseline = &self.inner;
+        let keyframes = apply_to_channel_handle!(channel.inner, |h: RChannelHandle<_>| {
+            baseline.channel(h).map(|c| {
+                c.keyframes()
+                    .iter()
+                    .map(|kf| (kf.time(), kf.value().to_py(py)).to_object(py))
+                    .collect::<Vec<_>>()
+            })
+        })?;
+        Ok(keyframes)
+    }
+
+    /// The keyframes of `channel` as a tuple of numpy arrays: the times, and
+    /// the values. Composite values like `Vec3` have one row per keyframe.
+    /// Throws `TypeError` if the channel isn't numeric.
+    fn keyframes_numpy(&self, channel: &ChannelHandle, py: Python<'_>) -> PyResult<PyObject> {
+        let baseline = &self.inner;
+        apply_to_channel_handle!(channel.inner, |h: RChannelHandle<_>| -> PyResult<_> {
+            let keyframes = baseline.channel(h)?.keyframes();
+            let times: Vec<f64> = keyframes.iter().map(|kf| kf.time()).collect();
+            let times = f64::to_numpy(py, times.iter())?;
+            let values = ToNumpy::to_numpy(py, keyframes.iter().map(|kf| kf.value()))?;
+            Ok((times, values).to_object(py))
+        })
+    }
+
+    /// Inserts a keyframe with `value` at `time`, keeping the keyframes
+    /// sorted by time.
+    fn insert_keyframe(
+        &mut self,
+        channel: &ChannelHandle,
+        time: f64,
+        value: &PyAny,
+    ) -> PyResult<()> {
+        let baseline = &mut self.inner;
+        apply_to_channel_handle!(channel.inner, |h: RChannelHandle<_>| insert_keyframe(
+            baseline, h, time, value
+        ))
+    }
+}
+
+fn prop_from_py<T>(_: StateId<T>, value: &PyAny) -> PyResult<DynTpProperty>
+where
+    T: ITpPropertyStatic + FromPy + Into<DynTpProperty>,
+{
+    T::from_py(value).map(Into::into)
+}
+
+fn empty_channel<T>(_: ChannelId<T>) -> DynChannel
+where
+    T: ITpProperty,
+    Channel<T>: Into<DynChannel>,
+{
+    Channel::new(std::iter::empty()).into()
+}
+
+fn set_state<T>(baseline: &mut rs::Baseline, h: RStateHandle<T>, value: &PyAny) -> PyResult<()>
+where
+    T: ITpPropertyStatic + FromPy,
+    RStateHandle<T>: Into<DynStateHandle>,
+    for<'a> &'a T: Into<DynTpPropertyRef<'a>>,
+{
+    let value = T::from_py(value)?;
+    if let Some(info) = baseline.state_constraints(h) {
+        info.check((&value).into())?;
+    }
+    baseline.state_mut(h)?.value = value;
+    Ok(())
+}
+
+fn insert_keyframe<T>(
+    baseline: &mut rs::Baseline,
+    h: RChannelHandle<T>,
+    time: f64,
+    value: &PyAny,
+) -> PyResult<()>
+where
+    T: ITpPropertyStatic + FromPy,
+{
+    let value = T::from_py(value)?;
+    baseline.channel_mut(h)?.insert(Keyframe::new(value, time));
+    Ok(())
+}
@@ -0,0 +1,238 @@
+//! Contracts, as `DynContract`s so that they can be handled from Python.
+//!
+//! Contracts are either declared from Python with a `ContractSchema`, or are
+//! compiled into the module and listed in `known_contracts!`.
+
+use crate::baseline::Handle;
+
+use eyre::Result;
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::{PyIndexError, PyKeyError, PyTypeError};
+use pyo3::prelude::*;
+use tp_client::baseline::Baseline;
+use tp_client::contract::properties::dynamic::TpPropertyType;
+use tp_client::contract::{self as rs, Contract as RContract, ContractId, DynContract, IContract};
+use tp_serialize::DeserializerBuilder;
+
+/// A contract compiled into the module, which Python can register by name.
+#[derive(Clone, Copy)]
+pub(crate) struct KnownContract {
+    pub id: ContractId,
+    pub register: fn(&mut Baseline) -> Result<DynContract>,
+    pub register_deserialize: fn(&mut DeserializerBuilder<'_>) -> Result<DynContract>,
+}
+impl KnownContract {
+    fn new<C: RContract>() -> Self {
+        fn register<C: RContract>(baseline: &mut Baseline) -> Result<DynContract> {
+            Ok(DynContract::from_contract(
+                &baseline.register_contract::<C>()?,
+            ))
+        }
+        fn register_deserialize<C: RContract>(
+            builder: &mut DeserializerBuilder<'_>,
+        ) -> Result<DynContract> {
+            Ok(DynContract::from_contract(
+                &builder.register_contract::<C>()?,
+            ))
+        }
+        Self {
+            id: C::ID,
+            register: register::<C>,
+            register_deserialize: register_deserialize::<C>,
+        }
+    }
+}
+
+macro_rules! known_contracts {
+    ($($c:ty),+ $(,)?) => {
+        fn all() -> Vec<KnownContract> {
+            vec![$(KnownContract::new::<$c>()),+]
+        }
+    };
+}
+known_contracts!(tp_contract_example::ExampleContract);
+
+/// Finds the known contract named `name`.
+pub(crate) fn find(name: &str) -> PyResult<KnownContract> {
+    all()
+        .into_iter()
+        .find(|c| c.id.name == name)
+        .ok_or_else(|| PyKeyError::new_err(format!("No known contract named `{name}`")))
+}
+
+/// The names of the contracts that can be registered.
+#[pyfunction]
+pub fn known_contracts() -> Vec<&'static str> {
+    all().into_iter().map(|c| c.id.name).collect()
+}
+
+/// The fields of a contract. `states` and `channels` are lists of
+/// `(name, type)`, where `type` is the rust name of the type of the field,
+/// like `"f32"` or `"Option<Vec3>"`.
+#[pyclass(module = "tp_python")]
+#[derive(Clone)]
+pub struct ContractSchema {
+    pub(crate) inner: rs::ContractSchema,
+}
+#[pymethods]
+impl ContractSchema {
+    #[new]
+    #[args(states = "Vec::new()", channels = "Vec::new()")]
+    fn new(
+        name: String,
+        version: (u16, u16, u16),
+        states: Vec<(String, &str)>,
+        channels: Vec<(String, &str)>,
+    ) -> PyResult<Self> {
+        let mut inner = rs::ContractSchema::new(name, version);
+        for (name, typ) in states {
+            inner = inner.with_state(name, parse_type(typ)?);
+        }
+        for (name, typ) in channels {
+            inner = inner.with_channel(name, parse_type(typ)?);
+        }
+        inner.validate()?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    #[getter]
+    fn version(&self) -> (u16, u16, u16) {
+        self.inner.version
+    }
+
+    #[getter]
+    fn states(&self) -> Vec<(String, String)> {
+        fields(&self.inner.states)
+    }
+
+    #[getter]
+    fn channels(&self) -> Vec<(String, String)> {
+        fields(&self.inner.channels)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "ContractSchema({:?}, {:?}, states={:?}, channels={:?})",
+            self.inner.name,
+            self.inner.version,
+            self.states(),
+            self.channels()
+        )
+    }
+
+    fn __richcmp__(&self, other: &Self, op: CompareOp, py: Python<'_>) -> PyObject {
+        match op {
+            CompareOp::Eq => (self.inner == other.inner).into_py(py),
+            CompareOp::Ne => (self.inner != other.inner).into_py(py),
+            _ => py.NotImplemented(),
+        }
+    }
+}
+impl From<rs::ContractSchema> for ContractSchema {
+    fn from(inner: rs::ContractSchema) -> Self {
+        Self { inner }
+    }
+}
+
+fn parse_type(typ: &str) -> PyResult<TpPropertyType> {
+    typ.parse::<TpPropertyType>()
+        .map_err(|e| PyTypeError::new_err(format!("{e:#}")))
+}
+
+fn fields(fields: &[rs::FieldSchema]) -> Vec<(String, String)> {
+    fields
+        .iter()
+        .map(|f| (f.name.clone(), f.prop_type.to_string()))
+        .collect()
+}
+
+/// A contract registered with a `Baseline` or `DeserializerBuilder`.
+#[pyclass(module = "tp_python")]
+pub struct Contract {
+    pub(crate) inner: DynContract,
+}
+#[pymethods]
+impl Contract {
+    #[getter]
+    fn handle(&self) -> Handle {
+        self.inner.handle().into()
+    }
+
+    #[getter]
+    fn name(&self) -> &'static str {
+        self.inner.id().name
+    }
+
+    #[getter]
+    fn version(&self) -> (u16, u16, u16) {
+        self.inner.id().version
+    }
+
+    #[getter]
+    fn schema(&self) -> ContractSchema {
+        rs::ContractSchema::from(&self.inner.layout()).into()
+    }
+
+    #[getter]
+    fn state_names(&self) -> Vec<&'static str> {
+        self.inner.layout().state_names.to_vec()
+    }
+
+    /// The rust names of the types of the states, like `"f32"`.
+    #[getter]
+    fn state_types(&self) -> Vec<String> {
+        let types = self.inner.layout().state_types;
+        types.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[getter]
+    fn channel_names(&self) -> Vec<&'static str> {
+        self.inner.layout().channel_names.to_vec()
+    }
+
+    /// The rust names of the types of the channels, like `"f32"`.
+    #[getter]
+    fn channel_types(&self) -> Vec<String> {
+        let types = self.inner.layout().channel_types;
+        types.iter().map(|t| t.to_string()).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        let (major, minor, patch) = self.version();
+        format!("Contract({:?}, {major}.{minor}.{patch})", self.name())
+    }
+}
+impl Contract {
+    /// The index of `field` in `names`. `field` is either a name or an index.
+    pub(crate) fn field_index(&self, field: &PyAny, names: &[&str]) -> PyResult<usize> {
+        if let Ok(name) = field.extract::<&str>() {
+            names
+                .iter()
+                .position(|n| *n == name)
+                .ok_or_else(|| PyKeyError::new_err(format!("No field named `{name}`")))
+        } else if let Ok(idx) = field.extract::<usize>() {
+            if idx < names.len() {
+                Ok(idx)
+            } else {
+                Err(PyIndexError::new_err(format!(
+                    "There are only {} fields",
+                    names.len()
+                )))
+            }
+        } else {
+            Err(PyTypeError::new_err(
+                "Expected the name or index of a field",
+            ))
+        }
+    }
+}
+impl From<DynContract> for Contract {
+    fn from(inner: DynContract) -> Self {
+        Self { inner }
+    }
+}
@@ -0,0 +1,50 @@
+//! Python bindings for `tp_client` and `tp_serialize`, via `pyo3`.
+//!
+//! Build and install into the active virtualenv with:
+//! ```sh
+//! cd client/python && maturin develop
+//! ```
+//! and then run the tests with `pytest client/python/tests`.
+//!
+//! Values of properties are converted as described in [`value`]. Contracts are
+//! either declared at runtime with a `ContractSchema`, or are one of the
+//! compiled in contracts listed by `known_contracts()`.
+
+#![deny(
+    bad_style,
+    improper_ctypes,
+    non_shorthand_field_patterns,
+    no_mangle_generic_items,
+    overflowing_literals,
+    path_statements,
+    patterns_in_fns_without_body,
+    private_in_public,
+    unconditional_recursion,
+    unused_allocation,
+    unused_comparisons,
+    unused_parens,
+    while_true
+)]
+
+mod baseline;
+mod contract;
+mod serialize;
+mod value;
+
+use pyo3::prelude::*;
+
+#[pymodule]
+fn tp_python(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<baseline::PyBaselineKind>()?;
+    m.add_class::<baseline::Handle>()?;
+    m.add_class::<baseline::StateHandle>()?;
+    m.add_class::<baseline::ChannelHandle>()?;
+    m.add_class::<baseline::Baseline>()?;
+    m.add_class::<contract::Contract>()?;
+    m.add_class::<contract::ContractSchema>()?;
+    m.add_class::<serialize::Serializer>()?;
+    m.add_class::<serialize::DeserializerBuilder>()?;
+    m.add_class::<serialize::Deserializer>()?;
+    m.add_function(wrap_pyfunction!(contract::known_contracts, m)?)?;
+    Ok(())
+}
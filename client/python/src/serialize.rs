@@ -0,0 +1,129 @@
+//! Bindings to `tp_serialize`.
+
+use crate::baseline::{Baseline, PyBaselineKind};
+use crate::contract::{self, Contract, ContractSchema};
+
+use flatbuffers::FlatBufferBuilder;
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use tp_serialize as ser;
+
+fn finished() -> PyErr {
+    PyRuntimeError::new_err("`finish()` was already called")
+}
+
+/// Serializes the objects of some of the contracts in a `Baseline`.
+#[pyclass(module = "tp_python")]
+pub struct Serializer {
+    baseline: Py<Baseline>,
+    contracts: Vec<Py<Contract>>,
+}
+#[pymethods]
+impl Serializer {
+    #[new]
+    fn new(baseline: Py<Baseline>) -> Self {
+        Self {
+            baseline,
+            contracts: Vec::new(),
+        }
+    }
+
+    /// Serializes `contract` and all of its objects.
+    fn serialize(&mut self, contract: Py<Contract>) {
+        self.contracts.push(contract);
+    }
+
+    /// The serialized flatbuffer.
+    fn finish(&self, py: Python<'_>) -> PyResult<Py<PyBytes>> {
+        // The baseline is only borrowed here, so that Python is free to use it
+        // until then.
+        let baseline = self.baseline.borrow(py);
+        let mut serializer = ser::Serializer::new(FlatBufferBuilder::new(), &baseline.inner);
+        for contract in &self.contracts {
+            serializer.serialize(&contract.borrow(py).inner)?;
+        }
+        let fbb = serializer.finish();
+        Ok(PyBytes::new(py, fbb.finished_data()).into())
+    }
+}
+
+/// Reads a flatbuffer from a `bytes`. Contracts have to be registered before
+/// calling `finish()`.
+#[pyclass(module = "tp_python")]
+pub struct DeserializerBuilder {
+    // Declared before `data`, so that it is dropped first.
+    inner: Option<ser::DeserializerBuilder<'static>>,
+    data: Py<PyBytes>,
+}
+#[pymethods]
+impl DeserializerBuilder {
+    #[new]
+    fn new(data: Py<PyBytes>, kind: PyBaselineKind, py: Python<'_>) -> PyResult<Self> {
+        let bytes = data.as_ref(py).as_bytes();
+        // SAFETY: `bytes` objects are immutable, and `data` keeps this one alive
+        // for as long as `inner` is.
+        let bytes: &'static [u8] =
+            unsafe { std::slice::from_raw_parts(bytes.as_ptr(), bytes.len()) };
+        let inner = ser::DeserializerBuilder::new(bytes, kind.into())?;
+        Ok(Self {
+            inner: Some(inner),
+            data,
+        })
+    }
+
+    /// Registers the known contract named `name`, which must also be in the
+    /// flatbuffer.
+    fn register_contract(&mut self, name: &str) -> PyResult<Contract> {
+        let known = contract::find(name)?;
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        Ok((known.register_deserialize)(inner)?.into())
+    }
+
+    /// Registers a contract declared at runtime, which must also be in the
+    /// flatbuffer.
+    fn register_dyn_contract(&mut self, schema: &ContractSchema) -> PyResult<Contract> {
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        Ok(inner.register_dyn_contract(&schema.inner)?.into())
+    }
+
+    /// The schemas of the contracts in the flatbuffer. These only have the
+    /// names and types of states.
+    fn serialized_schemas(&self) -> PyResult<Vec<ContractSchema>> {
+        let inner = self.inner.as_ref().ok_or_else(finished)?;
+        let schemas = inner.serialized_schemas()?;
+        Ok(schemas.into_iter().map(ContractSchema::from).collect())
+    }
+
+    fn finish(&mut self, py: Python<'_>) -> PyResult<Deserializer> {
+        let inner = self.inner.take().ok_or_else(finished)?;
+        Ok(Deserializer {
+            inner: Some(inner.finish()),
+            _data: self.data.clone_ref(py),
+        })
+    }
+}
+
+#[pyclass(module = "tp_python")]
+pub struct Deserializer {
+    // Declared before `_data`, so that it is dropped first.
+    inner: Option<ser::Deserializer<'static>>,
+    _data: Py<PyBytes>,
+}
+#[pymethods]
+impl Deserializer {
+    /// Deserializes all objects of `contract`, which must have been registered
+    /// with the `DeserializerBuilder`.
+    fn deserialize_objects(&mut self, contract: &Contract) -> PyResult<()> {
+        let inner = self.inner.as_mut().ok_or_else(finished)?;
+        Ok(inner.deserialize_objects(&contract.inner)?)
+    }
+
+    /// The deserialized baseline.
+    fn finish(&mut self) -> PyResult<Baseline> {
+        let inner = self.inner.take().ok_or_else(finished)?;
+        Ok(Baseline {
+            inner: inner.finish()?,
+        })
+    }
+}
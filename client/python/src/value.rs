@@ -0,0 +1,279 @@
+//! Conversion between Python objects and the values of properties.
+//!
+//! | Rust                                  | Python                          |
+//! |---------------------------------------|---------------------------------|
+//! | integers, `f32`, `f64`, `bool`        | `int`, `float`, `bool`          |
+//! | `String`                              | `str`                           |
+//! | `ObjectHandle`, `ContractDataHandle`  | `Handle`                        |
+//! | `Vec2`, `Vec3`, `Quat`, `Color`       | tuples of `float`               |
+//! | `Transform`                           | `(translation, rotation, scale)` |
+//! | `Bytes`                               | `bytes`                         |
+//! | `Vec<T>`                              | `list`                          |
+//! | `Option<T>`                           | `T` or `None`                   |
+//! | `TpMap<T>`                            | `dict[str, T]`                  |
+
+use crate::baseline::Handle;
+
+use numpy::PyArray1;
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+use tp_client::contract::properties::bytes::Bytes;
+use tp_client::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
+use tp_client::contract::properties::traits::TpMap;
+
+pub(crate) trait ToPy {
+    fn to_py(&self, py: Python<'_>) -> PyObject;
+}
+
+pub(crate) trait FromPy: Sized {
+    fn from_py(obj: &PyAny) -> PyResult<Self>;
+}
+
+/// Exports a sequence of values (usually the keyframes of a channel) as a
+/// numpy array. Only numeric types can be exported.
+pub(crate) trait ToNumpy: Sized {
+    fn to_numpy<'a>(py: Python<'_>, values: impl Iterator<Item = &'a Self>) -> PyResult<PyObject>
+    where
+        Self: 'a,
+    {
+        let _ = (py, values);
+        Err(PyTypeError::new_err(
+            "Only numeric values can be exported to numpy",
+        ))
+    }
+}
+
+// ---- Primitives ----
+
+macro_rules! extracted {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl ToPy for $t {
+                fn to_py(&self, py: Python<'_>) -> PyObject {
+                    self.to_object(py)
+                }
+            }
+            impl FromPy for $t {
+                fn from_py(obj: &PyAny) -> PyResult<Self> {
+                    obj.extract()
+                }
+            }
+        )+
+    };
+}
+extracted!(u8, u16, u32, u64, i8, i16, i32, i64, bool, f32, f64, String);
+
+/// One dimensional arrays.
+macro_rules! numeric {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl ToNumpy for $t {
+                fn to_numpy<'a>(
+                    py: Python<'_>,
+                    values: impl Iterator<Item = &'a Self>,
+                ) -> PyResult<PyObject> {
+                    Ok(PyArray1::from_iter(py, values.copied()).to_object(py))
+                }
+            }
+        )+
+    };
+}
+numeric!(u8, u16, u32, u64, i8, i16, i32, i64, bool, f32, f64);
+
+impl ToNumpy for String {}
+
+/// Covers `ObjectHandle` and `ContractDataHandle`.
+impl<T> ToPy for arena::Index<T> {
+    fn to_py(&self, py: Python<'_>) -> PyObject {
+        Handle::from(*self).into_py(py)
+    }
+}
+impl<T> FromPy for arena::Index<T> {
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        obj.extract::<Handle>().map(Into::into)
+    }
+}
+impl<T> ToNumpy for arena::Index<T> {}
+
+/// Tuples of `float`, and two dimensional arrays with one row per value.
+macro_rules! composites {
+    ($($t:ident { $($field:ident),+ $(,)? }),+ $(,)?) => {
+        $(
+            impl ToPy for $t {
+                fn to_py(&self, py: Python<'_>) -> PyObject {
+                    ($(self.$field),+).to_object(py)
+                }
+            }
+            impl FromPy for $t {
+                fn from_py(obj: &PyAny) -> PyResult<Self> {
+                    let ($($field),+) = obj.extract()?;
+                    Ok(Self { $($field),+ })
+                }
+            }
+            impl ToNumpy for $t {
+                fn to_numpy<'a>(
+                    py: Python<'_>,
+                    values: impl Iterator<Item = &'a Self>,
+                ) -> PyResult<PyObject> {
+                    let rows: Vec<Vec<f32>> = values.map(|v| vec![$(v.$field),+]).collect();
+                    let ncols = [$(stringify!($field)),+].len();
+                    let flat: Vec<f32> = rows.iter().flatten().copied().collect();
+                    let array = PyArray1::from_vec(py, flat).reshape([rows.len(), ncols])?;
+                    Ok(array.to_object(py))
+                }
+            }
+        )+
+    };
+}
+composites!(
+    Vec2 { x, y },
+    Vec3 { x, y, z },
+    Quat { x, y, z, w },
+    Color { r, g, b, a },
+);
+
+impl ToPy for Transform {
+    fn to_py(&self, py: Python<'_>) -> PyObject {
+        (
+            self.translation.to_py(py),
+            self.rotation.to_py(py),
+            self.scale.to_py(py),
+        )
+            .to_object(py)
+    }
+}
+impl FromPy for Transform {
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        let (translation, rotation, scale): (&PyAny, &PyAny, &PyAny) = obj.extract()?;
+        Ok(Transform::new(
+            Vec3::from_py(translation)?,
+            Quat::from_py(rotation)?,
+            Vec3::from_py(scale)?,
+        ))
+    }
+}
+impl ToNumpy for Transform {}
+
+impl ToPy for Bytes {
+    fn to_py(&self, py: Python<'_>) -> PyObject {
+        PyBytes::new(py, self.as_slice()).into()
+    }
+}
+impl FromPy for Bytes {
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        let bytes: &PyBytes = obj.downcast()?;
+        Ok(Bytes::from(bytes.as_bytes()))
+    }
+}
+impl ToNumpy for Bytes {}
+
+// ---- Containers ----
+
+impl<T: ToPy> ToPy for Vec<T> {
+    fn to_py(&self, py: Python<'_>) -> PyObject {
+        PyList::new(py, self.iter().map(|v| v.to_py(py))).into()
+    }
+}
+impl<T: FromPy> FromPy for Vec<T> {
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        let items: Vec<&PyAny> = obj.extract()?;
+        items.into_iter().map(T::from_py).collect()
+    }
+}
+impl<T> ToNumpy for Vec<T> {}
+
+impl<T: ToPy> ToPy for Option<T> {
+    fn to_py(&self, py: Python<'_>) -> PyObject {
+        match self {
+            Some(v) => v.to_py(py),
+            None => py.None(),
+        }
+    }
+}
+impl<T: FromPy> FromPy for Option<T> {
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        if obj.is_none() {
+            Ok(None)
+        } else {
+            T::from_py(obj).map(Some)
+        }
+    }
+}
+impl<T> ToNumpy for Option<T> {}
+
+impl<T: ToPy> ToPy for TpMap<T> {
+    fn to_py(&self, py: Python<'_>) -> PyObject {
+        let dict = PyDict::new(py);
+        for (k, v) in self {
+            dict.set_item(k, v.to_py(py))
+                .expect("`str` keys are always hashable");
+        }
+        dict.into()
+    }
+}
+impl<T: FromPy> FromPy for TpMap<T> {
+    fn from_py(obj: &PyAny) -> PyResult<Self> {
+        let dict: &PyDict = obj.downcast()?;
+        dict.iter()
+            .map(|(k, v)| -> PyResult<_> { Ok((k.extract()?, T::from_py(v)?)) })
+            .collect()
+    }
+}
+impl<T> ToNumpy for TpMap<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tp_client::object::ObjectHandle;
+
+    use arena::generational_arena as ga;
+    use numpy::PyArray2;
+
+    fn roundtrip<T: ToPy + FromPy + PartialEq + std::fmt::Debug>(py: Python<'_>, value: T) {
+        let obj = value.to_py(py);
+        assert_eq!(T::from_py(obj.as_ref(py)).unwrap(), value);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            roundtrip(py, 7u8);
+            roundtrip(py, -7i64);
+            roundtrip(py, 0.5f32);
+            roundtrip(py, String::from("hello"));
+            roundtrip(py, Some(Vec3::new(1.0, 2.0, 3.0)));
+            roundtrip(py, None::<Quat>);
+            roundtrip(py, Transform::default());
+            roundtrip(py, Bytes::from(vec![1, 2, 3]));
+            roundtrip(py, vec![true, false]);
+            let map: TpMap<u32> = [("a".to_string(), 1)].into_iter().collect();
+            roundtrip(py, map);
+            roundtrip(py, ObjectHandle::new(ga::Index::from_raw_parts(3, 4)));
+
+            assert!(u8::from_py(256.to_object(py).as_ref(py)).is_err());
+            assert!(Vec::<u8>::from_py("abc".to_object(py).as_ref(py)).is_err());
+        })
+    }
+
+    #[test]
+    fn test_to_numpy() {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| {
+            let values = [1.0f32, 2.0, 3.0];
+            let array = f32::to_numpy(py, values.iter()).unwrap();
+            let array: &PyArray1<f32> = array.as_ref(py).downcast().unwrap();
+            assert_eq!(array.to_vec().unwrap(), values);
+
+            let values = [Vec2::new(1.0, 2.0), Vec2::new(3.0, 4.0)];
+            let array = Vec2::to_numpy(py, values.iter()).unwrap();
+            let array: &PyArray2<f32> = array.as_ref(py).downcast().unwrap();
+            assert_eq!(array.shape(), [2, 2]);
+            assert_eq!(array.to_vec().unwrap(), [1.0, 2.0, 3.0, 4.0]);
+
+            let values = [String::from("no")];
+            assert!(String::to_numpy(py, values.iter()).is_err());
+        })
+    }
+}
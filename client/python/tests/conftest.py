@@ -0,0 +1,23 @@
+import pytest
+import tp_python as tp
+
+EXAMPLE = "teleportal.example-ffi-contract"
+
+
+@pytest.fixture
+def baseline():
+    return tp.Baseline(tp.BaselineKind.Main)
+
+
+@pytest.fixture
+def contract(baseline):
+    return baseline.register_contract(EXAMPLE)
+
+
+def create_object(baseline, contract, i=0, s=""):
+    """Creates an object of the example contract, whose handle states point
+    at itself and at `contract`."""
+    states = [i, 0, -i, 0, i / 2, 0.0, s, tp.Handle(0, 0), contract.handle]
+    obj = baseline.object_create(contract, states)
+    baseline.set_state(baseline.bind_state(contract, obj, "oh_0"), obj)
+    return obj
@@ -0,0 +1,107 @@
+import pytest
+import tp_python as tp
+
+from conftest import EXAMPLE, create_object
+
+
+def test_known_contracts():
+    assert EXAMPLE in tp.known_contracts()
+
+
+def test_contract(baseline, contract):
+    assert contract.name == EXAMPLE
+    assert contract.version == (1, 2, 3)
+    assert contract.state_names[:2] == ["u8_0", "u8_1"]
+    assert contract.state_types[:3] == ["u8", "u8", "i8"]
+    assert contract.channel_names == []
+
+    with pytest.raises(KeyError):
+        baseline.register_contract("no.such.contract")
+
+
+def test_states(baseline, contract):
+    obj = create_object(baseline, contract, 3, "a")
+    assert baseline.objects() == [obj]
+    assert baseline.object_contract(obj) == contract.handle
+
+    i8_0 = baseline.bind_state(contract, obj, "i8_0")
+    assert i8_0.prop_type == "i8"
+    assert baseline.state(i8_0) == -3
+    # Bound by index instead of name
+    assert baseline.bind_state(contract, obj, 2) == i8_0
+
+    u8_1 = baseline.bind_state(contract, obj, "u8_1")
+    str_0 = baseline.bind_state(contract, obj, "str_0")
+    assert baseline.state(str_0) == "a"
+
+    baseline.set_state(u8_1, 200)
+    baseline.set_state(str_0, "hello")
+    assert baseline.state(u8_1) == 200
+    assert baseline.state(str_0) == "hello"
+
+    oh_0 = baseline.bind_state(contract, obj, "oh_0")
+    assert baseline.state(oh_0) == obj
+    ch_0 = baseline.bind_state(contract, obj, "ch_0")
+    assert baseline.state(ch_0) == contract.handle
+
+
+def test_invalid_states(baseline, contract):
+    obj = create_object(baseline, contract)
+    u8_0 = baseline.bind_state(contract, obj, "u8_0")
+
+    with pytest.raises(OverflowError):
+        baseline.set_state(u8_0, 256)
+    with pytest.raises(TypeError):
+        baseline.set_state(u8_0, "one")
+    assert baseline.state(u8_0) == 0
+
+    with pytest.raises(KeyError):
+        baseline.bind_state(contract, obj, "nope")
+    with pytest.raises(IndexError):
+        baseline.bind_state(contract, obj, 100)
+    with pytest.raises(TypeError):
+        baseline.object_create(contract, ["not a u8"])
+    # States without a default can't be omitted
+    with pytest.raises(Exception):
+        baseline.object_create(contract, [1, 2])
+
+
+def test_object_remove(baseline, contract):
+    obj = create_object(baseline, contract)
+    baseline.object_remove(obj)
+    assert baseline.objects() == []
+    with pytest.raises(Exception):
+        baseline.object_remove(obj)
+
+
+def test_dyn_contract(baseline):
+    schema = tp.ContractSchema(
+        "teleportal.test.ball",
+        (0, 1, 0),
+        states=[("name", "String"), ("radius", "f32")],
+        channels=[("position", "Vec3")],
+    )
+    contract = baseline.register_dyn_contract(schema)
+    assert contract.schema == schema
+    assert contract.channel_types == ["Vec3"]
+
+    obj = baseline.object_create(contract, ["ball", 0.5])
+    radius = baseline.bind_state(contract, obj, "radius")
+    assert baseline.state(radius) == 0.5
+
+    position = baseline.bind_channel(contract, obj, "position")
+    baseline.insert_keyframe(position, 1.0, (1.0, 2.0, 3.0))
+    baseline.insert_keyframe(position, 0.0, (0.0, 0.0, 0.0))
+    assert baseline.keyframes(position) == [(0.0, (0.0, 0.0, 0.0)), (1.0, (1.0, 2.0, 3.0))]
+    times, values = baseline.keyframes_numpy(position)
+    assert values.shape == (2, 3)
+
+    baseline.unregister_contract(contract)
+    assert baseline.objects() == []
+
+
+def test_invalid_schema():
+    with pytest.raises(TypeError):
+        tp.ContractSchema("bad", (0, 0, 0), states=[("x", "f33")])
+    with pytest.raises(Exception):
+        tp.ContractSchema("bad", (0, 0, 0), states=[("x", "u8"), ("x", "u8")])
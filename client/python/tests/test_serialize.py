@@ -0,0 +1,56 @@
+import tp_python as tp
+
+from conftest import EXAMPLE, create_object
+
+
+def test_round_trip(baseline, contract):
+    for i in range(3):
+        create_object(baseline, contract, i, f"obj {i}")
+
+    serializer = tp.Serializer(baseline)
+    serializer.serialize(contract)
+    data = serializer.finish()
+    assert isinstance(data, bytes)
+
+    builder = tp.DeserializerBuilder(data, tp.BaselineKind.Fork)
+    contract = builder.register_contract(EXAMPLE)
+    deserializer = builder.finish()
+    deserializer.deserialize_objects(contract)
+    result = deserializer.finish()
+    assert result.kind == tp.BaselineKind.Fork
+
+    objects = [o for o in result.objects() if result.object_contract(o) == contract.handle]
+    assert len(objects) == 3
+    values = set()
+    for obj in objects:
+        f32_0 = result.state(result.bind_state(contract, obj, "f32_0"))
+        str_0 = result.state(result.bind_state(contract, obj, "str_0"))
+        values.add((f32_0, str_0))
+        # Handles are remapped to the deserialized objects
+        assert result.state(result.bind_state(contract, obj, "oh_0")) == obj
+    assert values == {(i / 2, f"obj {i}") for i in range(3)}
+
+
+def test_round_trip_dyn(baseline):
+    schema = tp.ContractSchema(
+        "teleportal.test.score", (1, 0, 0), states=[("player", "String"), ("score", "u32")]
+    )
+    contract = baseline.register_dyn_contract(schema)
+    baseline.object_create(contract, ["alice", 10])
+
+    serializer = tp.Serializer(baseline)
+    serializer.serialize(contract)
+    data = serializer.finish()
+
+    # The schema is read back out of the flatbuffer
+    builder = tp.DeserializerBuilder(data, tp.BaselineKind.Main)
+    schemas = builder.serialized_schemas()
+    assert schemas == [schema]
+    contract = builder.register_dyn_contract(schemas[0])
+    deserializer = builder.finish()
+    deserializer.deserialize_objects(contract)
+    result = deserializer.finish()
+
+    [obj] = [o for o in result.objects() if result.object_contract(o) == contract.handle]
+    assert result.state(result.bind_state(contract, obj, "player")) == "alice"
+    assert result.state(result.bind_state(contract, obj, "score")) == 10
@@ -0,0 +1,130 @@
+use crate::action::{ActionKind, IAction};
+use crate::lock::{Lock, LockTarget};
+use crate::time::Ticks;
+
+/// Acquires or releases a [`Lock`], on behalf of the owner of the collaction.
+/// Collactions without an owner can't hold locks.
+///
+/// When applied, the previous lock on `target` (if any) is stored in
+/// `replaced`, so that the `Engine` can restore it if the collaction is
+/// rejected. Unlike state actions, applying the action again doesn't reverse
+/// it.
+pub enum LockAction {
+    /// Locks `target` until `lease` from now. Renews the lease if the owner
+    /// already holds the lock, and fails if anyone else does, including on the
+    /// object of a state.
+    Acquire {
+        target: LockTarget,
+        lease: Ticks,
+        replaced: Option<Lock>,
+    },
+    /// Fails unless the owner holds the lock.
+    Release {
+        target: LockTarget,
+        replaced: Option<Lock>,
+    },
+}
+impl LockAction {
+    pub fn acquire(target: impl Into<LockTarget>, lease: Ticks) -> Self {
+        Self::Acquire {
+            target: target.into(),
+            lease,
+            replaced: None,
+        }
+    }
+
+    pub fn release(target: impl Into<LockTarget>) -> Self {
+        Self::Release {
+            target: target.into(),
+            replaced: None,
+        }
+    }
+
+    pub fn target(&self) -> LockTarget {
+        match self {
+            Self::Acquire { target, .. } | Self::Release { target, .. } => *target,
+        }
+    }
+}
+
+impl IAction for LockAction {
+    fn kind(&self) -> ActionKind {
+        ActionKind::Lock
+    }
+
+    fn into_bytes(self) -> Box<[u8]> {
+        todo!();
+    }
+}
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::action::lock")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use super::*;
+    use crate::action::c_api::Action as CAction;
+    use crate::action::Action;
+    use crate::contract::properties::primitives;
+    use crate::contract::properties::states::DynStateHandle;
+    use crate::object::c_api::ObjectHandle as CObjectHandle;
+
+    use rsharp::remangle;
+    use safer_ffi::prelude::*;
+
+    fn boxed(action: LockAction) -> repr_c::Box<CAction> {
+        Box::new(CAction::from(Action::from(action))).into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__lock_acquire_object(
+        obj: &CObjectHandle,
+        lease_millis: i32,
+    ) -> repr_c::Box<CAction> {
+        boxed(LockAction::acquire(obj.inner, Ticks::new(lease_millis)))
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__lock_release_object(obj: &CObjectHandle) -> repr_c::Box<CAction> {
+        boxed(LockAction::release(obj.inner))
+    }
+
+    macro_rules! monomorphize {
+        // Base case
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_Action_ $t:camel>] {
+                    use super::*;
+
+                    use crate::contract::properties::states::c_api::[<StateHandle_ $t:camel>] as Monomorphized_StateHandle;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__lock_acquire_state_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        lease_millis: i32,
+                    ) -> repr_c::Box<CAction> {
+                        let handle = DynStateHandle::from(handle.inner);
+                        boxed(LockAction::acquire(handle, Ticks::new(lease_millis)))
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__lock_release_state_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(LockAction::release(DynStateHandle::from(handle.inner)))
+                    }
+                }
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize!($path, $first_t);
+            monomorphize!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize, "tp_client::action::lock");
+}
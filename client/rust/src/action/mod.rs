@@ -1,12 +1,23 @@
+pub mod lock;
+pub mod object;
 pub mod property;
 
+use crate::contract::properties::states::DynStateHandle;
+use crate::lock::OwnerId;
+use lock::LockAction;
+use object::ObjectAction;
 use property::{ChannelAction, PropertyAction, StateAction};
 
 use enum_dispatch::enum_dispatch;
+#[cfg(feature = "c_api")]
+use safer_ffi::derive_ReprC;
+use std::fmt;
 
 #[enum_dispatch(IAction)]
 pub enum Action {
     Property(PropertyAction),
+    Lock(LockAction),
+    Object(ObjectAction),
 }
 
 /// All `Action` variants satisfy `IAction` trait
@@ -19,11 +30,33 @@ pub trait IAction {
 
 pub type ActionResult = eyre::Result<()>;
 
+#[cfg_attr(feature = "c_api", derive_ReprC, ReprC::opaque)]
 pub struct Collaction {
     actions: Vec<Action>,
+    rejection: Option<Rejection>,
+    owner: Option<OwnerId>,
 }
 
 impl Collaction {
+    pub fn new(actions: Vec<Action>) -> Self {
+        Self {
+            actions,
+            rejection: None,
+            owner: None,
+        }
+    }
+
+    /// Sets who the collaction is from, which decides the locks that it may
+    /// write through, see [`lock`](crate::lock).
+    pub fn with_owner(mut self, owner: OwnerId) -> Self {
+        self.owner = Some(owner);
+        self
+    }
+
+    pub fn owner(&self) -> Option<OwnerId> {
+        self.owner
+    }
+
     pub fn actions(&self) -> &[Action] {
         &self.actions
     }
@@ -31,11 +64,79 @@ impl Collaction {
     pub fn actions_mut(&mut self) -> &mut [Action] {
         &mut self.actions
     }
+
+    /// Moves the actions of `other` to the end of this collaction.
+    pub fn append(&mut self, mut other: Collaction) {
+        self.actions.append(&mut other.actions)
+    }
+
+    /// Keeps only the actions for which `f` returns true.
+    pub(crate) fn retain(&mut self, f: impl FnMut(&Action) -> bool) {
+        self.actions.retain(f)
+    }
+
+    /// Reverses the order of the actions.
+    pub(crate) fn reverse(&mut self) {
+        self.actions.reverse()
+    }
+
+    /// Why the `Engine` rejected the collaction, if it was rejected the last
+    /// time that it was applied.
+    pub fn rejection(&self) -> Option<&Rejection> {
+        self.rejection.as_ref()
+    }
+
+    pub(crate) fn set_rejection(&mut self, rejection: Option<Rejection>) {
+        self.rejection = rejection;
+    }
 }
 
 pub type CollactionResult = Result<Collaction, Collaction>;
 
+/// Why the `Engine` rejected a collaction.
+#[cfg_attr(feature = "c_api", derive_ReprC, ReprC::opaque)]
+#[derive(Debug, Clone)]
+pub struct Rejection {
+    /// The index of the action that failed.
+    pub action: usize,
+    /// Set if the action failed because the state had been written since the
+    /// version that it expected.
+    pub conflict: Option<Conflict>,
+    pub reason: String,
+}
+impl Rejection {
+    pub(crate) fn new(action: usize, err: eyre::Report) -> Self {
+        Self {
+            action,
+            conflict: err.downcast_ref::<Conflict>().copied(),
+            reason: format!("{:#}", err),
+        }
+    }
+}
+
+/// A state was not at the version that an action expected, because it was
+/// written by someone else in the meantime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub state: DynStateHandle,
+    pub expected: u64,
+    pub actual: u64,
+}
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "State was at version {}, but version {} was expected",
+            self.actual, self.expected
+        )
+    }
+}
+impl std::error::Error for Conflict {}
+
 // ---- ObjectAction types ----
+#[cfg_attr(feature = "c_api", derive_ReprC)]
+#[repr(u8)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ActionKind {
     StateWrite,
     StateIncrement,
@@ -50,4 +151,127 @@ pub enum ActionKind {
     Lock,
     StateAssert,
     QueueAssert,
+    StateMapInsert,
+    StateMapRemove,
+    StateBytesWrite,
+    StateAssertVersion,
+    StateCompareAndSet,
+    StateMerge,
+    ObjectReparent,
+}
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::action")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use super::{ActionKind, Collaction, IAction, Rejection};
+    use crate::contract::properties::c_api::impl_from_refcast;
+    use crate::lock::OwnerId;
+
+    use derive_more::{From, Into};
+    use ref_cast::RefCast;
+    use rsharp::remangle;
+    use rsharp::string::String as RString;
+    use safer_ffi::prelude::*;
+
+    #[remangle(substitute!())]
+    #[derive_ReprC]
+    #[ReprC::opaque]
+    #[derive(From, Into, RefCast)]
+    #[repr(C)]
+    pub struct Action {
+        pub inner: super::Action,
+    }
+    impl_from_refcast!(super::Action, Action);
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__drop(action: repr_c::Box<Action>) {
+        drop(action)
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__kind(action: &Action) -> ActionKind {
+        action.inner.kind()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Collaction__new() -> repr_c::Box<Collaction> {
+        Box::new(Collaction::new(Vec::new())).into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Collaction__drop(collaction: repr_c::Box<Collaction>) {
+        drop(collaction)
+    }
+
+    /// Takes ownership of `action` and appends it to the collaction.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Collaction__push(collaction: &mut Collaction, action: repr_c::Box<Action>) {
+        let action: Box<Action> = action.into();
+        collaction.actions.push(action.inner)
+    }
+
+    /// Sets who the collaction is from, which decides the locks that it may
+    /// write through.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Collaction__set_owner(collaction: &mut Collaction, owner: u64) {
+        collaction.owner = Some(OwnerId(owner))
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Collaction__len(collaction: &Collaction) -> usize {
+        collaction.actions().len()
+    }
+
+    /// Returns null if `idx` is out of bounds.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Collaction__action(collaction: &Collaction, idx: usize) -> Option<&Action> {
+        collaction.actions().get(idx).map(|action| action.into())
+    }
+
+    /// Returns null unless the collaction was rejected the last time that it
+    /// was applied.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Collaction__rejection(collaction: &Collaction) -> Option<&Rejection> {
+        collaction.rejection()
+    }
+
+    /// The index of the action that failed.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Rejection__action(rejection: &Rejection) -> usize {
+        rejection.action
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Rejection__reason(rejection: &Rejection) -> &RString {
+        (&rejection.reason).into()
+    }
+
+    /// Whether the action failed because the state was at a different version
+    /// than it expected.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Rejection__is_conflict(rejection: &Rejection) -> bool {
+        rejection.conflict.is_some()
+    }
+
+    /// The version that the conflicting state was at, or 0 if there was no
+    /// conflict.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Rejection__conflict_version(rejection: &Rejection) -> u64 {
+        rejection.conflict.map_or(0, |c| c.actual)
+    }
 }
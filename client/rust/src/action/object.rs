@@ -0,0 +1,92 @@
+use crate::action::{ActionKind, IAction};
+use crate::object::ObjectHandle;
+
+/// Changes the hierarchy of objects, see
+/// [`Baseline::object_reparent`](crate::baseline::Baseline::object_reparent).
+pub enum ObjectAction {
+    /// Moves `object` to be the child of `parent` at index `idx` of its
+    /// children, or its last child if `idx` is `None`. Fails if the object
+    /// or the new parent is locked by someone else.
+    ///
+    /// When applied, the previous parent and index of `object` are swapped
+    /// into `parent` and `idx`, so that applying the action again reverses it.
+    Reparent {
+        object: ObjectHandle,
+        parent: Option<ObjectHandle>,
+        idx: Option<usize>,
+    },
+}
+impl ObjectAction {
+    pub fn reparent(object: ObjectHandle, parent: Option<ObjectHandle>) -> Self {
+        Self::Reparent {
+            object,
+            parent,
+            idx: None,
+        }
+    }
+
+    pub fn reparent_at(object: ObjectHandle, parent: Option<ObjectHandle>, idx: usize) -> Self {
+        Self::Reparent {
+            object,
+            parent,
+            idx: Some(idx),
+        }
+    }
+}
+
+impl IAction for ObjectAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Reparent { .. } => ActionKind::ObjectReparent,
+        }
+    }
+
+    fn into_bytes(self) -> Box<[u8]> {
+        todo!();
+    }
+}
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::action::object")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use super::*;
+    use crate::action::c_api::Action as CAction;
+    use crate::action::Action;
+    use crate::object::c_api::ObjectHandle as CObjectHandle;
+
+    use rsharp::remangle;
+    use safer_ffi::prelude::*;
+
+    fn boxed(action: ObjectAction) -> repr_c::Box<CAction> {
+        Box::new(CAction::from(Action::from(action))).into()
+    }
+
+    /// Moves `obj` to the end of the children of `parent`. `parent` may be
+    /// null, to make `obj` a root.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__object_reparent(
+        obj: &CObjectHandle,
+        parent: Option<&CObjectHandle>,
+    ) -> repr_c::Box<CAction> {
+        boxed(ObjectAction::reparent(obj.inner, parent.map(|p| p.inner)))
+    }
+
+    /// Like `Action__object_reparent()`, but moves `obj` to index `idx` of the
+    /// children of `parent`, like Unity's `Transform.SetSiblingIndex()`.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__object_reparent_at(
+        obj: &CObjectHandle,
+        parent: Option<&CObjectHandle>,
+        idx: usize,
+    ) -> repr_c::Box<CAction> {
+        boxed(ObjectAction::reparent_at(
+            obj.inner,
+            parent.map(|p| p.inner),
+            idx,
+        ))
+    }
+}
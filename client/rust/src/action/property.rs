@@ -1,6 +1,7 @@
+use crate::contract::properties::bytes::Bytes;
 use crate::contract::properties::channels::DynChannelHandle;
-use crate::contract::properties::dynamic::DynTpProperty;
-use crate::contract::properties::states::DynStateHandle;
+use crate::contract::properties::dynamic::{DynTpPrimitive, DynTpProperty};
+use crate::contract::properties::states::{DynStateHandle, StateHandle};
 
 use crate::action::{ActionKind, IAction};
 
@@ -12,6 +13,8 @@ pub enum PropertyAction {
     Channel(ChannelAction),
 }
 
+/// Not applied by the [`Engine`](crate::engine::Engine) yet, which treats them
+/// as no-ops. For that reason, the C API leaves out builders for them.
 pub enum ChannelAction {
     Assert {
         handle: DynChannelHandle,
@@ -31,6 +34,107 @@ pub enum StateAction {
         handle: DynStateHandle,
         data: DynTpProperty,
     },
+    /// Inserts an entry into a map state. Prefer [`StateAction::map_insert`] to
+    /// construct this.
+    ///
+    /// When applied, the previous entry at `key` (if any) is swapped into
+    /// `value`, so that applying the action again reverses it.
+    MapInsert {
+        handle: DynStateHandle,
+        key: String,
+        value: Option<DynTpPrimitive>,
+    },
+    /// Removes an entry from a map state. Prefer [`StateAction::map_remove`] to
+    /// construct this.
+    ///
+    /// When applied, the removed entry (if any) is swapped into `removed`, so
+    /// that applying the action again reverses it.
+    MapRemove {
+        handle: DynStateHandle,
+        key: String,
+        removed: Option<DynTpPrimitive>,
+    },
+    /// Replaces `len` bytes starting at `offset` in a `Bytes` state with
+    /// `chunk`, growing the state if the range extends past its end. Prefer
+    /// [`StateAction::bytes_write`] to construct this.
+    ///
+    /// This allows large payloads to be written in pieces, without sending the
+    /// whole payload every time. When applied, the replaced bytes are swapped
+    /// into `chunk` and `len` becomes the length of the written chunk, so that
+    /// applying the action again reverses it.
+    BytesWrite {
+        handle: StateHandle<Bytes>,
+        offset: usize,
+        len: usize,
+        chunk: Bytes,
+    },
+    /// Fails with a [`Conflict`](crate::action::Conflict) unless the state is
+    /// at `version`, see
+    /// [`State::version`](crate::contract::properties::states::State::version).
+    ///
+    /// Unlike [`StateAction::Assert`], this is cheap for large states, and
+    /// tells apart two writes of the same value.
+    AssertVersion {
+        handle: DynStateHandle,
+        version: u64,
+    },
+    /// Writes `data` to the state if it is at `version`, and otherwise fails
+    /// with a [`Conflict`](crate::action::Conflict).
+    ///
+    /// When applied, the previous value is swapped into `data`, and `version`
+    /// becomes the new version of the state, so that applying the action again
+    /// reverses it.
+    CompareAndSet {
+        handle: DynStateHandle,
+        version: u64,
+        data: DynTpProperty,
+    },
+    /// Merges the CRDT `delta` into a `Bytes` state, see
+    /// [`crdt`](crate::contract::properties::crdt). Prefer
+    /// [`StateAction::merge`] to construct this.
+    ///
+    /// When applied, the previous value is swapped into `replaced`, and
+    /// applying the action again restores it.
+    Merge {
+        handle: StateHandle<Bytes>,
+        delta: Bytes,
+        replaced: Option<Bytes>,
+    },
+}
+impl StateAction {
+    pub fn map_insert(handle: DynStateHandle, key: String, value: DynTpPrimitive) -> Self {
+        Self::MapInsert {
+            handle,
+            key,
+            value: Some(value),
+        }
+    }
+
+    pub fn map_remove(handle: DynStateHandle, key: String) -> Self {
+        Self::MapRemove {
+            handle,
+            key,
+            removed: None,
+        }
+    }
+
+    /// Overwrites the bytes starting at `offset` with `chunk`.
+    pub fn bytes_write(handle: StateHandle<Bytes>, offset: usize, chunk: Bytes) -> Self {
+        Self::BytesWrite {
+            handle,
+            offset,
+            len: chunk.len(),
+            chunk,
+        }
+    }
+
+    pub fn merge(handle: StateHandle<Bytes>, delta: Bytes) -> Self {
+        Self::Merge {
+            handle,
+            delta,
+            replaced: None,
+        }
+    }
 }
 
 impl IAction for StateAction {
@@ -38,6 +142,12 @@ impl IAction for StateAction {
         match self {
             Self::Write { .. } => ActionKind::StateWrite,
             Self::Assert { .. } => ActionKind::StateAssert,
+            Self::MapInsert { .. } => ActionKind::StateMapInsert,
+            Self::MapRemove { .. } => ActionKind::StateMapRemove,
+            Self::BytesWrite { .. } => ActionKind::StateBytesWrite,
+            Self::AssertVersion { .. } => ActionKind::StateAssertVersion,
+            Self::CompareAndSet { .. } => ActionKind::StateCompareAndSet,
+            Self::Merge { .. } => ActionKind::StateMerge,
         }
     }
 
@@ -58,3 +168,279 @@ impl IAction for ChannelAction {
         todo!();
     }
 }
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::action::property")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use super::*;
+    use crate::action::c_api::Action as CAction;
+    use crate::action::Action;
+    use crate::contract::properties::c_api::c_types;
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
+    use crate::contract::properties::primitives;
+    use crate::contract::properties::states::c_api::StateHandle_Bytes;
+    use crate::contract::ContractDataHandle;
+    use crate::object::ObjectHandle;
+
+    use rsharp::remangle;
+    use safer_ffi::prelude::*;
+
+    // There are no builders for `ChannelAction`s on purpose, since the engine
+    // doesn't apply them yet. They should be added along with support for them.
+
+    fn boxed(action: StateAction) -> repr_c::Box<CAction> {
+        let action: Action = PropertyAction::from(action).into();
+        Box::new(CAction::from(action)).into()
+    }
+
+    macro_rules! monomorphize {
+        // Base case
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_Action_ $t:camel>] {
+                    use super::*;
+
+                    use crate::contract::properties::states::c_api::[<StateHandle_ $t:camel>] as Monomorphized_StateHandle;
+
+                    /// Takes ownership of `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_write_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        value: repr_c::Box<c_types::$t>,
+                    ) -> repr_c::Box<CAction> {
+                        let data = DynTpProperty::from($t::from(*value.into()));
+                        boxed(StateAction::Write { handle: handle.inner.into(), data })
+                    }
+
+                    /// Takes ownership of `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_assert_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        value: repr_c::Box<c_types::$t>,
+                    ) -> repr_c::Box<CAction> {
+                        let data = DynTpProperty::from($t::from(*value.into()));
+                        boxed(StateAction::Assert { handle: handle.inner.into(), data })
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_assert_version_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        version: u64,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(StateAction::AssertVersion { handle: handle.inner.into(), version })
+                    }
+
+                    /// Takes ownership of `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_compare_and_set_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        version: u64,
+                        value: repr_c::Box<c_types::$t>,
+                    ) -> repr_c::Box<CAction> {
+                        let data = DynTpProperty::from($t::from(*value.into()));
+                        boxed(StateAction::CompareAndSet { handle: handle.inner.into(), version, data })
+                    }
+                }
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize!($path, $first_t);
+            monomorphize!($path, $($tail_t),+);
+        };
+    }
+
+    // This is like doing `monomorphize!("whatever", Keyframe, u8, u16, ...)
+    primitives!(; types, monomorphize, "tp_client::action::property");
+
+    /// Same as `monomorphize`, but for actions on map states
+    macro_rules! monomorphize_map {
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_Action_Map $t:camel>] {
+                    use super::*;
+
+                    use crate::contract::properties::states::c_api::[<StateHandle_Map $t:camel>] as Monomorphized_StateHandle;
+                    use crate::contract::properties::states::c_api::[<State_Map $t:camel>] as Monomorphized_State;
+
+                    /// Replaces the whole map with the one in `value`, a state
+                    /// made with `State_Map*__new`. Takes ownership of `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_write_Map $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        value: repr_c::Box<Monomorphized_State>,
+                    ) -> repr_c::Box<CAction> {
+                        let value: Box<Monomorphized_State> = value.into();
+                        let data = DynTpProperty::from(value.inner.value);
+                        boxed(StateAction::Write { handle: handle.inner.into(), data })
+                    }
+
+                    /// Asserts that the whole map equals the one in `value`.
+                    /// Takes ownership of `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_assert_Map $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        value: repr_c::Box<Monomorphized_State>,
+                    ) -> repr_c::Box<CAction> {
+                        let value: Box<Monomorphized_State> = value.into();
+                        let data = DynTpProperty::from(value.inner.value);
+                        boxed(StateAction::Assert { handle: handle.inner.into(), data })
+                    }
+
+                    /// Takes ownership of `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_compare_and_set_Map $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        version: u64,
+                        value: repr_c::Box<Monomorphized_State>,
+                    ) -> repr_c::Box<CAction> {
+                        let value: Box<Monomorphized_State> = value.into();
+                        let data = DynTpProperty::from(value.inner.value);
+                        boxed(StateAction::CompareAndSet { handle: handle.inner.into(), version, data })
+                    }
+
+                    /// Takes ownership of `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_map_insert_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        key: &c_types::String,
+                        value: repr_c::Box<c_types::$t>,
+                    ) -> repr_c::Box<CAction> {
+                        let value = DynTpPrimitive::from($t::from(*value.into()));
+                        boxed(StateAction::map_insert(handle.inner.into(), key.inner.clone(), value))
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_map_remove_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        key: &c_types::String,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(StateAction::map_remove(handle.inner.into(), key.inner.clone()))
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_map_assert_version_ $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        version: u64,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(StateAction::AssertVersion { handle: handle.inner.into(), version })
+                    }
+                }
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_map!($path, $first_t);
+            monomorphize_map!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_map, "tp_client::action::property");
+
+    /// Same as `monomorphize`, but for actions on option states. A null
+    /// `value` is `None`.
+    macro_rules! monomorphize_option {
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_Action_Option $t:camel>] {
+                    use super::*;
+
+                    use crate::contract::properties::states::c_api::[<StateHandle_Option $t:camel>] as Monomorphized_StateHandle;
+
+                    fn data(value: Option<repr_c::Box<c_types::$t>>) -> DynTpProperty {
+                        DynTpProperty::from(value.map(|v| $t::from(*v.into())))
+                    }
+
+                    /// `value` may be null, to write `None`. Takes ownership of
+                    /// `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_write_Option $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        value: Option<repr_c::Box<c_types::$t>>,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(StateAction::Write { handle: handle.inner.into(), data: data(value) })
+                    }
+
+                    /// `value` may be null, to assert `None`. Takes ownership of
+                    /// `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_assert_Option $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        value: Option<repr_c::Box<c_types::$t>>,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(StateAction::Assert { handle: handle.inner.into(), data: data(value) })
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_assert_version_Option $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        version: u64,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(StateAction::AssertVersion { handle: handle.inner.into(), version })
+                    }
+
+                    /// `value` may be null, to write `None`. Takes ownership of
+                    /// `value`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Action__state_compare_and_set_Option $t:camel>](
+                        handle: &Monomorphized_StateHandle,
+                        version: u64,
+                        value: Option<repr_c::Box<c_types::$t>>,
+                    ) -> repr_c::Box<CAction> {
+                        boxed(StateAction::CompareAndSet {
+                            handle: handle.inner.into(),
+                            version,
+                            data: data(value),
+                        })
+                    }
+                }
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_option!($path, $first_t);
+            monomorphize_option!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_option, "tp_client::action::property");
+
+    /// Copies `chunk`.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__state_bytes_write(
+        handle: &StateHandle_Bytes,
+        offset: usize,
+        chunk: c_slice::Ref<u8>,
+    ) -> repr_c::Box<CAction> {
+        boxed(StateAction::bytes_write(
+            handle.inner,
+            offset,
+            chunk.as_slice().into(),
+        ))
+    }
+
+    /// Copies `delta`, which must be an encoded CRDT.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Action__state_merge(
+        handle: &StateHandle_Bytes,
+        delta: c_slice::Ref<u8>,
+    ) -> repr_c::Box<CAction> {
+        boxed(StateAction::merge(handle.inner, delta.as_slice().into()))
+    }
+}
@@ -1,30 +1,42 @@
 // Teleportal Platform v3
 // Copyright 2021 WiTag Inc. dba Teleportal
 
+use crate::apply_to_state_handle;
+use crate::contract::properties::bytes::Bytes;
 use crate::contract::properties::channels::{
     apply_to_channel, apply_to_channel_id, Channel, ChannelArenaHandle, ChannelArenaMap,
-    ChannelHandle, ChannelId, ChannelsIter, DynChannel, IChannels,
+    ChannelHandle, ChannelId, DynChannel,
 };
-use crate::contract::properties::dynamic::{apply_to_prop, DynTpProperty};
+use crate::contract::properties::crdt;
+use crate::contract::properties::dynamic::{apply_to_prop, DynTpProperty, DynTpPropertyRef};
+use crate::contract::properties::field::FieldInfo;
 use crate::contract::properties::states::{
-    apply_to_state_id, IStateHandle, IStates, State, StateArenaHandle, StateArenaMap, StateHandle,
-    StateId, StatesIter,
+    apply_to_state_id, DynStateHandle, IStateHandle, State, StateArenaHandle, StateArenaMap,
+    StateHandle, StateId,
 };
 
 use crate::contract::properties::traits::ITpPropertyStatic;
-use crate::contract::{Contract, ContractData, ContractDataHandle};
+use crate::contract::{
+    Contract, ContractData, ContractDataHandle, ContractId, ContractLayout, ContractSchema,
+    DynContract, IContract,
+};
 use crate::object::{Object, ObjectHandle};
+use crate::prefab::{Overrides, Prefab};
+use crate::spatial::{PositionStates, SpatialIndex};
 use crate::time::TimeWarp;
 
 use arena::Arena;
-use eyre::{eyre, Result};
+use eyre::{eyre, Result, WrapErr};
 use itertools::EitherOrBoth;
 use itertools::Itertools;
+use std::collections::HashMap;
+use std::mem;
 
 #[cfg(feature = "c_api")]
 use safer_ffi::derive_ReprC;
 
 #[cfg_attr(feature = "c_api", derive_ReprC)]
+#[cfg_attr(feature = "wasm", wasm_bindgen::prelude::wasm_bindgen)]
 #[repr(u8)]
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Hash)]
 pub enum BaselineKind {
@@ -39,6 +51,14 @@ pub struct Baseline {
     contracts: Arena<ContractData>,
     pub(crate) states: StateArenaMap, // maps from T to Arena<State<T>>
     pub(crate) channels: ChannelArenaMap, // maps from T to Arena<Channel<T>>
+    /// The field info of states whose fields have constraints
+    state_info: HashMap<DynStateHandle, &'static FieldInfo>,
+    /// The object that each state belongs to
+    state_objects: HashMap<DynStateHandle, ObjectHandle>,
+    /// The prefabs, by name
+    prefabs: HashMap<String, Prefab>,
+    /// Kept up to date as objects are created and removed
+    spatial: SpatialIndex,
 }
 
 impl Baseline {
@@ -54,6 +74,10 @@ impl Baseline {
             contracts,
             states,
             channels,
+            state_info: HashMap::new(),
+            state_objects: HashMap::new(),
+            prefabs: HashMap::new(),
+            spatial: SpatialIndex::default(),
         }
     }
 
@@ -64,32 +88,61 @@ impl Baseline {
     // ---- Object and Contract Acessors ----
 
     pub fn register_contract<C: Contract>(&mut self) -> Result<C> {
+        let handle = self.contract_insert(ContractLayout::of::<C>())?;
+        Ok(C::new(handle))
+    }
+
+    /// Registers a contract whose fields are only known at runtime.
+    ///
+    /// # Errors
+    /// Will error if `schema` is invalid, or if a contract with the same
+    /// name and version was already registered.
+    pub fn register_dyn_contract(&mut self, schema: &ContractSchema) -> Result<DynContract> {
+        let layout = schema.layout()?;
+        let handle = self.contract_insert(layout)?;
+        Ok(DynContract::new(handle, layout))
+    }
+
+    fn contract_insert(&mut self, layout: ContractLayout) -> Result<ContractDataHandle> {
         for (_, c_data) in self.contracts.iter() {
             let c_id = c_data.id();
-            if c_id == C::ID {
+            if c_id == layout.id {
                 return Err(eyre!("Contract already added!"));
             }
         }
-        let handle = self.contracts.insert(ContractData::new(C::ID));
-        Ok(C::new(handle))
+        Ok(self.contracts.insert(ContractData::new(layout)))
     }
 
     pub fn unregister_contract<C: Contract>(&mut self, handle: ContractDataHandle) -> Result<()> {
+        self.contract_remove(handle, C::ID)
+    }
+
+    /// Unregisters `contract`, and removes all of its objects and prefabs.
+    pub fn unregister_dyn_contract(&mut self, contract: &DynContract) -> Result<()> {
+        self.contract_remove(contract.handle(), contract.id())
+    }
+
+    fn contract_remove(&mut self, handle: ContractDataHandle, id: ContractId) -> Result<()> {
         let c_data = self
             .contracts
             .get_mut(handle)
             .ok_or_else(|| eyre!("There is no contract with that id to unregister!"))?;
 
-        if c_data.id() != C::ID {
+        if c_data.id() != id {
             return Err(eyre!("Handle did not match the provided contract type!"));
         }
 
+        self.prefabs.retain(|_, p| p.contract().handle() != handle);
         // Its ok to steal the hashmap because c_data will be deleted soon anyway
         let objs = std::mem::take(c_data.objects_mut());
         for o in objs {
-            self.object_remove::<C>(o)
-                .expect("Failed to remove object!")
+            // Already removed along with its parent
+            if self.objects.get(o).is_none() {
+                continue;
+            }
+            self.object_remove_dyn(o).expect("Failed to remove object!")
         }
+        self.spatial.untrack_contract(handle);
         self.contracts.remove(handle);
         Ok(())
     }
@@ -104,6 +157,14 @@ impl Baseline {
         self.objects.iter()
     }
 
+    /// All registered contracts, whether they were registered as a [`Contract`]
+    /// or as a [`DynContract`].
+    pub fn iter_contracts(&self) -> impl Iterator<Item = DynContract> + '_ {
+        self.contracts
+            .iter()
+            .map(|(handle, data)| DynContract::new(handle, *data.layout()))
+    }
+
     pub fn object(&self, obj: ObjectHandle) -> Result<&Object> {
         self.objects
             .get(obj)
@@ -117,23 +178,29 @@ impl Baseline {
     }
 
     /// Create an object with the given `states` and `channels`, corresponding
-    /// to contract `C`
+    /// to `contract`. This can be either a [`Contract`] or a [`DynContract`].
+    ///
+    /// Any trailing states that are omitted will be filled in with the
+    /// `#[default]` of their field.
     ///
     /// # Errors
     /// Will error if the types of any of the states and channels don't match
-    /// the contract.
-    pub fn object_create<C: Contract>(
+    /// the contract, if an omitted state has no default, or if a state is out
+    /// of the range of its field.
+    pub fn object_create<C: IContract>(
         &mut self,
         contract: &C,
         states: impl Iterator<Item = DynTpProperty>,
         channels: impl Iterator<Item = DynChannel>,
     ) -> Result<ObjectHandle> {
-        if !self.contracts.contains(contract.handle()) {
+        let contract_handle = contract.contract_handle();
+        if !self.contracts.contains(contract_handle) {
             return Err(eyre!("No such contract for that handle"));
         }
 
-        let state_types = C::States::enumerate_types();
-        let channel_types = C::Channels::enumerate_types();
+        let layout = contract.layout();
+        let state_types = layout.state_types;
+        let channel_types = layout.channel_types;
 
         // Check that all types match before attempting to create properties
         macro_rules! check_types {
@@ -161,15 +228,40 @@ impl Baseline {
             }};
         }
 
+        let state_info = layout.state_info;
+
+        // Fill in any omitted states with their defaults
+        let mut states: Vec<DynTpProperty> = states.collect();
+        for (i, info) in state_info.iter().enumerate().skip(states.len()) {
+            let default = info.default.clone().ok_or_else(|| {
+                eyre!("State at field index {} was omitted but has no default", i)
+            })?;
+            states.push(default);
+        }
+        let states = states.into_iter();
+
         let states: Vec<DynTpProperty> = check_types!(states, state_types)?;
         let channels: Vec<DynChannel> = check_types!(channels, channel_types)?;
 
+        for (i, (s, info)) in states.iter().zip(state_info).enumerate() {
+            info.check(DynTpPropertyRef::from(s))
+                .wrap_err_with(|| format!("State at field index {} was invalid", i))?;
+        }
+
         // actually do the creation
         let mut state_handles: Vec<arena::generational_arena::Index> = Vec::new();
         let mut channel_handles: Vec<arena::generational_arena::Index> = Vec::new();
+        let mut dyn_state_handles: Vec<DynStateHandle> = Vec::new();
 
-        for s in states {
-            apply_to_prop!(s, |s| state_handles.push(self.state_create(s).into()));
+        for (s, info) in states.into_iter().zip(state_info) {
+            apply_to_prop!(s, |s| {
+                let handle = self.state_create(s);
+                if info.is_constrained() {
+                    self.state_info.insert(handle.into(), info);
+                }
+                dyn_state_handles.push(handle.into());
+                state_handles.push(handle.into())
+            });
         }
         for c in channels {
             apply_to_channel!(c, |c| channel_handles.push(self.channel_create(c).into()));
@@ -178,32 +270,72 @@ impl Baseline {
         let object = Object::new(
             state_handles,
             channel_handles,
-            contract.handle(),
+            contract_handle,
             TimeWarp::default(),
         );
         let obj_handle = self.objects.insert(object);
+        for handle in dyn_state_handles {
+            self.state_objects.insert(handle, obj_handle);
+        }
         self.contracts
-            .get_mut(contract.handle())
+            .get_mut(contract_handle)
             .expect("We already checked this")
             .objects_mut()
             .insert(obj_handle);
+        if self.spatial.is_tracked(contract_handle) {
+            self.with_spatial(|spatial, baseline| spatial.insert_object(baseline, obj_handle))
+                .expect("The object was just created with all of its states");
+        }
         Ok(obj_handle)
     }
 
+    /// Removes `obj`, along with all of its descendants.
     pub fn object_remove<C: Contract>(&mut self, obj: ObjectHandle) -> Result<()> {
-        let o = if let Some(o) = self.objects.remove(obj) {
-            o
-        } else {
+        self.object_remove_dyn(obj)
+    }
+
+    /// Like [`Self::object_remove`], but the fields of the object are looked
+    /// up from its contract, so this works for objects of any contract.
+    pub fn object_remove_dyn(&mut self, obj: ObjectHandle) -> Result<()> {
+        if self.objects.get(obj).is_none() {
             return Err(eyre!("Object did not exist, so it could not be removed"));
-        };
+        }
+        // Reversing the preorder removes children before their parents, without
+        // recursing, so that deep hierarchies can't overflow the stack.
+        let objs: Vec<ObjectHandle> = std::iter::once(obj)
+            .chain(self.iter_descendants(obj))
+            .collect();
+        for obj in objs.into_iter().rev() {
+            self.object_remove_one(obj)?;
+        }
+        Ok(())
+    }
+
+    /// Removes `obj`, which must exist and have no children.
+    fn object_remove_one(&mut self, obj: ObjectHandle) -> Result<()> {
+        debug_assert!(self.objects[obj].children().is_empty());
+        self.object_detach(obj);
+        self.spatial.remove_object(obj);
+        let o = self.objects.remove(obj).expect("We already checked this");
 
         // remove all fields of the object
-        let states = StatesIter::<C::States>::new(o.contract());
-        let channels = ChannelsIter::<C::Channels>::new(o.contract());
+        let c_data = self
+            .contracts
+            .get_mut(o.contract())
+            .expect("Objects can't outlive their contract");
+        c_data.objects_mut().remove(&obj);
+        let layout = *c_data.layout();
+        for prefab in self.prefabs.values_mut() {
+            prefab.instances_mut().remove(&obj);
+        }
+        let states = layout.state_iter(o.contract());
+        let channels = layout.chan_iter(o.contract());
 
         for s in states {
             apply_to_state_id!(s, |id| {
-                let handle = self.bind_state(id, obj)?;
+                let handle = o.bind_state(id)?;
+                self.state_info.remove(&DynStateHandle::from(handle));
+                self.state_objects.remove(&DynStateHandle::from(handle));
                 if let Err(e) = self.state_remove(handle) {
                     log::warn!("Failed to remove state, state has been leaked: {}", e);
                 }
@@ -213,7 +345,7 @@ impl Baseline {
 
         for c in channels {
             apply_to_channel_id!(c, |id| {
-                let handle = self.bind_channel(id, obj)?;
+                let handle = o.bind_channel(id)?;
                 if let Err(e) = self.channel_remove(handle) {
                     log::warn!("Failed to remove channel, channel has been leaked: {}", e);
                 }
@@ -224,8 +356,305 @@ impl Baseline {
         Ok(())
     }
 
+    // ---- Hierarchy ----
+
+    /// Moves `obj` to be the child of `parent` at index `idx` of its children,
+    /// or its last child if `idx` is `None`. If `parent` is `None`, `obj`
+    /// becomes a root, and `idx` must be 0 or `None`.
+    ///
+    /// Returns the previous parent of `obj` and its index in that parent's
+    /// children (0 for roots), which can be passed back to undo the move.
+    ///
+    /// # Errors
+    /// Will error if either object doesn't exist, if `parent` is `obj` or one
+    /// of its descendants, or if `idx` is past the end of the children.
+    pub fn object_reparent(
+        &mut self,
+        obj: ObjectHandle,
+        parent: Option<ObjectHandle>,
+        idx: Option<usize>,
+    ) -> Result<(Option<ObjectHandle>, usize)> {
+        let old_parent = self.object(obj)?.parent();
+        let len = match parent {
+            Some(parent) => {
+                let p = self.object(parent)?;
+                if parent == obj || self.iter_ancestors(parent).any(|a| a == obj) {
+                    return Err(eyre!("An object can't be a descendant of itself"));
+                }
+                p.children().iter().filter(|c| **c != obj).count()
+            }
+            None => 0,
+        };
+        let idx = idx.unwrap_or(len);
+        if idx > len {
+            return Err(eyre!(
+                "Index {} is past the end of the {} other children",
+                idx,
+                len
+            ));
+        }
+
+        let old_idx = self.object_detach(obj);
+        self.objects[obj].set_parent(parent);
+        if let Some(parent) = parent {
+            self.objects[parent].children_mut().insert(idx, obj);
+        }
+        Ok((old_parent, old_idx))
+    }
+
+    /// Removes `obj` from the children of its parent, if it has one, and
+    /// returns the index that it had (0 for roots).
+    fn object_detach(&mut self, obj: ObjectHandle) -> usize {
+        let Some(parent) = self.objects[obj].parent() else {
+            return 0;
+        };
+        self.objects[obj].set_parent(None);
+        let children = self.objects[parent].children_mut();
+        let idx = children
+            .iter()
+            .position(|c| *c == obj)
+            .expect("Objects are children of their parent");
+        children.remove(idx);
+        idx
+    }
+
+    /// The objects that have no parent, in no particular order.
+    pub fn iter_roots(&self) -> impl Iterator<Item = ObjectHandle> + '_ {
+        self.objects
+            .iter()
+            .filter(|(_, o)| o.parent().is_none())
+            .map(|(handle, _)| handle)
+    }
+
+    /// The parent of `obj`, then its parent, and so on up to the root. Empty
+    /// if `obj` doesn't exist.
+    pub fn iter_ancestors(&self, obj: ObjectHandle) -> impl Iterator<Item = ObjectHandle> + '_ {
+        let parent = self.objects.get(obj).and_then(Object::parent);
+        std::iter::successors(parent, move |o| self.objects[*o].parent())
+    }
+
+    /// All of the descendants of `obj`, depth first, with each object followed
+    /// by its children in order. Empty if `obj` doesn't exist.
+    pub fn iter_descendants(&self, obj: ObjectHandle) -> impl Iterator<Item = ObjectHandle> + '_ {
+        let mut stack: Vec<ObjectHandle> = self
+            .objects
+            .get(obj)
+            .map(|o| o.children().iter().rev().copied().collect())
+            .unwrap_or_default();
+        std::iter::from_fn(move || {
+            let next = stack.pop()?;
+            stack.extend(self.objects[next].children().iter().rev());
+            Some(next)
+        })
+    }
+
+    // ---- Prefabs ----
+
+    /// Adds `prefab` to the baseline, so that it can be instantiated.
+    ///
+    /// # Errors
+    /// Will error if the contract of `prefab` isn't registered, or if there is
+    /// already a prefab with the same name.
+    pub fn prefab_insert(&mut self, mut prefab: Prefab) -> Result<()> {
+        let contract = prefab.contract();
+        match self.contracts.get(contract.handle()) {
+            Some(c_data) if c_data.id() == contract.id() => (),
+            _ => return Err(eyre!("The contract of the prefab isn't registered")),
+        }
+        if self.prefabs.contains_key(prefab.name()) {
+            return Err(eyre!("There is already a prefab named `{}`", prefab.name()));
+        }
+        prefab.instances_mut().clear();
+        self.prefabs.insert(prefab.name().to_owned(), prefab);
+        Ok(())
+    }
+
+    /// Removes the prefab named `name`. Its instances are kept, but are no
+    /// longer instances of any prefab.
+    pub fn prefab_remove(&mut self, name: &str) -> Result<Prefab> {
+        self.prefabs
+            .remove(name)
+            .ok_or_else(|| eyre!("There is no prefab named `{}`", name))
+    }
+
+    pub fn prefab(&self, name: &str) -> Result<&Prefab> {
+        self.prefabs
+            .get(name)
+            .ok_or_else(|| eyre!("There is no prefab named `{}`", name))
+    }
+
+    pub fn iter_prefabs(&self) -> impl Iterator<Item = &Prefab> {
+        self.prefabs.values()
+    }
+
+    /// The prefab that `obj` was instantiated from, if any.
+    pub fn object_prefab(&self, obj: ObjectHandle) -> Option<&Prefab> {
+        self.prefabs.values().find(|p| p.instances().contains(&obj))
+    }
+
+    /// Creates an object from the prefab named `prefab`, with the values of
+    /// its fields in `overrides` replaced. See [`Self::object_create`].
+    ///
+    /// # Errors
+    /// Will error if there is no such prefab, or if any of `overrides` are
+    /// invalid.
+    pub fn object_instantiate(
+        &mut self,
+        prefab: &str,
+        overrides: Overrides,
+    ) -> Result<ObjectHandle> {
+        let p = self.prefab(prefab)?;
+        let contract = p.contract();
+        let (states, channels) = p.values(overrides)?;
+        let obj = self.object_create(&contract, states.into_iter(), channels.into_iter())?;
+        self.prefabs
+            .get_mut(prefab)
+            .expect("We already checked this")
+            .instances_mut()
+            .insert(obj);
+        Ok(obj)
+    }
+
+    /// Replaces the values of the fields in `edits` in the prefab named
+    /// `name`, and in each of its instances that still had the prefab's
+    /// previous value for the field. Instances that changed a field, whether
+    /// when they were instantiated or since, keep their value. Readonly states
+    /// are only set when objects are created, so they are never propagated.
+    ///
+    /// Like writing [`State::value`] directly, this doesn't go through the
+    /// `Engine`, and doesn't change the versions of states.
+    ///
+    /// Returns the instances that were changed, in no particular order.
+    ///
+    /// # Errors
+    /// Will error if there is no such prefab, or if any of `edits` are invalid,
+    /// in which case nothing is changed.
+    pub fn prefab_update(&mut self, name: &str, edits: Overrides) -> Result<Vec<ObjectHandle>> {
+        let prefab = self
+            .prefabs
+            .get_mut(name)
+            .ok_or_else(|| eyre!("There is no prefab named `{}`", name))?;
+        let (state_edits, channel_edits) = prefab.edit(edits)?;
+        let contract = prefab.contract();
+        let instances: Vec<ObjectHandle> = prefab.instances().iter().copied().collect();
+        let state_info = contract.layout().state_info;
+
+        let mut updated = Vec::new();
+        for obj in instances {
+            let mut changed = false;
+            for edit in &state_edits {
+                if state_info[edit.idx].readonly {
+                    continue;
+                }
+                let id = contract
+                    .state_id(edit.idx)
+                    .expect("Edits are of the contract");
+                changed |= apply_to_state_id!(id, |id: StateId<_>| -> Result<bool> {
+                    let (mut old, mut new) = (edit.old.clone(), edit.new.clone());
+                    let handle = self.bind_state(id, obj)?;
+                    let state = self.state_mut(handle)?;
+                    if old.cast_mut() != Some(&mut state.value) {
+                        return Ok(false);
+                    }
+                    mem::swap(
+                        &mut state.value,
+                        new.cast_mut().expect("Types were checked"),
+                    );
+                    Ok(true)
+                })?;
+            }
+            for edit in &channel_edits {
+                let id = contract
+                    .chan_id(edit.idx)
+                    .expect("Edits are of the contract");
+                changed |= apply_to_channel_id!(id, |id: ChannelId<_>| -> Result<bool> {
+                    let (mut old, mut new) = (edit.old.clone(), edit.new.clone());
+                    let handle = self.bind_channel(id, obj)?;
+                    let channel = self.channel_mut(handle)?;
+                    if old.cast_mut() != Some(&mut *channel) {
+                        return Ok(false);
+                    }
+                    mem::swap(channel, new.cast_mut().expect("Types were checked"));
+                    Ok(true)
+                })?;
+            }
+            if changed {
+                updated.push(obj);
+            }
+        }
+        // Edits may have moved the instances
+        for &obj in &updated {
+            if self.spatial.contains(obj) {
+                self.with_spatial(|spatial, baseline| spatial.insert_object(baseline, obj))?;
+            }
+        }
+        Ok(updated)
+    }
+
+    // ---- Spatial index ----
+
+    /// The spatial index over the objects of the contracts that were tracked
+    /// with [`Self::spatial_track_contract()`]. Objects are added to and
+    /// removed from it as they are created, instantiated and removed.
+    pub fn spatial_index(&self) -> &SpatialIndex {
+        &self.spatial
+    }
+
+    /// Positions that are written directly to the baseline must be reported
+    /// to the index with [`SpatialIndex::on_state_write()`]. The [`Engine`]
+    /// does this for the writes it applies.
+    ///
+    /// [`Engine`]: crate::Engine
+    pub fn spatial_index_mut(&mut self) -> &mut SpatialIndex {
+        &mut self.spatial
+    }
+
+    /// Starts indexing the objects of `contract` by their position.
+    ///
+    /// # Errors
+    /// Errors if `contract` has no position states, or isn't in this baseline.
+    pub fn spatial_track_contract<C: Contract>(&mut self, contract: &C) -> Result<()> {
+        self.with_spatial(|spatial, baseline| spatial.track_contract(baseline, contract))
+    }
+
+    /// Like [`Self::spatial_track_contract()`], for contracts that can't
+    /// declare their position states.
+    ///
+    /// # Errors
+    /// Errors if `contract` isn't in this baseline, or `position` isn't made up
+    /// of its states.
+    pub fn spatial_track_positions(
+        &mut self,
+        contract: ContractDataHandle,
+        position: PositionStates,
+    ) -> Result<()> {
+        self.with_spatial(|spatial, baseline| spatial.track_positions(baseline, contract, position))
+    }
+
+    /// Runs `f` with the spatial index taken out of the baseline, so that it
+    /// can read the rest of the baseline while updating the index.
+    fn with_spatial<R>(&mut self, f: impl FnOnce(&mut SpatialIndex, &Self) -> R) -> R {
+        let mut spatial = mem::take(&mut self.spatial);
+        let result = f(&mut spatial, self);
+        self.spatial = spatial;
+        result
+    }
+
     // ---- Property accessors ----
 
+    /// Gets the field info of `state`, if its field has any constraints.
+    pub fn state_constraints(
+        &self,
+        state: impl Into<DynStateHandle>,
+    ) -> Option<&'static FieldInfo> {
+        self.state_info.get(&state.into()).copied()
+    }
+
+    /// Gets the object that `state` belongs to.
+    pub fn state_object(&self, state: impl Into<DynStateHandle>) -> Option<ObjectHandle> {
+        self.state_objects.get(&state.into()).copied()
+    }
+
     pub fn state<H: IStateHandle>(&self, state: H) -> Result<H::OutputRef<'_>> {
         state.get(self)
     }
@@ -234,6 +663,32 @@ impl Baseline {
         state.get_mut(self)
     }
 
+    /// Gets the version of `state`, see [`State::version`]. Useful when the
+    /// type of the state isn't known statically.
+    pub fn state_version(&self, state: impl Into<DynStateHandle>) -> Result<u64> {
+        apply_to_state_handle!(state.into(), |h: StateHandle<_>| self
+            .state(h)
+            .map(|s| s.version))
+    }
+
+    pub(crate) fn set_state_version(&mut self, state: DynStateHandle, version: u64) -> Result<()> {
+        apply_to_state_handle!(state, |h: StateHandle<_>| -> Result<()> {
+            self.state_mut(h)?.version = version;
+            Ok(())
+        })
+    }
+
+    /// Merges the CRDT `delta` into `state`, see
+    /// [`crdt`](crate::contract::properties::crdt). Returns the previous value.
+    ///
+    /// This doesn't change the version of the state, so that baselines can
+    /// merge in deltas that the `Engine` already applied elsewhere.
+    pub fn state_merge(&mut self, state: StateHandle<Bytes>, delta: &[u8]) -> Result<Bytes> {
+        let state = self.state_mut(state)?;
+        let merged = crdt::merge_bytes(&state.value, delta)?;
+        Ok(mem::replace(&mut state.value, merged))
+    }
+
     pub fn channel<T: ITpPropertyStatic>(&self, chan: ChannelHandle<T>) -> Result<&Channel<T>> {
         let arena = self
             .channels
@@ -379,10 +834,13 @@ pub mod c_api {
 
     use super::*;
     use crate::contract::c_api::ContractDataHandle as CContractDataHandle;
+    use crate::contract::properties::bytes::Bytes;
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     use crate::contract::properties::primitives;
     use crate::object::c_api::ObjectHandle as CObjectHandle;
 
     use rsharp::remangle;
+    use rsharp::result::RStatus;
     use safer_ffi::prelude::*;
 
     #[remangle(substitute!())]
@@ -429,6 +887,33 @@ pub mod c_api {
         baseline.object_mut(handle.inner).unwrap()
     }
 
+    /// Moves `obj` to the end of the children of `parent`. `parent` may be
+    /// null, to make `obj` a root.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Baseline__object_reparent(
+        b: &mut Baseline,
+        obj: &CObjectHandle,
+        parent: Option<&CObjectHandle>,
+    ) -> RStatus {
+        b.object_reparent(obj.inner, parent.map(|p| p.inner), None)?;
+        Ok(())
+    }
+
+    /// Like `Baseline__object_reparent()`, but moves `obj` to index `idx` of
+    /// the children of `parent`, like Unity's `Transform.SetSiblingIndex()`.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Baseline__object_reparent_at(
+        b: &mut Baseline,
+        obj: &CObjectHandle,
+        parent: Option<&CObjectHandle>,
+        idx: usize,
+    ) -> RStatus {
+        b.object_reparent(obj.inner, parent.map(|p| p.inner), Some(idx))?;
+        Ok(())
+    }
+
     macro_rules! monomorphize {
         // Base case
         ($path:literal, $t:ty $(,)?) => {
@@ -518,4 +1003,89 @@ pub mod c_api {
 
     // This is like doing `monomorphize!("whatever", Keyframe, u8, u16, ...)
     primitives!(; types, monomorphize, "tp_client::baseline");
+
+    /// Same as `monomorphize`, but for map states
+    macro_rules! monomorphize_map {
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_Baseline_Map $t:camel>] {
+                    use super::*;
+                    use crate::contract::properties::traits::TpMap;
+
+                    use crate::contract::properties::states::c_api::[<StateHandle_Map $t:camel>] as Monomorphized_StateHandle;
+                    use crate::contract::properties::states::c_api::[<State_Map $t:camel>] as Monomorphized_State;
+                    use crate::contract::properties::states::c_api::[<StateId_Map $t:camel>] as Monomorphized_StateId;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Baseline__state_Map $t:camel>]<'a>(
+                        b: &'a Baseline,
+                        state: &Monomorphized_StateHandle
+                    ) -> &'a Monomorphized_State {
+                        let s: &'a State<TpMap<$t>> = b.state(state.inner).unwrap();
+                        s.into()
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Baseline__bind_state_Map $t:camel>](
+                        b: &Baseline,
+                        id: &Monomorphized_StateId,
+                        obj: &CObjectHandle,
+                    ) -> repr_c::Box<Monomorphized_StateHandle> {
+                        let h: StateHandle<TpMap<$t>> = b.bind_state(id.inner, obj.inner).unwrap();
+                        Box::new(Monomorphized_StateHandle::from(h)).into()
+                    }
+                }
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_map!($path, $first_t);
+            monomorphize_map!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_map, "tp_client::baseline");
+
+    /// Same as `monomorphize`, but for option states
+    macro_rules! monomorphize_option {
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_Baseline_Option $t:camel>] {
+                    use super::*;
+
+                    use crate::contract::properties::states::c_api::[<StateHandle_Option $t:camel>] as Monomorphized_StateHandle;
+                    use crate::contract::properties::states::c_api::[<State_Option $t:camel>] as Monomorphized_State;
+                    use crate::contract::properties::states::c_api::[<StateId_Option $t:camel>] as Monomorphized_StateId;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Baseline__state_Option $t:camel>]<'a>(
+                        b: &'a Baseline,
+                        state: &Monomorphized_StateHandle
+                    ) -> &'a Monomorphized_State {
+                        let s: &'a State<Option<$t>> = b.state(state.inner).unwrap();
+                        s.into()
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Baseline__bind_state_Option $t:camel>](
+                        b: &Baseline,
+                        id: &Monomorphized_StateId,
+                        obj: &CObjectHandle,
+                    ) -> repr_c::Box<Monomorphized_StateHandle> {
+                        let h: StateHandle<Option<$t>> = b.bind_state(id.inner, obj.inner).unwrap();
+                        Box::new(Monomorphized_StateHandle::from(h)).into()
+                    }
+                }
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_option!($path, $first_t);
+            monomorphize_option!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_option, "tp_client::baseline");
 }
@@ -0,0 +1,371 @@
+//! Contracts whose fields are only known at runtime, such as ones loaded from
+//! data or declared by a scripting language.
+
+use crate::contract::properties::channels::DynChannelId;
+use crate::contract::properties::dynamic::TpPropertyType;
+use crate::contract::properties::field::FieldInfo;
+use crate::contract::properties::states::DynStateId;
+use crate::contract::{ContractDataHandle, ContractId, ContractLayout, IContract};
+
+use eyre::{eyre, Result, WrapErr};
+use std::collections::HashSet;
+use std::ops::Bound;
+use std::sync::Mutex;
+
+/// A field of a [`ContractSchema`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    pub name: String,
+    pub prop_type: TpPropertyType,
+    pub info: FieldInfo,
+}
+impl FieldSchema {
+    pub fn new(name: impl Into<String>, prop_type: TpPropertyType) -> Self {
+        Self {
+            name: name.into(),
+            prop_type,
+            info: FieldInfo::default(),
+        }
+    }
+}
+
+/// Describes the fields of a contract at runtime. Registering it with
+/// [`Baseline::register_dyn_contract`](crate::baseline::Baseline::register_dyn_contract)
+/// gives a [`DynContract`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractSchema {
+    pub name: String,
+    pub version: (u16, u16, u16),
+    pub states: Vec<FieldSchema>,
+    pub channels: Vec<FieldSchema>,
+}
+impl ContractSchema {
+    pub fn new(name: impl Into<String>, version: (u16, u16, u16)) -> Self {
+        Self {
+            name: name.into(),
+            version,
+            states: Vec::new(),
+            channels: Vec::new(),
+        }
+    }
+
+    pub fn with_state(mut self, name: impl Into<String>, prop_type: TpPropertyType) -> Self {
+        self.states.push(FieldSchema::new(name, prop_type));
+        self
+    }
+
+    pub fn with_channel(mut self, name: impl Into<String>, prop_type: TpPropertyType) -> Self {
+        self.channels.push(FieldSchema::new(name, prop_type));
+        self
+    }
+
+    /// Checks that the names of the fields are unique, that the defaults
+    /// have the type of their field and satisfy its range, and that the
+    /// attributes are ones that `#[states]` and `#[channels]` would accept.
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(eyre!("The contract must have a name"));
+        }
+        for (kind, fields) in [("state", &self.states), ("channel", &self.channels)] {
+            let mut names = HashSet::new();
+            for f in fields {
+                if !names.insert(f.name.as_str()) {
+                    return Err(eyre!("There is more than one {} named `{}`", kind, f.name));
+                }
+            }
+        }
+        for f in &self.states {
+            if let Some(range) = &f.info.range {
+                if !matches!(f.prop_type, TpPropertyType::Primitive(pt) if pt.is_numeric()) {
+                    return Err(eyre!(
+                        "State `{}` can't have a range, because it is not a numeric primitive",
+                        f.name
+                    ));
+                }
+                if range.start == Bound::Unbounded && range.end == Bound::Unbounded {
+                    return Err(eyre!(
+                        "The range of state `{}` must have at least one bound",
+                        f.name
+                    ));
+                }
+            }
+            if let Some(default) = &f.info.default {
+                if default.prop_type() != f.prop_type {
+                    return Err(eyre!(
+                        "The default of state `{}` was not a {}",
+                        f.name,
+                        f.prop_type
+                    ));
+                }
+                f.info
+                    .check(default.into())
+                    .wrap_err_with(|| format!("The default of state `{}` was invalid", f.name))?;
+            }
+        }
+        for f in &self.channels {
+            if f.info.default.is_some() {
+                return Err(eyre!("Channel `{}` can't have a default", f.name));
+            }
+            if f.info.range.is_some() {
+                return Err(eyre!("Channel `{}` can't have a range", f.name));
+            }
+            if f.info.readonly {
+                return Err(eyre!("Channel `{}` can't be readonly", f.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validates the schema, and turns it into a [`ContractLayout`].
+    ///
+    /// Layouts are `'static`, so this leaks the schema. Equal schemas share
+    /// their layout, so only the first call for each schema allocates.
+    pub fn layout(&self) -> Result<ContractLayout> {
+        static LAYOUTS: Mutex<Vec<(ContractSchema, ContractLayout)>> = Mutex::new(Vec::new());
+
+        self.validate()?;
+        let mut layouts = LAYOUTS.lock().expect("Poisoned lock");
+        if let Some((_, layout)) = layouts.iter().find(|(s, _)| s == self) {
+            return Ok(*layout);
+        }
+
+        fn leak<T>(v: Vec<T>) -> &'static [T] {
+            Box::leak(v.into_boxed_slice())
+        }
+        fn names(fields: &[FieldSchema]) -> &'static [&'static str] {
+            leak(
+                fields
+                    .iter()
+                    .map(|f| &*Box::leak(f.name.clone().into_boxed_str()))
+                    .collect(),
+            )
+        }
+        let layout = ContractLayout {
+            id: ContractId {
+                name: Box::leak(self.name.clone().into_boxed_str()),
+                version: self.version,
+            },
+            state_names: names(&self.states),
+            state_types: leak(self.states.iter().map(|f| f.prop_type).collect()),
+            state_info: leak(self.states.iter().map(|f| f.info.clone()).collect()),
+            channel_names: names(&self.channels),
+            channel_types: leak(self.channels.iter().map(|f| f.prop_type).collect()),
+            channel_info: leak(self.channels.iter().map(|f| f.info.clone()).collect()),
+        };
+        layouts.push((self.clone(), layout));
+        Ok(layout)
+    }
+}
+impl From<&ContractLayout> for ContractSchema {
+    fn from(layout: &ContractLayout) -> Self {
+        fn fields(
+            names: &[&str],
+            types: &[TpPropertyType],
+            info: &[FieldInfo],
+        ) -> Vec<FieldSchema> {
+            names
+                .iter()
+                .zip(types)
+                .zip(info)
+                .map(|((name, prop_type), info)| FieldSchema {
+                    name: name.to_string(),
+                    prop_type: *prop_type,
+                    info: info.clone(),
+                })
+                .collect()
+        }
+        Self {
+            name: layout.id.name.to_string(),
+            version: layout.id.version,
+            states: fields(layout.state_names, layout.state_types, layout.state_info),
+            channels: fields(
+                layout.channel_names,
+                layout.channel_types,
+                layout.channel_info,
+            ),
+        }
+    }
+}
+
+/// A contract whose fields are only known at runtime. Can be used anywhere
+/// that a [`Contract`](crate::contract::Contract) can, through [`IContract`].
+///
+/// Compile time contracts can be converted with [`DynContract::from_contract`],
+/// which is useful for handling all contracts the same way.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DynContract {
+    handle: ContractDataHandle,
+    layout: ContractLayout,
+}
+impl DynContract {
+    pub(crate) fn new(handle: ContractDataHandle, layout: ContractLayout) -> Self {
+        Self { handle, layout }
+    }
+
+    pub fn from_contract(contract: &impl IContract) -> Self {
+        Self::new(contract.contract_handle(), contract.layout())
+    }
+
+    pub fn handle(&self) -> ContractDataHandle {
+        self.handle
+    }
+
+    pub fn id(&self) -> ContractId {
+        self.layout.id
+    }
+
+    /// Returns `None` if there is no state at that index.
+    pub fn state_id(&self, idx: usize) -> Option<DynStateId> {
+        self.layout.state_id(self.handle, idx)
+    }
+
+    /// Returns `None` if there is no channel at that index.
+    pub fn chan_id(&self, idx: usize) -> Option<DynChannelId> {
+        self.layout.chan_id(self.handle, idx)
+    }
+
+    /// The index of the state named `name`.
+    pub fn state_idx(&self, name: &str) -> Option<usize> {
+        self.layout.state_names.iter().position(|n| *n == name)
+    }
+
+    /// The index of the channel named `name`.
+    pub fn chan_idx(&self, name: &str) -> Option<usize> {
+        self.layout.channel_names.iter().position(|n| *n == name)
+    }
+}
+impl IContract for DynContract {
+    fn contract_handle(&self) -> ContractDataHandle {
+        self.handle
+    }
+
+    fn layout(&self) -> ContractLayout {
+        self.layout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::{Baseline, BaselineKind};
+    use crate::contract::properties::channels::{Channel, DynChannel, Keyframe};
+    use crate::contract::properties::dynamic::{DynTpProperty, TpPrimitiveType};
+    use crate::contract::properties::field::FieldRange;
+    use crate::contract::properties::states::StateId;
+
+    fn schema() -> ContractSchema {
+        let mut schema = ContractSchema::new("teleportal.test.dyn", (1, 0, 0))
+            .with_state("name", TpPropertyType::Primitive(TpPrimitiveType::String))
+            .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8))
+            .with_channel("x", TpPropertyType::Primitive(TpPrimitiveType::F32));
+        schema.states[1].info = FieldInfo {
+            default: Some(DynTpProperty::from(10u8)),
+            range: Some(FieldRange {
+                start: Bound::Included(0.0),
+                end: Bound::Included(100.0),
+            }),
+            ..Default::default()
+        };
+        schema
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(schema().validate().is_ok());
+
+        let dup = schema().with_state("name", TpPropertyType::Primitive(TpPrimitiveType::U8));
+        assert!(dup.validate().is_err());
+
+        let mut wrong_default = schema();
+        wrong_default.states[1].info.default = Some(DynTpProperty::from(1.0f32));
+        assert!(wrong_default.validate().is_err());
+
+        let mut out_of_range = schema();
+        out_of_range.states[1].info.default = Some(DynTpProperty::from(200u8));
+        assert!(out_of_range.validate().is_err());
+
+        let mut non_numeric_range = schema();
+        non_numeric_range.states[0].info.range = schema().states[1].info.range;
+        assert!(non_numeric_range.validate().is_err());
+
+        let mut unbounded_range = schema();
+        unbounded_range.states[1].info.range = Some(FieldRange {
+            start: Bound::Unbounded,
+            end: Bound::Unbounded,
+        });
+        assert!(unbounded_range.validate().is_err());
+
+        let mut channel_range = schema();
+        channel_range.channels[0].info.range = schema().states[1].info.range;
+        assert!(channel_range.validate().is_err());
+
+        let mut channel_readonly = schema();
+        channel_readonly.channels[0].info.readonly = true;
+        assert!(channel_readonly.validate().is_err());
+
+        let mut channel_default = schema();
+        channel_default.channels[0].info.default = Some(DynTpProperty::from(1.0f32));
+        assert!(channel_default.validate().is_err());
+    }
+
+    #[test]
+    fn test_layout_is_interned() {
+        let a = schema().layout().unwrap();
+        let b = schema().layout().unwrap();
+        assert!(std::ptr::eq(a.state_names, b.state_names));
+        assert_eq!(a.id.name, "teleportal.test.dyn");
+        assert_eq!(ContractSchema::from(&a), schema());
+
+        let other = ContractSchema::new("teleportal.test.dyn", (2, 0, 0));
+        assert_ne!(other.layout().unwrap().id, a.id);
+    }
+
+    #[test]
+    fn test_baseline() {
+        let mut baseline = Baseline::new(BaselineKind::Main);
+        let c = baseline.register_dyn_contract(&schema()).unwrap();
+        assert!(baseline.register_dyn_contract(&schema()).is_err());
+        assert_eq!(baseline.contract_data(c.handle()).unwrap().id(), c.id());
+        assert_eq!(baseline.iter_contracts().collect::<Vec<_>>(), [c]);
+
+        let channel = DynChannel::from(Channel::new([Keyframe::new(1.0f32, 0.0)].into_iter()));
+        let obj = baseline
+            .object_create(
+                &c,
+                [DynTpProperty::from(String::from("bob"))].into_iter(),
+                [channel].into_iter(),
+            )
+            .unwrap();
+
+        let idx = c.state_idx("hp").unwrap();
+        assert!(c.state_id(idx).is_some());
+        assert!(c.state_id(2).is_none());
+        let hp = baseline
+            .bind_state(StateId::<u8>::new(idx, c.handle()), obj)
+            .unwrap();
+        assert_eq!(baseline.state(hp).unwrap().value, 10);
+        assert!(baseline.state_constraints(hp).is_some());
+
+        // Out of range
+        assert!(baseline
+            .object_create(
+                &c,
+                [
+                    DynTpProperty::from(String::new()),
+                    DynTpProperty::from(101u8)
+                ]
+                .into_iter(),
+                [].into_iter(),
+            )
+            .is_err());
+
+        baseline.object_remove_dyn(obj).unwrap();
+        assert!(baseline
+            .contract_data(c.handle())
+            .unwrap()
+            .objects()
+            .is_empty());
+        baseline.unregister_dyn_contract(&c).unwrap();
+        assert!(baseline.contract_data(c.handle()).is_err());
+    }
+}
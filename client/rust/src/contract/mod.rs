@@ -1,9 +1,15 @@
-pub use tp_contract_macro::{channels, states};
+pub use tp_contract_macro::{channels, states, Composite};
+pub mod dyn_contract;
 pub mod properties;
 
+pub use self::dyn_contract::{ContractSchema, DynContract, FieldSchema};
+
 use crate::contract::properties::channels::{ChannelsIter, DynChannelId, IChannels};
+use crate::contract::properties::dynamic::TpPropertyType;
+use crate::contract::properties::field::FieldInfo;
 use crate::contract::properties::states::{DynStateId, IStates, StatesIter};
 use crate::object::ObjectHandle;
+use crate::spatial::PositionStates;
 
 #[cfg(feature = "c_api")]
 use safer_ffi::derive_ReprC;
@@ -69,6 +75,84 @@ pub trait Contract {
             .copied()
             .map(|prop_type| DynChannelId::new(self.handle(), idx, prop_type))
     }
+
+    /// The states that describe the position of this contract's objects, if
+    /// any. Used by [`SpatialIndex`](crate::spatial::SpatialIndex).
+    fn position(&self) -> Option<PositionStates> {
+        None
+    }
+}
+
+/// The fields of a contract. Compile time contracts get theirs from
+/// [`ContractLayout::of`], and runtime ones from a [`ContractSchema`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContractLayout {
+    pub id: ContractId,
+    pub state_names: &'static [&'static str],
+    pub state_types: &'static [TpPropertyType],
+    pub state_info: &'static [FieldInfo],
+    pub channel_names: &'static [&'static str],
+    pub channel_types: &'static [TpPropertyType],
+    pub channel_info: &'static [FieldInfo],
+}
+impl ContractLayout {
+    pub fn of<C: Contract>() -> Self {
+        Self {
+            id: C::ID,
+            state_names: C::States::field_names(),
+            state_types: C::States::enumerate_types(),
+            state_info: C::States::field_info(),
+            channel_names: C::Channels::field_names(),
+            channel_types: C::Channels::enumerate_types(),
+            channel_info: C::Channels::field_info(),
+        }
+    }
+
+    /// Returns `None` if there is no state at that index.
+    pub fn state_id(&self, contract: ContractDataHandle, idx: usize) -> Option<DynStateId> {
+        self.state_types
+            .get(idx)
+            .map(|prop_type| DynStateId::new(contract, idx, *prop_type))
+    }
+
+    /// Returns `None` if there is no channel at that index.
+    pub fn chan_id(&self, contract: ContractDataHandle, idx: usize) -> Option<DynChannelId> {
+        self.channel_types
+            .get(idx)
+            .map(|prop_type| DynChannelId::new(contract, idx, *prop_type))
+    }
+
+    pub fn state_iter(
+        &self,
+        contract: ContractDataHandle,
+    ) -> impl Iterator<Item = DynStateId> + 'static {
+        let types = self.state_types;
+        (0..types.len()).map(move |idx| DynStateId::new(contract, idx, types[idx]))
+    }
+
+    pub fn chan_iter(
+        &self,
+        contract: ContractDataHandle,
+    ) -> impl Iterator<Item = DynChannelId> + 'static {
+        let types = self.channel_types;
+        (0..types.len()).map(move |idx| DynChannelId::new(contract, idx, types[idx]))
+    }
+}
+
+/// Anything that objects can be created from: either a [`Contract`], or a
+/// [`DynContract`].
+pub trait IContract {
+    fn contract_handle(&self) -> ContractDataHandle;
+    fn layout(&self) -> ContractLayout;
+}
+impl<C: Contract> IContract for C {
+    fn contract_handle(&self) -> ContractDataHandle {
+        self.handle()
+    }
+
+    fn layout(&self) -> ContractLayout {
+        ContractLayout::of::<C>()
+    }
 }
 
 /// Contains stateful data about the contract
@@ -79,12 +163,14 @@ pub trait Contract {
 )]
 pub struct ContractData {
     id: ContractId,
+    layout: ContractLayout,
     objects: HashSet<ObjectHandle>,
 }
 impl ContractData {
-    pub fn new(id: ContractId) -> Self {
+    pub fn new(layout: ContractLayout) -> Self {
         Self {
-            id,
+            id: layout.id,
+            layout,
             objects: Default::default(),
         }
     }
@@ -92,6 +178,10 @@ impl ContractData {
         self.id
     }
 
+    pub fn layout(&self) -> &ContractLayout {
+        &self.layout
+    }
+
     pub(super) fn objects_mut(&mut self) -> &mut HashSet<ObjectHandle> {
         &mut self.objects
     }
@@ -108,6 +198,7 @@ pub mod c_api {
 
     use super::{ContractData, ContractId};
     use crate::contract::properties::c_api::c_types;
+    use crate::object::ObjectHandle;
 
     use rsharp::remangle;
     use safer_ffi::prelude::*;
@@ -147,14 +238,43 @@ pub mod c_api {
 
     #[remangle(substitute!())]
     #[ffi_export]
-    pub fn ContractData__objects<'a>(cd: &'a ContractData) -> repr_c::Vec<c_types::ObjectHandle> {
-        // TODO: Avoid allocation/copy
-        let v: Vec<_> = cd
-            .objects
-            .iter()
-            .map(|h| c_types::ObjectHandle::from(*h))
-            .collect();
-        v.into()
+    pub fn ContractData__objects_len(cd: &ContractData) -> usize {
+        cd.objects.len()
+    }
+
+    /// Iterates over the objects of the contract without copying them. The
+    /// iterator borrows `cd`, so `cd` must outlive it.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn ContractData__objects_iter<'a>(
+        cd: &'a ContractData,
+    ) -> repr_c::Box<ContractData_ObjectsIter<'a>> {
+        Box::new(ContractData_ObjectsIter {
+            inner: cd.objects.iter(),
+        })
+        .into()
+    }
+
+    #[remangle(substitute!())]
+    #[derive_ReprC]
+    #[ReprC::opaque]
+    pub struct ContractData_ObjectsIter<'a> {
+        inner: std::collections::hash_set::Iter<'a, ObjectHandle>,
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn ContractData_ObjectsIter__drop(it: repr_c::Box<ContractData_ObjectsIter<'_>>) {
+        drop(it)
+    }
+
+    /// Returns null once there are no more objects.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn ContractData_ObjectsIter__next<'a>(
+        it: &mut ContractData_ObjectsIter<'a>,
+    ) -> Option<&'a c_types::ObjectHandle> {
+        it.inner.next().map(|h| h.into())
     }
 
     #[remangle(substitute!())]
@@ -0,0 +1,284 @@
+//! An opaque binary blob that can be stored in properties.
+//!
+//! Useful for meshes, textures, or any app-specific payload that teleportal
+//! doesn't need to understand. Unlike `Vec<u8>`, [`Bytes`] is a single
+//! primitive, so it is handled as one value everywhere instead of per-element.
+
+use eyre::{eyre, Result};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Deref, Range};
+use std::sync::Arc;
+
+/// An immutable, reference counted binary blob. Cloning is cheap and does not
+/// copy the underlying data.
+///
+/// A blob can be a view into part of a larger buffer, see [`Bytes::slice`].
+/// This is how deserializing borrows the data of a blob from the buffer it was
+/// read from, instead of copying it out.
+///
+/// Mutation is copy-on-write: if the buffer is shared with any other clones, it
+/// will first be copied.
+#[derive(Clone)]
+pub struct Bytes {
+    buf: Arc<[u8]>,
+    /// The part of `buf` that this blob is
+    range: Range<usize>,
+}
+impl Bytes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buf[self.range.clone()]
+    }
+
+    /// A blob of the bytes in `range`, which shares the buffer of `self`
+    /// instead of copying it.
+    ///
+    /// # Panics
+    /// Panics if `range` is out of bounds.
+    pub fn slice(&self, range: Range<usize>) -> Bytes {
+        assert!(
+            range.start <= range.end && range.end <= self.len(),
+            "range {range:?} was out of bounds for {} bytes",
+            self.len()
+        );
+        Self {
+            buf: self.buf.clone(),
+            range: self.range.start + range.start..self.range.start + range.end,
+        }
+    }
+
+    /// Replaces the `len` bytes starting at `offset` with `chunk`, returning the
+    /// bytes that were replaced. If the range extends past the end, it is
+    /// clamped to the end and the blob grows to fit `chunk`.
+    ///
+    /// Errors if `offset` is past the end of the blob.
+    pub fn splice(&mut self, offset: usize, len: usize, chunk: &[u8]) -> Result<Bytes> {
+        if offset > self.len() {
+            return Err(eyre!(
+                "offset {offset} was out of bounds for {} bytes",
+                self.len()
+            ));
+        }
+        let end = offset.saturating_add(len).min(self.len());
+
+        // Avoid reallocating when overwriting in place and we hold the only reference
+        if end - offset == chunk.len() {
+            let start = self.range.start;
+            if let Some(data) = Arc::get_mut(&mut self.buf) {
+                let data = &mut data[start + offset..start + end];
+                let removed = Bytes::from(&*data);
+                data.copy_from_slice(chunk);
+                return Ok(removed);
+            }
+        }
+
+        let mut data = self.as_slice().to_vec();
+        let removed: Vec<u8> = data.splice(offset..end, chunk.iter().copied()).collect();
+        *self = data.into();
+        Ok(removed.into())
+    }
+
+    /// Writes `chunk` starting at `offset`, growing the blob if needed.
+    /// Returns the bytes that were overwritten.
+    ///
+    /// Errors if `offset` is past the end of the blob.
+    pub fn write_chunk(&mut self, offset: usize, chunk: &[u8]) -> Result<Bytes> {
+        self.splice(offset, chunk.len(), chunk)
+    }
+}
+impl Default for Bytes {
+    fn default() -> Self {
+        Self::from(Vec::new())
+    }
+}
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+impl AsRef<[u8]> for Bytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_slice()
+    }
+}
+impl fmt::Debug for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Bytes").field(&self.as_slice()).finish()
+    }
+}
+impl PartialEq for Bytes {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+impl Eq for Bytes {}
+impl Hash for Bytes {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+impl From<Vec<u8>> for Bytes {
+    fn from(other: Vec<u8>) -> Self {
+        Self::from(Arc::<[u8]>::from(other))
+    }
+}
+impl From<&[u8]> for Bytes {
+    fn from(other: &[u8]) -> Self {
+        Self::from(Arc::<[u8]>::from(other))
+    }
+}
+impl From<Arc<[u8]>> for Bytes {
+    fn from(other: Arc<[u8]>) -> Self {
+        let range = 0..other.len();
+        Self { buf: other, range }
+    }
+}
+/// Only copies if `other` is a view into part of a larger buffer.
+impl From<Bytes> for Arc<[u8]> {
+    fn from(other: Bytes) -> Self {
+        if other.range == (0..other.buf.len()) {
+            other.buf
+        } else {
+            other.as_slice().into()
+        }
+    }
+}
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::contract::properties::bytes")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use crate::contract::properties::c_api::impl_from_refcast;
+
+    use derive_more::{From, Into};
+    use ref_cast::RefCast;
+    use rsharp::{remangle, rvec_fns};
+    use safer_ffi::prelude::*;
+
+    #[remangle(substitute!())]
+    #[derive_ReprC]
+    #[ReprC::opaque]
+    #[derive(Clone, PartialEq, From, Into, RefCast)]
+    #[repr(C)]
+    pub struct Bytes {
+        pub inner: super::Bytes,
+    }
+    impl_from_refcast!(super::Bytes, Bytes);
+
+    /// Takes ownership of `data`. The bytes are copied once into the reference
+    /// counted blob, since it can't reuse the allocation of a `Vec`.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Bytes__from_rvec(data: repr_c::Vec<u8>) -> repr_c::Box<Bytes> {
+        let data: Vec<u8> = data.into();
+        repr_c::Box::new(super::Bytes::from(data).into())
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Bytes__copy(data: c_slice::Ref<u8>) -> repr_c::Box<Bytes> {
+        repr_c::Box::new(super::Bytes::from(data.as_slice()).into())
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Bytes__drop(b: repr_c::Box<Bytes>) {
+        drop(b)
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Bytes__len(b: &Bytes) -> usize {
+        b.inner.len()
+    }
+
+    /// Borrows the data without copying it. The slice is only valid for as long
+    /// as `b` is.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Bytes__value(b: &Bytes) -> c_slice::Ref<u8> {
+        b.inner.as_slice().into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Bytes__to_rvec(b: &Bytes) -> repr_c::Vec<u8> {
+        b.inner.to_vec().into()
+    }
+
+    // Not `..::bytes`, which would clash with the `Bytes` class in C#.
+    rvec_fns!("tp_client::contract::properties", u8);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_chunk() {
+        let mut b = Bytes::from(vec![0, 1, 2, 3]);
+        let shared = b.clone();
+
+        // Overwrite in the middle
+        assert_eq!(b.write_chunk(1, &[9, 9]).unwrap(), Bytes::from(vec![1, 2]));
+        assert_eq!(b.as_slice(), [0, 9, 9, 3]);
+        // Clones are unaffected
+        assert_eq!(shared.as_slice(), [0, 1, 2, 3]);
+
+        // Grows past the end
+        assert_eq!(b.write_chunk(3, &[7, 8]).unwrap(), Bytes::from(vec![3]));
+        assert_eq!(b.as_slice(), [0, 9, 9, 7, 8]);
+
+        // Appends
+        assert_eq!(b.write_chunk(5, &[6]).unwrap(), Bytes::new());
+        assert_eq!(b.len(), 6);
+
+        assert!(b.write_chunk(7, &[1]).is_err());
+    }
+
+    #[test]
+    fn test_slice() {
+        let b = Bytes::from(vec![0, 1, 2, 3, 4]);
+        let s = b.slice(1..4);
+        assert_eq!(s.as_slice(), [1, 2, 3]);
+        assert_eq!(s, Bytes::from(vec![1, 2, 3]));
+        // Shares the buffer
+        assert!(std::ptr::eq(&b[1], &s[0]));
+
+        let nested = s.slice(1..2);
+        assert_eq!(nested.as_slice(), [2]);
+        assert_eq!(Arc::<[u8]>::from(nested).as_ref(), [2]);
+
+        // Writing to a view doesn't change the buffer it came from
+        let mut s = s;
+        s.write_chunk(0, &[9]).unwrap();
+        assert_eq!(s.as_slice(), [9, 2, 3]);
+        assert_eq!(b.as_slice(), [0, 1, 2, 3, 4]);
+
+        // Views that hold the only reference are written in place
+        let mut unique = Bytes::from(vec![0, 1, 2, 3]).slice(1..3);
+        unique.write_chunk(1, &[7]).unwrap();
+        assert_eq!(unique.as_slice(), [1, 7]);
+    }
+
+    #[test]
+    fn test_splice_reverses() {
+        let original = Bytes::from(vec![0, 1, 2, 3]);
+        let mut b = original.clone();
+        let chunk = [5, 5, 5];
+
+        let removed = b.splice(2, chunk.len(), &chunk).unwrap();
+        assert_eq!(b.as_slice(), [0, 1, 5, 5, 5]);
+
+        // Splicing the removed bytes back over the chunk restores the original
+        b.splice(2, chunk.len(), &removed).unwrap();
+        assert_eq!(b, original);
+    }
+}
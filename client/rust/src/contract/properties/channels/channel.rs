@@ -1,7 +1,7 @@
 use crate::contract::properties::traits::ITpProperty;
 
 #[cfg_attr(feature = "c_api", safer_ffi::derive_ReprC, ReprC::opaque)]
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Keyframe<T: ITpProperty> {
     value: T,
     time: f64,
@@ -20,7 +20,7 @@ impl<T: ITpProperty> Keyframe<T> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Channel<T: ITpProperty>(Vec<Keyframe<T>>);
 impl<T: ITpProperty> Channel<T> {
     pub fn new(keyframes: impl Iterator<Item = Keyframe<T>>) -> Self {
@@ -41,6 +41,13 @@ impl<T: ITpProperty> Channel<T> {
     pub fn keyframes_mut(&mut self) -> &mut Vec<Keyframe<T>> {
         &mut self.0
     }
+
+    /// Inserts `keyframe`, keeping the keyframes sorted by time. Keyframes with
+    /// the same time are kept in the order they were inserted.
+    pub fn insert(&mut self, keyframe: Keyframe<T>) {
+        let idx = self.0.partition_point(|k| k.time() <= keyframe.time());
+        self.0.insert(idx, keyframe);
+    }
 }
 
 #[cfg(feature = "c_api")]
@@ -48,7 +55,9 @@ pub mod c_api {
     #![allow(non_camel_case_types, non_snake_case, dead_code)]
 
     use super::*;
+    use crate::contract::properties::bytes::Bytes;
     use crate::contract::properties::c_api::{c_types, impl_from_refcast};
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     use crate::contract::properties::primitives;
     use crate::contract::ContractDataHandle;
     use crate::object::ObjectHandle;
@@ -143,17 +152,22 @@ pub mod c_api {
 
                     #[remangle($path)]
                     #[ffi_export]
-                    pub fn [<Channel_ $t:camel __keyframes>]<'a>(chan: &'a Monomorphized) -> repr_c::Vec<&'a Keyframe_Monomorphized> {
-                        // TODO(SER-365): Avoid allocation
-                        let v: Vec<&'a Keyframe_Monomorphized> = chan.inner.keyframes().iter().map(|k| k.into()).collect();
-                        v.into()
+                    pub fn [<Channel_ $t:camel __len>](chan: &Monomorphized) -> usize {
+                        chan.inner.keyframes().len()
+                    }
+
+                    /// Panics if `idx` is out of bounds.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<Channel_ $t:camel __keyframe>]<'a>(chan: &'a Monomorphized, idx: usize) -> &'a Keyframe_Monomorphized {
+                        (&chan.inner.keyframes()[idx]).into()
                     }
 
+                    /// Takes ownership of `kf`.
                     #[remangle($path)]
                     #[ffi_export]
-                    pub fn [<Channel_ $t:camel __keyframes_mut>]<'a>(chan: &'a mut Monomorphized) -> repr_c::Vec<&'a mut Keyframe_Monomorphized> {
-                        let v: Vec<&'a mut Keyframe_Monomorphized> = chan.inner.keyframes_mut().iter_mut().map(|k| k.into()).collect();
-                        v.into()
+                    pub fn [<Channel_ $t:camel __insert>](chan: &mut Monomorphized, kf: repr_c::Box<Keyframe_Monomorphized>) {
+                        chan.inner.insert(kf.into().inner)
                     }
                 }
 
@@ -171,3 +185,19 @@ pub mod c_api {
     // This is like doing `monomorphize!("whatever", Keyframe, u8, u16, ...)
     primitives!(; types, monomorphize, "tp_client::contract::properties::channels");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_keeps_order() {
+        let mut c = Channel::new([Keyframe::new(0u8, 1.0), Keyframe::new(1, 0.0)].into_iter());
+        c.insert(Keyframe::new(2, 0.5));
+        c.insert(Keyframe::new(3, 2.0));
+        c.insert(Keyframe::new(4, 0.5));
+
+        let values: Vec<u8> = c.keyframes().iter().map(|k| *k.value()).collect();
+        assert_eq!(values, [1, 2, 4, 0, 3]);
+    }
+}
@@ -1,12 +1,39 @@
-use super::{DynChannelMut, DynChannelRef};
+use super::{DynChannel, DynChannelMut, DynChannelRef};
+use crate::contract::properties::bytes::Bytes;
 use crate::contract::properties::channels::Channel;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::primitives;
+use crate::contract::properties::traits::TpMap;
 use crate::contract::ContractDataHandle;
 use crate::contract::ObjectHandle;
 
 macro_rules! impl_from {
     // base case
     ($t:ty) => {
+        impl From<Channel<$t>> for DynChannel {
+            fn from(other: Channel<$t>) -> Self {
+                Self::Primitive(other.into())
+            }
+        }
+
+        impl From<Channel<Vec<$t>>> for DynChannel {
+            fn from(other: Channel<Vec<$t>>) -> Self {
+                Self::Vec(other.into())
+            }
+        }
+
+        impl From<Channel<Option<$t>>> for DynChannel {
+            fn from(other: Channel<Option<$t>>) -> Self {
+                Self::Option(other.into())
+            }
+        }
+
+        impl From<Channel<TpMap<$t>>> for DynChannel {
+            fn from(other: Channel<TpMap<$t>>) -> Self {
+                Self::Map(other.into())
+            }
+        }
+
         impl <'a> From<&'a Channel<$t>> for DynChannelRef<'a> {
             fn from(other: &'a Channel<$t>) -> Self {
                 Self::Primitive(other.into())
@@ -30,6 +57,30 @@ macro_rules! impl_from {
                 Self::Vec(other.into())
             }
         }
+
+        impl <'a> From<&'a Channel<Option<$t>>> for DynChannelRef<'a> {
+            fn from(other: &'a Channel<Option<$t>>) -> Self {
+                Self::Option(other.into())
+            }
+        }
+
+        impl <'a> From<&'a mut Channel<Option<$t>>> for DynChannelMut<'a> {
+            fn from(other: &'a mut Channel<Option<$t>>) -> Self {
+                Self::Option(other.into())
+            }
+        }
+
+        impl <'a> From<&'a Channel<TpMap<$t>>> for DynChannelRef<'a> {
+            fn from(other: &'a Channel<TpMap<$t>>) -> Self {
+                Self::Map(other.into())
+            }
+        }
+
+        impl <'a> From<&'a mut Channel<TpMap<$t>>> for DynChannelMut<'a> {
+            fn from(other: &'a mut Channel<TpMap<$t>>) -> Self {
+                Self::Map(other.into())
+            }
+        }
     };
 
     // recursive case
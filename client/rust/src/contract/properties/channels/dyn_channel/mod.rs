@@ -1,20 +1,24 @@
 mod from_impls;
 
+use crate::contract::properties::bytes::Bytes;
 use crate::contract::properties::channels::Channel;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::dynamic::DynEnum;
 use crate::contract::properties::dynamic::{TpPrimitiveType, TpPropertyType};
 use crate::contract::properties::primitives;
+use crate::contract::properties::traits::{ITpPropertyStatic, TpMap};
 use crate::contract::ContractDataHandle;
 use crate::contract::ObjectHandle;
 
 use derive_more::From;
 use paste::paste;
+use std::any::Any;
 
 pub use crate::contract::properties::dynamic::__macro::{
     apply_to_channel, apply_to_channel_mut, apply_to_channel_ref,
 };
 
-DynEnum!(DynChannel, Channel);
+DynEnum!(DynChannel, Channel | derive(Clone, PartialEq));
 
 macro_rules! prim_enum_helper {
     ($enum_ident:ident, mut, $($variant:ty),+ $(,)?) => {
@@ -54,16 +58,40 @@ macro_rules! vec_enum_helper {
     };
 }
 
+// Used for the `Option` and `Map` variants
+macro_rules! container_enum_helper {
+    ($enum_ident:ident, $container:tt, mut, $($variant:ty),+ $(,)?) => {
+        paste!{
+            #[derive(From)]
+            pub enum $enum_ident<'a> {
+                $([<$variant:camel>](&'a mut Channel<$container<$variant>>)),+
+            }
+        }
+    };
+    ($enum_ident:ident, $container:tt, ref, $($variant:ty),+ $(,)?) => {
+        paste! {
+            #[derive(Copy, Clone, From)]
+            pub enum $enum_ident<'a> {
+                $([<$variant:camel>](&'a Channel<$container<$variant>>)),+
+            }
+        }
+    };
+}
+
 macro_rules! ref_enum {
     ($enum_ident:ident, ref) => {
         paste! {
             primitives!(; types, prim_enum_helper, [<$enum_ident PrimitiveRef>], ref);
             primitives!(; types, vec_enum_helper, [<$enum_ident VecRef>], ref);
+            primitives!(; types, container_enum_helper, [<$enum_ident OptionRef>], Option, ref);
+            primitives!(; types, container_enum_helper, [<$enum_ident MapRef>], TpMap, ref);
 
             #[derive(Copy, Clone)]  // to avoid conflict, we will implement `From` manually
             pub enum [<$enum_ident Ref>]<'a> {
                 Primitive([<$enum_ident PrimitiveRef>]<'a>),
                 Vec([<$enum_ident VecRef>]<'a>),
+                Option([<$enum_ident OptionRef>]<'a>),
+                Map([<$enum_ident MapRef>]<'a>),
             }
         }
     };
@@ -71,11 +99,15 @@ macro_rules! ref_enum {
         paste! {
             primitives!(; types, prim_enum_helper, [<$enum_ident PrimitiveMut>], mut);
             primitives!(; types, vec_enum_helper, [<$enum_ident VecMut>], mut);
+            primitives!(; types, container_enum_helper, [<$enum_ident OptionMut>], Option, mut);
+            primitives!(; types, container_enum_helper, [<$enum_ident MapMut>], TpMap, mut);
 
             // to avoid conflict, we will implement `From` manually
             pub enum [<$enum_ident Mut>]<'a> {
                 Primitive([<$enum_ident PrimitiveMut>]<'a>),
                 Vec([<$enum_ident VecMut>]<'a>),
+                Option([<$enum_ident OptionMut>]<'a>),
+                Map([<$enum_ident MapMut>]<'a>),
             }
         }
     };
@@ -97,29 +129,52 @@ macro_rules! prop_type_helper {
 }
 
 macro_rules! impl_prop_type {
-    ($main_ty:ty, $prim_ty:ty, $vec_ty:ty $(,)?) => {
+    ($main_ty:ty, $prim_ty:ty, $vec_ty:ty, $option_ty:ty, $map_ty:ty $(,)?) => {
             primitives!(; idents, prop_type_helper, $prim_ty, Primitive);
             primitives!(; idents, prop_type_helper, $vec_ty, Vec);
+            primitives!(; idents, prop_type_helper, $option_ty, Option);
+            primitives!(; idents, prop_type_helper, $map_ty, Map);
 
             impl $main_ty {
                 pub fn prop_type(&self) -> TpPropertyType {
                     match self {
                         Self::Primitive(inner) => inner.prop_type(),
                         Self::Vec(inner) => inner.prop_type(),
+                        Self::Option(inner) => inner.prop_type(),
+                        Self::Map(inner) => inner.prop_type(),
                     }
                 }
             }
     };
 }
 
-impl_prop_type!(DynChannel, DynChannelPrimitive, DynChannelVec);
+impl_prop_type!(
+    DynChannel,
+    DynChannelPrimitive,
+    DynChannelVec,
+    DynChannelOption,
+    DynChannelMap,
+);
+impl DynChannel {
+    /// Gets a mutable reference to the inner channel, if it is a `Channel<T>`.
+    pub fn cast_mut<T: ITpPropertyStatic>(&mut self) -> Option<&mut Channel<T>> {
+        fn downcast<U: Any, T: Any>(value: &mut U) -> Option<&mut T> {
+            (value as &mut dyn Any).downcast_mut()
+        }
+        apply_to_channel!(self, downcast)
+    }
+}
 impl_prop_type!(
     DynChannelRef<'_>,
     DynChannelPrimitiveRef<'_>,
-    DynChannelVecRef<'_>
+    DynChannelVecRef<'_>,
+    DynChannelOptionRef<'_>,
+    DynChannelMapRef<'_>,
 );
 impl_prop_type!(
     DynChannelMut<'_>,
     DynChannelPrimitiveMut<'_>,
-    DynChannelVecMut<'_>
+    DynChannelVecMut<'_>,
+    DynChannelOptionMut<'_>,
+    DynChannelMapMut<'_>,
 );
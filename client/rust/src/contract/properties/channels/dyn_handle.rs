@@ -1,16 +1,22 @@
 use super::dyn_channel::{DynChannelMut, DynChannelRef};
 use super::{handle::ChannelHandle, IChannelHandle};
 use crate::apply_to_channel_handle;
+use crate::contract::properties::bytes::Bytes;
 use crate::contract::properties::channels::Channel;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::dynamic::__macro::DynEnum;
 use crate::contract::properties::dynamic::{TpPrimitiveType, TpPropertyType};
 use crate::contract::properties::primitives;
-use crate::contract::properties::traits::ITpPropertyStatic;
+use crate::contract::properties::traits::{ITpPropertyStatic, TpMap};
+use crate::contract::ContractDataHandle;
+use crate::object::ObjectHandle;
 
 DynEnum!(DynChannelHandle, ChannelHandle | derive(Clone, PartialEq));
 
 impl Copy for DynChannelHandlePrimitive {}
 impl Copy for DynChannelHandleVec {}
+impl Copy for DynChannelHandleOption {}
+impl Copy for DynChannelHandleMap {}
 impl Copy for DynChannelHandle {}
 
 impl IChannelHandle for DynChannelHandle {
@@ -60,10 +66,16 @@ impl IChannelHandle for DynChannelHandle {
                 primitives!(idents, match_helper, p, DynChannelHandlePrimitive)
             }
             DynChannelHandle::Vec(v) => primitives!(idents, match_helper, v, DynChannelHandleVec),
+            DynChannelHandle::Option(o) => {
+                primitives!(idents, match_helper, o, DynChannelHandleOption)
+            }
+            DynChannelHandle::Map(m) => primitives!(idents, match_helper, m, DynChannelHandleMap),
         }
     }
 
     fn prop_type(&self) -> TpPropertyType {
+        use DynChannelHandleMap as M;
+        use DynChannelHandleOption as O;
         use DynChannelHandlePrimitive as P;
         use DynChannelHandleVec as V;
 
@@ -83,6 +95,49 @@ impl IChannelHandle for DynChannelHandle {
                 TpPropertyType::Primitive(primitives!(idents, helper_match, p, P))
             }
             Self::Vec(v) => TpPropertyType::Vec(primitives!(idents, helper_match, v, V)),
+            Self::Option(o) => TpPropertyType::Option(primitives!(idents, helper_match, o, O)),
+            Self::Map(m) => TpPropertyType::Map(primitives!(idents, helper_match, m, M)),
         }
     }
 }
+
+macro_rules! impl_from {
+    // base case
+    ($t:ty) => {
+        impl From<ChannelHandle<$t>> for DynChannelHandle {
+            fn from(other: ChannelHandle<$t>) -> Self {
+                Self::Primitive(DynChannelHandlePrimitive::from(other))
+            }
+        }
+
+        impl From<ChannelHandle<Vec<$t>>> for DynChannelHandle {
+            fn from(other: ChannelHandle<Vec<$t>>) -> Self {
+                Self::Vec(DynChannelHandleVec::from(other))
+            }
+        }
+
+        impl From<ChannelHandle<Option<$t>>> for DynChannelHandle {
+            fn from(other: ChannelHandle<Option<$t>>) -> Self {
+                Self::Option(DynChannelHandleOption::from(other))
+            }
+        }
+
+        impl From<ChannelHandle<TpMap<$t>>> for DynChannelHandle {
+            fn from(other: ChannelHandle<TpMap<$t>>) -> Self {
+                Self::Map(DynChannelHandleMap::from(other))
+            }
+        }
+    };
+
+    // recursive case
+    ($t:ty, $($tail:ty),+) => {
+        impl_from!($t);
+        impl_from!($($tail),+);
+    };
+
+    // handle trailing comma
+    ($($tail:ty),+,) => {
+        impl_from!($($tail),+);
+    };
+}
+primitives!(; types, impl_from);
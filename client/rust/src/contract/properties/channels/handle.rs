@@ -55,6 +55,8 @@ impl<T: ITpPropertyStatic> IChannelHandle for ChannelHandle<T> {
 pub mod c_api {
     #![allow(non_camel_case_types, non_snake_case, dead_code)]
 
+    use crate::contract::properties::bytes::Bytes;
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     use crate::contract::properties::primitives;
     use crate::contract::ContractDataHandle;
     use crate::object::ObjectHandle;
@@ -68,15 +70,26 @@ pub mod c_api {
         // Base case
         ($path:literal, $t:ty $(,)?) => {
             paste::paste! {
-
-                #[remangle($path)]
-                #[derive_ReprC]
-                #[ReprC::opaque]
-                #[derive(From, Into, RefCast, Debug, Copy, Clone, Eq, PartialEq)]
-                #[repr(C)]
-                pub struct [<ChannelHandle _ $t:camel>] {
-                    pub inner: super::ChannelHandle<$t>,
+                mod [<_ChannelHandle_ $t:camel>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(From, Into, RefCast, Debug, Copy, Clone, Eq, PartialEq)]
+                    #[repr(C)]
+                    pub struct [<ChannelHandle_ $t:camel>] {
+                        pub inner: super::super::ChannelHandle<$t>,
+                    }
+                    pub use [<ChannelHandle_ $t:camel>] as Monomorphized;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<ChannelHandle_ $t:camel __drop>](h: repr_c::Box<Monomorphized>) {
+                        drop(h)
+                    }
                 }
+                pub use [<_ChannelHandle_ $t:camel>]::[<ChannelHandle_ $t:camel>];
             }
         };
         // recursive case
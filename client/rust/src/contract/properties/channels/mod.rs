@@ -14,7 +14,8 @@ pub use crate::contract::properties::dynamic::apply_to_channel_id;
 
 use crate::contract::properties::dynamic::TpPropertyType;
 use crate::contract::properties::dynamic::__macro::{DynEnum, DynTpPropId};
-use crate::contract::properties::traits::{ITpData, ITpProperty, ITpPropertyStatic};
+use crate::contract::properties::field::FieldInfo;
+use crate::contract::properties::traits::{ITpProperty, ITpPropertyStatic, TpMap};
 use crate::contract::ContractDataHandle;
 
 use std::any::TypeId;
@@ -70,12 +71,14 @@ impl<T: ITpProperty> ChannelId<T> {
         }
     }
 }
-impl<T: ITpData> Copy for ChannelId<T> {}
+impl<T: ITpPropertyStatic> Copy for ChannelId<T> {}
 
 pub trait IChannels {
     fn type_ids() -> &'static [TypeId];
     fn enumerate_types() -> &'static [TpPropertyType];
     fn field_names() -> &'static [&'static str];
+    /// The attributes of each field, such as defaults and constraints.
+    fn field_info() -> &'static [FieldInfo];
 }
 
 impl IChannels for () {
@@ -90,6 +93,10 @@ impl IChannels for () {
     fn field_names() -> &'static [&'static str] {
         &[]
     }
+
+    fn field_info() -> &'static [FieldInfo] {
+        &[]
+    }
 }
 
 DynTpPropId!(DynChannelId, ChannelId);
@@ -105,6 +112,8 @@ pub mod c_api {
     pub use super::handle::c_api::*;
 
     use crate::contract::c_api::ContractDataHandle as CContractDataHandle;
+    use crate::contract::properties::bytes::Bytes;
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     use crate::contract::properties::primitives;
     use crate::contract::ContractDataHandle;
     use crate::object::ObjectHandle;
@@ -131,10 +140,17 @@ pub mod c_api {
                     }
                     pub use [<ChannelId_ $t:camel>] as Monomorphized;
 
+                    #[remangle($path)]
                     #[ffi_export]
                     pub fn [<ChannelId_ $t:camel __contract>]<'a>(id: &'a Monomorphized) -> repr_c::Box<CContractDataHandle> {
                         repr_c::Box::new(CContractDataHandle::from(id.inner.contract()))
                     }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<ChannelId_ $t:camel __drop>](id: repr_c::Box<Monomorphized>) {
+                        drop(id)
+                    }
                 }
                 pub use [<_ChannelId_ $t:camel>]::Monomorphized as [<ChannelId_ $t:camel>];
             }
@@ -0,0 +1,435 @@
+//! Fixed-size composite types that can be stored in properties.
+//!
+//! These are treated just like the scalar primitives: they implement
+//! [`ITpData`](super::traits::ITpData), so they can be used in `State<T>`,
+//! `Channel<T>`, the `states`/`channels` macros, and in `Vec`s.
+//!
+//! Channels of these types can be interpolated via [`CanTween`].
+//!
+//! Note that the set of property types is closed: every type needs a variant in
+//! the dynamically typed enums such as
+//! [`DynTpProperty`](super::dynamic::DynTpProperty). User-defined structs are
+//! instead flattened into one property per field: derive [`IComposite`] with
+//! `#[derive(Composite)]`, and mark the field with `#[flatten]` in the
+//! `states`/`channels` macros.
+
+use crate::contract::properties::dynamic::{DynTpProperty, TpPropertyType};
+use crate::contract::ContractDataHandle;
+
+use keyframe::num_traits::Float;
+use keyframe::CanTween;
+use std::any::TypeId;
+
+fn lerp(from: f32, to: f32, t: f32) -> f32 {
+    from + (to - from) * t
+}
+
+fn to_f32(time: impl Float) -> f32 {
+    time.to_f32()
+        .expect("`time` should be representable as an f32")
+}
+
+/// A 2D vector.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+impl Vec2 {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+}
+impl CanTween for Vec2 {
+    fn ease(from: Self, to: Self, time: impl Float) -> Self {
+        let t = to_f32(time);
+        Self::new(lerp(from.x, to.x, t), lerp(from.y, to.y, t))
+    }
+}
+
+/// A 3D vector.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+impl Vec3 {
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0);
+    pub const ONE: Self = Self::new(1.0, 1.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+}
+impl CanTween for Vec3 {
+    fn ease(from: Self, to: Self, time: impl Float) -> Self {
+        let t = to_f32(time);
+        Self::new(
+            lerp(from.x, to.x, t),
+            lerp(from.y, to.y, t),
+            lerp(from.z, to.z, t),
+        )
+    }
+}
+
+/// A quaternion representing a rotation. Should be kept normalized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Quat {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+impl Quat {
+    pub const IDENTITY: Self = Self::new(0.0, 0.0, 0.0, 1.0);
+
+    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Self {
+        Self { x, y, z, w }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    /// Returns the quaternion scaled to unit length. A quaternion that is too
+    /// short to have a direction normalizes to [`Quat::IDENTITY`].
+    pub fn normalize(self) -> Self {
+        let len = self.dot(self).sqrt();
+        if len == 0.0 || !len.is_finite() {
+            return Self::IDENTITY;
+        }
+        self.scale(1.0 / len)
+    }
+
+    fn scale(self, s: f32) -> Self {
+        Self::new(self.x * s, self.y * s, self.z * s, self.w * s)
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.x + other.x,
+            self.y + other.y,
+            self.z + other.z,
+            self.w + other.w,
+        )
+    }
+}
+impl Default for Quat {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+impl CanTween for Quat {
+    /// Spherical linear interpolation, along the shortest path.
+    fn ease(from: Self, to: Self, time: impl Float) -> Self {
+        let t = to_f32(time);
+        let mut to = to;
+        let mut dot = from.dot(to);
+        // `q` and `-q` are the same rotation, so pick whichever one is closer.
+        if dot < 0.0 {
+            to = to.scale(-1.0);
+            dot = -dot;
+        }
+
+        // When the rotations are almost the same, slerp is numerically unstable.
+        if dot > 0.9995 {
+            return from.scale(1.0 - t).add(to.scale(t)).normalize();
+        }
+
+        let theta_0 = dot.acos();
+        let theta = theta_0 * t;
+        let sin_theta_0 = theta_0.sin();
+        let s0 = (theta_0 - theta).sin() / sin_theta_0;
+        let s1 = theta.sin() / sin_theta_0;
+        from.scale(s0).add(to.scale(s1))
+    }
+}
+
+/// A linear RGBA color, with components usually in the range `0.0..=1.0`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+impl Color {
+    pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+    pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Creates an opaque color.
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self::new(r, g, b, 1.0)
+    }
+}
+impl CanTween for Color {
+    fn ease(from: Self, to: Self, time: impl Float) -> Self {
+        let t = to_f32(time);
+        Self::new(
+            lerp(from.r, to.r, t),
+            lerp(from.g, to.g, t),
+            lerp(from.b, to.b, t),
+            lerp(from.a, to.a, t),
+        )
+    }
+}
+
+/// A translation, rotation, and scale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+impl Transform {
+    pub const IDENTITY: Self = Self::new(Vec3::ZERO, Quat::IDENTITY, Vec3::ONE);
+
+    pub const fn new(translation: Vec3, rotation: Quat, scale: Vec3) -> Self {
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+}
+impl Default for Transform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+impl CanTween for Transform {
+    fn ease(from: Self, to: Self, time: impl Float) -> Self {
+        let t = to_f32(time);
+        Self::new(
+            CanTween::ease(from.translation, to.translation, t),
+            CanTween::ease(from.rotation, to.rotation, t),
+            CanTween::ease(from.scale, to.scale, t),
+        )
+    }
+}
+
+/// A user-defined struct whose fields are each stored as their own property.
+///
+/// Implement this with `#[derive(Composite)]` rather than by hand. Every field
+/// of the struct must be a property type, and the properties are named
+/// `outer.inner` in the `states`/`channels` struct that flattens it.
+pub trait IComposite: Sized {
+    /// The number of properties that the struct flattens into.
+    const LEN: usize;
+    /// Holds the `StateId` of each field.
+    type StateIds: Copy;
+    /// Holds the `ChannelId` of each field.
+    type ChannelIds: Copy;
+
+    fn type_ids() -> Vec<TypeId>;
+    fn enumerate_types() -> Vec<TpPropertyType>;
+    fn field_names() -> &'static [&'static str];
+
+    /// This is only exposed so that contract macros can call it. It isn't intended
+    /// for direct use by API clients
+    #[doc(hidden)]
+    fn state_ids(first: usize, contract: ContractDataHandle) -> Self::StateIds;
+
+    /// This is only exposed so that contract macros can call it. It isn't intended
+    /// for direct use by API clients
+    #[doc(hidden)]
+    fn channel_ids(first: usize, contract: ContractDataHandle) -> Self::ChannelIds;
+
+    /// Splits the struct into one property per field, in declaration order.
+    fn into_properties(self) -> Vec<DynTpProperty>;
+}
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::contract::properties::composite")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use crate::contract::properties::c_api::impl_from_refcast;
+
+    use derive_more::{From, Into};
+    use ref_cast::RefCast;
+    use rsharp::remangle;
+    use safer_ffi::prelude::*;
+
+    /// Wraps a composite type whose fields are all `f32`s.
+    macro_rules! wrap_f32s {
+        ($path:literal, $t:ident, $($field:ident),+ $(,)?) => {
+            paste::paste! {
+                // Module is simply to prevent name collisions on the rust side. It does
+                // nothing for C
+                mod [<_ $t>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(Clone, Copy, PartialEq, From, Into, RefCast)]
+                    #[repr(C)]
+                    pub struct $t {
+                        pub inner: super::super::$t,
+                    }
+                    impl_from_refcast!(super::super::$t, $t);
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<$t __new>]($($field: f32),+) -> repr_c::Box<$t> {
+                        repr_c::Box::new(super::super::$t::new($($field),+).into())
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<$t __drop>](v: repr_c::Box<$t>) {
+                        drop(v)
+                    }
+
+                    $(
+                        #[remangle($path)]
+                        #[ffi_export]
+                        pub fn [<$t __ $field>](v: &$t) -> f32 {
+                            v.inner.$field
+                        }
+                    )+
+                }
+                pub use [<_ $t>]::$t;
+            }
+        };
+    }
+
+    wrap_f32s!("tp_client::contract::properties::composite", Vec2, x, y);
+    wrap_f32s!("tp_client::contract::properties::composite", Vec3, x, y, z);
+    wrap_f32s!(
+        "tp_client::contract::properties::composite",
+        Quat,
+        x,
+        y,
+        z,
+        w
+    );
+    wrap_f32s!(
+        "tp_client::contract::properties::composite",
+        Color,
+        r,
+        g,
+        b,
+        a
+    );
+
+    mod _Transform {
+        use super::*;
+        use crate::contract::properties::composite::Transform as RTransform;
+
+        #[remangle(substitute!())]
+        #[derive_ReprC]
+        #[ReprC::opaque]
+        #[derive(Clone, Copy, PartialEq, From, Into, RefCast)]
+        #[repr(C)]
+        pub struct Transform {
+            pub inner: RTransform,
+        }
+        impl_from_refcast!(RTransform, Transform);
+
+        #[remangle(substitute!())]
+        #[ffi_export]
+        pub fn Transform__new(
+            translation: &Vec3,
+            rotation: &Quat,
+            scale: &Vec3,
+        ) -> repr_c::Box<Transform> {
+            repr_c::Box::new(RTransform::new(translation.inner, rotation.inner, scale.inner).into())
+        }
+
+        #[remangle(substitute!())]
+        #[ffi_export]
+        pub fn Transform__drop(v: repr_c::Box<Transform>) {
+            drop(v)
+        }
+
+        #[remangle(substitute!())]
+        #[ffi_export]
+        pub fn Transform__translation<'a>(v: &'a Transform) -> &'a Vec3 {
+            (&v.inner.translation).into()
+        }
+
+        #[remangle(substitute!())]
+        #[ffi_export]
+        pub fn Transform__rotation<'a>(v: &'a Transform) -> &'a Quat {
+            (&v.inner.rotation).into()
+        }
+
+        #[remangle(substitute!())]
+        #[ffi_export]
+        pub fn Transform__scale<'a>(v: &'a Transform) -> &'a Vec3 {
+            (&v.inner.scale).into()
+        }
+    }
+    pub use _Transform::Transform;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use keyframe::ease;
+    use keyframe::functions::Linear;
+
+    #[test]
+    fn test_vec_tween() {
+        let a = Vec3::new(0.0, 10.0, -2.0);
+        let b = Vec3::new(4.0, 20.0, 2.0);
+
+        assert_eq!(ease(Linear, a, b, 0.0), a);
+        assert_eq!(ease(Linear, a, b, 0.5), Vec3::new(2.0, 15.0, 0.0));
+        assert_eq!(ease(Linear, a, b, 1.0), b);
+
+        let a = Color::BLACK;
+        let b = Color::new(1.0, 0.5, 0.0, 0.0);
+        assert_eq!(ease(Linear, a, b, 0.5), Color::new(0.5, 0.25, 0.0, 0.5));
+    }
+
+    #[test]
+    fn test_quat_tween() {
+        fn approx_eq(a: Quat, b: Quat) -> bool {
+            // `q` and `-q` represent the same rotation
+            (a.dot(b).abs() - 1.0).abs() < 1e-5
+        }
+        let half_sqrt = std::f32::consts::FRAC_1_SQRT_2;
+
+        let a = Quat::IDENTITY;
+        // 90 degree rotation about the z axis
+        let b = Quat::new(0.0, 0.0, half_sqrt, half_sqrt);
+        // 45 degree rotation about the z axis
+        let (sin, cos) = std::f32::consts::FRAC_PI_8.sin_cos();
+        let mid = Quat::new(0.0, 0.0, sin, cos);
+
+        assert!(approx_eq(ease(Linear, a, b, 0.0), a));
+        assert!(approx_eq(ease(Linear, a, b, 0.5), mid));
+        assert!(approx_eq(ease(Linear, a, b, 1.0), b));
+
+        // Should take the shortest path even when the signs are flipped
+        let neg_b = b.scale(-1.0);
+        assert!(approx_eq(ease(Linear, a, neg_b, 0.5), mid));
+    }
+
+    #[test]
+    fn test_quat_normalize_zero() {
+        let zero = Quat::new(0.0, 0.0, 0.0, 0.0);
+        assert_eq!(zero.normalize(), Quat::IDENTITY);
+        // Its length underflows to zero
+        let tiny = Quat::new(0.0, 0.0, 0.0, 1e-30);
+        assert_eq!(tiny.normalize(), Quat::IDENTITY);
+
+        // Lerping between the same rotation goes through `normalize`
+        let q = Quat::new(0.0, 0.0, 0.0, 2.0);
+        assert_eq!(ease(Linear, q, q, 0.5), Quat::IDENTITY);
+        assert_eq!(
+            Quat::new(0.0, 3.0, 0.0, 0.0).normalize(),
+            Quat::new(0.0, 1.0, 0.0, 0.0)
+        );
+    }
+}
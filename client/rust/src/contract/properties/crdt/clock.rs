@@ -0,0 +1,99 @@
+//! Hybrid logical clocks, which order the edits to CRDTs.
+
+/// Identifies a replica of a CRDT. Every replica that edits the same CRDT must
+/// have a different id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct ReplicaId(pub u64);
+impl ReplicaId {
+    /// A random id, which is unique with very high probability.
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// When an edit was made. Timestamps are unique, and are ordered first by
+/// wall clock time, then by a counter that disambiguates edits made in the same
+/// millisecond, and finally by replica.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Timestamp {
+    pub millis: u64,
+    pub counter: u32,
+    pub replica: ReplicaId,
+}
+
+/// A hybrid logical clock.
+///
+/// Its timestamps follow the wall clock when it is ahead, but always increase,
+/// even if the wall clock goes backwards. They are also later than every
+/// timestamp that the clock [`observe`](Self::observe)d, so an edit is always
+/// ordered after the edits that its replica knew of.
+///
+/// The wall clock is passed in as milliseconds since an epoch that all replicas
+/// share, such as the unix epoch.
+#[derive(Debug, Clone)]
+pub struct Hlc {
+    last: Timestamp,
+}
+impl Hlc {
+    pub fn new(replica: ReplicaId) -> Self {
+        Self {
+            last: Timestamp {
+                replica,
+                ..Default::default()
+            },
+        }
+    }
+
+    pub fn replica(&self) -> ReplicaId {
+        self.last.replica
+    }
+
+    /// The last timestamp that was made or observed.
+    pub fn last(&self) -> Timestamp {
+        self.last
+    }
+
+    /// Makes a new timestamp, for an edit made at `wall_millis`.
+    pub fn tick(&mut self, wall_millis: u64) -> Timestamp {
+        if wall_millis > self.last.millis {
+            self.last.millis = wall_millis;
+            self.last.counter = 0;
+        } else {
+            self.last.counter += 1;
+        }
+        self.last
+    }
+
+    /// Advances the clock past a timestamp from another replica.
+    pub fn observe(&mut self, remote: Timestamp) {
+        let replica = self.last.replica;
+        if remote > self.last {
+            self.last = remote;
+        }
+        self.last.replica = replica;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hlc() {
+        let mut a = Hlc::new(ReplicaId(1));
+        let mut b = Hlc::new(ReplicaId(2));
+
+        let t1 = a.tick(100);
+        let t2 = a.tick(100);
+        let t3 = a.tick(50); // The wall clock went backwards
+        assert!(t1 < t2 && t2 < t3);
+        assert_eq!(t3.millis, 100);
+
+        // `b`'s wall clock is behind, but its edits still come after `a`'s
+        b.observe(t3);
+        let t4 = b.tick(10);
+        assert!(t4 > t3);
+        assert_eq!(t4.replica, ReplicaId(2));
+        assert_eq!(b.tick(200).counter, 0);
+    }
+}
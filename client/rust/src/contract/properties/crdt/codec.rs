@@ -0,0 +1,108 @@
+//! The binary encoding of CRDTs. All integers are little endian.
+
+use super::clock::{ReplicaId, Timestamp};
+
+use eyre::{eyre, Result};
+
+/// Encodes and decodes a CRDT, not including its kind.
+pub trait Encoding: Sized {
+    fn encode(&self, w: &mut Writer);
+    fn decode(r: &mut Reader) -> Result<Self>;
+}
+
+#[derive(Default)]
+pub struct Writer(Vec<u8>);
+impl Writer {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.0
+    }
+
+    pub fn u8(&mut self, v: u8) {
+        self.0.push(v);
+    }
+
+    pub fn u32(&mut self, v: u32) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, v: u64) {
+        self.0.extend_from_slice(&v.to_le_bytes());
+    }
+
+    pub fn count(&mut self, len: usize) {
+        self.u32(u32::try_from(len).expect("CRDTs can't hold more than u32::MAX items"));
+    }
+
+    pub fn bytes(&mut self, v: &[u8]) {
+        self.count(v.len());
+        self.0.extend_from_slice(v);
+    }
+
+    pub fn timestamp(&mut self, t: Timestamp) {
+        self.u64(t.millis);
+        self.u32(t.counter);
+        self.u64(t.replica.0);
+    }
+}
+
+pub struct Reader<'a>(&'a [u8]);
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self(data)
+    }
+
+    /// Errors if there is data left over.
+    pub fn finish(self) -> Result<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(eyre!("{} trailing bytes after the CRDT", self.0.len()))
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.slice(N)?.try_into().unwrap())
+    }
+
+    fn slice(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err(eyre!("The CRDT was truncated"));
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    pub fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+
+    pub fn count(&mut self) -> Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.count()?;
+        self.slice(len)
+    }
+
+    pub fn timestamp(&mut self) -> Result<Timestamp> {
+        Ok(Timestamp {
+            millis: self.u64()?,
+            counter: self.u32()?,
+            replica: ReplicaId(self.u64()?),
+        })
+    }
+}
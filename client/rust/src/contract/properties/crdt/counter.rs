@@ -0,0 +1,92 @@
+use super::codec::{Encoding, Reader, Writer};
+use super::{Crdt, CrdtKind, ReplicaId};
+
+use eyre::Result;
+use std::collections::BTreeMap;
+
+/// An integer that replicas can add to and subtract from concurrently.
+///
+/// Each replica keeps its own running totals of what it added and subtracted,
+/// which only ever grow. The value is the sum of all of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counter {
+    counts: BTreeMap<ReplicaId, (u64, u64)>,
+}
+impl Counter {
+    pub fn value(&self) -> i64 {
+        let (added, subtracted) = self
+            .counts
+            .values()
+            .fold((0u64, 0u64), |(a, s), (add, sub)| {
+                (a.wrapping_add(*add), s.wrapping_add(*sub))
+            });
+        added.wrapping_sub(subtracted) as i64
+    }
+
+    /// Adds `amount` on behalf of `replica`, returning the delta.
+    pub fn add(&mut self, replica: ReplicaId, amount: i64) -> Self {
+        let (added, subtracted) = self.counts.entry(replica).or_default();
+        if amount >= 0 {
+            *added += amount as u64;
+        } else {
+            *subtracted += amount.unsigned_abs();
+        }
+        Self {
+            counts: BTreeMap::from([(replica, (*added, *subtracted))]),
+        }
+    }
+}
+impl Crdt for Counter {
+    const KIND: CrdtKind = CrdtKind::Counter;
+
+    fn merge(&mut self, other: &Self) {
+        for (replica, (added, subtracted)) in &other.counts {
+            let entry = self.counts.entry(*replica).or_default();
+            entry.0 = entry.0.max(*added);
+            entry.1 = entry.1.max(*subtracted);
+        }
+    }
+}
+impl Encoding for Counter {
+    fn encode(&self, w: &mut Writer) {
+        w.count(self.counts.len());
+        for (replica, (added, subtracted)) in &self.counts {
+            w.u64(replica.0);
+            w.u64(*added);
+            w.u64(*subtracted);
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let mut counts = BTreeMap::new();
+        for _ in 0..r.count()? {
+            counts.insert(ReplicaId(r.u64()?), (r.u64()?, r.u64()?));
+        }
+        Ok(Self { counts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counter() {
+        let mut a = Counter::default();
+        let mut b = Counter::default();
+        let deltas = [
+            a.add(ReplicaId(1), 5),
+            b.add(ReplicaId(2), -2),
+            a.add(ReplicaId(1), -1),
+        ];
+        for delta in &deltas {
+            a.merge(delta);
+            b.merge(delta);
+            // Merging the same delta again has no effect
+            b.merge(delta);
+        }
+        assert_eq!(a.value(), 2);
+        assert_eq!(a, b);
+        assert_eq!(Counter::from_bytes(&a.to_bytes()).unwrap(), a);
+    }
+}
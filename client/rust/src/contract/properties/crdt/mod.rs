@@ -0,0 +1,209 @@
+//! Conflict-free replicated data types (CRDTs), for states that many users
+//! edit at the same time.
+//!
+//! Plain states are last writer wins: when two users write the same state at
+//! once, whichever collaction is applied last overwrites the other. CRDTs
+//! instead merge concurrent edits, so every replica ends up with the same value
+//! regardless of the order that it receives them in, and without a server to
+//! arbitrate.
+//!
+//! CRDTs are stored in `Bytes` states, in the encoding of [`Crdt::to_bytes`].
+//! An empty state is an empty CRDT of any kind. Each edit returns a *delta*,
+//! which is a CRDT holding only that edit. Deltas are applied by merging them
+//! into the state with [`StateAction::Merge`]. Merging is commutative,
+//! associative and idempotent, so deltas can be applied in any order, and
+//! more than once.
+//!
+//! Edits are ordered by the [`Timestamp`]s of a hybrid logical clock, [`Hlc`],
+//! which each replica keeps its own of.
+//!
+//! [`StateAction::Merge`]: crate::action::property::StateAction::Merge
+
+mod clock;
+mod codec;
+mod counter;
+mod register;
+mod sequence;
+mod set;
+mod value;
+
+pub use self::clock::{Hlc, ReplicaId, Timestamp};
+pub use self::counter::Counter;
+pub use self::register::LwwRegister;
+pub use self::sequence::{Sequence, Text};
+pub use self::set::OrSet;
+pub use self::value::CrdtValue;
+
+use self::codec::{Encoding, Reader, Writer};
+use crate::contract::properties::bytes::Bytes;
+
+use eyre::{eyre, Result};
+
+/// The kinds of CRDTs, which are the first byte of their encoding.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrdtKind {
+    LwwRegister = 1,
+    Counter = 2,
+    OrSet = 3,
+    Sequence = 4,
+}
+impl TryFrom<u8> for CrdtKind {
+    type Error = eyre::Report;
+
+    fn try_from(value: u8) -> Result<Self> {
+        Ok(match value {
+            1 => Self::LwwRegister,
+            2 => Self::Counter,
+            3 => Self::OrSet,
+            4 => Self::Sequence,
+            v => return Err(eyre!("{} is not a kind of CRDT", v)),
+        })
+    }
+}
+
+/// Implemented by every CRDT.
+pub trait Crdt: Default + Encoding {
+    const KIND: CrdtKind;
+
+    /// Merges the edits in `other` into `self`.
+    fn merge(&mut self, other: &Self);
+
+    fn to_bytes(&self) -> Bytes {
+        let mut w = Writer::new();
+        w.u8(Self::KIND as u8);
+        self.encode(&mut w);
+        w.finish().into()
+    }
+
+    /// Decodes the encoding of [`Crdt::to_bytes`]. Empty bytes are an empty
+    /// CRDT.
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.is_empty() {
+            return Ok(Self::default());
+        }
+        let mut r = Reader::new(bytes);
+        let kind = CrdtKind::try_from(r.u8()?)?;
+        if kind != Self::KIND {
+            return Err(eyre!("Expected a {:?}, but found a {:?}", Self::KIND, kind));
+        }
+        let this = Self::decode(&mut r)?;
+        r.finish()?;
+        Ok(this)
+    }
+}
+
+/// Merges the encoded CRDT `delta` into the encoded CRDT `state`, whatever
+/// their kind. Used by the `Engine` to apply `StateAction::Merge`.
+///
+/// # Errors
+/// Will error if either is not a valid CRDT, or if they are different kinds.
+pub fn merge_bytes(state: &[u8], delta: &[u8]) -> Result<Bytes> {
+    fn merge<C: Crdt>(state: &[u8], delta: &[u8]) -> Result<Bytes> {
+        let mut merged = C::from_bytes(state)?;
+        merged.merge(&C::from_bytes(delta)?);
+        Ok(merged.to_bytes())
+    }
+
+    let Some(&kind) = delta.first() else {
+        return Ok(state.into());
+    };
+    match CrdtKind::try_from(kind)? {
+        CrdtKind::LwwRegister => merge::<LwwRegister>(state, delta),
+        CrdtKind::Counter => merge::<Counter>(state, delta),
+        CrdtKind::OrSet => merge::<OrSet>(state, delta),
+        CrdtKind::Sequence => merge::<Sequence>(state, delta),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::property::{PropertyAction, StateAction};
+    use crate::action::Collaction;
+    use crate::baseline::BaselineKind;
+    use crate::contract::properties::dynamic::{DynTpProperty, TpPrimitiveType, TpPropertyType};
+    use crate::contract::properties::states::{StateHandle, StateId};
+    use crate::contract::ContractSchema;
+    use crate::engine::Engine;
+    use crate::realm::{Realm, RealmID};
+
+    /// An engine with one object, whose only state is `Bytes`.
+    fn replica() -> (Engine, StateHandle<Bytes>) {
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let schema = ContractSchema::new("teleportal.test.crdt", (0, 1, 0))
+            .with_state("doc", TpPropertyType::Primitive(TpPrimitiveType::Bytes));
+        let c = baseline.register_dyn_contract(&schema).unwrap();
+        let states = [DynTpProperty::from(Bytes::new())];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let doc = baseline
+            .bind_state(StateId::new(0, c.handle()), obj)
+            .unwrap();
+        (engine, doc)
+    }
+
+    fn merge(engine: &mut Engine, doc: StateHandle<Bytes>, delta: &impl Crdt) -> bool {
+        let action = StateAction::merge(doc, delta.to_bytes());
+        let collaction = Collaction::new(vec![PropertyAction::from(action).into()]);
+        engine.apply(collaction).is_ok()
+    }
+
+    fn text(engine: &Engine, doc: StateHandle<Bytes>) -> String {
+        let bytes = &engine.realm().baseline(BaselineKind::Fork)[doc].value;
+        Text::from_bytes(bytes).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_replicas_converge() {
+        let (mut a, doc_a) = replica();
+        let (mut b, doc_b) = replica();
+        let mut clock_a = Hlc::new(ReplicaId(1));
+        let mut clock_b = Hlc::new(ReplicaId(2));
+
+        // Both replicas start from the same text.
+        let mut base = Text::default();
+        let hello = base.insert_str(0, "hello", &mut clock_a, 1).unwrap();
+        assert!(merge(&mut a, doc_a, &hello));
+        assert!(merge(&mut b, doc_b, &hello));
+        clock_b.observe(clock_a.last());
+
+        // Concurrent edits, which each replica applies locally first.
+        let mut text_a = Text::default();
+        text_a.merge(&hello);
+        let mut text_b = text_a.clone();
+        let edit_a = text_a.insert_str(5, " world", &mut clock_a, 2).unwrap();
+        let edit_b = [
+            text_b.remove(0).unwrap(),
+            text_b.insert_str(0, "J", &mut clock_b, 2).unwrap(),
+        ];
+        assert!(merge(&mut a, doc_a, &edit_a));
+        for edit in &edit_b {
+            assert!(merge(&mut b, doc_b, edit));
+        }
+
+        // Then they receive each other's edits, in a different order, and
+        // some of them twice.
+        for edit in edit_b.iter().rev().chain(&edit_b) {
+            assert!(merge(&mut a, doc_a, edit));
+        }
+        assert!(merge(&mut b, doc_b, &edit_a));
+        assert!(merge(&mut b, doc_b, &edit_a));
+        assert_eq!(text(&a, doc_a), "Jello world");
+        assert_eq!(text(&b, doc_b), "Jello world");
+
+        // A rejected collaction reverses its merges.
+        let mut counter = Counter::default();
+        let delta = counter.add(ReplicaId(1), 3);
+        let edit = text_a.insert_str(0, "!", &mut clock_a, 3).unwrap();
+        let collaction = Collaction::new(vec![
+            PropertyAction::from(StateAction::merge(doc_a, edit.to_bytes())).into(),
+            PropertyAction::from(StateAction::merge(doc_a, delta.to_bytes())).into(),
+        ]);
+        let rejected = a.apply(collaction).unwrap_err();
+        assert_eq!(rejected.rejection().unwrap().action, 1);
+        assert_eq!(text(&a, doc_a), "Jello world");
+    }
+}
@@ -0,0 +1,97 @@
+use super::codec::{Encoding, Reader, Writer};
+use super::{Crdt, CrdtKind, CrdtValue, Hlc, Timestamp};
+
+use eyre::Result;
+
+/// A single value, where the latest write wins.
+///
+/// Unlike a plain state, "latest" is decided by the [`Timestamp`] of the
+/// write, and not by the order that writes are received in. So every replica
+/// agrees on the value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LwwRegister {
+    entry: Option<(Timestamp, Vec<u8>)>,
+}
+impl LwwRegister {
+    /// The value, or `None` if it was never set.
+    pub fn get<T: CrdtValue>(&self) -> Result<Option<T>> {
+        self.entry
+            .as_ref()
+            .map(|(_, v)| T::from_crdt_bytes(v))
+            .transpose()
+    }
+
+    /// When the value was set.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        self.entry.as_ref().map(|(t, _)| *t)
+    }
+
+    /// Sets the value, returning the delta.
+    pub fn set<T: CrdtValue>(&mut self, value: &T, clock: &mut Hlc, wall_millis: u64) -> Self {
+        if let Some(t) = self.timestamp() {
+            clock.observe(t);
+        }
+        let delta = Self {
+            entry: Some((clock.tick(wall_millis), value.to_crdt_bytes())),
+        };
+        self.merge(&delta);
+        delta
+    }
+}
+impl Crdt for LwwRegister {
+    const KIND: CrdtKind = CrdtKind::LwwRegister;
+
+    fn merge(&mut self, other: &Self) {
+        if other.timestamp() > self.timestamp() {
+            self.entry = other.entry.clone();
+        }
+    }
+}
+impl Encoding for LwwRegister {
+    fn encode(&self, w: &mut Writer) {
+        match &self.entry {
+            None => w.u8(0),
+            Some((t, v)) => {
+                w.u8(1);
+                w.timestamp(*t);
+                w.bytes(v);
+            }
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let entry = match r.u8()? {
+            0 => None,
+            _ => Some((r.timestamp()?, r.bytes()?.to_vec())),
+        };
+        Ok(Self { entry })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::properties::crdt::ReplicaId;
+
+    #[test]
+    fn test_register() {
+        let mut a = LwwRegister::default();
+        let mut b = LwwRegister::default();
+        let mut clock_a = Hlc::new(ReplicaId(1));
+        let mut clock_b = Hlc::new(ReplicaId(2));
+        assert_eq!(a.get::<String>().unwrap(), None);
+
+        let delta_a = a.set(&"red".to_string(), &mut clock_a, 10);
+        let delta_b = b.set(&"blue".to_string(), &mut clock_b, 20);
+        a.merge(&delta_b);
+        b.merge(&delta_a);
+        assert_eq!(a, b);
+        assert_eq!(a.get::<String>().unwrap().as_deref(), Some("blue"));
+
+        // Writes that knew of the current value win, even with an older wall clock
+        let delta = a.set(&"green".to_string(), &mut clock_a, 0);
+        b.merge(&delta);
+        assert_eq!(b.get::<String>().unwrap().as_deref(), Some("green"));
+        assert_eq!(LwwRegister::from_bytes(&b.to_bytes()).unwrap(), b);
+    }
+}
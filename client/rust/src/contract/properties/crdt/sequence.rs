@@ -0,0 +1,269 @@
+use super::codec::{Encoding, Reader, Writer};
+use super::{Crdt, CrdtKind, CrdtValue, Hlc, Timestamp};
+
+use eyre::{eyre, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Node {
+    /// The node that this was inserted after, or `None` if it was inserted at
+    /// the start.
+    after: Option<Timestamp>,
+    value: Vec<u8>,
+    /// Removed nodes are kept, since later inserts may refer to them.
+    removed: bool,
+}
+
+/// An ordered list that replicas can insert into and remove from concurrently,
+/// as a replicated growable array (RGA).
+///
+/// Every element is identified by the [`Timestamp`] of its insert, and
+/// remembers the element that it was inserted after. When several elements
+/// were inserted after the same one, the newest comes first. So concurrent
+/// inserts at the same place are kept together, in the same order on every
+/// replica.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sequence {
+    nodes: BTreeMap<Timestamp, Node>,
+}
+impl Sequence {
+    /// The ids of all nodes, including removed ones, in order.
+    fn order(&self) -> Vec<Timestamp> {
+        let mut children: BTreeMap<Option<Timestamp>, Vec<Timestamp>> = BTreeMap::new();
+        for (id, node) in &self.nodes {
+            children.entry(node.after).or_default().push(*id);
+        }
+        // Children are in ascending order, so the newest is popped first
+        let mut stack = children.remove(&None).unwrap_or_default();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(id) = stack.pop() {
+            order.push(id);
+            if let Some(c) = children.remove(&Some(id)) {
+                stack.extend(c);
+            }
+        }
+        order
+    }
+
+    /// The ids of the nodes that weren't removed, in order.
+    fn visible(&self) -> Vec<Timestamp> {
+        let mut order = self.order();
+        order.retain(|id| !self.nodes[id].removed);
+        order
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.values().filter(|n| !n.removed).count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn values<T: CrdtValue>(&self) -> Result<Vec<T>> {
+        self.visible()
+            .iter()
+            .map(|id| T::from_crdt_bytes(&self.nodes[id].value))
+            .collect()
+    }
+
+    /// Inserts `values` so that the first is at `index`, returning the delta.
+    ///
+    /// # Errors
+    /// Will error if `index` is greater than the length.
+    pub fn insert<'a, T: CrdtValue + 'a>(
+        &mut self,
+        index: usize,
+        values: impl IntoIterator<Item = &'a T>,
+        clock: &mut Hlc,
+        wall_millis: u64,
+    ) -> Result<Self> {
+        let visible = self.visible();
+        if index > visible.len() {
+            return Err(eyre!(
+                "Index {} is out of bounds for length {}",
+                index,
+                visible.len()
+            ));
+        }
+        // New nodes must be newer than the nodes that they are inserted before
+        if let Some(newest) = self.nodes.keys().next_back() {
+            clock.observe(*newest);
+        }
+
+        let mut after = index.checked_sub(1).map(|i| visible[i]);
+        let mut delta = Self::default();
+        for value in values {
+            let id = clock.tick(wall_millis);
+            let node = Node {
+                after,
+                value: value.to_crdt_bytes(),
+                removed: false,
+            };
+            delta.nodes.insert(id, node);
+            after = Some(id);
+        }
+        self.merge(&delta);
+        Ok(delta)
+    }
+
+    /// Removes the value at `index`, returning the delta.
+    ///
+    /// # Errors
+    /// Will error if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Result<Self> {
+        let id = *self
+            .visible()
+            .get(index)
+            .ok_or_else(|| eyre!("Index {} is out of bounds for length {}", index, self.len()))?;
+        let node = self.nodes.get_mut(&id).unwrap();
+        node.removed = true;
+        Ok(Self {
+            nodes: BTreeMap::from([(id, node.clone())]),
+        })
+    }
+}
+impl Crdt for Sequence {
+    const KIND: CrdtKind = CrdtKind::Sequence;
+
+    fn merge(&mut self, other: &Self) {
+        for (id, node) in &other.nodes {
+            self.nodes
+                .entry(*id)
+                .and_modify(|n| n.removed |= node.removed)
+                .or_insert_with(|| node.clone());
+        }
+    }
+}
+impl Encoding for Sequence {
+    fn encode(&self, w: &mut Writer) {
+        w.count(self.nodes.len());
+        for (id, node) in &self.nodes {
+            w.timestamp(*id);
+            match node.after {
+                None => w.u8(node.removed as u8),
+                Some(after) => {
+                    w.u8(2 | node.removed as u8);
+                    w.timestamp(after);
+                }
+            }
+            w.bytes(&node.value);
+        }
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let mut nodes = BTreeMap::new();
+        for _ in 0..r.count()? {
+            let id = r.timestamp()?;
+            let flags = r.u8()?;
+            let after = match flags & 2 {
+                0 => None,
+                _ => Some(r.timestamp()?),
+            };
+            let node = Node {
+                after,
+                value: r.bytes()?.to_vec(),
+                removed: flags & 1 != 0,
+            };
+            nodes.insert(id, node);
+        }
+        Ok(Self { nodes })
+    }
+}
+
+/// A [`Sequence`] of `char`s, for collaboratively edited text.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Text(pub Sequence);
+impl Text {
+    /// The number of chars.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Inserts `s` so that it starts at char `index`, returning the delta.
+    pub fn insert_str(
+        &mut self,
+        index: usize,
+        s: &str,
+        clock: &mut Hlc,
+        wall_millis: u64,
+    ) -> Result<Self> {
+        let chars: Vec<char> = s.chars().collect();
+        self.0.insert(index, &chars, clock, wall_millis).map(Self)
+    }
+
+    /// Removes the char at `index`, returning the delta.
+    pub fn remove(&mut self, index: usize) -> Result<Self> {
+        self.0.remove(index).map(Self)
+    }
+}
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for id in self.0.visible() {
+            let c = char::from_crdt_bytes(&self.0.nodes[&id].value)
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+impl Crdt for Text {
+    const KIND: CrdtKind = CrdtKind::Sequence;
+
+    fn merge(&mut self, other: &Self) {
+        self.0.merge(&other.0)
+    }
+}
+impl Encoding for Text {
+    fn encode(&self, w: &mut Writer) {
+        self.0.encode(w)
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self> {
+        Sequence::decode(r).map(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::properties::crdt::ReplicaId;
+
+    #[test]
+    fn test_sequence() {
+        let mut a = Sequence::default();
+        let mut clock_a = Hlc::new(ReplicaId(1));
+        let mut clock_b = Hlc::new(ReplicaId(2));
+        let base = a.insert(0, &[1u8, 2, 3], &mut clock_a, 1).unwrap();
+        let mut b = Sequence::default();
+        b.merge(&base);
+
+        // Concurrent inserts at the same place stay together
+        let delta_a = a.insert(1, &[10u8, 11], &mut clock_a, 2).unwrap();
+        let delta_b = b.insert(1, &[20u8, 21], &mut clock_b, 3).unwrap();
+        let remove = b.remove(0).unwrap();
+        a.merge(&remove);
+        a.merge(&delta_b);
+        b.merge(&delta_a);
+        assert_eq!(a, b);
+        assert_eq!(a.values::<u8>().unwrap(), vec![20, 21, 10, 11, 2, 3]);
+        assert!(a.insert(7, &[0u8], &mut clock_a, 4).is_err());
+        assert_eq!(Sequence::from_bytes(&a.to_bytes()).unwrap(), a);
+    }
+
+    #[test]
+    fn test_text() {
+        let mut clock = Hlc::new(ReplicaId(1));
+        let mut text = Text::default();
+        text.insert_str(0, "helo", &mut clock, 1).unwrap();
+        text.insert_str(3, "l", &mut clock, 1).unwrap();
+        text.remove(0).unwrap();
+        assert_eq!(text.to_string(), "ello");
+        assert_eq!(text.len(), 4);
+    }
+}
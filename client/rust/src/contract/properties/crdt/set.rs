@@ -0,0 +1,146 @@
+use super::codec::{Encoding, Reader, Writer};
+use super::{Crdt, CrdtKind, CrdtValue, Hlc, Timestamp};
+
+use eyre::Result;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// An observed-remove set.
+///
+/// Every insert is tagged with a unique [`Timestamp`], and a remove only
+/// removes the tags that its replica had observed. So when an insert and a
+/// remove of the same value are concurrent, the insert wins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrSet {
+    /// The tags of the inserts of each value that weren't removed.
+    adds: BTreeMap<Vec<u8>, BTreeSet<Timestamp>>,
+    /// The tags that were removed. These are kept so that an insert received
+    /// after its remove stays removed.
+    removed: BTreeSet<Timestamp>,
+}
+impl OrSet {
+    pub fn contains<T: CrdtValue>(&self, value: &T) -> bool {
+        self.adds.contains_key(&value.to_crdt_bytes())
+    }
+
+    pub fn len(&self) -> usize {
+        self.adds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.adds.is_empty()
+    }
+
+    /// The values in the set, ordered by their encoding.
+    pub fn values<T: CrdtValue>(&self) -> Result<Vec<T>> {
+        self.adds.keys().map(|v| T::from_crdt_bytes(v)).collect()
+    }
+
+    /// Inserts `value`, returning the delta.
+    pub fn insert<T: CrdtValue>(&mut self, value: &T, clock: &mut Hlc, wall_millis: u64) -> Self {
+        let delta = Self {
+            adds: BTreeMap::from([(
+                value.to_crdt_bytes(),
+                BTreeSet::from([clock.tick(wall_millis)]),
+            )]),
+            removed: BTreeSet::new(),
+        };
+        self.merge(&delta);
+        delta
+    }
+
+    /// Removes `value`, returning the delta. The delta is empty if the set
+    /// didn't contain `value`.
+    pub fn remove<T: CrdtValue>(&mut self, value: &T) -> Self {
+        let delta = Self {
+            adds: BTreeMap::new(),
+            removed: self
+                .adds
+                .get(&value.to_crdt_bytes())
+                .cloned()
+                .unwrap_or_default(),
+        };
+        self.merge(&delta);
+        delta
+    }
+}
+impl Crdt for OrSet {
+    const KIND: CrdtKind = CrdtKind::OrSet;
+
+    fn merge(&mut self, other: &Self) {
+        self.removed.extend(other.removed.iter().copied());
+        for (value, tags) in &other.adds {
+            self.adds
+                .entry(value.clone())
+                .or_default()
+                .extend(tags.iter().copied());
+        }
+        let removed = &self.removed;
+        self.adds.retain(|_, tags| {
+            tags.retain(|t| !removed.contains(t));
+            !tags.is_empty()
+        });
+    }
+}
+impl Encoding for OrSet {
+    fn encode(&self, w: &mut Writer) {
+        w.count(self.adds.len());
+        for (value, tags) in &self.adds {
+            w.bytes(value);
+            w.count(tags.len());
+            tags.iter().for_each(|t| w.timestamp(*t));
+        }
+        w.count(self.removed.len());
+        self.removed.iter().for_each(|t| w.timestamp(*t));
+    }
+
+    fn decode(r: &mut Reader) -> Result<Self> {
+        let mut adds = BTreeMap::new();
+        for _ in 0..r.count()? {
+            let value = r.bytes()?.to_vec();
+            let tags = (0..r.count()?)
+                .map(|_| r.timestamp())
+                .collect::<Result<_>>()?;
+            adds.insert(value, tags);
+        }
+        let removed = (0..r.count()?)
+            .map(|_| r.timestamp())
+            .collect::<Result<_>>()?;
+        Ok(Self { adds, removed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::properties::crdt::ReplicaId;
+
+    #[test]
+    fn test_or_set() {
+        let mut a = OrSet::default();
+        let mut b = OrSet::default();
+        let mut clock_a = Hlc::new(ReplicaId(1));
+        let mut clock_b = Hlc::new(ReplicaId(2));
+
+        let insert = a.insert(&7u32, &mut clock_a, 1);
+        b.merge(&insert);
+        assert!(b.contains(&7u32));
+
+        // A concurrent remove and re-insert: the insert wins
+        let remove = a.remove(&7u32);
+        let reinsert = b.insert(&7u32, &mut clock_b, 2);
+        let other = b.insert(&9u32, &mut clock_b, 3);
+        for delta in [&reinsert, &other] {
+            a.merge(delta);
+        }
+        b.merge(&remove);
+        assert_eq!(a, b);
+        assert_eq!(a.values::<u32>().unwrap(), vec![7, 9]);
+
+        // A remove received before its insert still removes it
+        let mut c = OrSet::default();
+        c.merge(&b.remove(&9u32));
+        c.merge(&other);
+        assert!(!c.contains(&9u32));
+        assert_eq!(OrSet::from_bytes(&a.to_bytes()).unwrap(), a);
+    }
+}
@@ -0,0 +1,75 @@
+use crate::contract::properties::bytes::Bytes;
+
+use eyre::{eyre, Result};
+
+/// A value that can be held by a CRDT.
+///
+/// CRDTs hold their values encoded, so that they can be merged without knowing
+/// their type. Values that are equal must have equal encodings.
+pub trait CrdtValue: Sized {
+    fn to_crdt_bytes(&self) -> Vec<u8>;
+    fn from_crdt_bytes(bytes: &[u8]) -> Result<Self>;
+}
+
+macro_rules! impl_numeric {
+    ($($t:ty),*) => {$(
+        impl CrdtValue for $t {
+            fn to_crdt_bytes(&self) -> Vec<u8> {
+                self.to_le_bytes().to_vec()
+            }
+
+            fn from_crdt_bytes(bytes: &[u8]) -> Result<Self> {
+                let bytes = bytes.try_into().map_err(|_| {
+                    eyre!("Expected {} bytes, but got {}", core::mem::size_of::<$t>(), bytes.len())
+                })?;
+                Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    )*};
+}
+impl_numeric!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl CrdtValue for bool {
+    fn to_crdt_bytes(&self) -> Vec<u8> {
+        vec![*self as u8]
+    }
+
+    fn from_crdt_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            _ => Err(eyre!("Invalid bool")),
+        }
+    }
+}
+
+impl CrdtValue for char {
+    fn to_crdt_bytes(&self) -> Vec<u8> {
+        (*self as u32).to_crdt_bytes()
+    }
+
+    fn from_crdt_bytes(bytes: &[u8]) -> Result<Self> {
+        let c = u32::from_crdt_bytes(bytes)?;
+        char::from_u32(c).ok_or_else(|| eyre!("{:#x} is not a char", c))
+    }
+}
+
+impl CrdtValue for String {
+    fn to_crdt_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_crdt_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(core::str::from_utf8(bytes)?.to_owned())
+    }
+}
+
+impl CrdtValue for Bytes {
+    fn to_crdt_bytes(&self) -> Vec<u8> {
+        self.to_vec()
+    }
+
+    fn from_crdt_bytes(bytes: &[u8]) -> Result<Self> {
+        Ok(bytes.into())
+    }
+}
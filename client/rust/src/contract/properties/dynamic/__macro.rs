@@ -18,6 +18,12 @@ macro_rules! dyn_enum_helper {
             String(String),
             ObjectHandle($crate::object::ObjectHandle),
             ContractDataHandle($crate::contract::ContractDataHandle),
+            Vec2($crate::contract::properties::composite::Vec2),
+            Vec3($crate::contract::properties::composite::Vec3),
+            Quat($crate::contract::properties::composite::Quat),
+            Color($crate::contract::properties::composite::Color),
+            Transform($crate::contract::properties::composite::Transform),
+            Bytes($crate::contract::properties::bytes::Bytes),
         }
     };
     ($ident:ident, $t:tt$( | $attr:meta)?) => {
@@ -38,6 +44,12 @@ macro_rules! dyn_enum_helper {
             String($t<String>),
             ObjectHandle($t<$crate::object::ObjectHandle>),
             ContractDataHandle($t<$crate::contract::ContractDataHandle>),
+            Vec2($t<$crate::contract::properties::composite::Vec2>),
+            Vec3($t<$crate::contract::properties::composite::Vec3>),
+            Quat($t<$crate::contract::properties::composite::Quat>),
+            Color($t<$crate::contract::properties::composite::Color>),
+            Transform($t<$crate::contract::properties::composite::Transform>),
+            Bytes($t<$crate::contract::properties::bytes::Bytes>),
         }
     };
     ($ident:ident, $outer:tt, $inner:tt$( | $attr:meta)?) => {
@@ -58,6 +70,12 @@ macro_rules! dyn_enum_helper {
             String($outer<$inner<String>>),
             ObjectHandle($outer<$inner<$crate::object::ObjectHandle>>),
             ContractDataHandle($outer<$inner<$crate::contract::ContractDataHandle>>),
+            Vec2($outer<$inner<$crate::contract::properties::composite::Vec2>>),
+            Vec3($outer<$inner<$crate::contract::properties::composite::Vec3>>),
+            Quat($outer<$inner<$crate::contract::properties::composite::Quat>>),
+            Color($outer<$inner<$crate::contract::properties::composite::Color>>),
+            Transform($outer<$inner<$crate::contract::properties::composite::Transform>>),
+            Bytes($outer<$inner<$crate::contract::properties::bytes::Bytes>>),
         }
     };
 }
@@ -67,39 +85,67 @@ macro_rules! dyn_enum_helper {
 pub(in crate::contract::properties) use dyn_enum_helper;
 
 /// Creates an enum that holds all possible types, possibly wrapped by a `container` type
+///
+/// Any module invoking this must have [`TpMap`](crate::contract::properties::traits::TpMap)
+/// in scope.
 macro_rules! DynEnum {
-    ($ident:ident, $container:tt, $prim_ident:ident, $vec_ident:ident$( | $attr:meta)?) => {
+    ($ident:ident, $container:tt, $prim_ident:ident, $vec_ident:ident, $option_ident:ident, $map_ident:ident$( | $attr:meta)?) => {
         $crate::contract::properties::dynamic::__macro::dyn_enum_helper!($prim_ident, $container$( | $attr)?);
         $crate::contract::properties::dynamic::__macro::dyn_enum_helper!(
             $vec_ident, $container, Vec$( | $attr)?
         );
+        $crate::contract::properties::dynamic::__macro::dyn_enum_helper!(
+            $option_ident, $container, Option$( | $attr)?
+        );
+        $crate::contract::properties::dynamic::__macro::dyn_enum_helper!(
+            $map_ident, $container, TpMap$( | $attr)?
+        );
 
         #[derive(Debug, ::derive_more::From, ::derive_more::TryInto)]
         $(#[$attr])?
         pub enum $ident {
             Primitive($prim_ident),
             Vec($vec_ident),
+            Option($option_ident),
+            Map($map_ident),
         }
     };
-    ($ident:ident, $prim_ident:ident, $vec_ident:ident$( | $attr:meta)?) => {
+    ($ident:ident, $prim_ident:ident, $vec_ident:ident, $option_ident:ident, $map_ident:ident$( | $attr:meta)?) => {
         $crate::contract::properties::dynamic::__macro::dyn_enum_helper!($prim_ident$( | $attr)?);
         $crate::contract::properties::dynamic::__macro::dyn_enum_helper!($vec_ident, Vec$( | $attr)?);
+        $crate::contract::properties::dynamic::__macro::dyn_enum_helper!($option_ident, Option$( | $attr)?);
+        $crate::contract::properties::dynamic::__macro::dyn_enum_helper!($map_ident, TpMap$( | $attr)?);
 
         #[derive(Debug, ::derive_more::From, ::derive_more::TryInto)]
         $(#[$attr])?
         pub enum $ident {
             Primitive($prim_ident),
             Vec($vec_ident),
+            Option($option_ident),
+            Map($map_ident),
         }
     };
     ($ident:ident, $container:tt$( | $attr:meta)?) => {
         ::paste::paste! {
-            DynEnum!($ident, $container, [<$ident Primitive>], [<$ident Vec>]$( | $attr)?);
+            DynEnum!(
+                $ident,
+                $container,
+                [<$ident Primitive>],
+                [<$ident Vec>],
+                [<$ident Option>],
+                [<$ident Map>]$( | $attr)?
+            );
         }
     };
     ($ident:ident$( | $attr:meta)?) => {
         ::paste::paste! {
-            DynEnum!($ident, [<$ident Primitive>], [<$ident Vec>]$( | $attr)?);
+            DynEnum!(
+                $ident,
+                [<$ident Primitive>],
+                [<$ident Vec>],
+                [<$ident Option>],
+                [<$ident Map>]$( | $attr)?
+            );
         }
     };
 }
@@ -116,7 +162,10 @@ macro_rules! DynTpPropId {
                 pub fn new(contract: $crate::contract::ContractDataHandle, idx: usize, typ: $crate::contract::properties::dynamic::TpPropertyType) -> Self {
                     use $crate::contract::properties::dynamic::TpPrimitiveType;
                     use $crate::object::ObjectHandle;
+                    use $crate::contract::properties::bytes::Bytes;
+                    use $crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
                     use $crate::contract::properties::dynamic::TpPropertyType;
+                    use $crate::contract::properties::traits::TpMap;
 
                     match typ {
                         TpPropertyType::Primitive(dt) => {
@@ -135,6 +184,12 @@ macro_rules! DynTpPropId {
                                 TpPrimitiveType::String => $container::<String>::new(idx, contract).into(),
                                 TpPrimitiveType::ObjectHandle => $container::<ObjectHandle>::new(idx, contract).into(),
                                 TpPrimitiveType::ContractDataHandle => $container::<ContractDataHandle>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec2 => $container::<Vec2>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec3 => $container::<Vec3>::new(idx, contract).into(),
+                                TpPrimitiveType::Quat => $container::<Quat>::new(idx, contract).into(),
+                                TpPrimitiveType::Color => $container::<Color>::new(idx, contract).into(),
+                                TpPrimitiveType::Transform => $container::<Transform>::new(idx, contract).into(),
+                                TpPrimitiveType::Bytes => $container::<Bytes>::new(idx, contract).into(),
                             };
                             single.into()
                         },
@@ -154,8 +209,64 @@ macro_rules! DynTpPropId {
                                 TpPrimitiveType::String => $container::<Vec<String>>::new(idx, contract).into(),
                                 TpPrimitiveType::ObjectHandle => $container::<Vec<ObjectHandle>>::new(idx, contract).into(),
                                 TpPrimitiveType::ContractDataHandle => $container::<Vec<ContractDataHandle>>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec2 => $container::<Vec<Vec2>>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec3 => $container::<Vec<Vec3>>::new(idx, contract).into(),
+                                TpPrimitiveType::Quat => $container::<Vec<Quat>>::new(idx, contract).into(),
+                                TpPrimitiveType::Color => $container::<Vec<Color>>::new(idx, contract).into(),
+                                TpPrimitiveType::Transform => $container::<Vec<Transform>>::new(idx, contract).into(),
+                                TpPrimitiveType::Bytes => $container::<Vec<Bytes>>::new(idx, contract).into(),
                             };
                             vec.into()
+                        },
+                        TpPropertyType::Option(dt) => {
+                            let option: [<$ident Option>] = match dt {
+                                TpPrimitiveType::U8 => $container::<Option<u8>>::new(idx, contract).into(),
+                                TpPrimitiveType::U16 => $container::<Option<u16>>::new(idx, contract).into(),
+                                TpPrimitiveType::U32 => $container::<Option<u32>>::new(idx, contract).into(),
+                                TpPrimitiveType::U64 => $container::<Option<u64>>::new(idx, contract).into(),
+                                TpPrimitiveType::I8 => $container::<Option<i8>>::new(idx, contract).into(),
+                                TpPrimitiveType::I16 => $container::<Option<i16>>::new(idx, contract).into(),
+                                TpPrimitiveType::I32 => $container::<Option<i32>>::new(idx, contract).into(),
+                                TpPrimitiveType::I64 => $container::<Option<i64>>::new(idx, contract).into(),
+                                TpPrimitiveType::Bool => $container::<Option<bool>>::new(idx, contract).into(),
+                                TpPrimitiveType::F32 => $container::<Option<f32>>::new(idx, contract).into(),
+                                TpPrimitiveType::F64 => $container::<Option<f64>>::new(idx, contract).into(),
+                                TpPrimitiveType::String => $container::<Option<String>>::new(idx, contract).into(),
+                                TpPrimitiveType::ObjectHandle => $container::<Option<ObjectHandle>>::new(idx, contract).into(),
+                                TpPrimitiveType::ContractDataHandle => $container::<Option<ContractDataHandle>>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec2 => $container::<Option<Vec2>>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec3 => $container::<Option<Vec3>>::new(idx, contract).into(),
+                                TpPrimitiveType::Quat => $container::<Option<Quat>>::new(idx, contract).into(),
+                                TpPrimitiveType::Color => $container::<Option<Color>>::new(idx, contract).into(),
+                                TpPrimitiveType::Transform => $container::<Option<Transform>>::new(idx, contract).into(),
+                                TpPrimitiveType::Bytes => $container::<Option<Bytes>>::new(idx, contract).into(),
+                            };
+                            option.into()
+                        }
+                        TpPropertyType::Map(dt) => {
+                            let map: [<$ident Map>] = match dt {
+                                TpPrimitiveType::U8 => $container::<TpMap<u8>>::new(idx, contract).into(),
+                                TpPrimitiveType::U16 => $container::<TpMap<u16>>::new(idx, contract).into(),
+                                TpPrimitiveType::U32 => $container::<TpMap<u32>>::new(idx, contract).into(),
+                                TpPrimitiveType::U64 => $container::<TpMap<u64>>::new(idx, contract).into(),
+                                TpPrimitiveType::I8 => $container::<TpMap<i8>>::new(idx, contract).into(),
+                                TpPrimitiveType::I16 => $container::<TpMap<i16>>::new(idx, contract).into(),
+                                TpPrimitiveType::I32 => $container::<TpMap<i32>>::new(idx, contract).into(),
+                                TpPrimitiveType::I64 => $container::<TpMap<i64>>::new(idx, contract).into(),
+                                TpPrimitiveType::Bool => $container::<TpMap<bool>>::new(idx, contract).into(),
+                                TpPrimitiveType::F32 => $container::<TpMap<f32>>::new(idx, contract).into(),
+                                TpPrimitiveType::F64 => $container::<TpMap<f64>>::new(idx, contract).into(),
+                                TpPrimitiveType::String => $container::<TpMap<String>>::new(idx, contract).into(),
+                                TpPrimitiveType::ObjectHandle => $container::<TpMap<ObjectHandle>>::new(idx, contract).into(),
+                                TpPrimitiveType::ContractDataHandle => $container::<TpMap<ContractDataHandle>>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec2 => $container::<TpMap<Vec2>>::new(idx, contract).into(),
+                                TpPrimitiveType::Vec3 => $container::<TpMap<Vec3>>::new(idx, contract).into(),
+                                TpPrimitiveType::Quat => $container::<TpMap<Quat>>::new(idx, contract).into(),
+                                TpPrimitiveType::Color => $container::<TpMap<Color>>::new(idx, contract).into(),
+                                TpPrimitiveType::Transform => $container::<TpMap<Transform>>::new(idx, contract).into(),
+                                TpPrimitiveType::Bytes => $container::<TpMap<Bytes>>::new(idx, contract).into(),
+                            };
+                            map.into()
                         }
                     }
                 }
@@ -171,11 +282,13 @@ pub(in crate::contract::properties) use DynTpPropId;
 #[doc(hidden)]
 #[macro_export]
 macro_rules! apply_to_dyn {
-    ($mod_path:path, $enum_type:ident, $prim_type:ident, $vec_type:ident, $dyn_prop:expr, $closure:expr) => {{
+    ($mod_path:path, $enum_type:ident, $prim_type:ident, $vec_type:ident, $option_type:ident, $map_type:ident, $dyn_prop:expr, $closure:expr) => {{
         ::tp_client::paste::paste! {
             use $mod_path::$enum_type;
             use $mod_path::$prim_type as PS;
             use $mod_path::$vec_type as PV;
+            use $mod_path::$option_type as PO;
+            use $mod_path::$map_type as PM;
         }
         match $dyn_prop {
             $enum_type::Primitive(s) => match s {
@@ -193,6 +306,12 @@ macro_rules! apply_to_dyn {
                 PS::String(id) => $closure(id),
                 PS::ObjectHandle(id) => $closure(id),
                 PS::ContractDataHandle(id) => $closure(id),
+                PS::Vec2(id) => $closure(id),
+                PS::Vec3(id) => $closure(id),
+                PS::Quat(id) => $closure(id),
+                PS::Color(id) => $closure(id),
+                PS::Transform(id) => $closure(id),
+                PS::Bytes(id) => $closure(id),
             },
             $enum_type::Vec(s) => match s {
                 PV::U8(id) => $closure(id),
@@ -209,6 +328,56 @@ macro_rules! apply_to_dyn {
                 PV::String(id) => $closure(id),
                 PV::ObjectHandle(id) => $closure(id),
                 PV::ContractDataHandle(id) => $closure(id),
+                PV::Vec2(id) => $closure(id),
+                PV::Vec3(id) => $closure(id),
+                PV::Quat(id) => $closure(id),
+                PV::Color(id) => $closure(id),
+                PV::Transform(id) => $closure(id),
+                PV::Bytes(id) => $closure(id),
+            },
+            $enum_type::Option(s) => match s {
+                PO::U8(id) => $closure(id),
+                PO::U16(id) => $closure(id),
+                PO::U32(id) => $closure(id),
+                PO::U64(id) => $closure(id),
+                PO::I8(id) => $closure(id),
+                PO::I16(id) => $closure(id),
+                PO::I32(id) => $closure(id),
+                PO::I64(id) => $closure(id),
+                PO::Bool(id) => $closure(id),
+                PO::F32(id) => $closure(id),
+                PO::F64(id) => $closure(id),
+                PO::String(id) => $closure(id),
+                PO::ObjectHandle(id) => $closure(id),
+                PO::ContractDataHandle(id) => $closure(id),
+                PO::Vec2(id) => $closure(id),
+                PO::Vec3(id) => $closure(id),
+                PO::Quat(id) => $closure(id),
+                PO::Color(id) => $closure(id),
+                PO::Transform(id) => $closure(id),
+                PO::Bytes(id) => $closure(id),
+            },
+            $enum_type::Map(s) => match s {
+                PM::U8(id) => $closure(id),
+                PM::U16(id) => $closure(id),
+                PM::U32(id) => $closure(id),
+                PM::U64(id) => $closure(id),
+                PM::I8(id) => $closure(id),
+                PM::I16(id) => $closure(id),
+                PM::I32(id) => $closure(id),
+                PM::I64(id) => $closure(id),
+                PM::Bool(id) => $closure(id),
+                PM::F32(id) => $closure(id),
+                PM::F64(id) => $closure(id),
+                PM::String(id) => $closure(id),
+                PM::ObjectHandle(id) => $closure(id),
+                PM::ContractDataHandle(id) => $closure(id),
+                PM::Vec2(id) => $closure(id),
+                PM::Vec3(id) => $closure(id),
+                PM::Quat(id) => $closure(id),
+                PM::Color(id) => $closure(id),
+                PM::Transform(id) => $closure(id),
+                PM::Bytes(id) => $closure(id),
             },
         }
     }};
@@ -227,6 +396,8 @@ macro_rules! apply_to_state_id {
             DynStateId,
             DynStateIdPrimitive,
             DynStateIdVec,
+            DynStateIdOption,
+            DynStateIdMap,
             $dyn_state_id,
             $closure
         )
@@ -246,6 +417,8 @@ macro_rules! apply_to_channel_id {
             DynChannelId,
             DynChannelIdPrimitive,
             DynChannelIdVec,
+            DynChannelIdOption,
+            DynChannelIdMap,
             $dyn_channel_id,
             $closure
         )
@@ -265,6 +438,8 @@ macro_rules! apply_to_prop {
             DynTpProperty,
             DynTpPrimitive,
             DynTpVec,
+            DynTpOption,
+            DynTpMap,
             $dyn_prop,
             $closure
         )
@@ -284,6 +459,8 @@ macro_rules! apply_to_channel {
             DynChannel,
             DynChannelPrimitive,
             DynChannelVec,
+            DynChannelOption,
+            DynChannelMap,
             $dyn_prop,
             $closure
         )
@@ -303,6 +480,8 @@ macro_rules! apply_to_channel_ref {
             DynChannelRef,
             DynChannelPrimitiveRef,
             DynChannelVecRef,
+            DynChannelOptionRef,
+            DynChannelMapRef,
             $dyn_prop,
             $closure
         )
@@ -322,6 +501,8 @@ macro_rules! apply_to_channel_mut {
             DynChannelMut,
             DynChannelPrimitiveMut,
             DynChannelVecMut,
+            DynChannelOptionMut,
+            DynChannelMapMut,
             $dyn_prop,
             $closure
         )
@@ -341,6 +522,8 @@ macro_rules! apply_to_state_handle {
             DynStateHandle,
             DynStateHandlePrimitive,
             DynStateHandleVec,
+            DynStateHandleOption,
+            DynStateHandleMap,
             $dyn_handle,
             $closure
         )
@@ -360,6 +543,8 @@ macro_rules! apply_to_channel_handle {
             DynChannelHandle,
             DynChannelHandlePrimitive,
             DynChannelHandleVec,
+            DynChannelHandleOption,
+            DynChannelHandleMap,
             $dyn_handle,
             $closure
         )
@@ -379,6 +564,8 @@ macro_rules! apply_to_prop_ref {
             DynTpPropertyRef,
             DynTpPrimitiveRef,
             DynTpVecRef,
+            DynTpOptionRef,
+            DynTpMapRef,
             $dyn_prop_ref,
             $closure
         )
@@ -398,6 +585,8 @@ macro_rules! apply_to_prop_mut {
             DynTpPropertyMut,
             DynTpPrimitiveMut,
             DynTpVecMut,
+            DynTpOptionMut,
+            DynTpMapMut,
             $dyn_prop_mut,
             $closure
         )
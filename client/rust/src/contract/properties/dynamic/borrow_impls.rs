@@ -1,4 +1,5 @@
 use super::property::{
+    DynTpMap, DynTpMapMut, DynTpMapRef, DynTpOption, DynTpOptionMut, DynTpOptionRef,
     DynTpPrimitive, DynTpPrimitiveMut, DynTpPrimitiveRef, DynTpProperty, DynTpPropertyMut,
     DynTpPropertyRef, DynTpVec, DynTpVecMut, DynTpVecRef,
 };
@@ -29,9 +30,29 @@ impl<'b> BBorrow<'b, DynTpPropertyRef<'b>> for DynTpProperty {
                 }
             };
         }
+        macro_rules! helper_option {
+            ($enum:ident, $($ident:ident),+ $(,)?) => {
+                match $enum {
+                    $(
+                        DynTpOption::$ident(inner) => DynTpOptionRef::from(inner).into(),
+                    )+
+                }
+            };
+        }
+        macro_rules! helper_map {
+            ($enum:ident, $($ident:ident),+ $(,)?) => {
+                match $enum {
+                    $(
+                        DynTpMap::$ident(inner) => DynTpMapRef::from(inner).into(),
+                    )+
+                }
+            };
+        }
         match self {
             Self::Primitive(prim) => primitives!(idents, helper_primitive, prim),
             Self::Vec(vec) => primitives!(idents, helper_vec, vec),
+            Self::Option(option) => primitives!(idents, helper_option, option),
+            Self::Map(map) => primitives!(idents, helper_map, map),
         }
     }
 }
@@ -61,9 +82,29 @@ impl<'b> BBorrowMut<'b, DynTpPropertyMut<'b>> for DynTpProperty {
                 }
             };
         }
+        macro_rules! helper_option {
+            ($enum:ident, $($ident:ident),+ $(,)?) => {
+                match $enum {
+                    $(
+                        DynTpOption::$ident(inner) => DynTpOptionMut::from(inner).into(),
+                    )+
+                }
+            };
+        }
+        macro_rules! helper_map {
+            ($enum:ident, $($ident:ident),+ $(,)?) => {
+                match $enum {
+                    $(
+                        DynTpMap::$ident(inner) => DynTpMapMut::from(inner).into(),
+                    )+
+                }
+            };
+        }
         match self {
             Self::Primitive(prim) => primitives!(idents, helper_primitive, prim),
             Self::Vec(vec) => primitives!(idents, helper_vec, vec),
+            Self::Option(option) => primitives!(idents, helper_option, option),
+            Self::Map(map) => primitives!(idents, helper_map, map),
         }
     }
 }
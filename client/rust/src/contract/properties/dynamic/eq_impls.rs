@@ -1,5 +1,8 @@
-use super::property::{DynTpPrimitive, DynTpProperty, DynTpVec};
+use super::property::{DynTpMap, DynTpOption, DynTpPrimitive, DynTpProperty, DynTpVec};
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::primitives;
+use crate::contract::properties::traits::TpMap;
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
@@ -44,6 +47,32 @@ where
     }
 }
 
+impl<T> PartialEq<Option<T>> for DynTpProperty
+where
+    DynTpOption: PartialEq<Option<T>>,
+{
+    fn eq(&self, other: &Option<T>) -> bool {
+        if let Self::Option(o) = self {
+            o == other
+        } else {
+            false
+        }
+    }
+}
+
+impl<T> PartialEq<TpMap<T>> for DynTpProperty
+where
+    DynTpMap: PartialEq<TpMap<T>>,
+{
+    fn eq(&self, other: &TpMap<T>) -> bool {
+        if let Self::Map(m) = self {
+            m == other
+        } else {
+            false
+        }
+    }
+}
+
 // ---- PartialEq impls with dyn on RHS (cannot do blanket impl on LHS impl due to covered orphan rule E0210) ----
 
 macro_rules! impl_eq {
@@ -66,6 +95,18 @@ macro_rules! impl_eq {
                 other == self
             }
         }
+
+        impl PartialEq<DynTpProperty> for Option<$t> {
+            fn eq(&self, other: &DynTpProperty) -> bool {
+                other == self
+            }
+        }
+
+        impl PartialEq<DynTpProperty> for TpMap<$t> {
+            fn eq(&self, other: &DynTpProperty) -> bool {
+                other == self
+            }
+        }
     };
     // recursive case
     ($t:ty, $($tail:ty),+) => {
@@ -109,4 +150,26 @@ mod tests {
         // compare different container type, different primitive, different val
         assert_ne!(u16_1, vec![2u32])
     }
+
+    #[test]
+    fn test_partialeq_containers() {
+        let some_1 = DynTpProperty::from(Some(1u16));
+        let none = DynTpProperty::from(None::<u16>);
+        let map: TpMap<u16> = [("one".to_string(), 1u16)].into_iter().collect();
+        let dyn_map = DynTpProperty::from(map.clone());
+
+        assert_eq!(some_1, Some(1u16));
+        assert_eq!(Some(1u16), some_1);
+        assert_ne!(some_1, none);
+        assert_eq!(none, None::<u16>);
+        assert_ne!(none, None::<u32>);
+        assert_ne!(some_1, 1u16);
+        assert_ne!(some_1, vec![1u16]);
+
+        assert_eq!(dyn_map, map);
+        assert_eq!(map, dyn_map);
+        assert_ne!(dyn_map, TpMap::<u16>::new());
+        assert_ne!(dyn_map, TpMap::<u32>::new());
+        assert_ne!(dyn_map, Some(1u16));
+    }
 }
@@ -1,8 +1,12 @@
 use super::property::{
+    DynTpMap, DynTpMapMut, DynTpMapRef, DynTpOption, DynTpOptionMut, DynTpOptionRef,
     DynTpPrimitive, DynTpPrimitiveMut, DynTpPrimitiveRef, DynTpProperty, DynTpPropertyMut,
     DynTpPropertyRef, DynTpVec, DynTpVecMut, DynTpVecRef,
 };
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::primitives;
+use crate::contract::properties::traits::TpMap;
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
@@ -86,6 +90,44 @@ macro_rules! impl_from {
                 DynTpVecMut::from(other).into()
             }
         }
+
+        // ---- Impl conversion from option to property ----
+        impl From<Option<$t>> for DynTpProperty {
+            fn from(other: Option<$t>) -> Self {
+                DynTpOption::from(other).into()
+            }
+        }
+
+        impl <'a> From<&'a Option<$t>> for DynTpPropertyRef<'a> {
+            fn from(other: &'a Option<$t>) -> Self {
+                DynTpOptionRef::from(other).into()
+            }
+        }
+
+        impl <'a> From<&'a mut Option<$t>> for DynTpPropertyMut<'a> {
+            fn from(other: &'a mut Option<$t>) -> Self {
+                DynTpOptionMut::from(other).into()
+            }
+        }
+
+        // ---- Impl conversion from map to property ----
+        impl From<TpMap<$t>> for DynTpProperty {
+            fn from(other: TpMap<$t>) -> Self {
+                DynTpMap::from(other).into()
+            }
+        }
+
+        impl <'a> From<&'a TpMap<$t>> for DynTpPropertyRef<'a> {
+            fn from(other: &'a TpMap<$t>) -> Self {
+                DynTpMapRef::from(other).into()
+            }
+        }
+
+        impl <'a> From<&'a mut TpMap<$t>> for DynTpPropertyMut<'a> {
+            fn from(other: &'a mut TpMap<$t>) -> Self {
+                DynTpMapMut::from(other).into()
+            }
+        }
     };
 
     // recursive case
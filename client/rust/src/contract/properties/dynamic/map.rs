@@ -0,0 +1,221 @@
+// Re-export to relocate the type into the current module
+pub use super::property::DynTpMap;
+
+use super::primitive::TpPrimitiveType;
+use super::property::{
+    prop_type_helper, DynTpMapMut, DynTpMapRef, DynTpPrimitive, DynTpPrimitiveRef,
+};
+use super::TpPropertyType;
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
+use crate::contract::properties::primitives;
+use crate::contract::properties::traits::TpMap;
+use crate::contract::ContractDataHandle;
+use crate::object::ObjectHandle;
+
+use eyre::{eyre, Result};
+use paste::paste;
+
+// Used in `insert()`
+macro_rules! insert_helper {
+    ($this:expr, $key:expr, $value:expr, $($variant:ident),+ $(,)?) => {
+        match ($this, $value) {
+            $(
+                (Self::$variant(map), DynTpPrimitive::$variant(value)) => {
+                    Ok(map.insert($key, value).map(DynTpPrimitive::from))
+                }
+            )+
+            (map, value) => Err(eyre!(
+                "Can't insert a {:?} into a map of {:?}",
+                value.prop_type().primitive_type(),
+                map.prop_type().primitive_type(),
+            )),
+        }
+    };
+}
+
+// Used in `remove()`
+macro_rules! remove_helper {
+    ($this:expr, $key:expr, $($variant:ident),+ $(,)?) => {
+        match $this {
+            $(Self::$variant(map) => map.remove($key).map(DynTpPrimitive::from)),+
+        }
+    };
+}
+
+/// Generates the functions that mutate individual entries of a map. Shared by
+/// `DynTpMap` and `DynTpMapMut`.
+macro_rules! entry_fns {
+    () => {
+        /// Inserts `value` at `key`, returning the previous value if there was one.
+        ///
+        /// Errors if `value` is not the same type as the values of the map.
+        pub fn insert(
+            &mut self,
+            key: String,
+            value: DynTpPrimitive,
+        ) -> Result<Option<DynTpPrimitive>> {
+            primitives!(idents, insert_helper, self, key, value)
+        }
+
+        /// Removes the entry at `key`, returning its value if there was one.
+        pub fn remove(&mut self, key: &str) -> Option<DynTpPrimitive> {
+            primitives!(idents, remove_helper, self, key)
+        }
+    };
+}
+
+impl DynTpMap {
+    pub const fn prop_type(&self) -> TpPropertyType {
+        primitives!(idents, prop_type_helper, self, Map)
+    }
+
+    /// Creates an empty map that would hold values of type `typ`.
+    pub fn new(typ: TpPrimitiveType) -> Self {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match typ {
+                    $(TpPrimitiveType::$variant => Self::$variant(TpMap::new())),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+
+    entry_fns!();
+}
+
+impl DynTpMapMut<'_> {
+    entry_fns!();
+}
+
+impl<'a> From<&'a DynTpMap> for DynTpMapRef<'a> {
+    fn from(other: &'a DynTpMap) -> Self {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match other {
+                    $(DynTpMap::$variant(inner) => Self::from(inner)),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+}
+
+impl<'a> DynTpMapRef<'a> {
+    pub fn len(&self) -> usize {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match *self {
+                    $(Self::$variant(map) => map.len()),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the value at `key`, if any.
+    pub fn get(&self, key: &str) -> Option<DynTpPrimitiveRef<'a>> {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match *self {
+                    $(Self::$variant(map) => map.get(key).map(DynTpPrimitiveRef::from)),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+
+    /// Iterates over the entries of the map, sorted by key.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (&'a str, DynTpPrimitiveRef<'a>)> + 'a> {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match *self {
+                    $(Self::$variant(map) => Box::new(
+                        map.iter().map(|(k, v)| (k.as_str(), DynTpPrimitiveRef::from(v)))
+                    )),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+}
+
+macro_rules! impl_equality {
+    // base case
+    ($t:ty) => {
+        paste! {
+            impl PartialEq<TpMap<$t>> for DynTpMap {
+                fn eq(&self, other: &TpMap<$t>) -> bool {
+                    if let Self::[<$t:camel>](inner) = self {
+                        inner == other
+                    } else {
+                        false
+                    }
+                }
+            }
+
+            impl PartialEq<DynTpMap> for TpMap<$t> {
+                fn eq(&self, other: &DynTpMap) -> bool {
+                    if let DynTpMap::[<$t:camel>](other) = other {
+                        self == other
+                    } else {
+                        false
+                    }
+                }
+            }
+        }
+    };
+    // recursive case
+    ($t:ty, $($tail:ty),+) => {
+        impl_equality!($t);
+        impl_equality!($($tail),+);
+    };
+    // handle trailing comma
+    ($($tail:ty),+,) => {
+        impl_equality!($($tail),+);
+    };
+}
+
+primitives!(; types, impl_equality);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_entries() {
+        let mut map = DynTpMap::new(TpPrimitiveType::I32);
+        assert_eq!(map, TpMap::<i32>::new());
+        assert_eq!(map.prop_type(), TpPropertyType::Map(TpPrimitiveType::I32));
+
+        assert_eq!(map.insert("a".into(), 1i32.into()).unwrap(), None);
+        assert_eq!(map.insert("b".into(), 2i32.into()).unwrap(), None);
+        assert_eq!(
+            map.insert("a".into(), 3i32.into()).unwrap(),
+            Some(DynTpPrimitive::from(1i32))
+        );
+        // Wrong type
+        assert!(map.insert("c".into(), 3u32.into()).is_err());
+
+        let expected: TpMap<i32> = [("a".to_string(), 3), ("b".to_string(), 2)]
+            .into_iter()
+            .collect();
+        assert_eq!(map, expected);
+
+        let map_ref = DynTpMapRef::from(&map);
+        assert_eq!(map_ref.len(), 2);
+        assert_eq!(map_ref.get("b"), Some(DynTpPrimitiveRef::from(&2i32)));
+        assert_eq!(map_ref.get("c"), None);
+        let keys: Vec<&str> = map_ref.iter().map(|(k, _v)| k).collect();
+        assert_eq!(keys, ["a", "b"]);
+
+        assert_eq!(map.remove("a"), Some(DynTpPrimitive::from(3i32)));
+        assert_eq!(map.remove("a"), None);
+        assert_eq!(DynTpMapRef::from(&map).len(), 1);
+    }
+}
@@ -1,6 +1,8 @@
 mod borrow_impls;
 mod eq_impls;
 mod from_impls;
+mod map;
+mod option;
 mod primitive;
 mod property;
 mod vec;
@@ -9,13 +11,20 @@ mod vec;
 #[doc(hidden)]
 pub mod __macro;
 
+pub use map::DynTpMap;
+pub use option::DynTpOption;
 pub use primitive::{DynTpPrimitive, TpPrimitiveType};
 pub use property::{
-    DynTpPrimitiveMut, DynTpPrimitiveRef, DynTpProperty, DynTpPropertyMut, DynTpPropertyRef,
-    DynTpVecMut, DynTpVecRef,
+    DynTpMapMut, DynTpMapRef, DynTpOptionMut, DynTpOptionRef, DynTpPrimitiveMut,
+    DynTpPrimitiveRef, DynTpProperty, DynTpPropertyMut, DynTpPropertyRef, DynTpVecMut,
+    DynTpVecRef,
 };
 pub use vec::DynTpVec;
 
+use eyre::WrapErr;
+use std::fmt;
+use std::str::FromStr;
+
 pub(in crate::contract::properties) use __macro::DynEnum;
 pub use __macro::{apply_to_channel_id, apply_to_prop, apply_to_state_id};
 
@@ -24,12 +33,81 @@ pub use __macro::{apply_to_channel_id, apply_to_prop, apply_to_state_id};
 pub enum TpPropertyType {
     Vec(TpPrimitiveType),
     Primitive(TpPrimitiveType),
+    Option(TpPrimitiveType),
+    /// A map with `String` keys
+    Map(TpPrimitiveType),
 }
 impl TpPropertyType {
     pub const fn primitive_type(&self) -> TpPrimitiveType {
         match self {
             Self::Vec(pt) => *pt,
             Self::Primitive(pt) => *pt,
+            Self::Option(pt) => *pt,
+            Self::Map(pt) => *pt,
+        }
+    }
+}
+/// Formats as the name of the rust type, like `f32`, `Vec<Vec3>`,
+/// `Option<String>` or `Map<u32>`.
+impl fmt::Display for TpPropertyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Vec(pt) => write!(f, "Vec<{}>", pt),
+            Self::Primitive(pt) => write!(f, "{}", pt),
+            Self::Option(pt) => write!(f, "Option<{}>", pt),
+            Self::Map(pt) => write!(f, "Map<{}>", pt),
+        }
+    }
+}
+/// The inverse of the `Display` impl.
+impl FromStr for TpPropertyType {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let container = |prefix: &str| {
+            s.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_suffix('>'))
+                .map(|inner| inner.trim().parse::<TpPrimitiveType>())
+        };
+        if let Some(pt) = container("Vec<") {
+            Ok(Self::Vec(pt?))
+        } else if let Some(pt) = container("Option<") {
+            Ok(Self::Option(pt?))
+        } else if let Some(pt) = container("Map<") {
+            Ok(Self::Map(pt?))
+        } else {
+            s.parse()
+                .map(Self::Primitive)
+                .wrap_err_with(|| format!("`{}` is not a property type", s))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_property_type() {
+        assert_eq!(
+            "f32".parse::<TpPropertyType>().unwrap(),
+            TpPropertyType::Primitive(TpPrimitiveType::F32)
+        );
+        assert_eq!(
+            "Vec<Vec3>".parse::<TpPropertyType>().unwrap(),
+            TpPropertyType::Vec(TpPrimitiveType::Vec3)
+        );
+        assert_eq!(
+            "Map< String >".parse::<TpPropertyType>().unwrap(),
+            TpPropertyType::Map(TpPrimitiveType::String)
+        );
+        assert!("Vec<Vec<u8>>".parse::<TpPropertyType>().is_err());
+        assert!("usize".parse::<TpPropertyType>().is_err());
+
+        for name in ["u64", "Vec<Bytes>", "Option<Quat>", "Map<ObjectHandle>"] {
+            let t: TpPropertyType = name.parse().unwrap();
+            assert_eq!(t.to_string(), name);
         }
     }
 }
@@ -0,0 +1,155 @@
+// Re-export to relocate the type into the current module
+pub use super::property::DynTpOption;
+
+use super::primitive::TpPrimitiveType;
+use super::property::{prop_type_helper, DynTpOptionRef, DynTpPrimitive, DynTpPrimitiveRef};
+use super::TpPropertyType;
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
+use crate::contract::properties::primitives;
+use crate::contract::ContractDataHandle;
+use crate::object::ObjectHandle;
+
+use paste::paste;
+
+impl DynTpOption {
+    pub const fn prop_type(&self) -> TpPropertyType {
+        primitives!(idents, prop_type_helper, self, Option)
+    }
+
+    /// Creates a `None` that would hold values of type `typ`.
+    pub fn none(typ: TpPrimitiveType) -> Self {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match typ {
+                    $(TpPrimitiveType::$variant => Self::$variant(None)),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+
+    pub fn is_some(&self) -> bool {
+        DynTpOptionRef::from(self).get().is_some()
+    }
+
+    /// Converts into an `Option` of the dynamically typed primitive.
+    pub fn into_inner(self) -> Option<DynTpPrimitive> {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match self {
+                    $(Self::$variant(inner) => inner.map(DynTpPrimitive::from)),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+}
+
+/// Wraps the primitive in `Some`
+impl From<DynTpPrimitive> for DynTpOption {
+    fn from(other: DynTpPrimitive) -> Self {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match other {
+                    $(DynTpPrimitive::$variant(inner) => Self::$variant(Some(inner))),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+}
+
+impl<'a> From<&'a DynTpOption> for DynTpOptionRef<'a> {
+    fn from(other: &'a DynTpOption) -> Self {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match other {
+                    $(DynTpOption::$variant(inner) => Self::from(inner)),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+}
+
+impl<'a> DynTpOptionRef<'a> {
+    /// Gets the contained value, if any.
+    pub fn get(&self) -> Option<DynTpPrimitiveRef<'a>> {
+        macro_rules! helper {
+            ($($variant:ident),+ $(,)?) => {
+                match *self {
+                    $(Self::$variant(inner) => inner.as_ref().map(DynTpPrimitiveRef::from)),+
+                }
+            };
+        }
+        primitives!(idents, helper)
+    }
+}
+
+macro_rules! impl_equality {
+    // base case
+    ($t:ty) => {
+        paste! {
+            impl PartialEq<Option<$t>> for DynTpOption {
+                fn eq(&self, other: &Option<$t>) -> bool {
+                    if let Self::[<$t:camel>](inner) = self {
+                        inner == other
+                    } else {
+                        false
+                    }
+                }
+            }
+
+            impl PartialEq<DynTpOption> for Option<$t> {
+                fn eq(&self, other: &DynTpOption) -> bool {
+                    if let DynTpOption::[<$t:camel>](other) = other {
+                        self == other
+                    } else {
+                        false
+                    }
+                }
+            }
+        }
+    };
+    // recursive case
+    ($t:ty, $($tail:ty),+) => {
+        impl_equality!($t);
+        impl_equality!($($tail),+);
+    };
+    // handle trailing comma
+    ($($tail:ty),+,) => {
+        impl_equality!($($tail),+);
+    };
+}
+
+primitives!(; types, impl_equality);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option() {
+        let none = DynTpOption::none(TpPrimitiveType::U32);
+        assert_eq!(none, None::<u32>);
+        assert_ne!(none, None::<u16>);
+        assert!(!none.is_some());
+        assert_eq!(
+            none.prop_type(),
+            TpPropertyType::Option(TpPrimitiveType::U32)
+        );
+
+        let some = DynTpOption::from(DynTpPrimitive::from(String::from("hi")));
+        assert_eq!(some, Some(String::from("hi")));
+        assert!(some.is_some());
+        assert_eq!(
+            DynTpOptionRef::from(&some).get(),
+            Some(DynTpPrimitiveRef::from(&String::from("hi")))
+        );
+        assert_eq!(
+            some.into_inner(),
+            Some(DynTpPrimitive::from(String::from("hi")))
+        );
+    }
+}
@@ -1,11 +1,16 @@
 // Re-export to relocate the type into the current module
 pub use super::property::DynTpPrimitive;
 
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::primitives;
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
+use eyre::eyre;
 use paste::paste;
+use std::fmt;
+use std::str::FromStr;
 
 use super::TpPropertyType;
 
@@ -26,6 +31,82 @@ pub enum TpPrimitiveType {
     String,
     ObjectHandle,
     ContractDataHandle,
+    Vec2,
+    Vec3,
+    Quat,
+    Color,
+    Transform,
+    Bytes,
+}
+
+impl TpPrimitiveType {
+    pub const ALL: &'static [Self] = {
+        macro_rules! helper {
+            ($($ident:ident),+ $(,)?) => {
+                &[$(Self::$ident),+]
+            };
+        }
+        primitives!(; idents, helper)
+    };
+
+    /// The name of the rust type, like `u8` or `Vec3`.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::U64 => "u64",
+            Self::I8 => "i8",
+            Self::I16 => "i16",
+            Self::I32 => "i32",
+            Self::I64 => "i64",
+            Self::Bool => "bool",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::String => "String",
+            Self::ObjectHandle => "ObjectHandle",
+            Self::ContractDataHandle => "ContractDataHandle",
+            Self::Vec2 => "Vec2",
+            Self::Vec3 => "Vec3",
+            Self::Quat => "Quat",
+            Self::Color => "Color",
+            Self::Transform => "Transform",
+            Self::Bytes => "Bytes",
+        }
+    }
+
+    /// Whether this is one of the integer or float types.
+    pub const fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Self::U8
+                | Self::U16
+                | Self::U32
+                | Self::U64
+                | Self::I8
+                | Self::I16
+                | Self::I32
+                | Self::I64
+                | Self::F32
+                | Self::F64
+        )
+    }
+}
+impl fmt::Display for TpPrimitiveType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+impl FromStr for TpPrimitiveType {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|t| t.name() == s)
+            .ok_or_else(|| eyre!("`{}` is not a primitive type", s))
+    }
 }
 
 impl DynTpPrimitive {
@@ -47,6 +128,12 @@ impl DynTpPrimitive {
             Self::ContractDataHandle(_) => {
                 TpPropertyType::Primitive(TpPrimitiveType::ContractDataHandle)
             }
+            Self::Vec2(_) => TpPropertyType::Primitive(TpPrimitiveType::Vec2),
+            Self::Vec3(_) => TpPropertyType::Primitive(TpPrimitiveType::Vec3),
+            Self::Quat(_) => TpPropertyType::Primitive(TpPrimitiveType::Quat),
+            Self::Color(_) => TpPropertyType::Primitive(TpPrimitiveType::Color),
+            Self::Transform(_) => TpPropertyType::Primitive(TpPrimitiveType::Transform),
+            Self::Bytes(_) => TpPropertyType::Primitive(TpPrimitiveType::Bytes),
         }
     }
 }
@@ -119,4 +206,11 @@ mod tests {
         assert_ne!(dyn_1337, u32_7331);
         assert_ne!(u32_7331, dyn_1337);
     }
+    #[test]
+    fn test_name_roundtrip() {
+        for t in TpPrimitiveType::ALL {
+            assert_eq!(t.to_string().parse::<TpPrimitiveType>().unwrap(), *t);
+        }
+        assert!("usize".parse::<TpPrimitiveType>().is_err());
+    }
 }
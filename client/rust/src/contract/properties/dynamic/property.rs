@@ -1,26 +1,42 @@
 use super::primitive::TpPrimitiveType;
 use super::TpPropertyType;
+use crate::apply_to_prop;
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::dynamic::__macro::DynEnum;
 use crate::contract::properties::primitives;
-use crate::contract::properties::traits::ITpProperty;
+use crate::contract::properties::traits::{ITpProperty, ITpPropertyStatic, TpMap};
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
 use derive_more::From;
 use paste::paste;
+use std::any::Any;
 
 DynEnum!(
     DynTpProperty,
     DynTpPrimitive,
-    DynTpVec | derive(Clone, PartialEq)
+    DynTpVec,
+    DynTpOption,
+    DynTpMap | derive(Clone, PartialEq)
 );
 impl DynTpProperty {
     pub const fn prop_type(&self) -> TpPropertyType {
         match self {
             Self::Primitive(tpp) => tpp.prop_type(),
             Self::Vec(tpv) => tpv.prop_type(),
+            Self::Option(tpo) => tpo.prop_type(),
+            Self::Map(tpm) => tpm.prop_type(),
         }
     }
+
+    /// Gets a mutable reference to the inner value, if it is of type `T`.
+    pub fn cast_mut<T: ITpPropertyStatic>(&mut self) -> Option<&mut T> {
+        fn downcast<U: Any, T: Any>(value: &mut U) -> Option<&mut T> {
+            (value as &mut dyn Any).downcast_mut()
+        }
+        apply_to_prop!(self, downcast)
+    }
 }
 
 impl ITpProperty for DynTpProperty {
@@ -75,6 +91,28 @@ macro_rules! vec_enum_helper {
 #[doc(hidden)]
 pub(in crate::contract::properties) use vec_enum_helper;
 
+// Used to generate Option and Map variant enums
+macro_rules! container_enum_helper {
+    ($enum_ident:ident, $container:tt, mut, $($variant_type:ty),+ $(,)?) => {
+        paste! {
+            #[derive(Debug, From, PartialEq)]
+            pub enum $enum_ident<'a> {
+                $([<$variant_type:camel>](&'a mut $container<$variant_type>)),+
+            }
+        }
+    };
+    ($enum_ident:ident, $container:tt, $($variant_type:ty),+ $(,)?) => {
+        paste! {
+            #[derive(Debug, From, PartialEq)]
+            pub enum $enum_ident<'a> {
+                $([<$variant_type:camel>](&'a $container<$variant_type>)),+
+            }
+        }
+    };
+}
+#[doc(hidden)]
+pub(in crate::contract::properties) use container_enum_helper;
+
 // Used in the `prop_type() -> TpPropertyType` function
 macro_rules! prop_type_helper {
     ($enum:expr, $prop_type:ident, $($variant:ident),+ $(,)?) => {
@@ -109,17 +147,39 @@ macro_rules! DynEnumRef {
                 }
             }
 
+            // Option variant
+            primitives!(; types, container_enum_helper, [<$prefix Option $suffix>], Option$(, $maybe_mut)?);
+            impl [<$prefix Option $suffix>]<'_> {
+                pub const fn prop_type(&self) -> TpPropertyType {
+                    use $crate::contract::properties::dynamic::property::prop_type_helper;
+                    primitives!(idents, prop_type_helper, self, Option)
+                }
+            }
+
+            // Map variant
+            primitives!(; types, container_enum_helper, [<$prefix Map $suffix>], TpMap$(, $maybe_mut)?);
+            impl [<$prefix Map $suffix>]<'_> {
+                pub const fn prop_type(&self) -> TpPropertyType {
+                    use $crate::contract::properties::dynamic::property::prop_type_helper;
+                    primitives!(idents, prop_type_helper, self, Map)
+                }
+            }
+
             // Main enum
             #[$attr]
             pub enum [<$prefix Property $suffix>]<'a> {
                 Primitive([<$prefix Primitive $suffix>]<'a>),
                 Vec([<$prefix Vec $suffix>]<'a>),
+                Option([<$prefix Option $suffix>]<'a>),
+                Map([<$prefix Map $suffix>]<'a>),
             }
             impl [<$prefix Property $suffix>]<'_> {
                 pub fn prop_type(&self) -> TpPropertyType {
                     match self {
                         Self::Primitive(p) => p.prop_type(),
                         Self::Vec(v) => v.prop_type(),
+                        Self::Option(o) => o.prop_type(),
+                        Self::Map(m) => m.prop_type(),
                     }
                 }
             }
@@ -152,6 +212,18 @@ impl Clone for DynTpVecRef<'_> {
         primitives!(idents, clone_helper, *self)
     }
 }
+impl Clone for DynTpOptionRef<'_> {
+    fn clone(&self) -> Self {
+        primitives!(idents, clone_helper, *self)
+    }
+}
+impl Clone for DynTpMapRef<'_> {
+    fn clone(&self) -> Self {
+        primitives!(idents, clone_helper, *self)
+    }
+}
 
 impl Copy for DynTpPrimitiveRef<'_> {}
 impl Copy for DynTpVecRef<'_> {}
+impl Copy for DynTpOptionRef<'_> {}
+impl Copy for DynTpMapRef<'_> {}
@@ -3,6 +3,8 @@ pub use super::property::DynTpVec;
 
 use super::primitive::TpPrimitiveType;
 use super::TpPropertyType;
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
@@ -25,6 +27,12 @@ impl DynTpVec {
             Self::String(_) => TpPropertyType::Vec(TpPrimitiveType::String),
             Self::ObjectHandle(_) => TpPropertyType::Vec(TpPrimitiveType::ObjectHandle),
             Self::ContractDataHandle(_) => TpPropertyType::Vec(TpPrimitiveType::ContractDataHandle),
+            Self::Vec2(_) => TpPropertyType::Vec(TpPrimitiveType::Vec2),
+            Self::Vec3(_) => TpPropertyType::Vec(TpPrimitiveType::Vec3),
+            Self::Quat(_) => TpPropertyType::Vec(TpPrimitiveType::Quat),
+            Self::Color(_) => TpPropertyType::Vec(TpPrimitiveType::Color),
+            Self::Transform(_) => TpPropertyType::Vec(TpPrimitiveType::Transform),
+            Self::Bytes(_) => TpPropertyType::Vec(TpPrimitiveType::Bytes),
         }
     }
 }
@@ -100,4 +108,10 @@ impl_equality!(
     String,
     ObjectHandle,
     ContractDataHandle,
+    Vec2,
+    Vec3,
+    Quat,
+    Color,
+    Transform,
+    Bytes,
 );
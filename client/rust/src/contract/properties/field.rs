@@ -0,0 +1,108 @@
+//! Metadata about the fields of `#[states]` and `#[channels]` structs.
+//!
+//! Fields can be annotated with the following attributes:
+//! * `#[default(expr)]`: The value to use when the field is not provided to
+//!   [`Baseline::object_create`](crate::baseline::Baseline::object_create).
+//!   `expr` must have the same type as the field. Only supported on states.
+//! * `#[range(start..=end)]`: The range that the numeric value of the field must
+//!   be in. Any of the range syntaxes can be used. Only supported on states of
+//!   the numeric primitives.
+//! * `#[readonly]`: The field may only be set when the object is created. Only
+//!   supported on states.
+//! * Doc comments are recorded as well.
+//!
+//! Range and readonly constraints are enforced by the [`Engine`](crate::engine::Engine).
+
+use crate::contract::properties::dynamic::{DynTpPrimitiveRef, DynTpProperty, DynTpPropertyRef};
+
+use eyre::{eyre, Result};
+use std::ops::{Bound, RangeBounds};
+
+/// Information about a single field, as declared by its attributes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FieldInfo {
+    pub default: Option<DynTpProperty>,
+    pub range: Option<FieldRange>,
+    pub readonly: bool,
+    /// The doc comments of the field, or an empty string if there were none.
+    pub doc: &'static str,
+}
+impl FieldInfo {
+    /// Whether the field has any constraints that restrict writing to it.
+    pub fn is_constrained(&self) -> bool {
+        self.readonly || self.range.is_some()
+    }
+
+    /// Checks that `value` satisfies the range of the field, if any. Does not
+    /// check `readonly`.
+    pub fn check(&self, value: DynTpPropertyRef) -> Result<()> {
+        let range = match &self.range {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+        let v = match value {
+            DynTpPropertyRef::Primitive(p) => as_f64(p),
+            _ => None,
+        }
+        .ok_or_else(|| eyre!("Only numeric fields can have a range"))?;
+        if !range.contains(v) {
+            return Err(eyre!("{v} was out of the field's range"));
+        }
+        Ok(())
+    }
+}
+
+/// The range of values that a numeric field may hold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldRange {
+    pub start: Bound<f64>,
+    pub end: Bound<f64>,
+}
+impl FieldRange {
+    pub fn contains(&self, v: f64) -> bool {
+        (self.start, self.end).contains(&v)
+    }
+}
+
+fn as_f64(p: DynTpPrimitiveRef) -> Option<f64> {
+    use DynTpPrimitiveRef as P;
+    Some(match p {
+        P::U8(&v) => v.into(),
+        P::U16(&v) => v.into(),
+        P::U32(&v) => v.into(),
+        P::U64(&v) => v as f64,
+        P::I8(&v) => v.into(),
+        P::I16(&v) => v.into(),
+        P::I32(&v) => v.into(),
+        P::I64(&v) => v as f64,
+        P::F32(&v) => v.into(),
+        P::F64(&v) => v,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_range() {
+        let info = FieldInfo {
+            range: Some(FieldRange {
+                start: Bound::Included(0.0),
+                end: Bound::Excluded(10.0),
+            }),
+            ..Default::default()
+        };
+        assert!(info.is_constrained());
+        assert!(info.check(DynTpPropertyRef::from(&0u8)).is_ok());
+        assert!(info.check(DynTpPropertyRef::from(&9.5f32)).is_ok());
+        assert!(info.check(DynTpPropertyRef::from(&10i64)).is_err());
+        assert!(info.check(DynTpPropertyRef::from(&-1i32)).is_err());
+        assert!(info.check(DynTpPropertyRef::from(&true)).is_err());
+
+        let unconstrained = FieldInfo::default();
+        assert!(!unconstrained.is_constrained());
+        assert!(unconstrained.check(DynTpPropertyRef::from(&true)).is_ok());
+    }
+}
@@ -1,5 +1,9 @@
+pub mod bytes;
 pub mod channels;
+pub mod composite;
+pub mod crdt;
 pub mod dynamic;
+pub mod field;
 pub mod states;
 pub mod traits;
 
@@ -74,6 +78,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         );
     };
     (; types, $macro_name:ident, $($x:tt)+) => {
@@ -93,6 +103,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         );
     };
     (; idents, $macro_name:ident) => {
@@ -111,6 +127,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         );
     };
     (; types, $macro_name:ident) => {
@@ -129,6 +151,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         );
     };
     (idents, $macro_name:ident, $($x:tt)+) => {
@@ -148,6 +176,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         )
     };
     (types, $macro_name:ident, $($x:tt)+) => {
@@ -167,6 +201,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         )
     };
     (idents, $macro_name:ident) => {
@@ -185,6 +225,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         )
     };
     (types, $macro_name:ident) => {
@@ -203,6 +249,12 @@ macro_rules! primitives {
             String,
             ObjectHandle,
             ContractDataHandle,
+            Vec2,
+            Vec3,
+            Quat,
+            Color,
+            Transform,
+            Bytes,
         )
     };
     (idents) => {
@@ -220,6 +272,12 @@ macro_rules! primitives {
         String,
         ObjectHandle,
         ContractDataHandle,
+        Vec2,
+        Vec3,
+        Quat,
+        Color,
+        Transform,
+        Bytes,
     };
     (types) => {
         u8,
@@ -236,6 +294,12 @@ macro_rules! primitives {
         String,
         ObjectHandle,
         ContractDataHandle,
+        Vec2,
+        Vec3,
+        Quat,
+        Color,
+        Transform,
+        Bytes,
     };
 }
 pub(crate) use primitives;
@@ -261,8 +325,95 @@ pub mod c_api {
     }
     pub(crate) use impl_from_refcast;
 
+    use crate::contract::properties::bytes::Bytes;
+    use crate::contract::properties::channels::ChannelId;
+    use crate::contract::properties::composite::{Color, IComposite, Quat, Transform, Vec2, Vec3};
+    use crate::contract::properties::states::StateId;
+    use crate::contract::properties::traits::ITpPropertyStatic;
+    use crate::contract::ContractDataHandle;
+    use crate::object::ObjectHandle;
+
+    use safer_ffi::layout::ReprC;
+    use safer_ffi::prelude::*;
+
+    /// Describes how a property type is passed through the C API. Used by the
+    /// functions generated with `#[states(c_api = "...")]` and
+    /// `#[channels(c_api = "...")]`.
+    pub trait CProperty: ITpPropertyStatic {
+        type StateId: ReprC + From<StateId<Self>>;
+        type ChannelId: ReprC + From<ChannelId<Self>>;
+        /// The type that values are passed as. Opaque types are boxed, and may
+        /// be null.
+        type Arg: ReprC;
+
+        /// Returns `None` if `arg` was null.
+        fn from_arg(arg: Self::Arg) -> Option<Self>;
+    }
+
+    macro_rules! impl_c_property {
+        // Passed by value
+        (value: $($t:ty),+ $(,)?) => {
+            paste::paste! {
+                $(
+                    impl CProperty for $t {
+                        type StateId = crate::contract::properties::states::c_api::[<StateId_ $t:camel>];
+                        type ChannelId = crate::contract::properties::channels::c_api::[<ChannelId_ $t:camel>];
+                        type Arg = $t;
+
+                        fn from_arg(arg: Self::Arg) -> Option<Self> {
+                            Some(arg)
+                        }
+                    }
+                )+
+            }
+        };
+        // Passed as boxed opaque types
+        (boxed: $($t:ty),+ $(,)?) => {
+            paste::paste! {
+                $(
+                    impl CProperty for $t {
+                        type StateId = crate::contract::properties::states::c_api::[<StateId_ $t:camel>];
+                        type ChannelId = crate::contract::properties::channels::c_api::[<ChannelId_ $t:camel>];
+                        type Arg = Option<repr_c::Box<c_types::$t>>;
+
+                        fn from_arg(arg: Self::Arg) -> Option<Self> {
+                            arg.map(|v| <$t>::from(*v.into()))
+                        }
+                    }
+                )+
+            }
+        };
+    }
+    impl_c_property!(value: u8, u16, u32, u64, i8, i16, i32, i64, bool, f32, f64);
+    impl_c_property!(
+        boxed: String,
+        ObjectHandle,
+        ContractDataHandle,
+        Vec2,
+        Vec3,
+        Quat,
+        Color,
+        Transform,
+        Bytes,
+    );
+
+    /// Describes how an [`IComposite`] is passed through the C API. Implemented
+    /// by `#[derive(Composite)]` for structs with `#[composite(c_api = "...")]`,
+    /// and used by the functions generated for `#[flatten]` fields.
+    pub trait CComposite: IComposite {
+        /// The type that values are passed as. It is boxed, and may be null.
+        type Arg: ReprC;
+
+        /// Returns `None` if `arg` was null.
+        fn from_arg(arg: Self::Arg) -> Option<Self>;
+    }
+
     pub mod c_types {
         pub use crate::contract::c_api::ContractDataHandle;
+        pub use crate::contract::properties::bytes::c_api::Bytes;
+        pub use crate::contract::properties::composite::c_api::{
+            Color, Quat, Transform, Vec2, Vec3,
+        };
         pub use crate::object::c_api::ObjectHandle;
 
         pub use bool;
@@ -1,12 +1,14 @@
 use super::dyn_state::{DynStateMut, DynStateRef};
 use super::{handle::StateHandle, IStateHandle};
 use crate::apply_to_state_handle;
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::dynamic::__macro::DynEnum;
 use crate::contract::properties::dynamic::{
     DynTpPropertyMut, DynTpPropertyRef, TpPrimitiveType, TpPropertyType,
 };
 use crate::contract::properties::primitives;
-use crate::contract::properties::traits::ITpPropertyStatic;
+use crate::contract::properties::traits::{ITpPropertyStatic, TpMap};
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
@@ -63,10 +65,16 @@ impl IStateHandle for DynStateHandle {
                 primitives!(idents, match_helper, p, DynStateHandlePrimitive)
             }
             DynStateHandle::Vec(v) => primitives!(idents, match_helper, v, DynStateHandleVec),
+            DynStateHandle::Option(o) => {
+                primitives!(idents, match_helper, o, DynStateHandleOption)
+            }
+            DynStateHandle::Map(m) => primitives!(idents, match_helper, m, DynStateHandleMap),
         }
     }
 
     fn prop_type(&self) -> TpPropertyType {
+        use DynStateHandleMap as M;
+        use DynStateHandleOption as O;
         use DynStateHandlePrimitive as P;
         use DynStateHandleVec as V;
 
@@ -86,12 +94,16 @@ impl IStateHandle for DynStateHandle {
                 TpPropertyType::Primitive(primitives!(idents, helper_match, p, P))
             }
             Self::Vec(v) => TpPropertyType::Vec(primitives!(idents, helper_match, v, V)),
+            Self::Option(o) => TpPropertyType::Option(primitives!(idents, helper_match, o, O)),
+            Self::Map(m) => TpPropertyType::Map(primitives!(idents, helper_match, m, M)),
         }
     }
 }
 
 impl Copy for DynStateHandlePrimitive {}
 impl Copy for DynStateHandleVec {}
+impl Copy for DynStateHandleOption {}
+impl Copy for DynStateHandleMap {}
 impl Copy for DynStateHandle {}
 
 macro_rules! impl_from {
@@ -108,6 +120,18 @@ macro_rules! impl_from {
                 Self::Vec(DynStateHandleVec::from(other))
             }
         }
+
+        impl From<StateHandle<Option<$t>>> for DynStateHandle {
+            fn from(other: StateHandle<Option<$t>>) -> Self {
+                Self::Option(DynStateHandleOption::from(other))
+            }
+        }
+
+        impl From<StateHandle<TpMap<$t>>> for DynStateHandle {
+            fn from(other: StateHandle<TpMap<$t>>) -> Self {
+                Self::Map(DynStateHandleMap::from(other))
+            }
+        }
     };
 
     // recursive case
@@ -1,8 +1,11 @@
 use derive_more::{From, Into};
 
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::properties::dynamic::{DynTpProperty, DynTpPropertyMut, DynTpPropertyRef};
 use crate::contract::properties::primitives;
 use crate::contract::properties::states::State;
+use crate::contract::properties::traits::TpMap;
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
@@ -59,6 +62,48 @@ macro_rules! impl_from {
                 Self(dyn_prop)
             }
         }
+
+        impl From<State<Option<$t>>> for DynState {
+            fn from(other: State<Option<$t>>) -> Self {
+                let dyn_prop = DynTpProperty::from(other.value);
+                Self(dyn_prop)
+            }
+        }
+
+        impl<'a> From<&'a State<Option<$t>>> for DynStateRef<'a> {
+            fn from(other: &'a State<Option<$t>>) -> Self {
+                let dyn_prop = DynTpPropertyRef::from(&other.value);
+                Self(dyn_prop)
+            }
+        }
+
+        impl<'a> From<&'a mut State<Option<$t>>> for DynStateMut<'a> {
+            fn from(other: &'a mut State<Option<$t>>) -> Self {
+                let dyn_prop = DynTpPropertyMut::from(&mut other.value);
+                Self(dyn_prop)
+            }
+        }
+
+        impl From<State<TpMap<$t>>> for DynState {
+            fn from(other: State<TpMap<$t>>) -> Self {
+                let dyn_prop = DynTpProperty::from(other.value);
+                Self(dyn_prop)
+            }
+        }
+
+        impl<'a> From<&'a State<TpMap<$t>>> for DynStateRef<'a> {
+            fn from(other: &'a State<TpMap<$t>>) -> Self {
+                let dyn_prop = DynTpPropertyRef::from(&other.value);
+                Self(dyn_prop)
+            }
+        }
+
+        impl<'a> From<&'a mut State<TpMap<$t>>> for DynStateMut<'a> {
+            fn from(other: &'a mut State<TpMap<$t>>) -> Self {
+                let dyn_prop = DynTpPropertyMut::from(&mut other.value);
+                Self(dyn_prop)
+            }
+        }
     };
 
     // recursive case
@@ -55,7 +55,10 @@ pub mod c_api {
     #![allow(non_camel_case_types, non_snake_case, dead_code)]
     use super::*;
 
+    use crate::contract::properties::bytes::Bytes;
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     use crate::contract::properties::primitives;
+    use crate::contract::properties::traits::TpMap;
     use crate::contract::ContractDataHandle;
     use crate::object::ObjectHandle;
 
@@ -99,4 +102,72 @@ pub mod c_api {
     }
     // This is like doing `monomorphize!("whatever", Keyframe, u8, u16, ...)
     primitives!(; types, monomorphize, "tp_client::contract::properties::states");
+
+    /// Same as `monomorphize`, but for `StateHandle<TpMap<T>>`
+    macro_rules! monomorphize_map {
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_StateHandle_Map $t:camel>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(From, Into, RefCast, Copy, Clone, Eq, PartialEq)]
+                    #[repr(C)]
+                    pub struct [<StateHandle_Map $t:camel>] {
+                        pub inner: StateHandle<TpMap<$t>>,
+                    }
+                    pub use [<StateHandle_Map $t:camel>] as Monomorphized;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<StateHandle_Map $t:camel __drop>](h: repr_c::Box<Monomorphized>) {
+                        drop(h)
+                    }
+                }
+                pub use [<_StateHandle_Map $t:camel>]::[<StateHandle_Map $t:camel>];
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_map!($path, $first_t);
+            monomorphize_map!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_map, "tp_client::contract::properties::states");
+
+    /// Same as `monomorphize`, but for `StateHandle<Option<T>>`
+    macro_rules! monomorphize_option {
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_StateHandle_Option $t:camel>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(From, Into, RefCast, Copy, Clone, Eq, PartialEq)]
+                    #[repr(C)]
+                    pub struct [<StateHandle_Option $t:camel>] {
+                        pub inner: StateHandle<Option<$t>>,
+                    }
+                    pub use [<StateHandle_Option $t:camel>] as Monomorphized;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<StateHandle_Option $t:camel __drop>](h: repr_c::Box<Monomorphized>) {
+                        drop(h)
+                    }
+                }
+                pub use [<_StateHandle_Option $t:camel>]::[<StateHandle_Option $t:camel>];
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_option!($path, $first_t);
+            monomorphize_option!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_option, "tp_client::contract::properties::states");
 }
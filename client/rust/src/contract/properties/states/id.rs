@@ -1,5 +1,5 @@
 use crate::contract::properties::dynamic::__macro::{DynEnum, DynTpPropId};
-use crate::contract::properties::traits::{ITpData, ITpProperty};
+use crate::contract::properties::traits::{ITpProperty, ITpPropertyStatic, TpMap};
 use crate::contract::ContractDataHandle;
 
 use std::marker::PhantomData;
@@ -36,7 +36,7 @@ impl<T: ITpProperty> StateId<T> {
         }
     }
 }
-impl<T: ITpData> Copy for StateId<T> {}
+impl<T: ITpPropertyStatic> Copy for StateId<T> {}
 
 DynTpPropId!(DynStateId, StateId);
 
@@ -45,8 +45,11 @@ pub mod c_api {
     #![allow(non_camel_case_types, non_snake_case, dead_code)]
 
     use crate::contract::c_api::ContractDataHandle as CContractDataHandle;
+    use crate::contract::properties::bytes::Bytes;
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     use crate::contract::properties::primitives;
     use crate::contract::properties::states::StateId;
+    use crate::contract::properties::traits::TpMap;
     use crate::contract::ContractDataHandle;
     use crate::object::ObjectHandle;
 
@@ -97,4 +100,84 @@ pub mod c_api {
     }
     // This is like doing `monomorphize!("whatever", Keyframe, u8, u16, ...)
     primitives!(; types, monomorphize, "tp_client::contract::properties::states");
+
+    /// Same as `monomorphize`, but for `StateId<TpMap<T>>`
+    macro_rules! monomorphize_map {
+        ($path:literal, $t:ty $(,)?) => {
+            paste! {
+                mod [<_StateId_Map $t:camel>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(From, Into, RefCast, Copy, Clone)]
+                    #[repr(C)]
+                    pub struct [<StateId_Map $t:camel>] {
+                        pub inner: StateId<TpMap<$t>>,
+                    }
+                    pub use [<StateId_Map $t:camel>] as Monomorphized;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<StateId_Map $t:camel __contract>]<'a>(id: &'a Monomorphized) -> repr_c::Box<CContractDataHandle> {
+                        repr_c::Box::new(CContractDataHandle::from(id.inner.contract()))
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<StateId_Map $t:camel __drop>](id: repr_c::Box<Monomorphized>) {
+                        drop(id)
+                    }
+                }
+                pub use [<_StateId_Map $t:camel>]::Monomorphized as [<StateId_Map $t:camel>];
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_map!($path, $first_t);
+            monomorphize_map!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_map, "tp_client::contract::properties::states");
+
+    /// Same as `monomorphize`, but for `StateId<Option<T>>`
+    macro_rules! monomorphize_option {
+        ($path:literal, $t:ty $(,)?) => {
+            paste! {
+                mod [<_StateId_Option $t:camel>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(From, Into, RefCast, Copy, Clone)]
+                    #[repr(C)]
+                    pub struct [<StateId_Option $t:camel>] {
+                        pub inner: StateId<Option<$t>>,
+                    }
+                    pub use [<StateId_Option $t:camel>] as Monomorphized;
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<StateId_Option $t:camel __contract>]<'a>(id: &'a Monomorphized) -> repr_c::Box<CContractDataHandle> {
+                        repr_c::Box::new(CContractDataHandle::from(id.inner.contract()))
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<StateId_Option $t:camel __drop>](id: repr_c::Box<Monomorphized>) {
+                        drop(id)
+                    }
+                }
+                pub use [<_StateId_Option $t:camel>]::Monomorphized as [<StateId_Option $t:camel>];
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_option!($path, $first_t);
+            monomorphize_option!($path, $($tail_t),+);
+        };
+    }
+    primitives!(; types, monomorphize_option, "tp_client::contract::properties::states");
 }
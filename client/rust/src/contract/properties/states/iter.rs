@@ -1,5 +1,6 @@
 use super::DynStateId;
 use crate::contract::properties::dynamic::TpPropertyType;
+use crate::contract::properties::field::FieldInfo;
 use crate::contract::properties::prop_iter;
 
 use std::any::TypeId;
@@ -8,6 +9,8 @@ pub trait IStates: Sized {
     fn type_ids() -> &'static [TypeId];
     fn enumerate_types() -> &'static [TpPropertyType];
     fn field_names() -> &'static [&'static str];
+    /// The attributes of each field, such as defaults and constraints.
+    fn field_info() -> &'static [FieldInfo];
 }
 
 impl IStates for () {
@@ -22,6 +25,10 @@ impl IStates for () {
     fn field_names() -> &'static [&'static str] {
         &[]
     }
+
+    fn field_info() -> &'static [FieldInfo] {
+        &[]
+    }
 }
 
 prop_iter!(StatesIter, IStates, DynStateId);
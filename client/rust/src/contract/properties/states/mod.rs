@@ -21,11 +21,19 @@ use typemap::ShareMap;
 #[derive(Debug, PartialEq)]
 pub struct State<T: ITpPropertyStatic> {
     pub value: T,
+    pub(crate) version: u64,
 }
 
 impl<T: ITpPropertyStatic> State<T> {
     pub fn new(value: T) -> Self {
-        Self { value }
+        Self { value, version: 0 }
+    }
+
+    /// Counts the writes to the state that the `Engine` applied, starting
+    /// from 0. Writes that are reversed, such as those of a rejected
+    /// collaction, don't count. Writing to `value` directly doesn't change it.
+    pub fn version(&self) -> u64 {
+        self.version
     }
 }
 
@@ -58,8 +66,11 @@ pub mod c_api {
     pub use super::id::c_api::*;
 
     use super::*;
+    use crate::contract::properties::bytes::Bytes;
     use crate::contract::properties::c_api::{c_types, impl_from_refcast};
+    use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     use crate::contract::properties::primitives;
+    use crate::contract::properties::traits::TpMap;
     use crate::contract::ContractDataHandle;
     use crate::object::ObjectHandle;
 
@@ -113,6 +124,12 @@ pub mod c_api {
                         let new_value = $t::from(*new_value.into());
                         state.inner.value = new_value;
                     }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_ $t:camel __version>](state: &Monomorphized) -> u64 {
+                        state.inner.version()
+                    }
                 }
                 pub use [<_State_ $t:camel>]::Monomorphized as [<State_ $t:camel>];
             }
@@ -126,4 +143,141 @@ pub mod c_api {
 
     // This is like doing `monomorphize!("whatever", State, u8, u16, ...)
     primitives!(; types, monomorphize, "tp_client::contract::properties::states");
+
+    /// Same as `monomorphize`, but for `State<Option<T>>` and `State<TpMap<T>>`
+    macro_rules! monomorphize_containers {
+        ($path:literal, $t:ty $(,)?) => {
+            paste::paste! {
+                mod [<_State_Option $t:camel>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(From, Into, RefCast)]
+                    #[repr(C)]
+                    pub struct [<State_Option $t:camel>] {
+                        pub inner: State<Option<$t>>
+                    }
+
+                    pub use [<State_Option $t:camel>] as Monomorphized;
+                    impl_from_refcast!(State<Option<$t>>, Monomorphized);
+
+                    /// `value` may be null, to represent `None`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Option $t:camel __new>](value: Option<repr_c::Box<c_types::$t>>) -> repr_c::Box<Monomorphized> {
+                        let value = value.map(|v| $t::from(*v.into()));
+                        repr_c::Box::new(State::new(value).into())
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Option $t:camel __drop>](s: repr_c::Box<Monomorphized>) {
+                        drop(s)
+                    }
+
+                    /// Returns null if the state is `None`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Option $t:camel __value>]<'a>(state: &'a Monomorphized) -> Option<&'a c_types::$t> {
+                        state.inner.value.as_ref().map(|v| v.into())
+                    }
+
+                    /// `new_value` may be null, to represent `None`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Option $t:camel __value_set>]<'a>(state: &'a mut Monomorphized, new_value: Option<repr_c::Box<c_types::$t>>) {
+                        state.inner.value = new_value.map(|v| $t::from(*v.into()));
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Option $t:camel __version>](state: &Monomorphized) -> u64 {
+                        state.inner.version()
+                    }
+                }
+                pub use [<_State_Option $t:camel>]::Monomorphized as [<State_Option $t:camel>];
+
+                mod [<_State_Map $t:camel>] {
+                    use super::*;
+
+                    #[remangle($path)]
+                    #[derive_ReprC]
+                    #[ReprC::opaque]
+                    #[derive(From, Into, RefCast)]
+                    #[repr(C)]
+                    pub struct [<State_Map $t:camel>] {
+                        pub inner: State<TpMap<$t>>
+                    }
+
+                    pub use [<State_Map $t:camel>] as Monomorphized;
+                    impl_from_refcast!(State<TpMap<$t>>, Monomorphized);
+
+                    /// Creates a state holding an empty map.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __new>]() -> repr_c::Box<Monomorphized> {
+                        repr_c::Box::new(State::new(TpMap::new()).into())
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __drop>](s: repr_c::Box<Monomorphized>) {
+                        drop(s)
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __len>](state: &Monomorphized) -> usize {
+                        state.inner.value.len()
+                    }
+
+                    /// Returns the keys of the map, in sorted order.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __keys>]<'a>(state: &'a Monomorphized) -> repr_c::Vec<&'a c_types::String> {
+                        let v: Vec<&'a c_types::String> = state.inner.value.keys().map(|k| k.into()).collect();
+                        v.into()
+                    }
+
+                    /// Returns null if there is no entry for `key`.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __get>]<'a>(state: &'a Monomorphized, key: &c_types::String) -> Option<&'a c_types::$t> {
+                        state.inner.value.get(key.inner.as_str()).map(|v| v.into())
+                    }
+
+                    /// Returns whether an existing entry was replaced.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __insert>](state: &mut Monomorphized, key: &c_types::String, value: repr_c::Box<c_types::$t>) -> bool {
+                        let value = $t::from(*value.into());
+                        state.inner.value.insert(key.inner.clone(), value).is_some()
+                    }
+
+                    /// Returns whether there was an entry to remove.
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __remove>](state: &mut Monomorphized, key: &c_types::String) -> bool {
+                        state.inner.value.remove(key.inner.as_str()).is_some()
+                    }
+
+                    #[remangle($path)]
+                    #[ffi_export]
+                    pub fn [<State_Map $t:camel __version>](state: &Monomorphized) -> u64 {
+                        state.inner.version()
+                    }
+                }
+                pub use [<_State_Map $t:camel>]::Monomorphized as [<State_Map $t:camel>];
+            }
+        };
+        // recursive case
+        ($path:literal, $first_t:ty, $($tail_t:ty),+ $(,)?) => {
+            monomorphize_containers!($path, $first_t);
+            monomorphize_containers!($path, $($tail_t),+);
+        };
+    }
+
+    primitives!(; types, monomorphize_containers, "tp_client::contract::properties::states");
 }
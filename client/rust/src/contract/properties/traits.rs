@@ -2,10 +2,13 @@
 
 use crate::contract::properties::dynamic::{TpPrimitiveType, TpPropertyType};
 
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
 use crate::contract::ContractDataHandle;
 use crate::object::ObjectHandle;
 
 use paste::paste;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
 // ---- ITpData and primitives ----
@@ -52,6 +55,12 @@ impl_itpdata!(
     String,
     ObjectHandle,
     ContractDataHandle,
+    Vec2,
+    Vec3,
+    Quat,
+    Color,
+    Transform,
+    Bytes,
 );
 
 // ---- ITpProperty and containers ----
@@ -79,11 +88,27 @@ pub trait ITpPropertyStatic: ITpProperty + 'static {
     const PROPERTY_TYPE: TpPropertyType;
 }
 
+/// A map from string keys to values, that can be stored in a property.
+///
+/// Entries are kept sorted by key, so that iteration order (and therefore
+/// serialization) is deterministic.
+pub type TpMap<T> = BTreeMap<String, T>;
+
 /// Vecs of ITpDatas are valid for storing in a property
 impl<T: ITpData> ITpPropertyStatic for Vec<T> {
     const PROPERTY_TYPE: TpPropertyType = TpPropertyType::Vec(T::DATA_TYPE);
 }
 
+/// Options of ITpDatas are valid for storing in a property
+impl<T: ITpData> ITpPropertyStatic for Option<T> {
+    const PROPERTY_TYPE: TpPropertyType = TpPropertyType::Option(T::DATA_TYPE);
+}
+
+/// Maps of ITpDatas are valid for storing in a property
+impl<T: ITpData> ITpPropertyStatic for TpMap<T> {
+    const PROPERTY_TYPE: TpPropertyType = TpPropertyType::Map(T::DATA_TYPE);
+}
+
 /// All ITpDatas are also valid for storing in a property
 impl<T: ITpData> ITpPropertyStatic for T {
     const PROPERTY_TYPE: TpPropertyType = TpPropertyType::Primitive(T::DATA_TYPE);
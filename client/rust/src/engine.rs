@@ -1,18 +1,40 @@
+use crate::action::lock::LockAction;
+use crate::action::object::ObjectAction;
 use crate::action::property::{PropertyAction, StateAction};
-use crate::action::{Action, ActionKind, ActionResult, Collaction, CollactionResult, IAction};
+use crate::action::{
+    Action, ActionKind, ActionResult, Collaction, CollactionResult, Conflict, IAction, Rejection,
+};
+use crate::apply_to_state_handle;
 use crate::baseline::BaselineKind;
+use crate::contract::properties::crdt;
+use crate::contract::properties::dynamic::{
+    DynTpProperty, DynTpPropertyMut, DynTpPropertyRef, TpPropertyType,
+};
+use crate::contract::properties::states::dyn_handle::{DynStateHandle, DynStateHandlePrimitive};
+use crate::contract::properties::states::dyn_state::DynStateMut;
+use crate::contract::properties::states::{IStateHandle, StateHandle};
+use crate::lock::{Lock, LockEvent, LockTarget, Locked, Locks, OwnerId};
+use crate::object::ObjectHandle;
 use crate::realm::Realm;
-use crate::time::Ticks;
+use crate::spatial::SpatialIndex;
+use crate::time::{RealmTime, Ticks};
 
 use better_borrow::BBorrow;
 use crossbeam_channel::{Receiver, RecvTimeoutError, Sender, TryRecvError};
 use eyre::{eyre, WrapErr};
+use std::mem;
 
 type TryApplyResult = Result<CollactionResult, TryRecvError>;
 type ApplyResult = Result<CollactionResult, RecvTimeoutError>;
 
 pub type ActionSender = Sender<Collaction>;
 
+/// Called with the result of every collaction that the `Engine` applies.
+pub type CollactionHook = Box<dyn FnMut(&CollactionResult) + Send>;
+
+/// Called with every change to the locks of the `Engine`.
+pub type LockHook = Box<dyn FnMut(&LockEvent) + Send>;
+
 /// Manages reading and writing to the `Realm`.
 ///
 /// # Threading architecture
@@ -29,6 +51,13 @@ pub type ActionSender = Sender<Collaction>;
 pub struct Engine {
     realm: Realm,
     receiver: Receiver<Collaction>,
+    collaction_hooks: Vec<CollactionHook>,
+    locks: Locks,
+    lock_checks: bool,
+    lock_hooks: Vec<LockHook>,
+    /// Changes to the locks by the collaction being applied, which are only
+    /// reported if it is approved
+    lock_events: Vec<LockEvent>,
 }
 impl Engine {
     pub fn new(realm: Realm, queue_capacity: Option<usize>) -> (Self, ActionSender) {
@@ -38,12 +67,29 @@ impl Engine {
             crossbeam_channel::unbounded()
         };
 
-        let this = Self { realm, receiver };
+        let this = Self {
+            realm,
+            receiver,
+            collaction_hooks: Vec::new(),
+            locks: Locks::default(),
+            lock_checks: true,
+            lock_hooks: Vec::new(),
+            lock_events: Vec::new(),
+        };
         (this, sender)
     }
 
+    /// Advances the realm time, and removes the locks that expired.
     pub fn tick(&mut self, ticks_since_last_call: Ticks) {
         *self.realm.time_mut().ticks_mut() += ticks_since_last_call;
+
+        for (target, lock) in self.locks.expire(self.realm.time()) {
+            let event = LockEvent::Expired {
+                target,
+                owner: lock.owner,
+            };
+            self.lock_hooks.iter_mut().for_each(|hook| hook(&event));
+        }
     }
 
     pub fn realm(&self) -> &Realm {
@@ -54,11 +100,52 @@ impl Engine {
         &mut self.realm
     }
 
+    /// The spatial index over the fork baseline. Positional states written by
+    /// the `Engine` are kept up to date automatically.
+    pub fn spatial_index(&self) -> &SpatialIndex {
+        self.realm.baseline(BaselineKind::Fork).spatial_index()
+    }
+
+    pub fn spatial_index_mut(&mut self) -> &mut SpatialIndex {
+        self.realm
+            .baseline_mut(BaselineKind::Fork)
+            .spatial_index_mut()
+    }
+
+    /// Registers `hook` to be called with the result of every collaction that
+    /// is applied from now on, right after it is approved or rejected.
+    pub fn on_collaction_result(&mut self, hook: impl FnMut(&CollactionResult) + Send + 'static) {
+        self.collaction_hooks.push(Box::new(hook));
+    }
+
+    /// The locks on objects and states, see [`lock`](crate::lock).
+    pub fn locks(&self) -> &Locks {
+        &self.locks
+    }
+
+    /// Registers `hook` to be called with every change to the locks from now
+    /// on. Changes made by a collaction are reported right after it is
+    /// approved, before the hooks of `on_collaction_result`.
+    pub fn on_lock_change(&mut self, hook: impl FnMut(&LockEvent) + Send + 'static) {
+        self.lock_hooks.push(Box::new(hook));
+    }
+
+    /// Whether to reject writes through locks held by others, and lock actions
+    /// that their owner isn't allowed to make. Enabled by default.
+    ///
+    /// Engines that mirror an authoritative one, such as a server's, should
+    /// disable this. They only apply collactions that were already approved,
+    /// and their realm time may not match.
+    pub fn set_lock_checks(&mut self, enabled: bool) {
+        self.lock_checks = enabled;
+    }
+
     /// Same as `apply_timeout()`, but immediately returns if there are no
     /// collactions pending.
     pub fn try_apply(&mut self) -> TryApplyResult {
         let collaction = self.receiver.try_recv()?;
         let result = self.apply_collaction(collaction);
+        self.run_collaction_hooks(&result);
         Ok(result)
     }
 
@@ -68,29 +155,53 @@ impl Engine {
     pub fn apply_timeout(&mut self, timeout: std::time::Duration) -> ApplyResult {
         let collaction = self.receiver.recv_timeout(timeout)?;
         let result = self.apply_collaction(collaction);
+        self.run_collaction_hooks(&result);
         Ok(result)
     }
 
+    /// Applies `collaction` right away, ahead of any pending collactions, and
+    /// returns the `CollactionResult`.
+    pub fn apply(&mut self, collaction: Collaction) -> CollactionResult {
+        let result = self.apply_collaction(collaction);
+        self.run_collaction_hooks(&result);
+        result
+    }
+
+    fn run_collaction_hooks(&mut self, result: &CollactionResult) {
+        for event in mem::take(&mut self.lock_events) {
+            self.lock_hooks.iter_mut().for_each(|hook| hook(&event));
+        }
+        for hook in &mut self.collaction_hooks {
+            hook(result);
+        }
+    }
+
     fn apply_collaction(&mut self, mut collaction: Collaction) -> CollactionResult {
         // Keep track of applied Actions
         let mut applied_actions = Vec::new();
+        collaction.set_rejection(None);
+        let owner = collaction.owner();
+        let lock_events = self.lock_events.len();
 
         // Iterate through all Actions in this Collaction.
         let actions = collaction.actions_mut();
-        for action in actions {
-            let action_result = self.apply_action(action);
+        for (idx, action) in actions.iter_mut().enumerate() {
+            let action_result = self.apply_action(action, owner);
             match action_result {
                 Ok(()) => {
                     // Keep track of previously-applied Actions.
                     applied_actions.push(action);
                 }
-                Err(_) => {
+                Err(err) => {
                     // Reverse previously-applied Actions within this Collaction.
-                    applied_actions.push(action);
+                    // The failed Action itself is never applied, because every
+                    // Action is validated before it mutates anything.
                     // Go in FIFO order
                     self.reverse_actions(applied_actions.into_iter().rev());
+                    self.lock_events.truncate(lock_events);
 
                     // Bail and reject this Collaction.
+                    collaction.set_rejection(Some(Rejection::new(idx, err)));
                     return Err(collaction);
                 }
             }
@@ -100,7 +211,26 @@ impl Engine {
         Ok(collaction)
     }
 
-    fn apply_action(&mut self, action: &mut Action) -> ActionResult {
+    fn apply_action(&mut self, action: &mut Action, owner: Option<OwnerId>) -> ActionResult {
+        if let Action::Lock(action) = action {
+            return self.apply_lock(action, owner);
+        }
+        if let Some(handle) = written_state(action) {
+            self.check_lock(handle.into(), owner)?;
+        }
+        if let Action::Object(ObjectAction::Reparent { object, parent, .. }) = action {
+            for target in std::iter::once(*object).chain(*parent) {
+                self.check_lock(LockTarget::Object(target), owner)?;
+            }
+        }
+        self.swap_action(action)?;
+        self.bump_version(action, true);
+        Ok(())
+    }
+
+    /// Applies `action` by swapping its data with that of the state, without
+    /// changing the version of the state.
+    fn swap_action(&mut self, action: &mut Action) -> ActionResult {
         match action {
             Action::Property(PropertyAction::State(action)) => {
                 // Get data from the Action and compare it against the BaselineFork.
@@ -116,28 +246,107 @@ impl Engine {
                             Err(eyre!("Assert failed!"))
                         }
                     }
-                    StateAction::Write { handle, data } => {
-                        // Get data from the Action and apply it to the BaselineFork.
-                        let state = self
-                            .realm_mut()
-                            .baseline_mut(BaselineKind::Fork)
-                            .state_mut(*handle)
-                            .wrap_err("Invalid Handle")?;
-
-                        // Sanity check that all types remain the same
-                        debug_assert!(state.0.prop_type() == data.prop_type());
-                        // TODO[SER-272]: handle DynTpProperty<DynTpData> and DynTpData in general
-
-                        // Swap the current value with the new data.
-                        // This optimizes applying the Action and allows
-                        // for its simple reversal if needed.
-
-                        // TODO: hard ;(
-                        //mem::swap(&mut state.value, data);
-                        todo!("Figure out how to swap")
+                    StateAction::Write { handle, data } => self.write_state(*handle, data),
+                    StateAction::AssertVersion { handle, version } => {
+                        self.check_version(*handle, *version)
+                    }
+                    StateAction::CompareAndSet {
+                        handle,
+                        version,
+                        data,
+                    } => {
+                        self.check_version(*handle, *version)?;
+                        self.write_state(*handle, data)
+                    }
+                    StateAction::MapInsert { handle, key, value }
+                    | StateAction::MapRemove {
+                        handle,
+                        key,
+                        removed: value,
+                    } => {
+                        // Sanity check the types before touching `value`, so
+                        // that the action is left intact if it gets rejected.
+                        let TpPropertyType::Map(value_type) = handle.prop_type() else {
+                            return Err(eyre!("State is not a map"));
+                        };
+                        if let Some(v) = value {
+                            if v.prop_type().primitive_type() != value_type {
+                                return Err(eyre!("Data did not match the type of the map"));
+                            }
+                        }
+                        self.check_constraints(*handle, None)?;
+
+                        let baseline = self.realm.baseline_mut(BaselineKind::Fork);
+                        let DynStateMut(DynTpPropertyMut::Map(mut map)) =
+                            baseline.state_mut(*handle).wrap_err("Invalid Handle")?
+                        else {
+                            unreachable!("Handle was already checked to be a map");
+                        };
+
+                        // Swap the current entry with the one in the Action,
+                        // so that re-applying the Action reverses it.
+                        *value = match value.take() {
+                            Some(v) => map
+                                .insert(key.clone(), v)
+                                .expect("Types were already checked"),
+                            None => map.remove(key),
+                        };
+                        Ok(())
+                    }
+                    StateAction::BytesWrite {
+                        handle,
+                        offset,
+                        len,
+                        chunk,
+                    } => {
+                        self.check_constraints((*handle).into(), None)?;
+
+                        let baseline = self.realm.baseline_mut(BaselineKind::Fork);
+                        let state = baseline.state_mut(*handle).wrap_err("Invalid Handle")?;
+
+                        // Swap the replaced bytes into the Action, so that
+                        // re-applying the Action reverses it.
+                        let replaced = state.value.splice(*offset, *len, chunk.as_slice())?;
+                        *len = chunk.len();
+                        *chunk = replaced;
+                        Ok(())
+                    }
+                    StateAction::Merge {
+                        handle,
+                        delta,
+                        replaced,
+                    } => {
+                        self.check_constraints((*handle).into(), None)?;
+
+                        let baseline = self.realm.baseline_mut(BaselineKind::Fork);
+                        let state = baseline.state_mut(*handle).wrap_err("Invalid Handle")?;
+
+                        // Keep the previous value in the Action, so that
+                        // re-applying the Action restores it.
+                        match replaced.take() {
+                            Some(previous) => state.value = previous,
+                            None => {
+                                let merged = crdt::merge_bytes(&state.value, delta)?;
+                                *replaced = Some(mem::replace(&mut state.value, merged));
+                            }
+                        }
+                        Ok(())
                     }
                 }
             }
+            Action::Object(ObjectAction::Reparent {
+                object,
+                parent,
+                idx,
+            }) => {
+                let baseline = self.realm.baseline_mut(BaselineKind::Fork);
+                // Swap the previous position into the Action, so that
+                // re-applying the Action reverses it.
+                let (old_parent, old_idx) = baseline.object_reparent(*object, *parent, *idx)?;
+                *parent = old_parent;
+                *idx = Some(old_idx);
+                Ok(())
+            }
             _ => {
                 tracing::warn!("Action not yet implemented. Treating as no-op.",);
                 Ok(())
@@ -145,15 +354,242 @@ impl Engine {
         }
     }
 
+    /// Swaps `data` with the value of the state at `handle`.
+    fn write_state(&mut self, handle: DynStateHandle, data: &mut DynTpProperty) -> ActionResult {
+        // Sanity check that all types remain the same
+        if handle.prop_type() != data.prop_type() {
+            return Err(eyre!("Data did not match the type of the state"));
+        }
+        // TODO[SER-272]: handle DynTpProperty<DynTpData> and DynTpData in general
+        self.check_constraints(handle, Some(BBorrow::borrow(data)))?;
+
+        // Get data from the Action and apply it to the BaselineFork.
+        let baseline = self.realm.baseline_mut(BaselineKind::Fork);
+        apply_to_state_handle!(handle, |h: StateHandle<_>| -> ActionResult {
+            let state = baseline.state_mut(h).wrap_err("Invalid Handle")?;
+            let data = data.cast_mut().expect("Types were already checked");
+
+            // Swap the current value with the new data.
+            // This optimizes applying the Action and allows
+            // for its simple reversal if needed.
+            mem::swap(&mut state.value, data);
+            Ok(())
+        })?;
+
+        // Keep the spatial index in sync with positional states.
+        if let DynStateHandle::Primitive(DynStateHandlePrimitive::F32(h)) = handle {
+            let baseline = self.realm.baseline_mut(BaselineKind::Fork);
+            let value = baseline[h].value;
+            baseline.spatial_index_mut().on_state_write(h, value);
+        }
+        Ok(())
+    }
+
+    /// Fails with a [`Conflict`] unless the state at `handle` is at `version`.
+    fn check_version(&self, handle: DynStateHandle, version: u64) -> ActionResult {
+        let baseline = self.realm.baseline(BaselineKind::Fork);
+        let actual = baseline.state_version(handle).wrap_err("Invalid Handle")?;
+        if actual != version {
+            return Err(Conflict {
+                state: handle,
+                expected: version,
+                actual,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Moves the version of the state that `action` wrote to forwards or
+    /// backwards by one, depending on whether the action was just applied or
+    /// reversed.
+    fn bump_version(&mut self, action: &mut Action, forward: bool) {
+        let Some(handle) = written_state(action) else {
+            return;
+        };
+        let baseline = self.realm.baseline_mut(BaselineKind::Fork);
+        let version = baseline
+            .state_version(handle)
+            .expect("The state was just written");
+        let version = match forward {
+            true => version.wrapping_add(1),
+            false => version.wrapping_sub(1),
+        };
+        baseline
+            .set_state_version(handle, version)
+            .expect("The state was just written");
+
+        // The action now expects the version that it would reverse.
+        let Action::Property(PropertyAction::State(action)) = action else {
+            return;
+        };
+        if let StateAction::CompareAndSet { version: v, .. } = action {
+            *v = version;
+        }
+    }
+
+    /// Checks that writing to `handle` doesn't break the constraints of its
+    /// field. `data` is the new value, or `None` for partial writes, which only
+    /// check that the state isn't readonly.
+    fn check_constraints(
+        &self,
+        handle: DynStateHandle,
+        data: Option<DynTpPropertyRef>,
+    ) -> ActionResult {
+        let baseline = self.realm.baseline(BaselineKind::Fork);
+        let info = match baseline.state_constraints(handle) {
+            Some(info) => info,
+            None => return Ok(()),
+        };
+        if info.readonly {
+            return Err(eyre!("State is readonly"));
+        }
+        if let Some(data) = data {
+            info.check(data)?;
+        }
+        Ok(())
+    }
+
+    /// Fails with [`Locked`] if someone other than `owner` holds a lock on
+    /// `target`, or on the object of `target` if it is a state.
+    fn check_lock(&self, target: LockTarget, owner: Option<OwnerId>) -> ActionResult {
+        if !self.lock_checks || self.locks.is_empty() {
+            return Ok(());
+        }
+        let now = self.realm.time();
+        let object = match target {
+            LockTarget::State(h) => self
+                .realm
+                .baseline(BaselineKind::Fork)
+                .state_object(h)
+                .map(LockTarget::Object),
+            LockTarget::Object(_) => None,
+        };
+        for target in std::iter::once(target).chain(object) {
+            if let Some(lock) = self.locks.get(target, now) {
+                if Some(lock.owner) != owner {
+                    return Err(Locked { target, lock }.into());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fails with [`Locked`] if someone other than `owner` holds a lock on any
+    /// state of `obj`. Acquiring a lock on a state checks the lock on its object,
+    /// and this is the other way around, so that two owners can't lock each
+    /// other out of a state.
+    fn check_state_locks(&self, obj: ObjectHandle, owner: OwnerId) -> ActionResult {
+        if !self.lock_checks {
+            return Ok(());
+        }
+        let now = self.realm.time();
+        let baseline = self.realm.baseline(BaselineKind::Fork);
+        let conflict = self.locks.iter().find(|(target, lock)| {
+            let on_obj = match target {
+                LockTarget::State(h) => baseline.state_object(*h) == Some(obj),
+                LockTarget::Object(_) => false,
+            };
+            on_obj && lock.owner != owner && lock.is_held(now)
+        });
+        match conflict {
+            Some((target, lock)) => Err(Locked { target, lock }.into()),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_lock(&mut self, action: &mut LockAction, owner: Option<OwnerId>) -> ActionResult {
+        let owner = owner.ok_or_else(|| eyre!("Only collactions with an owner can hold locks"))?;
+        let now = self.realm.time();
+        match action {
+            LockAction::Acquire {
+                target,
+                lease,
+                replaced,
+            } => {
+                if *lease <= Ticks::new(0) {
+                    return Err(eyre!("The lease must be positive"));
+                }
+                let baseline = self.realm.baseline(BaselineKind::Fork);
+                match *target {
+                    LockTarget::Object(obj) => baseline.object(obj).map(|_| ()),
+                    LockTarget::State(h) => baseline.state_version(h).map(|_| ()),
+                }
+                .wrap_err("Invalid Handle")?;
+                self.check_lock(*target, Some(owner))?;
+                if let LockTarget::Object(obj) = *target {
+                    self.check_state_locks(obj, owner)?;
+                }
+
+                let lock = Lock {
+                    owner,
+                    expires: RealmTime::from(now.ticks() + *lease),
+                };
+                *replaced = self.locks.insert(*target, lock);
+                let event = match replaced {
+                    Some(l) if l.owner == owner && l.is_held(now) => LockEvent::Renewed {
+                        target: *target,
+                        lock,
+                    },
+                    _ => LockEvent::Acquired {
+                        target: *target,
+                        lock,
+                    },
+                };
+                self.lock_events.push(event);
+            }
+            LockAction::Release { target, replaced } => {
+                let holder = self.locks.get(*target, now).map(|l| l.owner);
+                if self.lock_checks && holder != Some(owner) {
+                    return Err(eyre!("{:?} is not locked by {:?}", target, owner));
+                }
+                *replaced = self.locks.remove(*target);
+                // Without lock checks, there may have been nothing to release, or
+                // the lock may have been held by someone else
+                if let Some(lock) = replaced {
+                    self.lock_events.push(LockEvent::Released {
+                        target: *target,
+                        owner: lock.owner,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Restores the lock that `action` replaced.
+    fn reverse_lock(&mut self, action: &mut LockAction) {
+        let target = action.target();
+        let (LockAction::Acquire { replaced, .. } | LockAction::Release { replaced, .. }) = action;
+        match replaced.take() {
+            Some(lock) => self.locks.insert(target, lock),
+            None => self.locks.remove(target),
+        };
+    }
+
     fn reverse_action(&mut self, action: &mut Action) {
+        if let Action::Lock(action) = action {
+            self.reverse_lock(action);
+            return;
+        }
         // Reverse Action by applying the previous value to the BaselineFork,
         // where applicable.
         match action.kind() {
-            ActionKind::StateAssert => {} // no-op
-            ActionKind::StateWrite => {
+            ActionKind::StateAssert | ActionKind::StateAssertVersion => {} // no-op
+            ActionKind::StateWrite
+            | ActionKind::StateMapInsert
+            | ActionKind::StateMapRemove
+            | ActionKind::StateBytesWrite
+            | ActionKind::StateCompareAndSet
+            | ActionKind::StateMerge => {
                 // Reverse by re-applying the Action.
                 // This triggers a value swap.
-                self.apply_action(action)
+                self.swap_action(action)
+                    .expect("TODO: How do we handle failure");
+                self.bump_version(action, false);
+            }
+            ActionKind::ObjectReparent => {
+                self.swap_action(action)
                     .expect("TODO: How do we handle failure");
             }
             _ => {
@@ -170,3 +606,868 @@ impl Engine {
         }
     }
 }
+
+/// The state that `action` writes to, if any.
+fn written_state(action: &Action) -> Option<DynStateHandle> {
+    let Action::Property(PropertyAction::State(action)) = action else {
+        return None;
+    };
+    match action {
+        StateAction::Write { handle, .. }
+        | StateAction::MapInsert { handle, .. }
+        | StateAction::MapRemove { handle, .. }
+        | StateAction::CompareAndSet { handle, .. } => Some(*handle),
+        StateAction::BytesWrite { handle, .. } | StateAction::Merge { handle, .. } => {
+            Some((*handle).into())
+        }
+        StateAction::Assert { .. } | StateAction::AssertVersion { .. } => None,
+    }
+}
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::engine")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use crate::action::Collaction;
+    use crate::realm::Realm;
+
+    use derive_more::{From, Into};
+    use rsharp::callback::{Callback, Dispatcher, RCallback};
+    use rsharp::remangle;
+    use safer_ffi::prelude::*;
+
+    /// Bundles the `Engine` with a sender for its queue, since C can't receive
+    /// both from `Engine__new()`.
+    #[remangle(substitute!())]
+    #[derive_ReprC]
+    #[ReprC::opaque]
+    pub struct Engine {
+        pub inner: super::Engine,
+        sender: super::ActionSender,
+    }
+
+    #[remangle(substitute!())]
+    #[derive_ReprC]
+    #[ReprC::opaque]
+    #[derive(From, Into, Clone)]
+    pub struct ActionSender {
+        pub inner: super::ActionSender,
+    }
+
+    #[remangle(substitute!())]
+    #[derive_ReprC]
+    #[ReprC::opaque]
+    #[derive(From, Into)]
+    pub struct CollactionResult {
+        pub inner: super::CollactionResult,
+    }
+
+    /// Takes ownership of `realm`. A `queue_capacity` of 0 means the queue is
+    /// unbounded.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__new(realm: repr_c::Box<Realm>, queue_capacity: usize) -> repr_c::Box<Engine> {
+        let realm: Box<Realm> = realm.into();
+        let queue_capacity = if queue_capacity == 0 {
+            None
+        } else {
+            Some(queue_capacity)
+        };
+        let (inner, sender) = super::Engine::new(*realm, queue_capacity);
+        Box::new(Engine { inner, sender }).into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__drop(engine: repr_c::Box<Engine>) {
+        drop(engine)
+    }
+
+    /// Creates a new sender for the engine's queue of collactions.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__sender(engine: &Engine) -> repr_c::Box<ActionSender> {
+        Box::new(ActionSender::from(engine.sender.clone())).into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__realm(engine: &Engine) -> &Realm {
+        engine.inner.realm()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__realm_mut(engine: &mut Engine) -> &mut Realm {
+        engine.inner.realm_mut()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__tick(engine: &mut Engine, ticks_since_last_call: i32) {
+        engine.inner.tick(ticks_since_last_call.into())
+    }
+
+    /// Returns null if there were no pending collactions.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__try_apply(engine: &mut Engine) -> Option<repr_c::Box<CollactionResult>> {
+        // The queue can't be disconnected, since `engine` holds a sender.
+        let result = engine.inner.try_apply().ok()?;
+        Some(Box::new(CollactionResult::from(result)).into())
+    }
+
+    /// Returns null if there were no pending collactions within the timeout.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__apply_timeout(
+        engine: &mut Engine,
+        timeout_millis: u64,
+    ) -> Option<repr_c::Box<CollactionResult>> {
+        let timeout = std::time::Duration::from_millis(timeout_millis);
+        let result = engine.inner.apply_timeout(timeout).ok()?;
+        Some(Box::new(CollactionResult::from(result)).into())
+    }
+
+    /// Takes ownership of `callback`, and calls it with whether each applied
+    /// collaction was approved. If `dispatcher` is not null, `callback` is only
+    /// called on the thread of `dispatcher`.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Engine__on_collaction_result(
+        engine: &mut Engine,
+        callback: RCallback<bool>,
+        dispatcher: Option<&Dispatcher>,
+    ) {
+        let callback = Callback::new(callback, dispatcher);
+        engine
+            .inner
+            .on_collaction_result(move |result| callback.call(result.is_ok()));
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn ActionSender__drop(sender: repr_c::Box<ActionSender>) {
+        drop(sender)
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn ActionSender__clone(sender: &ActionSender) -> repr_c::Box<ActionSender> {
+        Box::new(sender.clone()).into()
+    }
+
+    /// Takes ownership of `collaction` and queues it to be applied by the
+    /// engine. Returns false if the engine no longer exists.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn ActionSender__send(sender: &ActionSender, collaction: repr_c::Box<Collaction>) -> bool {
+        let collaction: Box<Collaction> = collaction.into();
+        sender.inner.send(*collaction).is_ok()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn CollactionResult__drop(result: repr_c::Box<CollactionResult>) {
+        drop(result)
+    }
+
+    /// Whether the collaction was approved, as opposed to rejected.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn CollactionResult__is_approved(result: &CollactionResult) -> bool {
+        result.inner.is_ok()
+    }
+
+    /// The collaction that was approved or rejected.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn CollactionResult__collaction(result: &CollactionResult) -> &Collaction {
+        match &result.inner {
+            Ok(c) | Err(c) => c,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::properties::bytes::Bytes;
+    use crate::contract::properties::dynamic::DynTpProperty;
+    use crate::contract::properties::traits::TpMap;
+    use crate::contract::{states, Contract, ContractDataHandle, ContractId};
+    use crate::object::ObjectHandle;
+    use crate::realm::RealmID;
+
+    #[states]
+    struct ScoreStates {
+        scores: TpMap<u32>,
+        leader: Option<String>,
+        banner: Bytes,
+    }
+
+    struct ScoreContract {
+        handle: ContractDataHandle,
+        states: ScoreStates,
+    }
+    impl Contract for ScoreContract {
+        type States = ScoreStates;
+        type Channels = ();
+
+        const ID: ContractId = ContractId {
+            name: "teleportal.test.scores",
+            version: (0, 0, 0),
+        };
+
+        fn new(handle: ContractDataHandle) -> Self {
+            Self {
+                handle,
+                states: ScoreStates::new(handle),
+            }
+        }
+
+        fn states(&self) -> &Self::States {
+            &self.states
+        }
+
+        fn channels(&self) -> &Self::Channels {
+            &()
+        }
+
+        fn handle(&self) -> ContractDataHandle {
+            self.handle
+        }
+    }
+
+    #[states]
+    struct AvatarStates {
+        tags: TpMap<u32>,
+        #[default(1.0)]
+        #[range(0.0..=1.0)]
+        opacity: f32,
+        #[readonly]
+        #[default(String::from("player"))]
+        kind: String,
+    }
+
+    struct AvatarContract {
+        handle: ContractDataHandle,
+        states: AvatarStates,
+    }
+    impl Contract for AvatarContract {
+        type States = AvatarStates;
+        type Channels = ();
+
+        const ID: ContractId = ContractId {
+            name: "teleportal.test.avatar",
+            version: (0, 0, 0),
+        };
+
+        fn new(handle: ContractDataHandle) -> Self {
+            Self {
+                handle,
+                states: AvatarStates::new(handle),
+            }
+        }
+
+        fn states(&self) -> &Self::States {
+            &self.states
+        }
+
+        fn channels(&self) -> &Self::Channels {
+            &()
+        }
+
+        fn handle(&self) -> ContractDataHandle {
+            self.handle
+        }
+    }
+
+    #[test]
+    fn test_map_actions() {
+        let (mut engine, sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: ScoreContract = baseline.register_contract().unwrap();
+        let states = [
+            DynTpProperty::from(TpMap::<u32>::new()),
+            DynTpProperty::from(None::<String>),
+            DynTpProperty::from(Bytes::new()),
+        ];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let scores = baseline.bind_state(c.states().scores(), obj).unwrap();
+        let leader = baseline.bind_state(c.states().leader(), obj).unwrap();
+
+        let insert = |k: &str, v: u32| -> Action {
+            PropertyAction::from(StateAction::map_insert(scores.into(), k.into(), v.into())).into()
+        };
+        let remove = |k: &str| -> Action {
+            PropertyAction::from(StateAction::map_remove(scores.into(), k.into())).into()
+        };
+        let get = |engine: &Engine| -> TpMap<u32> {
+            engine.realm().baseline(BaselineKind::Fork)[scores]
+                .value
+                .clone()
+        };
+
+        sender
+            .send(Collaction::new(vec![
+                insert("alice", 1),
+                insert("bob", 2),
+                insert("alice", 3),
+            ]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_ok());
+        let expected: TpMap<u32> = [("alice".to_string(), 3), ("bob".to_string(), 2)]
+            .into_iter()
+            .collect();
+        assert_eq!(get(&engine), expected);
+
+        // Removing a missing key is a no-op
+        sender
+            .send(Collaction::new(vec![remove("bob"), remove("carol")]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_ok());
+        assert_eq!(get(&engine).len(), 1);
+
+        // A failing action reverses the previous inserts and removes
+        let failing_assert: Action = PropertyAction::from(StateAction::Assert {
+            handle: leader.into(),
+            data: Some(String::from("alice")).into(),
+        })
+        .into();
+        sender
+            .send(Collaction::new(vec![
+                remove("alice"),
+                insert("bob", 5),
+                insert("alice", 4),
+                failing_assert,
+            ]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_err());
+        let expected: TpMap<u32> = [("alice".to_string(), 3)].into_iter().collect();
+        assert_eq!(get(&engine), expected);
+    }
+    #[test]
+    fn test_bytes_write() {
+        let (mut engine, sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: ScoreContract = baseline.register_contract().unwrap();
+        let states = [
+            DynTpProperty::from(TpMap::<u32>::new()),
+            DynTpProperty::from(None::<String>),
+            DynTpProperty::from(Bytes::from(vec![0, 1, 2, 3])),
+        ];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let banner = baseline.bind_state(c.states().banner(), obj).unwrap();
+        let leader = baseline.bind_state(c.states().leader(), obj).unwrap();
+
+        let write = |offset: usize, chunk: &[u8]| -> Action {
+            PropertyAction::from(StateAction::bytes_write(banner, offset, chunk.into())).into()
+        };
+        let get = |engine: &Engine| -> Bytes {
+            engine.realm().baseline(BaselineKind::Fork)[banner]
+                .value
+                .clone()
+        };
+
+        sender
+            .send(Collaction::new(vec![write(2, &[7, 7, 7]), write(5, &[8])]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_ok());
+        assert_eq!(get(&engine).as_slice(), [0, 1, 7, 7, 7, 8]);
+
+        // A failing action reverses the previous writes, including growth
+        let failing_assert: Action = PropertyAction::from(StateAction::Assert {
+            handle: leader.into(),
+            data: Some(String::from("alice")).into(),
+        })
+        .into();
+        sender
+            .send(Collaction::new(vec![
+                write(0, &[9]),
+                write(6, &[9, 9]),
+                failing_assert,
+            ]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_err());
+        assert_eq!(get(&engine).as_slice(), [0, 1, 7, 7, 7, 8]);
+    }
+
+    #[test]
+    fn test_constraints() {
+        let (mut engine, sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: AvatarContract = baseline.register_contract().unwrap();
+
+        // Out of range on creation
+        let states = [
+            DynTpProperty::from(TpMap::<u32>::new()),
+            DynTpProperty::from(2.0f32),
+        ];
+        assert!(baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .is_err());
+        // Missing a state that has no default
+        assert!(baseline
+            .object_create(&c, [].into_iter(), [].into_iter())
+            .is_err());
+
+        // Omitted states are filled in with defaults
+        let states = [DynTpProperty::from(TpMap::<u32>::new())];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let tags = baseline.bind_state(c.states().tags(), obj).unwrap();
+        let opacity = baseline.bind_state(c.states().opacity(), obj).unwrap();
+        let kind = baseline.bind_state(c.states().kind(), obj).unwrap();
+        assert_eq!(baseline[opacity].value, 1.0);
+        assert_eq!(baseline[kind].value, "player");
+        assert!(baseline.state_constraints(tags).is_none());
+        assert!(baseline.state_constraints(kind).unwrap().readonly);
+
+        let write = |handle: DynStateHandle, data: DynTpProperty| -> Action {
+            PropertyAction::from(StateAction::Write { handle, data }).into()
+        };
+        let get_opacity =
+            |engine: &Engine| engine.realm().baseline(BaselineKind::Fork)[opacity].value;
+
+        sender
+            .send(Collaction::new(vec![
+                write(opacity.into(), 0.5f32.into()),
+                PropertyAction::from(StateAction::map_insert(
+                    tags.into(),
+                    "a".into(),
+                    1u32.into(),
+                ))
+                .into(),
+            ]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_ok());
+        assert_eq!(get_opacity(&engine), 0.5);
+
+        // Out of range
+        sender
+            .send(Collaction::new(vec![write(opacity.into(), 1.5f32.into())]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_err());
+        assert_eq!(get_opacity(&engine), 0.5);
+
+        // Readonly, which also reverses the earlier write
+        sender
+            .send(Collaction::new(vec![
+                write(opacity.into(), 0.0f32.into()),
+                write(kind.into(), String::from("npc").into()),
+            ]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_err());
+        assert_eq!(get_opacity(&engine), 0.5);
+        assert_eq!(
+            engine.realm().baseline(BaselineKind::Fork)[kind].value,
+            "player"
+        );
+    }
+
+    #[test]
+    fn test_collaction_hooks() {
+        use std::sync::{Arc, Mutex};
+
+        let (mut engine, sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: AvatarContract = baseline.register_contract().unwrap();
+        let states = [DynTpProperty::from(TpMap::<u32>::new())];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let opacity = baseline.bind_state(c.states().opacity(), obj).unwrap();
+
+        let approved = Arc::new(Mutex::new(Vec::new()));
+        let approved2 = Arc::clone(&approved);
+        engine.on_collaction_result(move |result| approved2.lock().unwrap().push(result.is_ok()));
+
+        let write = |value: f32| -> Collaction {
+            Collaction::new(vec![PropertyAction::from(StateAction::Write {
+                handle: opacity.into(),
+                data: value.into(),
+            })
+            .into()])
+        };
+        sender.send(write(0.5)).unwrap();
+        sender.send(write(1.5)).unwrap();
+        engine.try_apply().unwrap();
+        engine
+            .apply_timeout(std::time::Duration::from_millis(10))
+            .unwrap();
+        assert!(engine.try_apply().is_err());
+
+        assert_eq!(*approved.lock().unwrap(), [true, false]);
+    }
+
+    #[test]
+    fn test_versions() {
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: AvatarContract = baseline.register_contract().unwrap();
+        let states = [DynTpProperty::from(TpMap::<u32>::new())];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let opacity = baseline.bind_state(c.states().opacity(), obj).unwrap();
+
+        let action = |action: StateAction| -> Action { PropertyAction::from(action).into() };
+        let write = |value: f32| {
+            action(StateAction::Write {
+                handle: opacity.into(),
+                data: value.into(),
+            })
+        };
+        let cas = |version: u64, value: f32| {
+            action(StateAction::CompareAndSet {
+                handle: opacity.into(),
+                version,
+                data: value.into(),
+            })
+        };
+        let get = |engine: &Engine| {
+            let state = &engine.realm().baseline(BaselineKind::Fork)[opacity];
+            (state.value, state.version())
+        };
+        assert_eq!(get(&engine), (1.0, 0));
+
+        engine
+            .apply(Collaction::new(vec![write(0.5), write(0.5)]))
+            .unwrap();
+        assert_eq!(get(&engine), (0.5, 2));
+
+        // Writing the same value still conflicts
+        let stale = engine
+            .apply(Collaction::new(vec![
+                write(0.25),
+                action(StateAction::AssertVersion {
+                    handle: opacity.into(),
+                    version: 1,
+                }),
+            ]))
+            .unwrap_err();
+        let rejection = stale.rejection().unwrap();
+        assert_eq!(rejection.action, 1);
+        let conflict = rejection.conflict.unwrap();
+        assert_eq!((conflict.expected, conflict.actual), (1, 3));
+        assert_eq!(conflict.state, DynStateHandle::from(opacity));
+        // Reversed writes don't count
+        assert_eq!(get(&engine), (0.5, 2));
+
+        // Failures other than conflicts are reported too
+        let rejected = engine
+            .apply(Collaction::new(vec![cas(2, 0.25), write(1.5)]))
+            .unwrap_err();
+        let rejection = rejected.rejection().unwrap();
+        assert_eq!(rejection.action, 1);
+        assert!(rejection.conflict.is_none());
+        assert_eq!(get(&engine), (0.5, 2));
+
+        let mut applied = engine.apply(Collaction::new(vec![cas(2, 0.25)])).unwrap();
+        assert!(applied.rejection().is_none());
+        assert_eq!(get(&engine), (0.25, 3));
+        // Applying it again reverses it, unless the state was written since
+        applied.reverse();
+        let applied = engine.apply(applied).unwrap();
+        assert_eq!(get(&engine), (0.5, 4));
+        engine.apply(Collaction::new(vec![write(0.75)])).unwrap();
+        let rejected = engine.apply(applied).unwrap_err();
+        assert!(rejected.rejection().unwrap().conflict.is_some());
+        assert_eq!(get(&engine), (0.75, 5));
+    }
+
+    #[test]
+    fn test_locks() {
+        use crate::lock::LockEvent as E;
+        use std::sync::{Arc, Mutex};
+
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: AvatarContract = baseline.register_contract().unwrap();
+        let states = [DynTpProperty::from(TpMap::<u32>::new())];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let opacity = baseline.bind_state(c.states().opacity(), obj).unwrap();
+        let (alice, bob) = (OwnerId(1), OwnerId(2));
+        let state = LockTarget::from(DynStateHandle::from(opacity));
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events2 = Arc::clone(&events);
+        engine.on_lock_change(move |e| events2.lock().unwrap().push(*e));
+
+        let write = |value: f32| -> Action {
+            PropertyAction::from(StateAction::Write {
+                handle: opacity.into(),
+                data: value.into(),
+            })
+            .into()
+        };
+        let acquire = |target: LockTarget, lease: i32| -> Action {
+            LockAction::acquire(target, Ticks::new(lease)).into()
+        };
+        let release = |target: LockTarget| -> Action { LockAction::release(target).into() };
+        let from =
+            |owner: OwnerId, actions: Vec<Action>| Collaction::new(actions).with_owner(owner);
+
+        // Only collactions with an owner can lock
+        assert!(engine
+            .apply(Collaction::new(vec![acquire(obj.into(), 100)]))
+            .is_err());
+        assert!(engine
+            .apply(from(alice, vec![acquire(obj.into(), 100)]))
+            .is_ok());
+        let now = engine.realm().time();
+        assert_eq!(engine.locks().get(obj, now).unwrap().owner, alice);
+
+        // Locking the object locks its states, for everyone but the owner
+        let rejected = engine.apply(from(bob, vec![write(0.5)])).unwrap_err();
+        assert!(rejected.rejection().unwrap().reason.contains("locked"));
+        assert!(engine.apply(Collaction::new(vec![write(0.5)])).is_err());
+        assert!(engine.apply(from(bob, vec![acquire(state, 10)])).is_err());
+        assert!(engine.apply(from(alice, vec![write(0.5)])).is_ok());
+
+        // Renewing, then a rejected release that is reversed
+        assert!(engine
+            .apply(from(alice, vec![acquire(obj.into(), 200)]))
+            .is_ok());
+        assert!(engine
+            .apply(from(alice, vec![release(obj.into()), write(2.0)]))
+            .is_err());
+        assert!(engine.apply(from(bob, vec![release(obj.into())])).is_err());
+        assert_eq!(engine.locks().owned_by(alice, now).count(), 1);
+
+        // Expiring
+        engine.tick(Ticks::new(150));
+        assert!(engine.apply(from(bob, vec![write(0.25)])).is_err());
+        engine.tick(Ticks::new(60));
+        assert!(engine.locks().is_empty());
+        assert!(engine.apply(from(bob, vec![write(0.25)])).is_ok());
+
+        // Locking a single state
+        assert!(engine
+            .apply(from(bob, vec![acquire(state, 100), release(state)]))
+            .is_ok());
+
+        let target = LockTarget::Object(obj);
+        let lock = |expires: i32| Lock {
+            owner: alice,
+            expires: RealmTime::from(Ticks::new(expires)),
+        };
+        let bobs = Lock {
+            owner: bob,
+            expires: RealmTime::from(Ticks::new(310)),
+        };
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                E::Acquired {
+                    target,
+                    lock: lock(100)
+                },
+                E::Renewed {
+                    target,
+                    lock: lock(200)
+                },
+                E::Expired {
+                    target,
+                    owner: alice
+                },
+                E::Acquired {
+                    target: state,
+                    lock: bobs
+                },
+                E::Released {
+                    target: state,
+                    owner: bob
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lock_state_and_object() {
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: AvatarContract = baseline.register_contract().unwrap();
+        let states = [DynTpProperty::from(TpMap::<u32>::new())];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let opacity = baseline.bind_state(c.states().opacity(), obj).unwrap();
+        let (alice, bob) = (OwnerId(1), OwnerId(2));
+        let state = LockTarget::from(DynStateHandle::from(opacity));
+        let object = LockTarget::Object(obj);
+
+        let acquire = |owner, target| {
+            let action: Action = LockAction::acquire(target, Ticks::new(100)).into();
+            Collaction::new(vec![action]).with_owner(owner)
+        };
+        let release = |owner, target| {
+            let action: Action = LockAction::release(target).into();
+            Collaction::new(vec![action]).with_owner(owner)
+        };
+
+        // The state first, then its object
+        assert!(engine.apply(acquire(bob, state)).is_ok());
+        let rejected = engine.apply(acquire(alice, object)).unwrap_err();
+        assert!(rejected.rejection().unwrap().reason.contains("locked"));
+        // The same owner may hold both
+        assert!(engine.apply(acquire(bob, object)).is_ok());
+        assert!(engine.apply(release(bob, object)).is_ok());
+        assert!(engine.apply(release(bob, state)).is_ok());
+
+        // The object first, then one of its states
+        assert!(engine.apply(acquire(alice, object)).is_ok());
+        let rejected = engine.apply(acquire(bob, state)).unwrap_err();
+        assert!(rejected.rejection().unwrap().reason.contains("locked"));
+        let now = engine.realm().time();
+        assert_eq!(engine.locks().get(object, now).unwrap().owner, alice);
+        assert!(engine.locks().get(state, now).is_none());
+    }
+
+    #[test]
+    fn test_release_without_lock_checks() {
+        use crate::lock::LockEvent as E;
+        use std::sync::{Arc, Mutex};
+
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        engine.set_lock_checks(false);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: AvatarContract = baseline.register_contract().unwrap();
+        let states = [DynTpProperty::from(TpMap::<u32>::new())];
+        let obj = baseline
+            .object_create(&c, states.into_iter(), [].into_iter())
+            .unwrap();
+        let (alice, bob) = (OwnerId(1), OwnerId(2));
+        let target = LockTarget::Object(obj);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events2 = Arc::clone(&events);
+        engine.on_lock_change(move |e| events2.lock().unwrap().push(*e));
+
+        let release =
+            |owner| Collaction::new(vec![LockAction::release(target).into()]).with_owner(owner);
+        // Releasing a lock that isn't held does nothing
+        assert!(engine.apply(release(bob)).is_ok());
+        let acquire: Action = LockAction::acquire(target, Ticks::new(100)).into();
+        assert!(engine
+            .apply(Collaction::new(vec![acquire]).with_owner(alice))
+            .is_ok());
+        // Someone else may release it, but it was still alice's lock
+        assert!(engine.apply(release(bob)).is_ok());
+        assert!(engine.locks().is_empty());
+
+        let lock = Lock {
+            owner: alice,
+            expires: RealmTime::from(Ticks::new(100)),
+        };
+        assert_eq!(
+            *events.lock().unwrap(),
+            [
+                E::Acquired { target, lock },
+                E::Released {
+                    target,
+                    owner: alice
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: AvatarContract = baseline.register_contract().unwrap();
+        let mut create = || {
+            let states = [DynTpProperty::from(TpMap::<u32>::new())];
+            baseline
+                .object_create(&c, states.into_iter(), [].into_iter())
+                .unwrap()
+        };
+        let [root, a, b, a1] = [(); 4].map(|_| create());
+
+        baseline.object_reparent(a, Some(root), None).unwrap();
+        baseline.object_reparent(b, Some(root), Some(0)).unwrap();
+        baseline.object_reparent(a1, Some(a), None).unwrap();
+        assert_eq!(baseline[root].children(), [b, a]);
+        assert_eq!(baseline[a1].parent(), Some(a));
+        let descendants: Vec<_> = baseline.iter_descendants(root).collect();
+        assert_eq!(descendants, [b, a, a1]);
+        let ancestors: Vec<_> = baseline.iter_ancestors(a1).collect();
+        assert_eq!(ancestors, [a, root]);
+        assert_eq!(baseline.iter_roots().collect::<Vec<_>>(), [root]);
+
+        // Cycles and indices past the end
+        assert!(baseline.object_reparent(root, Some(a1), None).is_err());
+        assert!(baseline.object_reparent(a, Some(a), None).is_err());
+        assert!(baseline.object_reparent(b, Some(root), Some(2)).is_err());
+        assert!(baseline.object_reparent(b, None, Some(1)).is_err());
+        // Moving within the same parent
+        let previous = baseline.object_reparent(b, Some(root), Some(1)).unwrap();
+        assert_eq!(previous, (Some(root), 0));
+        assert_eq!(baseline[root].children(), [a, b]);
+
+        let reparent = |obj, parent| -> Action { ObjectAction::reparent(obj, parent).into() };
+        let children = |engine: &Engine, obj: ObjectHandle| -> Vec<ObjectHandle> {
+            engine.realm().baseline(BaselineKind::Fork)[obj]
+                .children()
+                .to_vec()
+        };
+
+        // Reversed when the collaction is rejected
+        let failing: Action = LockAction::release(root).into();
+        assert!(engine
+            .apply(Collaction::new(vec![reparent(a1, None), failing]))
+            .is_err());
+        assert_eq!(children(&engine, a), [a1]);
+
+        // Applying it again reverses it
+        let mut applied = engine
+            .apply(Collaction::new(vec![reparent(a1, Some(b))]))
+            .unwrap();
+        assert!(children(&engine, a).is_empty());
+        assert_eq!(children(&engine, b), [a1]);
+        applied.reverse();
+        engine.apply(applied).unwrap();
+        assert_eq!(children(&engine, a), [a1]);
+        assert!(children(&engine, b).is_empty());
+
+        // The new parent can be locked
+        let acquire: Action = LockAction::acquire(b, Ticks::new(100)).into();
+        assert!(engine
+            .apply(Collaction::new(vec![acquire]).with_owner(OwnerId(1)))
+            .is_ok());
+        assert!(engine
+            .apply(Collaction::new(vec![reparent(a1, Some(b))]))
+            .is_err());
+
+        // Removing an object removes its descendants
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        baseline.object_remove::<AvatarContract>(a).unwrap();
+        assert!(baseline.object(a1).is_err());
+        assert_eq!(baseline[root].children(), [b]);
+        assert_eq!(baseline.iter_objects().count(), 2);
+
+        // Deep hierarchies don't overflow the stack. Built from the bottom up, so
+        // that reparenting doesn't have to walk the ancestors.
+        let mut top = b;
+        for _ in 0..10_000 {
+            let states = [DynTpProperty::from(TpMap::<u32>::new())];
+            let obj = baseline
+                .object_create(&c, states.into_iter(), [].into_iter())
+                .unwrap();
+            baseline.object_reparent(top, Some(obj), None).unwrap();
+            top = obj;
+        }
+        baseline.object_remove::<AvatarContract>(top).unwrap();
+        assert_eq!(baseline.iter_objects().count(), 1);
+    }
+}
@@ -0,0 +1,435 @@
+//! Undo and redo of collactions, for editing tools.
+
+use crate::action::{Action, Collaction, CollactionResult};
+use crate::engine::Engine;
+
+use eyre::{eyre, Result};
+#[cfg(feature = "c_api")]
+use safer_ffi::derive_ReprC;
+
+/// A history of edits that can be undone and redone, where each edit is a
+/// collaction that was approved by the [`Engine`].
+///
+/// The `Engine` swaps the previous values of states into the actions that it
+/// applies, so an approved collaction already holds everything needed to
+/// reverse it: applying its actions again, in reverse order, restores the
+/// previous values. Undoing an edit does exactly that, which in turn leaves
+/// the collaction ready to be redone.
+///
+/// Only the collactions passed to [`History::record`] are edits, so changes
+/// from elsewhere, such as other users, are never undone. Undoing an edit
+/// still overwrites any of their changes to the same states, unless the edit
+/// asserted those states, in which case the undo is rejected.
+///
+/// Locks aren't edits, so the lock actions of recorded collactions are left
+/// out. Undoing an edit neither releases nor reacquires the locks that it
+/// took, and applying an `Acquire` again would only renew the lock.
+#[cfg_attr(feature = "c_api", derive_ReprC, ReprC::opaque)]
+#[derive(Default)]
+pub struct History {
+    /// Applied edits, most recent last
+    undo: Vec<Collaction>,
+    /// Undone edits, most recently undone last
+    redo: Vec<Collaction>,
+    /// The edits of the open transaction, and how deeply it is nested
+    transaction: Option<(Collaction, usize)>,
+    limit: Option<usize>,
+}
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only keeps the `limit` most recent edits.
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Records a collaction that the engine approved as a new edit, which
+    /// discards the edits that can be redone. Its lock actions are dropped,
+    /// and collactions with nothing else in them are ignored.
+    pub fn record(&mut self, mut collaction: Collaction) {
+        collaction.retain(|action| !matches!(action, Action::Lock(_)));
+        if collaction.actions().is_empty() {
+            return;
+        }
+        self.redo.clear();
+        match &mut self.transaction {
+            Some((edits, _)) => edits.append(collaction),
+            None => self.push(collaction),
+        }
+    }
+
+    /// Groups every edit recorded until the matching
+    /// [`History::commit_transaction`] into a single edit. Transactions may be
+    /// nested, in which case only the outermost one counts.
+    pub fn begin_transaction(&mut self) {
+        match &mut self.transaction {
+            Some((_, depth)) => *depth += 1,
+            None => self.transaction = Some((Collaction::new(Vec::new()), 1)),
+        }
+    }
+
+    /// Ends the innermost open transaction.
+    ///
+    /// # Errors
+    /// Will error if there is no open transaction.
+    pub fn commit_transaction(&mut self) -> Result<()> {
+        let (_, depth) = self
+            .transaction
+            .as_mut()
+            .ok_or_else(|| eyre!("There is no open transaction"))?;
+        *depth -= 1;
+        if *depth == 0 {
+            let (edits, _) = self
+                .transaction
+                .take()
+                .expect("Transaction was just checked");
+            if !edits.actions().is_empty() {
+                self.push(edits);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Reverses the most recent edit in `engine`. Returns `false` if there was
+    /// nothing to undo.
+    ///
+    /// # Errors
+    /// Will error if a transaction is open, or if the engine rejected the undo.
+    /// Rejected edits stay where they were, so they can be undone later.
+    pub fn undo(&mut self, engine: &mut Engine) -> Result<bool> {
+        self.check_no_transaction()?;
+        let Some(edit) = self.undo.pop() else {
+            return Ok(false);
+        };
+        match apply(engine, edit, true) {
+            Ok(edit) => {
+                self.redo.push(edit);
+                Ok(true)
+            }
+            Err(edit) => {
+                self.undo.push(edit);
+                Err(eyre!("The engine rejected undoing the edit"))
+            }
+        }
+    }
+
+    /// Applies the most recently undone edit to `engine` again. Returns `false`
+    /// if there was nothing to redo.
+    ///
+    /// # Errors
+    /// Will error if a transaction is open, or if the engine rejected the redo.
+    /// Rejected edits stay where they were, so they can be redone later.
+    pub fn redo(&mut self, engine: &mut Engine) -> Result<bool> {
+        self.check_no_transaction()?;
+        let Some(edit) = self.redo.pop() else {
+            return Ok(false);
+        };
+        match apply(engine, edit, false) {
+            Ok(edit) => {
+                self.undo.push(edit);
+                Ok(true)
+            }
+            Err(edit) => {
+                self.redo.push(edit);
+                Err(eyre!("The engine rejected redoing the edit"))
+            }
+        }
+    }
+
+    /// Forgets every edit, including those of the open transaction.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.transaction = None;
+    }
+
+    fn push(&mut self, edit: Collaction) {
+        self.undo.push(edit);
+        if let Some(limit) = self.limit {
+            if self.undo.len() > limit {
+                self.undo.drain(..self.undo.len() - limit);
+            }
+        }
+    }
+
+    fn check_no_transaction(&self) -> Result<()> {
+        match self.in_transaction() {
+            true => Err(eyre!("A transaction is open")),
+            false => Ok(()),
+        }
+    }
+}
+
+/// Applies `edit` to `engine`, with its actions in reverse order if `reverse`
+/// is set. Either way, the returned collaction has them in their original
+/// order.
+fn apply(engine: &mut Engine, mut edit: Collaction, reverse: bool) -> CollactionResult {
+    if reverse {
+        edit.reverse();
+    }
+    // Rejected collactions are reversed by the engine, so either way the
+    // actions are ready to be applied in the other direction.
+    let mut result = engine.apply(edit);
+    if reverse {
+        let (Ok(edit) | Err(edit)) = &mut result;
+        edit.reverse();
+    }
+    result
+}
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::history")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use super::History;
+    use crate::engine::c_api::{CollactionResult, Engine};
+
+    use rsharp::remangle;
+    use rsharp::result::{RResult, RStatus};
+    use safer_ffi::prelude::*;
+
+    /// Only keeps the `limit` most recent edits. A `limit` of 0 means there is
+    /// no limit.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__new(limit: usize) -> repr_c::Box<History> {
+        let history = match limit {
+            0 => History::new(),
+            limit => History::new().with_limit(limit),
+        };
+        Box::new(history).into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__drop(history: repr_c::Box<History>) {
+        drop(history)
+    }
+
+    /// Takes ownership of `result`, and records its collaction as an edit if it
+    /// was approved. Returns whether it was approved.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__record(history: &mut History, result: repr_c::Box<CollactionResult>) -> bool {
+        let result: Box<CollactionResult> = result.into();
+        match result.inner {
+            Ok(collaction) => {
+                history.record(collaction);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__begin_transaction(history: &mut History) {
+        history.begin_transaction()
+    }
+
+    /// Errors if there is no open transaction.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__commit_transaction(history: &mut History) -> RStatus {
+        history.commit_transaction()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__in_transaction(history: &History) -> bool {
+        history.in_transaction()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__can_undo(history: &History) -> bool {
+        history.can_undo()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__can_redo(history: &History) -> bool {
+        history.can_redo()
+    }
+
+    /// Returns false if there was nothing to undo. Errors if a transaction is
+    /// open, or if the engine rejected the undo.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__undo(history: &mut History, engine: &mut Engine) -> RResult<bool> {
+        history.undo(&mut engine.inner)
+    }
+
+    /// Returns false if there was nothing to redo. Errors if a transaction is
+    /// open, or if the engine rejected the redo.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__redo(history: &mut History, engine: &mut Engine) -> RResult<bool> {
+        history.redo(&mut engine.inner)
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn History__clear(history: &mut History) {
+        history.clear()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::lock::LockAction;
+    use crate::action::property::{PropertyAction, StateAction};
+    use crate::baseline::BaselineKind;
+    use crate::contract::properties::dynamic::{DynTpProperty, TpPrimitiveType, TpPropertyType};
+    use crate::contract::properties::states::{StateHandle, StateId};
+    use crate::contract::ContractSchema;
+    use crate::lock::OwnerId;
+    use crate::realm::{Realm, RealmID};
+    use crate::time::Ticks;
+
+    #[test]
+    fn test_undo_redo() {
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let schema = ContractSchema::new("teleportal.test.history", (0, 1, 0))
+            .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8));
+        let c = baseline.register_dyn_contract(&schema).unwrap();
+        let obj = baseline
+            .object_create(&c, [DynTpProperty::from(0u8)].into_iter(), [].into_iter())
+            .unwrap();
+        let hp: StateHandle<u8> = baseline
+            .bind_state(StateId::new(0, c.handle()), obj)
+            .unwrap();
+
+        let write = |value: u8| -> Action {
+            PropertyAction::from(StateAction::Write {
+                handle: hp.into(),
+                data: value.into(),
+            })
+            .into()
+        };
+        let assert = |value: u8| -> Action {
+            PropertyAction::from(StateAction::Assert {
+                handle: hp.into(),
+                data: value.into(),
+            })
+            .into()
+        };
+        let get = |engine: &Engine| engine.realm().baseline(BaselineKind::Fork)[hp].value;
+        let mut history = History::new().with_limit(3);
+        let edit = |engine: &mut Engine, history: &mut History, actions: Vec<Action>| {
+            let collaction = engine.apply(Collaction::new(actions)).unwrap();
+            history.record(collaction);
+        };
+
+        assert!(!history.undo(&mut engine).unwrap());
+        edit(&mut engine, &mut history, vec![write(1)]);
+        edit(&mut engine, &mut history, vec![write(2), write(3)]);
+        assert_eq!(get(&engine), 3);
+        assert!(history.undo(&mut engine).unwrap());
+        assert_eq!(get(&engine), 1);
+        assert!(history.undo(&mut engine).unwrap());
+        assert_eq!(get(&engine), 0);
+        assert!(!history.can_undo());
+        assert!(history.redo(&mut engine).unwrap());
+        assert!(history.redo(&mut engine).unwrap());
+        assert_eq!(get(&engine), 3);
+        assert!(!history.redo(&mut engine).unwrap());
+
+        // A new edit discards the ones that could be redone
+        history.undo(&mut engine).unwrap();
+        edit(&mut engine, &mut history, vec![write(4)]);
+        assert!(!history.can_redo());
+
+        // Nested transactions become a single edit
+        history.begin_transaction();
+        edit(&mut engine, &mut history, vec![write(5)]);
+        history.begin_transaction();
+        edit(&mut engine, &mut history, vec![write(6)]);
+        history.commit_transaction().unwrap();
+        assert!(history.undo(&mut engine).is_err());
+        history.commit_transaction().unwrap();
+        assert!(history.commit_transaction().is_err());
+        assert_eq!(get(&engine), 6);
+        history.undo(&mut engine).unwrap();
+        assert_eq!(get(&engine), 4);
+
+        // Undoing an edit that asserted a state that has since changed
+        history.redo(&mut engine).unwrap();
+        edit(&mut engine, &mut history, vec![write(7), assert(7)]);
+        engine.apply(Collaction::new(vec![write(8)])).unwrap();
+        assert!(history.undo(&mut engine).is_err());
+        assert_eq!(get(&engine), 8);
+        engine.apply(Collaction::new(vec![write(7)])).unwrap();
+        history.undo(&mut engine).unwrap();
+        assert_eq!(get(&engine), 6);
+
+        // Only the 3 most recent edits were kept
+        history.undo(&mut engine).unwrap();
+        history.undo(&mut engine).unwrap();
+        assert_eq!(get(&engine), 1);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_locks_are_not_edits() {
+        let (mut engine, _sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let schema = ContractSchema::new("teleportal.test.history", (0, 1, 0))
+            .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8));
+        let c = baseline.register_dyn_contract(&schema).unwrap();
+        let obj = baseline
+            .object_create(&c, [DynTpProperty::from(0u8)].into_iter(), [].into_iter())
+            .unwrap();
+        let hp: StateHandle<u8> = baseline
+            .bind_state(StateId::new(0, c.handle()), obj)
+            .unwrap();
+
+        let owner = OwnerId(1);
+        let write: Action = PropertyAction::from(StateAction::Write {
+            handle: hp.into(),
+            data: 1u8.into(),
+        })
+        .into();
+        let acquire: Action = LockAction::acquire(obj, Ticks::new(100)).into();
+        let mut history = History::new();
+        let collaction = Collaction::new(vec![acquire, write]).with_owner(owner);
+        history.record(engine.apply(collaction).unwrap());
+        let lock_only = Collaction::new(vec![LockAction::release(obj).into()]).with_owner(owner);
+        history.record(engine.apply(lock_only).unwrap());
+        assert!(engine.locks().get(obj, engine.realm().time()).is_none());
+
+        // Undoing the write doesn't reacquire the lock, and the release alone
+        // wasn't an edit.
+        assert!(history.undo(&mut engine).unwrap());
+        assert_eq!(engine.realm().baseline(BaselineKind::Fork)[hp].value, 0);
+        assert!(engine.locks().get(obj, engine.realm().time()).is_none());
+        assert!(!history.can_undo());
+
+        // Likewise while someone else holds the lock, which the undo doesn't
+        // get to replace.
+        let other = Collaction::new(vec![LockAction::acquire(obj, Ticks::new(100)).into()])
+            .with_owner(OwnerId(2));
+        engine.apply(other).unwrap();
+        assert!(history.redo(&mut engine).is_err());
+        let lock = engine.locks().get(obj, engine.realm().time()).unwrap();
+        assert_eq!(lock.owner, OwnerId(2));
+    }
+}
@@ -21,6 +21,7 @@ extern crate self as tp_client;
 
 /// Reexported for the sake of the proc macros
 mod re_exports {
+    pub use ::eyre;
     pub use ::lazy_static;
     pub use ::paste;
 }
@@ -30,9 +31,15 @@ pub mod action;
 pub mod baseline;
 pub mod contract;
 pub mod engine;
+pub mod history;
+pub mod lock;
 pub mod object;
+pub mod prefab;
 pub mod realm;
+pub mod spatial;
 pub mod time;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use engine::Engine;
 
@@ -41,6 +48,7 @@ pub use engine::Engine;
 #[safer_ffi::cfg_headers]
 #[test]
 fn generate_headers() -> ::std::io::Result<()> {
+    rsharp::registry::to_file("rsharp_registry.json")?;
     let builder = ::safer_ffi::headers::builder();
     if ::std::env::var("HEADERS_TO_STDOUT")
         .ok()
@@ -0,0 +1,160 @@
+//! Ownership of objects and states, for realms that many users edit at once.
+//!
+//! An owner acquires a [`Lock`] on an object or a single state with a
+//! [`LockAction`], which lasts for a lease of [`RealmTime`]. While the lock is
+//! held, the `Engine` rejects writes to it from collactions of any other
+//! owner, see [`Collaction::with_owner`]. Locking an object locks all of its
+//! states, so an object can't be locked while someone else holds a lock on one
+//! of its states, nor the other way around. Owners renew their lease by acquiring the lock again, and locks that
+//! aren't renewed expire when the `Engine` ticks past them.
+//!
+//! [`LockAction`]: crate::action::lock::LockAction
+//! [`Collaction::with_owner`]: crate::action::Collaction::with_owner
+
+use crate::contract::properties::states::DynStateHandle;
+use crate::object::ObjectHandle;
+use crate::time::RealmTime;
+
+use std::collections::HashMap;
+use std::fmt;
+
+/// Identifies who a collaction is from, such as a user or a client of a server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OwnerId(pub u64);
+
+/// What a [`Lock`] is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LockTarget {
+    Object(ObjectHandle),
+    State(DynStateHandle),
+}
+impl From<ObjectHandle> for LockTarget {
+    fn from(other: ObjectHandle) -> Self {
+        Self::Object(other)
+    }
+}
+impl From<DynStateHandle> for LockTarget {
+    fn from(other: DynStateHandle) -> Self {
+        Self::State(other)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lock {
+    pub owner: OwnerId,
+    /// The lock is held until the realm reaches this time.
+    pub expires: RealmTime,
+}
+impl Lock {
+    pub fn is_held(&self, now: RealmTime) -> bool {
+        now < self.expires
+    }
+}
+
+/// The locks of an `Engine`.
+#[derive(Debug, Default)]
+pub struct Locks(HashMap<LockTarget, Lock>);
+impl Locks {
+    /// The lock on `target`, unless it expired by `now`.
+    pub fn get(&self, target: impl Into<LockTarget>, now: RealmTime) -> Option<Lock> {
+        self.0
+            .get(&target.into())
+            .copied()
+            .filter(|l| l.is_held(now))
+    }
+
+    /// All locks, including expired ones that the `Engine` hasn't removed yet.
+    pub fn iter(&self) -> impl Iterator<Item = (LockTarget, Lock)> + '_ {
+        self.0.iter().map(|(t, l)| (*t, *l))
+    }
+
+    /// The targets that `owner` holds a lock on.
+    pub fn owned_by(
+        &self,
+        owner: OwnerId,
+        now: RealmTime,
+    ) -> impl Iterator<Item = LockTarget> + '_ {
+        self.0
+            .iter()
+            .filter(move |(_, l)| l.owner == owner && l.is_held(now))
+            .map(|(t, _)| *t)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn insert(&mut self, target: LockTarget, lock: Lock) -> Option<Lock> {
+        self.0.insert(target, lock)
+    }
+
+    pub(crate) fn remove(&mut self, target: LockTarget) -> Option<Lock> {
+        self.0.remove(&target)
+    }
+
+    /// Removes the locks that expired by `now`.
+    pub(crate) fn expire(&mut self, now: RealmTime) -> Vec<(LockTarget, Lock)> {
+        let expired: Vec<_> = self.iter().filter(|(_, l)| !l.is_held(now)).collect();
+        for (target, _) in &expired {
+            self.0.remove(target);
+        }
+        expired
+    }
+}
+
+/// A change to the locks of an `Engine`, see
+/// [`Engine::on_lock_change`](crate::engine::Engine::on_lock_change).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockEvent {
+    Acquired {
+        target: LockTarget,
+        lock: Lock,
+    },
+    /// The owner acquired a lock that it already held, extending its lease.
+    Renewed {
+        target: LockTarget,
+        lock: Lock,
+    },
+    Released {
+        target: LockTarget,
+        owner: OwnerId,
+    },
+    Expired {
+        target: LockTarget,
+        owner: OwnerId,
+    },
+}
+impl LockEvent {
+    pub fn target(&self) -> LockTarget {
+        match self {
+            Self::Acquired { target, .. }
+            | Self::Renewed { target, .. }
+            | Self::Released { target, .. }
+            | Self::Expired { target, .. } => *target,
+        }
+    }
+}
+
+/// An action was rejected because someone else holds a lock on what it
+/// writes to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locked {
+    pub target: LockTarget,
+    pub lock: Lock,
+}
+impl fmt::Display for Locked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} is locked by {:?} until {:?}",
+            self.target,
+            self.lock.owner,
+            self.lock.expires.ticks()
+        )
+    }
+}
+impl std::error::Error for Locked {}
@@ -16,6 +16,8 @@ pub struct Object {
     channels: Vec<ga::Index>, // map from ChannelID -> ChannelHandle
     contract: ContractDataHandle,
     time_warp: TimeWarp,
+    parent: Option<ObjectHandle>,
+    children: Vec<ObjectHandle>,
 }
 impl Object {
     pub(crate) fn new(
@@ -29,6 +31,8 @@ impl Object {
             channels,
             contract,
             time_warp,
+            parent: None,
+            children: Vec::new(),
         }
     }
 
@@ -82,6 +86,25 @@ impl Object {
         &mut self.time_warp
     }
 
+    /// The object that this object is a child of, if any. See
+    /// [`Baseline::object_reparent`](crate::baseline::Baseline::object_reparent).
+    pub fn parent(&self) -> Option<ObjectHandle> {
+        self.parent
+    }
+
+    /// The children of this object, in order.
+    pub fn children(&self) -> &[ObjectHandle] {
+        &self.children
+    }
+
+    pub(crate) fn set_parent(&mut self, parent: Option<ObjectHandle>) {
+        self.parent = parent;
+    }
+
+    pub(crate) fn children_mut(&mut self) -> &mut Vec<ObjectHandle> {
+        &mut self.children
+    }
+
     pub(crate) fn bind_state<T: ITpPropertyStatic>(
         &self,
         id: StateId<T>,
@@ -120,6 +143,7 @@ pub type ObjectHandle = arena::Index<Object>;
 pub mod c_api {
     #![allow(non_camel_case_types, non_snake_case, dead_code)]
 
+    use super::Object;
     use crate::contract::properties::c_api::impl_from_refcast;
 
     use derive_more::{From, Into};
@@ -142,4 +166,27 @@ pub mod c_api {
     pub fn ObjectHandle__drop(c: repr_c::Box<ObjectHandle>) {
         drop(c)
     }
+
+    /// Returns null if the object has no parent.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Object__parent(obj: &Object) -> Option<repr_c::Box<ObjectHandle>> {
+        obj.parent()
+            .map(|parent| Box::new(ObjectHandle::from(parent)).into())
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Object__children_len(obj: &Object) -> usize {
+        obj.children().len()
+    }
+
+    /// Returns null if `idx` is out of bounds.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Object__child(obj: &Object, idx: usize) -> Option<repr_c::Box<ObjectHandle>> {
+        obj.children()
+            .get(idx)
+            .map(|child| Box::new(ObjectHandle::from(*child)).into())
+    }
 }
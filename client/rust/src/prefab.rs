@@ -0,0 +1,372 @@
+//! Prefabs, which are named templates for the objects of a contract.
+//!
+//! A [`Prefab`] holds a value for every state and channel of its contract.
+//! Instantiating it with [`Baseline::object_instantiate`] creates an object
+//! with those values, except for the fields that are overridden by name with
+//! [`Overrides`]. Baselines keep track of the instances of their prefabs, and
+//! [`Baseline::prefab_update`] propagates edits of a prefab to its instances.
+//!
+//! [`Baseline::object_instantiate`]: crate::baseline::Baseline::object_instantiate
+//! [`Baseline::prefab_update`]: crate::baseline::Baseline::prefab_update
+
+use crate::contract::properties::channels::DynChannel;
+use crate::contract::properties::dynamic::DynTpProperty;
+use crate::contract::{DynContract, IContract};
+use crate::object::ObjectHandle;
+
+use eyre::{eyre, Result, WrapErr};
+use std::collections::HashSet;
+use std::mem;
+
+/// Values for some of the fields of a contract, by field name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Overrides {
+    states: Vec<(String, DynTpProperty)>,
+    channels: Vec<(String, DynChannel)>,
+}
+impl Overrides {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_state(mut self, name: impl Into<String>, value: impl Into<DynTpProperty>) -> Self {
+        self.states.push((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_channel(mut self, name: impl Into<String>, channel: impl Into<DynChannel>) -> Self {
+        self.channels.push((name.into(), channel.into()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty() && self.channels.is_empty()
+    }
+
+    /// Looks up the index of the field of each override. When a field is
+    /// overridden more than once, the last override wins.
+    ///
+    /// # Errors
+    /// Will error if `contract` has no field with the name of an override, if
+    /// the override has a different type than its field, or if a state is out
+    /// of the range of its field.
+    fn resolve(self, contract: &DynContract) -> Result<Resolved> {
+        let layout = contract.layout();
+        let mut states = Vec::with_capacity(self.states.len());
+        for (name, value) in self.states {
+            let idx = contract
+                .state_idx(&name)
+                .ok_or_else(|| eyre!("There is no state named `{}`", name))?;
+            let prop_type = layout.state_types[idx];
+            if value.prop_type() != prop_type {
+                return Err(eyre!("State `{}` was not a {}", name, prop_type));
+            }
+            layout.state_info[idx]
+                .check((&value).into())
+                .wrap_err_with(|| format!("State `{}` was invalid", name))?;
+            states.push((idx, value));
+        }
+        let mut channels = Vec::with_capacity(self.channels.len());
+        for (name, channel) in self.channels {
+            let idx = contract
+                .chan_idx(&name)
+                .ok_or_else(|| eyre!("There is no channel named `{}`", name))?;
+            let prop_type = layout.channel_types[idx];
+            if channel.prop_type() != prop_type {
+                return Err(eyre!("Channel `{}` was not a {}", name, prop_type));
+            }
+            channels.push((idx, channel));
+        }
+        Ok(Resolved { states, channels })
+    }
+}
+
+/// [`Overrides`] by field index.
+struct Resolved {
+    states: Vec<(usize, DynTpProperty)>,
+    channels: Vec<(usize, DynChannel)>,
+}
+
+/// A field of a prefab that was edited, see [`Prefab::edit`].
+pub(crate) struct Edit<T> {
+    pub idx: usize,
+    pub old: T,
+    pub new: T,
+}
+
+/// A named template for the objects of a contract. See the [module
+/// docs](self).
+#[derive(Debug, Clone)]
+pub struct Prefab {
+    name: String,
+    contract: DynContract,
+    states: Vec<DynTpProperty>,
+    channels: Vec<DynChannel>,
+    /// The objects that were instantiated from the prefab, which the
+    /// `Baseline` keeps up to date.
+    instances: HashSet<ObjectHandle>,
+}
+impl Prefab {
+    /// A prefab of `contract`, whose states have the `#[default]` of their
+    /// field unless they are in `values`.
+    ///
+    /// # Errors
+    /// Will error if `name` is empty, if `values` is missing a state with no
+    /// default or any of the channels, since channels have no defaults, or if
+    /// any of `values` are invalid, see [`Overrides`].
+    pub fn new(
+        name: impl Into<String>,
+        contract: &impl IContract,
+        values: Overrides,
+    ) -> Result<Self> {
+        let name = name.into();
+        if name.is_empty() {
+            return Err(eyre!("The prefab must have a name"));
+        }
+        let contract = DynContract::from_contract(contract);
+        let layout = contract.layout();
+        let values = values.resolve(&contract)?;
+
+        let mut states: Vec<_> = layout
+            .state_info
+            .iter()
+            .map(|info| info.default.clone())
+            .collect();
+        for (idx, value) in values.states {
+            states[idx] = Some(value);
+        }
+        let mut channels: Vec<Option<DynChannel>> = vec![None; layout.channel_types.len()];
+        for (idx, channel) in values.channels {
+            channels[idx] = Some(channel);
+        }
+
+        let states = states
+            .into_iter()
+            .zip(layout.state_names)
+            .map(|(s, name)| s.ok_or_else(|| eyre!("State `{}` has no default", name)))
+            .collect::<Result<_>>()?;
+        let channels = channels
+            .into_iter()
+            .zip(layout.channel_names)
+            .map(|(c, name)| c.ok_or_else(|| eyre!("Channel `{}` was not given", name)))
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            name,
+            contract,
+            states,
+            channels,
+            instances: HashSet::new(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn contract(&self) -> DynContract {
+        self.contract
+    }
+
+    /// The values of the states, in the order of the contract's fields.
+    pub fn states(&self) -> &[DynTpProperty] {
+        &self.states
+    }
+
+    /// The channels, in the order of the contract's fields.
+    pub fn channels(&self) -> &[DynChannel] {
+        &self.channels
+    }
+
+    pub fn state(&self, name: &str) -> Option<&DynTpProperty> {
+        self.contract.state_idx(name).map(|idx| &self.states[idx])
+    }
+
+    pub fn channel(&self, name: &str) -> Option<&DynChannel> {
+        self.contract.chan_idx(name).map(|idx| &self.channels[idx])
+    }
+
+    /// The objects that were instantiated from the prefab, when it is in a
+    /// `Baseline`.
+    pub fn instances(&self) -> &HashSet<ObjectHandle> {
+        &self.instances
+    }
+
+    pub(crate) fn instances_mut(&mut self) -> &mut HashSet<ObjectHandle> {
+        &mut self.instances
+    }
+
+    /// The states and channels of an instance with `overrides`.
+    pub(crate) fn values(
+        &self,
+        overrides: Overrides,
+    ) -> Result<(Vec<DynTpProperty>, Vec<DynChannel>)> {
+        let overrides = overrides.resolve(&self.contract)?;
+        let mut states = self.states.clone();
+        let mut channels = self.channels.clone();
+        for (idx, value) in overrides.states {
+            states[idx] = value;
+        }
+        for (idx, channel) in overrides.channels {
+            channels[idx] = channel;
+        }
+        Ok((states, channels))
+    }
+
+    /// Replaces the values of the fields in `edits`. Nothing is replaced if
+    /// any of them are invalid.
+    pub(crate) fn edit(
+        &mut self,
+        edits: Overrides,
+    ) -> Result<(Vec<Edit<DynTpProperty>>, Vec<Edit<DynChannel>>)> {
+        let edits = edits.resolve(&self.contract)?;
+        let states = edits
+            .states
+            .into_iter()
+            .map(|(idx, new)| {
+                let old = mem::replace(&mut self.states[idx], new.clone());
+                Edit { idx, old, new }
+            })
+            .collect();
+        let channels = edits
+            .channels
+            .into_iter()
+            .map(|(idx, new)| {
+                let old = mem::replace(&mut self.channels[idx], new.clone());
+                Edit { idx, old, new }
+            })
+            .collect();
+        Ok((states, channels))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::baseline::{Baseline, BaselineKind};
+    use crate::contract::properties::channels::{Channel, Keyframe};
+    use crate::contract::properties::dynamic::{TpPrimitiveType, TpPropertyType};
+    use crate::contract::properties::field::{FieldInfo, FieldRange};
+    use crate::contract::properties::states::StateId;
+    use crate::contract::ContractSchema;
+
+    use std::ops::Bound;
+
+    fn schema() -> ContractSchema {
+        let mut schema = ContractSchema::new("teleportal.test.prefab", (1, 0, 0))
+            .with_state("name", TpPropertyType::Primitive(TpPrimitiveType::String))
+            .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8))
+            .with_state("team", TpPropertyType::Primitive(TpPrimitiveType::U8))
+            .with_channel("x", TpPropertyType::Primitive(TpPrimitiveType::F32));
+        schema.states[1].info = FieldInfo {
+            default: Some(DynTpProperty::from(10u8)),
+            range: Some(FieldRange {
+                start: Bound::Included(0.0),
+                end: Bound::Included(100.0),
+            }),
+            ..Default::default()
+        };
+        schema.states[2].info = FieldInfo {
+            default: Some(DynTpProperty::from(0u8)),
+            readonly: true,
+            ..Default::default()
+        };
+        schema
+    }
+
+    fn x(value: f32) -> DynChannel {
+        Channel::new([Keyframe::new(value, 0.0)].into_iter()).into()
+    }
+
+    #[test]
+    fn test_prefabs() {
+        let mut baseline = Baseline::new(BaselineKind::Main);
+        let c = baseline.register_dyn_contract(&schema()).unwrap();
+        let values = Overrides::new()
+            .with_state("name", String::from("goblin"))
+            .with_channel("x", x(0.0));
+
+        // Missing a state with no default, a channel, or invalid
+        let no_name = Overrides::new().with_channel("x", x(0.0));
+        assert!(Prefab::new("goblin", &c, no_name).is_err());
+        let no_x = Overrides::new().with_state("name", String::new());
+        assert!(Prefab::new("goblin", &c, no_x).is_err());
+        for invalid in [
+            values.clone().with_state("hp", 101u8),
+            values.clone().with_state("hp", 1.0f32),
+            values.clone().with_state("mp", 1u8),
+        ] {
+            assert!(Prefab::new("goblin", &c, invalid).is_err());
+        }
+
+        let prefab = Prefab::new("goblin", &c, values).unwrap();
+        assert_eq!(prefab.state("hp"), Some(&DynTpProperty::from(10u8)));
+        baseline.prefab_insert(prefab.clone()).unwrap();
+        assert!(baseline.prefab_insert(prefab).is_err());
+
+        let plain = baseline
+            .object_instantiate("goblin", Overrides::new())
+            .unwrap();
+        let boss = baseline
+            .object_instantiate("goblin", Overrides::new().with_state("hp", 50u8))
+            .unwrap();
+        assert!(baseline
+            .object_instantiate("goblin", Overrides::new().with_state("hp", 200u8))
+            .is_err());
+        assert!(baseline
+            .object_instantiate("orc", Overrides::new())
+            .is_err());
+        let instances = baseline.prefab("goblin").unwrap().instances();
+        assert_eq!(instances, &HashSet::from([plain, boss]));
+        assert_eq!(baseline.object_prefab(boss).unwrap().name(), "goblin");
+
+        let hp = |b: &Baseline, obj| {
+            let id = StateId::<u8>::new(c.state_idx("hp").unwrap(), c.handle());
+            b[b.bind_state(id, obj).unwrap()].value
+        };
+        assert_eq!((hp(&baseline, plain), hp(&baseline, boss)), (10, 50));
+
+        // Edits only reach the instances that still have the old value, and
+        // never readonly states.
+        let edits = Overrides::new()
+            .with_state("hp", 20u8)
+            .with_state("team", 1u8);
+        let updated = baseline.prefab_update("goblin", edits).unwrap();
+        assert_eq!(updated, [plain]);
+        assert_eq!((hp(&baseline, plain), hp(&baseline, boss)), (20, 50));
+        let team = StateId::<u8>::new(c.state_idx("team").unwrap(), c.handle());
+        assert_eq!(baseline[baseline.bind_state(team, plain).unwrap()].value, 0);
+        assert!(baseline
+            .prefab_update("goblin", Overrides::new().with_state("hp", 101u8))
+            .is_err());
+        assert_eq!(hp(&baseline, plain), 20);
+
+        let updated = baseline
+            .prefab_update("goblin", Overrides::new().with_channel("x", x(1.0)))
+            .unwrap();
+        assert_eq!(HashSet::from_iter(updated), HashSet::from([plain, boss]));
+
+        baseline.object_remove_dyn(boss).unwrap();
+        let instances = baseline.prefab("goblin").unwrap().instances();
+        assert_eq!(instances, &HashSet::from([plain]));
+
+        // Removing a prefab keeps its instances
+        baseline.prefab_remove("goblin").unwrap();
+        assert!(baseline.object_prefab(plain).is_none());
+        assert!(baseline.object(plain).is_ok());
+
+        baseline
+            .prefab_insert(
+                Prefab::new(
+                    "goblin",
+                    &c,
+                    Overrides::new()
+                        .with_state("name", String::new())
+                        .with_channel("x", x(0.0)),
+                )
+                .unwrap(),
+            )
+            .unwrap();
+        baseline.unregister_dyn_contract(&c).unwrap();
+        assert_eq!(baseline.iter_prefabs().count(), 0);
+    }
+}
@@ -6,6 +6,10 @@ use crate::{
     time::RealmTime,
 };
 
+#[cfg(feature = "c_api")]
+use safer_ffi::derive_ReprC;
+
+#[cfg_attr(feature = "c_api", derive_ReprC, ReprC::opaque)]
 pub struct RealmID(String);
 impl RealmID {
     pub fn new(id: String) -> Self {
@@ -16,6 +20,7 @@ impl RealmID {
 /// A Realm holds all the data necessary to describe the state of a particular
 /// virtual space. This includes but is not limited to contracts, objects, and
 /// additional data global to that virtual space.
+#[cfg_attr(feature = "c_api", derive_ReprC, ReprC::opaque)]
 pub struct Realm {
     realm_id: RealmID,
     time: RealmTime,
@@ -69,3 +74,72 @@ impl Realm {
         }
     }
 }
+
+#[cfg(feature = "c_api")]
+#[rsharp::substitute("tp_client::realm")]
+pub mod c_api {
+    #![allow(non_camel_case_types, non_snake_case, dead_code)]
+
+    use super::*;
+
+    use rsharp::remangle;
+    use rsharp::string::String as RString;
+    use safer_ffi::prelude::*;
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn RealmID__new(id: &RString) -> repr_c::Box<RealmID> {
+        Box::new(RealmID::new(id.inner.clone())).into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn RealmID__drop(id: repr_c::Box<RealmID>) {
+        drop(id)
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn RealmID__value(id: &RealmID) -> &RString {
+        (&id.0).into()
+    }
+
+    /// Takes ownership of `realm_id`.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Realm__new(realm_id: repr_c::Box<RealmID>) -> repr_c::Box<Realm> {
+        let realm_id: Box<RealmID> = realm_id.into();
+        Box::new(Realm::new(*realm_id)).into()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Realm__drop(realm: repr_c::Box<Realm>) {
+        drop(realm)
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Realm__id(realm: &Realm) -> &RealmID {
+        realm.id()
+    }
+
+    /// The number of ticks since the realm was created.
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Realm__ticks(realm: &Realm) -> i32 {
+        realm.time().ticks().as_millis()
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Realm__baseline(realm: &Realm, kind: BaselineKind) -> &Baseline {
+        realm.baseline(kind)
+    }
+
+    #[remangle(substitute!())]
+    #[ffi_export]
+    pub fn Realm__baseline_mut(realm: &mut Realm, kind: BaselineKind) -> &mut Baseline {
+        realm.baseline_mut(kind)
+    }
+}
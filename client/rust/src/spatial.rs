@@ -0,0 +1,664 @@
+//! Spatial queries over the objects in a [`Baseline`].
+//!
+//! Contracts opt into spatial indexing by returning the states that make up
+//! their position from [`Contract::position()`]. A [`SpatialIndex`] can then
+//! track the objects of those contracts, and answer radius, AABB and
+//! nearest-neighbor queries about them.
+
+use crate::baseline::Baseline;
+use crate::contract::properties::states::{StateHandle, StateId};
+use crate::contract::{Contract, ContractDataHandle};
+use crate::object::ObjectHandle;
+
+use eyre::{eyre, Result, WrapErr};
+use std::collections::{HashMap, HashSet};
+
+/// A point in 3D space. 2D positions have a `z` of `0.0`.
+pub type Point = [f32; 3];
+
+/// The side length of a cell in a [`SpatialIndex`] created with `Default`.
+pub const DEFAULT_CELL_SIZE: f32 = 1.0;
+
+/// Describes which states of a contract make up the position of its objects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionStates {
+    pub x: StateId<f32>,
+    pub y: StateId<f32>,
+    pub z: Option<StateId<f32>>,
+}
+impl PositionStates {
+    pub fn new_2d(x: StateId<f32>, y: StateId<f32>) -> Self {
+        Self { x, y, z: None }
+    }
+
+    pub fn new_3d(x: StateId<f32>, y: StateId<f32>, z: StateId<f32>) -> Self {
+        Self { x, y, z: Some(z) }
+    }
+}
+
+/// An axis-aligned bounding box. `min` and `max` are both inclusive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+impl Aabb {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max }
+    }
+
+    /// The smallest `Aabb` that contains the sphere at `center` with `radius`.
+    pub fn around(center: Point, radius: f32) -> Self {
+        let radius = radius.abs();
+        Self {
+            min: [center[0] - radius, center[1] - radius, center[2] - radius],
+            max: [center[0] + radius, center[1] + radius, center[2] + radius],
+        }
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (0..3).all(|i| self.min[i] <= p[i] && p[i] <= self.max[i])
+    }
+}
+
+type Cell = [i32; 3];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    X = 0,
+    Y,
+    Z,
+}
+
+struct Entry {
+    pos: Point,
+    cell: Cell,
+    contract: ContractDataHandle,
+    states: [Option<StateHandle<f32>>; 3],
+}
+
+/// A uniform grid over the positions of objects.
+///
+/// Every [`Baseline`] owns an index, see [`Baseline::spatial_index()`], which
+/// it keeps up to date as objects of tracked contracts are created and
+/// removed. Positions are kept up to date by calling
+/// [`Self::on_state_write()`], which the [`Engine`] does as it applies
+/// `StateAction::Write`s.
+///
+/// A standalone index does not observe the [`Baseline`] on its own, so
+/// objects have to be added with [`Self::insert_object()`] and removed with
+/// [`Self::remove_object()`].
+///
+/// [`Engine`]: crate::Engine
+pub struct SpatialIndex {
+    cell_size: f32,
+    cells: HashMap<Cell, HashSet<ObjectHandle>>,
+    objects: HashMap<ObjectHandle, Entry>,
+    /// Maps from the states that make up positions to their object and axis.
+    states: HashMap<StateHandle<f32>, (ObjectHandle, Axis)>,
+    contracts: HashMap<ContractDataHandle, PositionStates>,
+}
+impl SpatialIndex {
+    /// Creates an empty index. Queries are fastest when `cell_size` is close to
+    /// the typical radius being queried.
+    ///
+    /// # Panics
+    /// Panics if `cell_size` is not a positive, finite number.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.0,
+            "`cell_size` must be positive and finite"
+        );
+        Self {
+            cell_size,
+            cells: Default::default(),
+            objects: Default::default(),
+            states: Default::default(),
+            contracts: Default::default(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    pub fn contains(&self, obj: ObjectHandle) -> bool {
+        self.objects.contains_key(&obj)
+    }
+
+    pub fn is_tracked(&self, contract: ContractDataHandle) -> bool {
+        self.contracts.contains_key(&contract)
+    }
+
+    /// Whether `state` makes up the position of an object in the index.
+    pub fn is_position(&self, state: StateHandle<f32>) -> bool {
+        self.states.contains_key(&state)
+    }
+
+    /// The last known position of `obj`, or `None` if it isn't in the index.
+    pub fn position(&self, obj: ObjectHandle) -> Option<Point> {
+        self.objects.get(&obj).map(|e| e.pos)
+    }
+
+    // ---- Maintenance ----
+
+    /// Starts tracking `contract`, and inserts all of its current objects.
+    ///
+    /// # Errors
+    /// Errors if `contract` has no position states, or isn't in `baseline`.
+    pub fn track_contract<C: Contract>(&mut self, baseline: &Baseline, contract: &C) -> Result<()> {
+        let position = contract
+            .position()
+            .ok_or_else(|| eyre!("Contract does not declare any position states"))?;
+        self.track_positions(baseline, contract.handle(), position)
+    }
+
+    /// Like [`Self::track_contract()`], for contracts that can't declare their
+    /// position states, such as a [`DynContract`](crate::contract::DynContract).
+    ///
+    /// # Errors
+    /// Errors if `contract` isn't in `baseline`, or `position` isn't made up of
+    /// its states.
+    pub fn track_positions(
+        &mut self,
+        baseline: &Baseline,
+        contract: ContractDataHandle,
+        position: PositionStates,
+    ) -> Result<()> {
+        let objects = baseline.contract_data(contract)?.objects();
+
+        self.contracts.insert(contract, position);
+        for &obj in objects {
+            self.insert_object(baseline, obj)?;
+        }
+        Ok(())
+    }
+
+    /// Stops tracking `contract`, removing all of its objects from the index.
+    pub fn untrack_contract(&mut self, contract: ContractDataHandle) {
+        if self.contracts.remove(&contract).is_none() {
+            return;
+        }
+        let objs: Vec<ObjectHandle> = self
+            .objects
+            .iter()
+            .filter(|(_, e)| e.contract == contract)
+            .map(|(obj, _)| *obj)
+            .collect();
+        for obj in objs {
+            self.remove_object(obj);
+        }
+    }
+
+    /// Inserts `obj` at the position currently stored in `baseline`. If `obj`
+    /// was already in the index, its position is refreshed.
+    ///
+    /// # Errors
+    /// Errors if the object doesn't exist, or its contract isn't tracked.
+    pub fn insert_object(&mut self, baseline: &Baseline, obj: ObjectHandle) -> Result<()> {
+        let contract = baseline.object(obj)?.contract();
+        let position = *self
+            .contracts
+            .get(&contract)
+            .ok_or_else(|| eyre!("The object's contract is not tracked by the index"))?;
+
+        let ids = [Some(position.x), Some(position.y), position.z];
+        let mut states = [None; 3];
+        let mut pos = [0.0; 3];
+        for (i, id) in ids.into_iter().enumerate() {
+            if let Some(id) = id {
+                let handle = baseline
+                    .bind_state(id, obj)
+                    .wrap_err("Failed to bind position state")?;
+                pos[i] = baseline.state(handle)?.value;
+                states[i] = Some(handle);
+            }
+        }
+
+        self.remove_object(obj);
+        let axes = [Axis::X, Axis::Y, Axis::Z];
+        for (handle, axis) in states.iter().zip(axes) {
+            if let Some(handle) = handle {
+                self.states.insert(*handle, (obj, axis));
+            }
+        }
+        let cell = self.cell(pos);
+        self.cells.entry(cell).or_default().insert(obj);
+        self.objects.insert(
+            obj,
+            Entry {
+                pos,
+                cell,
+                contract,
+                states,
+            },
+        );
+        Ok(())
+    }
+
+    /// Removes `obj` from the index. Returns `false` if it wasn't in the index.
+    pub fn remove_object(&mut self, obj: ObjectHandle) -> bool {
+        let entry = if let Some(entry) = self.objects.remove(&obj) {
+            entry
+        } else {
+            return false;
+        };
+        for handle in entry.states.iter().flatten() {
+            self.states.remove(handle);
+        }
+        self.remove_from_cell(obj, entry.cell);
+        true
+    }
+
+    /// Updates the position of the object that `state` belongs to. Returns
+    /// `false` if `state` isn't part of the position of any indexed object.
+    pub fn on_state_write(&mut self, state: StateHandle<f32>, value: f32) -> bool {
+        let (obj, axis) = if let Some(v) = self.states.get(&state) {
+            *v
+        } else {
+            return false;
+        };
+        let entry = self
+            .objects
+            .get_mut(&obj)
+            .expect("Every tracked state should belong to an indexed object");
+        entry.pos[axis as usize] = value;
+
+        let old_cell = entry.cell;
+        let new_cell = cell_of(entry.pos, self.cell_size);
+        if old_cell != new_cell {
+            entry.cell = new_cell;
+            self.remove_from_cell(obj, old_cell);
+            self.cells.entry(new_cell).or_default().insert(obj);
+        }
+        true
+    }
+
+    /// Removes every object and contract from the index.
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.objects.clear();
+        self.states.clear();
+        self.contracts.clear();
+    }
+
+    // ---- Queries ----
+
+    /// All objects within `radius` of `center`, in no particular order.
+    pub fn query_radius(&self, center: Point, radius: f32) -> Vec<ObjectHandle> {
+        let radius_sq = radius * radius;
+        self.candidates(Aabb::around(center, radius))
+            .filter(|(_, p)| distance_sq(*p, center) <= radius_sq)
+            .map(|(obj, _)| obj)
+            .collect()
+    }
+
+    /// All objects inside of `aabb`, in no particular order.
+    pub fn query_aabb(&self, aabb: Aabb) -> Vec<ObjectHandle> {
+        self.candidates(aabb)
+            .filter(|(_, p)| aabb.contains(*p))
+            .map(|(obj, _)| obj)
+            .collect()
+    }
+
+    /// The object closest to `point`, and its distance from `point`.
+    pub fn nearest(&self, point: Point) -> Option<(ObjectHandle, f32)> {
+        self.k_nearest(point, 1).into_iter().next()
+    }
+
+    /// Up to `k` objects closest to `point` along with their distances, sorted
+    /// from nearest to farthest.
+    pub fn k_nearest(&self, point: Point, k: usize) -> Vec<(ObjectHandle, f32)> {
+        if k == 0 || self.objects.is_empty() {
+            return Vec::new();
+        }
+        let center = self.cell(point);
+        // Rings can be far apart, so only the rings of occupied cells are visited.
+        let mut cells: Vec<(u32, &HashSet<ObjectHandle>)> = self
+            .cells
+            .iter()
+            .map(|(cell, objs)| (ring_of(*cell, center), objs))
+            .collect();
+        cells.sort_unstable_by_key(|(ring, _)| *ring);
+
+        let mut found: Vec<(ObjectHandle, f32)> = Vec::new();
+        let mut cells = cells.into_iter().peekable();
+        while let Some(&(ring, _)) = cells.peek() {
+            // Anything in this ring or a farther one is at least
+            // `(ring - 1) * cell_size` away.
+            if found.len() >= k {
+                sort_by_distance(&mut found);
+                found.truncate(k);
+                let bound = ring.saturating_sub(1) as f32 * self.cell_size;
+                if found[k - 1].1 <= bound * bound {
+                    break;
+                }
+            }
+            while let Some((_, objs)) = cells.next_if(|(r, _)| *r == ring) {
+                found.extend(
+                    objs.iter()
+                        .map(|obj| (*obj, distance_sq(self.objects[obj].pos, point))),
+                );
+            }
+        }
+
+        sort_by_distance(&mut found);
+        found.truncate(k);
+        found
+            .into_iter()
+            .map(|(obj, dist_sq)| (obj, dist_sq.sqrt()))
+            .collect()
+    }
+
+    // ---- Helpers ----
+
+    fn cell(&self, p: Point) -> Cell {
+        cell_of(p, self.cell_size)
+    }
+
+    fn remove_from_cell(&mut self, obj: ObjectHandle, cell: Cell) {
+        if let Some(objs) = self.cells.get_mut(&cell) {
+            objs.remove(&obj);
+            if objs.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    /// All objects in the cells that overlap `aabb`, along with their positions.
+    fn candidates(&self, aabb: Aabb) -> impl Iterator<Item = (ObjectHandle, Point)> + '_ {
+        let min = self.cell(aabb.min);
+        let max = self.cell(aabb.max);
+        let in_range = move |c: &Cell| (0..3).all(|i| min[i] <= c[i] && c[i] <= max[i]);
+
+        // Walking every cell in a large box is wasteful when most are empty, so
+        // whichever of the two is smaller gets iterated.
+        let n_cells_in_range = (0..3)
+            .map(|i| (max[i] as i64 - min[i] as i64 + 1).max(0) as u64)
+            .fold(1u64, |acc, n| acc.saturating_mul(n));
+        let cells: Vec<&HashSet<ObjectHandle>> = if n_cells_in_range > self.cells.len() as u64 {
+            self.cells
+                .iter()
+                .filter(|(c, _)| in_range(c))
+                .map(|(_, objs)| objs)
+                .collect()
+        } else {
+            let mut cells = Vec::new();
+            for x in min[0]..=max[0] {
+                for y in min[1]..=max[1] {
+                    for z in min[2]..=max[2] {
+                        if let Some(objs) = self.cells.get(&[x, y, z]) {
+                            cells.push(objs);
+                        }
+                    }
+                }
+            }
+            cells
+        };
+
+        cells
+            .into_iter()
+            .flatten()
+            .map(move |obj| (*obj, self.objects[obj].pos))
+    }
+}
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        Self::new(DEFAULT_CELL_SIZE)
+    }
+}
+
+fn cell_of(p: Point, cell_size: f32) -> Cell {
+    [
+        (p[0] / cell_size).floor() as i32,
+        (p[1] / cell_size).floor() as i32,
+        (p[2] / cell_size).floor() as i32,
+    ]
+}
+
+/// The chebyshev distance between two cells.
+fn ring_of(cell: Cell, center: Cell) -> u32 {
+    (0..3)
+        .map(|i| (cell[i] as i64 - center[i] as i64).unsigned_abs() as u32)
+        .max()
+        .unwrap()
+}
+
+fn distance_sq(a: Point, b: Point) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum()
+}
+
+fn sort_by_distance(v: &mut [(ObjectHandle, f32)]) {
+    v.sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::property::{PropertyAction, StateAction};
+    use crate::action::{Action, Collaction};
+    use crate::baseline::BaselineKind;
+    use crate::contract::properties::dynamic::DynTpProperty;
+    use crate::contract::{states, ContractId};
+    use crate::prefab::{Overrides, Prefab};
+    use crate::realm::{Realm, RealmID};
+    use crate::Engine;
+
+    #[states]
+    struct PosStates {
+        x: f32,
+        y: f32,
+    }
+
+    struct PosContract {
+        handle: ContractDataHandle,
+        states: PosStates,
+    }
+    impl Contract for PosContract {
+        type States = PosStates;
+        type Channels = ();
+
+        const ID: ContractId = ContractId {
+            name: "teleportal.test.spatial",
+            version: (0, 0, 0),
+        };
+
+        fn new(handle: ContractDataHandle) -> Self {
+            Self {
+                handle,
+                states: PosStates::new(handle),
+            }
+        }
+
+        fn states(&self) -> &Self::States {
+            &self.states
+        }
+
+        fn channels(&self) -> &Self::Channels {
+            &()
+        }
+
+        fn handle(&self) -> ContractDataHandle {
+            self.handle
+        }
+
+        fn position(&self) -> Option<PositionStates> {
+            Some(PositionStates::new_2d(self.states.x(), self.states.y()))
+        }
+    }
+
+    fn create(baseline: &mut Baseline, c: &PosContract, x: f32, y: f32) -> ObjectHandle {
+        let states = [DynTpProperty::from(x), DynTpProperty::from(y)];
+        baseline
+            .object_create(c, states.into_iter(), [].into_iter())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_queries() {
+        let mut baseline = Baseline::new(BaselineKind::Main);
+        let c: PosContract = baseline.register_contract().unwrap();
+        let origin = create(&mut baseline, &c, 0.0, 0.0);
+        let near = create(&mut baseline, &c, 1.5, 0.0);
+        let far = create(&mut baseline, &c, -10.0, 20.0);
+
+        let mut index = SpatialIndex::new(2.0);
+        index.track_contract(&baseline, &c).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.position(far), Some([-10.0, 20.0, 0.0]));
+
+        let mut in_radius = index.query_radius([0.0, 0.0, 0.0], 2.0);
+        in_radius.sort();
+        let mut expected = vec![origin, near];
+        expected.sort();
+        assert_eq!(in_radius, expected);
+
+        let in_box = index.query_aabb(Aabb::new([-11.0, 19.0, -1.0], [-9.0, 21.0, 1.0]));
+        assert_eq!(in_box, vec![far]);
+
+        assert_eq!(index.nearest([1.0, 0.1, 0.0]).unwrap().0, near);
+        assert_eq!(index.nearest([-100.0, 100.0, 0.0]).unwrap().0, far);
+        let k = index.k_nearest([0.0, 0.0, 0.0], 5);
+        assert_eq!(
+            k.iter().map(|(o, _)| *o).collect::<Vec<_>>(),
+            [origin, near, far]
+        );
+
+        assert!(index.remove_object(near));
+        assert!(!index.contains(near));
+        assert_eq!(index.query_radius([0.0, 0.0, 0.0], 2.0), vec![origin]);
+    }
+
+    #[test]
+    fn test_k_nearest_far_apart() {
+        let mut baseline = Baseline::new(BaselineKind::Main);
+        let c: PosContract = baseline.register_contract().unwrap();
+        let min = create(&mut baseline, &c, f32::MIN, f32::MIN);
+        let max = create(&mut baseline, &c, f32::MAX, f32::MAX);
+        let origin = create(&mut baseline, &c, 0.0, 0.0);
+
+        // The cells are billions of rings apart, which must not be walked one by one
+        let mut index = SpatialIndex::new(1.0);
+        index.track_contract(&baseline, &c).unwrap();
+        let k = index.k_nearest([0.0, 0.0, 0.0], 2);
+        assert_eq!(k[0], (origin, 0.0));
+        assert!([min, max].contains(&k[1].0));
+        assert_eq!(index.nearest([f32::MAX, f32::MAX, 0.0]).unwrap().0, max);
+    }
+
+    #[test]
+    fn test_state_write() {
+        let mut baseline = Baseline::new(BaselineKind::Main);
+        let c: PosContract = baseline.register_contract().unwrap();
+        let obj = create(&mut baseline, &c, 0.0, 0.0);
+
+        let mut index = SpatialIndex::new(1.0);
+        index.track_contract(&baseline, &c).unwrap();
+
+        let x = baseline.bind_state(c.states().x(), obj).unwrap();
+        assert!(index.on_state_write(x, 50.0));
+        assert_eq!(index.position(obj), Some([50.0, 0.0, 0.0]));
+        assert!(index.query_radius([0.0, 0.0, 0.0], 1.0).is_empty());
+        assert_eq!(index.query_radius([50.0, 0.0, 0.0], 1.0), vec![obj]);
+
+        index.untrack_contract(c.handle());
+        assert!(index.is_empty());
+        assert!(!index.on_state_write(x, 0.0));
+    }
+
+    #[test]
+    fn test_baseline_maintenance() {
+        let mut baseline = Baseline::new(BaselineKind::Main);
+        let c: PosContract = baseline.register_contract().unwrap();
+        let before = create(&mut baseline, &c, 0.0, 0.0);
+        baseline.spatial_track_contract(&c).unwrap();
+        assert!(baseline.spatial_index().contains(before));
+
+        // Objects created or instantiated afterwards are indexed too.
+        let parent = create(&mut baseline, &c, 1.0, 0.0);
+        let child = create(&mut baseline, &c, 2.0, 0.0);
+        baseline.object_reparent(child, Some(parent), None).unwrap();
+        let values = Overrides::new()
+            .with_state("x", 3.0f32)
+            .with_state("y", 0.0f32);
+        let prefab = Prefab::new("pos", &c, values).unwrap();
+        baseline.prefab_insert(prefab).unwrap();
+        let instance = baseline
+            .object_instantiate("pos", Overrides::new().with_state("y", 4.0f32))
+            .unwrap();
+        let index = baseline.spatial_index();
+        assert_eq!(index.len(), 4);
+        assert_eq!(index.position(child), Some([2.0, 0.0, 0.0]));
+        assert_eq!(index.position(instance), Some([3.0, 4.0, 0.0]));
+
+        // Editing the prefab moves its instances.
+        baseline
+            .prefab_update("pos", Overrides::new().with_state("x", 5.0f32))
+            .unwrap();
+        assert_eq!(
+            baseline.spatial_index().position(instance),
+            Some([5.0, 4.0, 0.0])
+        );
+
+        // Removing an object removes its descendants from the index as well.
+        baseline.object_remove::<PosContract>(parent).unwrap();
+        let index = baseline.spatial_index();
+        assert!(!index.contains(parent));
+        assert!(!index.contains(child));
+        assert_eq!(index.query_radius([1.5, 0.0, 0.0], 1.0), vec![]);
+
+        baseline
+            .unregister_contract::<PosContract>(c.handle())
+            .unwrap();
+        assert!(baseline.spatial_index().is_empty());
+        assert!(!baseline.spatial_index().is_tracked(c.handle()));
+    }
+
+    #[test]
+    fn test_engine_write() {
+        let (mut engine, sender) = Engine::new(Realm::new(RealmID::new("test".into())), None);
+        let baseline = engine.realm_mut().baseline_mut(BaselineKind::Fork);
+        let c: PosContract = baseline.register_contract().unwrap();
+        let obj = create(baseline, &c, 0.0, 0.0);
+        let x = baseline.bind_state(c.states().x(), obj).unwrap();
+        let y = baseline.bind_state(c.states().y(), obj).unwrap();
+
+        baseline.spatial_track_contract(&c).unwrap();
+
+        let write = |h: StateHandle<f32>, v: f32| -> Action {
+            PropertyAction::from(StateAction::Write {
+                handle: h.into(),
+                data: v.into(),
+            })
+            .into()
+        };
+
+        sender
+            .send(Collaction::new(vec![write(x, 3.0), write(y, -4.0)]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_ok());
+        assert_eq!(engine.spatial_index().position(obj), Some([3.0, -4.0, 0.0]));
+        assert_eq!(engine.realm().baseline(BaselineKind::Fork)[x].value, 3.0);
+
+        // A failing action reverses the write, which restores the position too.
+        let failing_assert: Action = PropertyAction::from(StateAction::Assert {
+            handle: y.into(),
+            data: 100f32.into(),
+        })
+        .into();
+        sender
+            .send(Collaction::new(vec![write(x, 10.0), failing_assert]))
+            .unwrap();
+        assert!(engine.try_apply().unwrap().is_err());
+        assert_eq!(engine.spatial_index().position(obj), Some([3.0, -4.0, 0.0]));
+        assert_eq!(engine.realm().baseline(BaselineKind::Fork)[x].value, 3.0);
+    }
+}
@@ -0,0 +1,439 @@
+//! Bindings to JavaScript and TypeScript, via `wasm-bindgen`.
+//!
+//! Build with:
+//! ```sh
+//! wasm-pack build client/rust --target nodejs -- --no-default-features --features wasm
+//! ```
+//! which also generates the `.d.ts` for the package. The tests run in Node:
+//! ```sh
+//! wasm-pack test --node client/rust --no-default-features --features wasm
+//! ```
+//!
+//! Values of properties are converted as described in [`value`]. Note that
+//! wasm has no threads to block on, so collactions are applied with
+//! `Engine.tryApply()` rather than with a timeout.
+//!
+//! Baselines are loaded from and saved to flatbuffers by the `wasm` feature of
+//! `tp_serialize`, since it depends on this crate.
+
+mod value;
+
+use self::value::{FromJs, ToJs};
+use crate::action::property::{PropertyAction, StateAction};
+use crate::action::Action;
+use crate::baseline::{Baseline, BaselineKind};
+use crate::contract::properties::channels::{apply_to_channel_id, Channel, ChannelId, DynChannel};
+use crate::contract::properties::dynamic::{DynTpProperty, TpPropertyType};
+use crate::contract::properties::states::{
+    self, apply_to_state_id, DynStateHandle, DynStateId, IStateHandle, StateId,
+};
+use crate::contract::properties::traits::ITpProperty;
+use crate::contract::{self, ContractDataHandle, DynContract, IContract};
+use crate::engine::ActionSender;
+use crate::object::ObjectHandle;
+use crate::realm::RealmID;
+use crate::{action, apply_to_state_handle, engine, realm};
+
+use eyre::Result;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &str = r#"
+/** An `ObjectHandle` or `ContractDataHandle`. */
+export interface Handle {
+    index: number;
+    generation: number;
+}
+
+export interface Vec2 { x: number; y: number; }
+export interface Vec3 { x: number; y: number; z: number; }
+export interface Quat { x: number; y: number; z: number; w: number; }
+export interface Color { r: number; g: number; b: number; a: number; }
+export interface Transform { translation: Vec3; rotation: Quat; scale: Vec3; }
+
+export type TpPrimitive =
+    | number
+    | bigint
+    | boolean
+    | string
+    | Handle
+    | Vec2
+    | Vec3
+    | Quat
+    | Color
+    | Transform
+    | Uint8Array;
+
+/** The value of a property, matching its type. */
+export type TpValue =
+    | TpPrimitive
+    | TpPrimitive[]
+    | null
+    | Record<string, TpPrimitive>;
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Handle")]
+    #[derive(Clone, Debug)]
+    pub type Handle;
+
+    #[wasm_bindgen(typescript_type = "Handle[]")]
+    pub type HandleArray;
+
+    #[wasm_bindgen(typescript_type = "TpValue")]
+    pub type TpValue;
+
+    #[wasm_bindgen(typescript_type = "TpValue[]")]
+    pub type TpValueArray;
+}
+
+fn js_error(err: eyre::Report) -> JsError {
+    JsError::new(&format!("{err:#}"))
+}
+
+// ---- Realm ----
+
+#[wasm_bindgen]
+pub struct Realm {
+    inner: realm::Realm,
+}
+#[wasm_bindgen]
+impl Realm {
+    #[wasm_bindgen(constructor)]
+    pub fn new(id: String) -> Self {
+        Self {
+            inner: realm::Realm::new(RealmID::new(id)),
+        }
+    }
+}
+
+// ---- Engine ----
+
+#[wasm_bindgen]
+pub struct Engine {
+    inner: engine::Engine,
+    sender: ActionSender,
+}
+#[wasm_bindgen]
+impl Engine {
+    /// Takes ownership of `realm`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(realm: Realm, queue_capacity: Option<usize>) -> Self {
+        let (inner, sender) = engine::Engine::new(realm.inner, queue_capacity);
+        Self { inner, sender }
+    }
+
+    pub fn tick(&mut self, ticks_since_last_call: i32) {
+        self.inner.tick(ticks_since_last_call.into())
+    }
+
+    /// Applies the next pending collaction, and returns whether it was
+    /// approved. Returns `undefined` if there are no collactions pending.
+    #[wasm_bindgen(js_name = tryApply)]
+    pub fn try_apply(&mut self) -> Option<bool> {
+        self.inner.try_apply().ok().map(|result| result.is_ok())
+    }
+
+    /// Queues `collaction` to be applied, and takes ownership of it. Returns
+    /// `false` if the queue is full.
+    pub fn send(&self, collaction: Collaction) -> bool {
+        let collaction = action::Collaction::new(collaction.actions);
+        self.sender.try_send(collaction).is_ok()
+    }
+
+    /// The handles of all objects in the baseline.
+    pub fn objects(&self, kind: BaselineKind) -> HandleArray {
+        let handles: Vec<ObjectHandle> =
+            self.baseline(kind).iter_objects().map(|(h, _)| h).collect();
+        handles.to_js().unchecked_into()
+    }
+
+    /// The handle of the contract that `object` was created from.
+    #[wasm_bindgen(js_name = objectContract)]
+    pub fn object_contract(&self, kind: BaselineKind, object: Handle) -> Result<Handle, JsError> {
+        let object = ObjectHandle::from_js(&object).map_err(js_error)?;
+        let obj = self.baseline(kind).object(object).map_err(js_error)?;
+        Ok(obj.contract().to_js().unchecked_into())
+    }
+
+    /// The name of the contract, as in its `ContractId`.
+    #[wasm_bindgen(js_name = contractName)]
+    pub fn contract_name(&self, kind: BaselineKind, contract: Handle) -> Result<String, JsError> {
+        let contract = ContractDataHandle::from_js(&contract).map_err(js_error)?;
+        let data = self
+            .baseline(kind)
+            .contract_data(contract)
+            .map_err(js_error)?;
+        Ok(data.id().name.to_string())
+    }
+
+    /// Binds the state at `index` in the contract of `object`. `typ` is the
+    /// rust name of the type of the state, like `"f32"` or `"Vec<Vec3>"`.
+    #[wasm_bindgen(js_name = bindState)]
+    pub fn bind_state(
+        &self,
+        kind: BaselineKind,
+        object: Handle,
+        index: usize,
+        typ: &str,
+    ) -> Result<StateHandle, JsError> {
+        let bind = || -> Result<DynStateHandle> {
+            let baseline = self.baseline(kind);
+            let object = ObjectHandle::from_js(&object)?;
+            let contract = baseline.object(object)?.contract();
+            let id = DynStateId::new(contract, index, typ.parse()?);
+            apply_to_state_id!(id, |id: StateId<_>| baseline
+                .bind_state(id, object)
+                .map(DynStateHandle::from))
+        };
+        Ok(bind().map_err(js_error)?.into())
+    }
+
+    /// Registers a contract declared at runtime.
+    #[wasm_bindgen(js_name = registerContract)]
+    pub fn register_contract(
+        &mut self,
+        kind: BaselineKind,
+        schema: &ContractSchema,
+    ) -> Result<Contract, JsError> {
+        let baseline = self.baseline_mut(kind);
+        let inner = baseline
+            .register_dyn_contract(&schema.inner)
+            .map_err(js_error)?;
+        Ok(Contract { inner })
+    }
+
+    /// Creates an object of `contract`. `states` holds the values of the
+    /// contract's states, in order. Trailing states that have a default may be
+    /// omitted. All channels start out empty.
+    #[wasm_bindgen(js_name = objectCreate)]
+    pub fn object_create(
+        &mut self,
+        kind: BaselineKind,
+        contract: &Contract,
+        states: TpValueArray,
+    ) -> Result<Handle, JsError> {
+        let contract = contract.inner;
+        let create = |baseline: &mut Baseline| -> Result<ObjectHandle> {
+            let states = js_sys::Array::from(&states)
+                .iter()
+                .enumerate()
+                .map(|(i, value)| {
+                    let id = contract
+                        .state_id(i)
+                        .ok_or_else(|| eyre::eyre!("The contract only has {} states", i))?;
+                    apply_to_state_id!(id, |id: StateId<_>| prop_from_js(id, &value))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let channels = (0..contract.layout().channel_types.len())
+                .map(|i| {
+                    let id = contract.chan_id(i).expect("`i` is in bounds");
+                    apply_to_channel_id!(id, |id: ChannelId<_>| empty_channel(id))
+                })
+                .collect::<Vec<_>>();
+            baseline.object_create(&contract, states.into_iter(), channels.into_iter())
+        };
+        let obj = create(self.baseline_mut(kind)).map_err(js_error)?;
+        Ok(obj.to_js().unchecked_into())
+    }
+
+    /// Reads the value of `state`.
+    pub fn state(&self, kind: BaselineKind, state: &StateHandle) -> Result<TpValue, JsError> {
+        let baseline = self.baseline(kind);
+        let value = apply_to_state_handle!(state.inner, |h: states::StateHandle<_>| baseline
+            .state(h)
+            .map(|s| s.value.to_js()));
+        Ok(value.map_err(js_error)?.unchecked_into())
+    }
+}
+impl Engine {
+    pub fn inner(&self) -> &engine::Engine {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut engine::Engine {
+        &mut self.inner
+    }
+
+    fn baseline(&self, kind: BaselineKind) -> &Baseline {
+        self.inner.realm().baseline(kind)
+    }
+
+    fn baseline_mut(&mut self, kind: BaselineKind) -> &mut Baseline {
+        self.inner.realm_mut().baseline_mut(kind)
+    }
+}
+
+// ---- Contracts ----
+
+/// The fields of a contract that is declared at runtime. Types are the rust
+/// names of the types of the fields, like `"f32"` or `"Option<Vec3>"`.
+#[wasm_bindgen]
+pub struct ContractSchema {
+    inner: contract::ContractSchema,
+}
+#[wasm_bindgen]
+impl ContractSchema {
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: String, major: u16, minor: u16, patch: u16) -> Self {
+        Self {
+            inner: contract::ContractSchema::new(name, (major, minor, patch)),
+        }
+    }
+
+    #[wasm_bindgen(js_name = addState)]
+    pub fn add_state(&mut self, name: String, typ: &str) -> Result<(), JsError> {
+        let typ: TpPropertyType = typ.parse().map_err(js_error)?;
+        self.inner
+            .states
+            .push(contract::FieldSchema::new(name, typ));
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = addChannel)]
+    pub fn add_channel(&mut self, name: String, typ: &str) -> Result<(), JsError> {
+        let typ: TpPropertyType = typ.parse().map_err(js_error)?;
+        self.inner
+            .channels
+            .push(contract::FieldSchema::new(name, typ));
+        Ok(())
+    }
+}
+
+/// A contract registered with `Engine.registerContract()`.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct Contract {
+    inner: DynContract,
+}
+#[wasm_bindgen]
+impl Contract {
+    #[wasm_bindgen(getter)]
+    pub fn handle(&self) -> Handle {
+        self.inner.handle().to_js().unchecked_into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.inner.id().name.to_string()
+    }
+
+    /// The index of the state named `name`, to pass to `Engine.bindState()`.
+    #[wasm_bindgen(js_name = stateIndex)]
+    pub fn state_index(&self, name: &str) -> Option<usize> {
+        self.inner.state_idx(name)
+    }
+}
+impl Contract {
+    pub fn inner(&self) -> &DynContract {
+        &self.inner
+    }
+}
+impl From<DynContract> for Contract {
+    fn from(inner: DynContract) -> Self {
+        Self { inner }
+    }
+}
+
+// ---- States ----
+
+/// A handle to a state, of any type.
+#[wasm_bindgen]
+#[derive(Clone, Copy)]
+pub struct StateHandle {
+    inner: DynStateHandle,
+}
+#[wasm_bindgen]
+impl StateHandle {
+    /// The rust name of the type of the state, like `"f32"` or `"Vec<Vec3>"`.
+    #[wasm_bindgen(getter, js_name = propType)]
+    pub fn prop_type(&self) -> String {
+        self.inner.prop_type().to_string()
+    }
+}
+impl From<DynStateHandle> for StateHandle {
+    fn from(inner: DynStateHandle) -> Self {
+        Self { inner }
+    }
+}
+impl From<StateHandle> for DynStateHandle {
+    fn from(h: StateHandle) -> Self {
+        h.inner
+    }
+}
+
+// ---- Collactions ----
+
+/// Builds a collaction, to send to the `Engine`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Collaction {
+    actions: Vec<Action>,
+}
+#[wasm_bindgen]
+impl Collaction {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes `value` to `state`. Throws if `value` doesn't match the type of
+    /// `state`.
+    pub fn write(&mut self, state: &StateHandle, value: TpValue) -> Result<(), JsError> {
+        let data = state_data(state.inner, &value).map_err(js_error)?;
+        self.push(StateAction::Write {
+            handle: state.inner,
+            data,
+        });
+        Ok(())
+    }
+
+    /// Rejects the collaction if `state` isn't equal to `value` when it is
+    /// applied.
+    pub fn assert(&mut self, state: &StateHandle, value: TpValue) -> Result<(), JsError> {
+        let data = state_data(state.inner, &value).map_err(js_error)?;
+        self.push(StateAction::Assert {
+            handle: state.inner,
+            data,
+        });
+        Ok(())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.actions.len()
+    }
+}
+impl Collaction {
+    fn push(&mut self, action: StateAction) {
+        self.actions.push(PropertyAction::from(action).into());
+    }
+}
+
+fn prop_from_js<T>(_: StateId<T>, value: &JsValue) -> Result<DynTpProperty>
+where
+    T: FromJs + Into<DynTpProperty>,
+{
+    T::from_js(value).map(Into::into)
+}
+
+fn empty_channel<T>(_: ChannelId<T>) -> DynChannel
+where
+    T: ITpProperty,
+    Channel<T>: Into<DynChannel>,
+{
+    Channel::new(std::iter::empty()).into()
+}
+
+/// Converts `value` to the type of `state`.
+fn state_data(state: DynStateHandle, value: &JsValue) -> Result<DynTpProperty> {
+    fn helper<T>(_: states::StateHandle<T>, value: &JsValue) -> Result<DynTpProperty>
+    where
+        T: FromJs + Into<DynTpProperty>,
+    {
+        T::from_js(value).map(Into::into)
+    }
+    apply_to_state_handle!(state, |h: states::StateHandle<_>| helper(h, value))
+}
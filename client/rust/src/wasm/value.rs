@@ -0,0 +1,324 @@
+//! Conversion between JS values and the values of properties.
+//!
+//! | Rust                                  | JS                                    |
+//! |---------------------------------------|---------------------------------------|
+//! | `u8`..`u32`, `i8`..`i32`, `f32`, `f64` | `number`                              |
+//! | `u64`, `i64`                          | `bigint` (a `number` is also accepted) |
+//! | `bool`, `String`                      | `boolean`, `string`                   |
+//! | `ObjectHandle`, `ContractDataHandle`  | `{ index, generation }`               |
+//! | `Vec2`, `Vec3`, `Quat`                | `{ x, y }`, `{ x, y, z }`, `{ x, y, z, w }` |
+//! | `Color`                               | `{ r, g, b, a }`                      |
+//! | `Transform`                           | `{ translation, rotation, scale }`    |
+//! | `Bytes`                               | `Uint8Array`                          |
+//! | `Vec<T>`                              | `T[]`                                 |
+//! | `Option<T>`                           | `T` or `null`                         |
+//! | `TpMap<T>`                            | `Record<string, T>`                   |
+
+use crate::contract::properties::bytes::Bytes;
+use crate::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
+use crate::contract::properties::traits::TpMap;
+
+use arena::generational_arena as ga;
+use eyre::{eyre, Result, WrapErr};
+use js_sys::{Array, BigInt, Object, Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+
+pub(super) trait ToJs {
+    fn to_js(&self) -> JsValue;
+}
+
+pub(super) trait FromJs: Sized {
+    fn from_js(value: &JsValue) -> Result<Self>;
+}
+
+// ---- Primitives ----
+
+macro_rules! numbers {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl ToJs for $t {
+                fn to_js(&self) -> JsValue {
+                    JsValue::from(*self)
+                }
+            }
+            impl FromJs for $t {
+                fn from_js(value: &JsValue) -> Result<Self> {
+                    let n = value
+                        .as_f64()
+                        .ok_or_else(|| eyre!("Expected a number, but got {:?}", value))?;
+                    if n.fract() != 0.0 || n < <$t>::MIN as f64 || n > <$t>::MAX as f64 {
+                        return Err(eyre!("{} is not a valid {}", n, stringify!($t)));
+                    }
+                    Ok(n as $t)
+                }
+            }
+        )+
+    };
+}
+numbers!(u8, u16, u32, i8, i16, i32);
+
+/// Accepts a `number` too, since those are much more common in JS.
+macro_rules! big_numbers {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl ToJs for $t {
+                fn to_js(&self) -> JsValue {
+                    JsValue::from(*self)
+                }
+            }
+            impl FromJs for $t {
+                fn from_js(value: &JsValue) -> Result<Self> {
+                    if let Some(n) = value.as_f64() {
+                        if n.fract() != 0.0 || n < <$t>::MIN as f64 || n > <$t>::MAX as f64 {
+                            return Err(eyre!("{} is not a valid {}", n, stringify!($t)));
+                        }
+                        return Ok(n as $t);
+                    }
+                    let big = value
+                        .dyn_ref::<BigInt>()
+                        .ok_or_else(|| eyre!("Expected a bigint, but got {:?}", value))?;
+                    let s = String::from(big.to_string(10).map_err(|_| eyre!("Invalid bigint"))?);
+                    s.parse()
+                        .wrap_err_with(|| format!("{} is not a valid {}", s, stringify!($t)))
+                }
+            }
+        )+
+    };
+}
+big_numbers!(u64, i64);
+
+macro_rules! floats {
+    ($($t:ty),+ $(,)?) => {
+        $(
+            impl ToJs for $t {
+                fn to_js(&self) -> JsValue {
+                    JsValue::from(*self)
+                }
+            }
+            impl FromJs for $t {
+                fn from_js(value: &JsValue) -> Result<Self> {
+                    let n = value
+                        .as_f64()
+                        .ok_or_else(|| eyre!("Expected a number, but got {:?}", value))?;
+                    Ok(n as $t)
+                }
+            }
+        )+
+    };
+}
+floats!(f32, f64);
+
+impl ToJs for bool {
+    fn to_js(&self) -> JsValue {
+        JsValue::from(*self)
+    }
+}
+impl FromJs for bool {
+    fn from_js(value: &JsValue) -> Result<Self> {
+        value
+            .as_bool()
+            .ok_or_else(|| eyre!("Expected a boolean, but got {:?}", value))
+    }
+}
+
+impl ToJs for String {
+    fn to_js(&self) -> JsValue {
+        JsValue::from_str(self)
+    }
+}
+impl FromJs for String {
+    fn from_js(value: &JsValue) -> Result<Self> {
+        value
+            .as_string()
+            .ok_or_else(|| eyre!("Expected a string, but got {:?}", value))
+    }
+}
+
+/// Covers `ObjectHandle` and `ContractDataHandle`.
+impl<T> ToJs for arena::Index<T> {
+    fn to_js(&self) -> JsValue {
+        let (index, generation) = ga::Index::from(*self).into_raw_parts();
+        object(&[
+            ("index", JsValue::from(index as f64)),
+            ("generation", JsValue::from(generation as f64)),
+        ])
+    }
+}
+impl<T> FromJs for arena::Index<T> {
+    fn from_js(value: &JsValue) -> Result<Self> {
+        let index: u32 = field(value, "index")?;
+        let generation: u64 = field(value, "generation")?;
+        let index = ga::Index::from_raw_parts(index as usize, generation);
+        Ok(arena::Index::new(index))
+    }
+}
+
+macro_rules! composites {
+    ($($t:ident { $($field:ident),+ $(,)? }),+ $(,)?) => {
+        $(
+            impl ToJs for $t {
+                fn to_js(&self) -> JsValue {
+                    object(&[$((stringify!($field), self.$field.to_js())),+])
+                }
+            }
+            impl FromJs for $t {
+                fn from_js(value: &JsValue) -> Result<Self> {
+                    Ok(Self {
+                        $($field: field(value, stringify!($field))?),+
+                    })
+                }
+            }
+        )+
+    };
+}
+composites!(
+    Vec2 { x, y },
+    Vec3 { x, y, z },
+    Quat { x, y, z, w },
+    Color { r, g, b, a },
+    Transform {
+        translation,
+        rotation,
+        scale
+    },
+);
+
+impl ToJs for Bytes {
+    fn to_js(&self) -> JsValue {
+        Uint8Array::from(self.as_slice()).into()
+    }
+}
+impl FromJs for Bytes {
+    fn from_js(value: &JsValue) -> Result<Self> {
+        let array = value
+            .dyn_ref::<Uint8Array>()
+            .ok_or_else(|| eyre!("Expected a Uint8Array, but got {:?}", value))?;
+        Ok(Bytes::from(array.to_vec()))
+    }
+}
+
+// ---- Containers ----
+
+impl<T: ToJs> ToJs for [T] {
+    fn to_js(&self) -> JsValue {
+        self.iter().map(ToJs::to_js).collect::<Array>().into()
+    }
+}
+impl<T: ToJs> ToJs for Vec<T> {
+    fn to_js(&self) -> JsValue {
+        self.as_slice().to_js()
+    }
+}
+impl<T: FromJs> FromJs for Vec<T> {
+    fn from_js(value: &JsValue) -> Result<Self> {
+        if !Array::is_array(value) {
+            return Err(eyre!("Expected an array, but got {:?}", value));
+        }
+        Array::from(value)
+            .iter()
+            .enumerate()
+            .map(|(i, v)| T::from_js(&v).wrap_err_with(|| format!("Invalid element {i}")))
+            .collect()
+    }
+}
+
+impl<T: ToJs> ToJs for Option<T> {
+    fn to_js(&self) -> JsValue {
+        match self {
+            Some(v) => v.to_js(),
+            None => JsValue::NULL,
+        }
+    }
+}
+impl<T: FromJs> FromJs for Option<T> {
+    fn from_js(value: &JsValue) -> Result<Self> {
+        if value.is_null() || value.is_undefined() {
+            Ok(None)
+        } else {
+            T::from_js(value).map(Some)
+        }
+    }
+}
+
+impl<T: ToJs> ToJs for TpMap<T> {
+    fn to_js(&self) -> JsValue {
+        let fields: Vec<_> = self.iter().map(|(k, v)| (k.as_str(), v.to_js())).collect();
+        object(&fields)
+    }
+}
+impl<T: FromJs> FromJs for TpMap<T> {
+    fn from_js(value: &JsValue) -> Result<Self> {
+        let obj = value
+            .dyn_ref::<Object>()
+            .ok_or_else(|| eyre!("Expected an object, but got {:?}", value))?;
+        Object::entries(obj)
+            .iter()
+            .map(|entry| {
+                let entry = Array::from(&entry);
+                let key = String::from_js(&entry.get(0))?;
+                let value = T::from_js(&entry.get(1))
+                    .wrap_err_with(|| format!("Invalid value at key `{key}`"))?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+}
+
+// ---- Helpers ----
+
+fn object(fields: &[(&str, JsValue)]) -> JsValue {
+    let obj = Object::new();
+    for (key, value) in fields {
+        Reflect::set(&obj, &JsValue::from_str(key), value).expect("`obj` is an object");
+    }
+    obj.into()
+}
+
+fn field<T: FromJs>(value: &JsValue, key: &str) -> Result<T> {
+    if !value.is_object() {
+        return Err(eyre!("Expected an object, but got {:?}", value));
+    }
+    let v = Reflect::get(value, &JsValue::from_str(key))
+        .map_err(|_| eyre!("Failed to get field `{key}`"))?;
+    T::from_js(&v).wrap_err_with(|| format!("Invalid field `{key}`"))
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use crate::object::ObjectHandle;
+
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn roundtrip<T: ToJs + FromJs + PartialEq + std::fmt::Debug>(value: T) {
+        assert_eq!(T::from_js(&value.to_js()).unwrap(), value);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_roundtrip() {
+        roundtrip(7u8);
+        roundtrip(-7i32);
+        roundtrip(u64::MAX);
+        roundtrip(0.5f32);
+        roundtrip(String::from("hello"));
+        roundtrip(Some(Vec3::new(1.0, 2.0, 3.0)));
+        roundtrip(None::<Quat>);
+        roundtrip(Transform::default());
+        roundtrip(Bytes::from(vec![1, 2, 3]));
+        roundtrip(vec![true, false]);
+        let map: TpMap<u32> = [("a".to_string(), 1)].into_iter().collect();
+        roundtrip(map);
+
+        let handle = ObjectHandle::new(ga::Index::from_raw_parts(3, 4));
+        roundtrip(handle);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_invalid() {
+        assert!(u8::from_js(&JsValue::from(256)).is_err());
+        assert!(u8::from_js(&JsValue::from(1.5)).is_err());
+        assert!(u64::from_js(&JsValue::from(3)).is_ok());
+        assert!(String::from_js(&JsValue::from(3)).is_err());
+        assert!(Vec2::from_js(&object(&[("x", JsValue::from(1))])).is_err());
+    }
+}
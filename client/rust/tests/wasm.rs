@@ -0,0 +1,184 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use tp_client::baseline::BaselineKind;
+use tp_client::contract::properties::composite::Vec3;
+use tp_client::contract::properties::dynamic::DynTpProperty;
+use tp_client::contract::{states, Contract, ContractDataHandle, ContractId};
+use tp_client::wasm::{Collaction, Engine, Realm};
+
+use js_sys::{Array, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[states]
+struct BallStates {
+    name: String,
+    radius: f32,
+    position: Vec3,
+    bounces: Option<u64>,
+}
+
+struct BallContract {
+    handle: ContractDataHandle,
+    states: BallStates,
+}
+impl Contract for BallContract {
+    type States = BallStates;
+    type Channels = ();
+
+    const ID: ContractId = ContractId {
+        name: "teleportal.test.ball",
+        version: (0, 0, 0),
+    };
+
+    fn new(handle: ContractDataHandle) -> Self {
+        Self {
+            handle,
+            states: BallStates::new(handle),
+        }
+    }
+
+    fn states(&self) -> &Self::States {
+        &self.states
+    }
+
+    fn channels(&self) -> &Self::Channels {
+        &()
+    }
+
+    fn handle(&self) -> ContractDataHandle {
+        self.handle
+    }
+}
+
+/// Makes an engine with a single ball in the fork.
+fn engine() -> Engine {
+    let mut engine = Engine::new(Realm::new("test".into()), None);
+    let baseline = engine
+        .inner_mut()
+        .realm_mut()
+        .baseline_mut(BaselineKind::Fork);
+    let c: BallContract = baseline.register_contract().unwrap();
+    let states = [
+        DynTpProperty::from(String::from("ball")),
+        DynTpProperty::from(1.0f32),
+        DynTpProperty::from(Vec3::new(0.0, 0.0, 0.0)),
+        DynTpProperty::from(None::<u64>),
+    ];
+    baseline
+        .object_create(&c, states.into_iter(), [].into_iter())
+        .unwrap();
+    engine
+}
+
+fn first_object(engine: &Engine) -> tp_client::wasm::Handle {
+    let objects: Array = engine.objects(BaselineKind::Fork).unchecked_into();
+    assert_eq!(objects.length(), 1);
+    objects.get(0).unchecked_into()
+}
+
+#[wasm_bindgen_test]
+fn test_read_states() {
+    let engine = engine();
+    let obj = first_object(&engine);
+
+    let contract = engine
+        .object_contract(BaselineKind::Fork, obj.clone())
+        .map_err(JsValue::from)
+        .unwrap();
+    assert_eq!(
+        engine
+            .contract_name(BaselineKind::Fork, contract)
+            .map_err(JsValue::from)
+            .unwrap(),
+        "teleportal.test.ball"
+    );
+
+    let name = engine
+        .bind_state(BaselineKind::Fork, obj.clone(), 0, "String")
+        .map_err(JsValue::from)
+        .unwrap();
+    assert_eq!(name.prop_type(), "String");
+    let value: JsValue = engine
+        .state(BaselineKind::Fork, &name)
+        .map_err(JsValue::from)
+        .unwrap()
+        .into();
+    assert_eq!(value.as_string().unwrap(), "ball");
+
+    // The type has to match the contract
+    assert!(engine
+        .bind_state(BaselineKind::Fork, obj, 0, "f32")
+        .is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_write_states() {
+    let mut engine = engine();
+    let obj = first_object(&engine);
+    let bind = |idx, typ| {
+        engine
+            .bind_state(BaselineKind::Fork, obj.clone(), idx, typ)
+            .map_err(JsValue::from)
+            .unwrap()
+    };
+    let radius = bind(1, "f32");
+    let position = bind(2, "Vec3");
+    let bounces = bind(3, "Option<u64>");
+
+    let position_value = js_sys::Object::new();
+    for (key, value) in [("x", 1.0), ("y", 2.0), ("z", 3.0)] {
+        Reflect::set(&position_value, &key.into(), &value.into()).unwrap();
+    }
+
+    let mut collaction = Collaction::new();
+    collaction
+        .assert(&radius, JsValue::from(1.0).unchecked_into())
+        .map_err(JsValue::from)
+        .unwrap();
+    collaction
+        .write(&radius, JsValue::from(2.5).unchecked_into())
+        .map_err(JsValue::from)
+        .unwrap();
+    collaction
+        .write(&position, JsValue::from(position_value).unchecked_into())
+        .map_err(JsValue::from)
+        .unwrap();
+    collaction
+        .write(&bounces, JsValue::from(3).unchecked_into())
+        .map_err(JsValue::from)
+        .unwrap();
+    // Wrong type
+    assert!(collaction
+        .write(&radius, JsValue::from("big").unchecked_into())
+        .is_err());
+    assert_eq!(collaction.length(), 4);
+
+    assert!(engine.send(collaction));
+    assert_eq!(engine.try_apply(), Some(true));
+    assert_eq!(engine.try_apply(), None);
+
+    let read = |state| -> JsValue {
+        engine
+            .state(BaselineKind::Fork, state)
+            .map_err(JsValue::from)
+            .unwrap()
+            .into()
+    };
+    assert_eq!(read(&radius).as_f64(), Some(2.5));
+    let position = read(&position);
+    assert_eq!(
+        Reflect::get(&position, &"z".into()).unwrap().as_f64(),
+        Some(3.0)
+    );
+    assert_eq!(read(&bounces), JsValue::from(3u64));
+
+    // A failed assert rejects the collaction
+    let mut collaction = Collaction::new();
+    collaction
+        .assert(&radius, JsValue::from(1.0).unchecked_into())
+        .map_err(JsValue::from)
+        .unwrap();
+    assert!(engine.send(collaction));
+    assert_eq!(engine.try_apply(), Some(false));
+}
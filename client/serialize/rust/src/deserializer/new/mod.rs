@@ -39,36 +39,47 @@
 //!    the correct type for its contract. Also, for every state in the object, ensure that it exists in
 //!    the serialized flatbuffer by validating that the baseline.states index is in the bounds of
 //!    the array. Once everything has been validated, we can instantiate the object in the
-//!    baseline, looking up the appropriate state handles from the state map.
+//!    baseline, looking up the appropriate state handles from the state map. Before the
+//!    objects of each contract, its prefabs are deserialized, so that objects that are
+//!    instances of a prefab can be instantiated from it, overriding all of their states.
 //! 6. Iterate over the states that referenced the null object. Have them store the appropriate
 //!    `ObjectHandle` instead, by using the mapping from the original serialized object index to the
 //!    deserialized `ObjectHandle`.
-//! 7. Delete the null contract and its null object.
-//! 8. Everything should be deserialized in the baseline now. Return the baseline to the caller.
+//! 7. Restore the hierarchy of the objects, which uses the same mapping. The children of each
+//!    object are reparented to it in order, since objects can have children of other contracts.
+//! 8. Delete the null contract and its null object.
+//! 9. Everything should be deserialized in the baseline now. Return the baseline to the caller.
 
 mod contracts;
-mod null_contract;
 mod objects;
 mod states;
 
 use self::contracts::InstantiatedContracts;
-use self::null_contract::NullContract;
 use self::objects::InstantiatedObjects;
 use self::states::InstantiatedStates;
-use crate::types::{ContractsIdx, ObjectsIdx, StatesIdx};
+use crate::types::{ContractsIdx, ObjectsIdx, PrefabsIdx, StatesIdx};
 use crate::{fb, rs};
 
 use eyre::{eyre, Result, WrapErr};
-use tp_client::contract::properties::dynamic::{DynTpPrimitive, DynTpProperty};
+use paste::paste;
+use std::collections::HashMap;
+use tp_client::contract::properties::dynamic::{
+    DynTpMap, DynTpOption, DynTpPrimitive, DynTpProperty,
+};
 use tp_client::contract::properties::states::id::DynStateIdPrimitive;
-use tp_client::contract::properties::states::{DynStateId, IStates};
+use tp_client::contract::properties::states::DynStateId;
+
+/// The name of the contract of the null object. It has no fields.
+const NULL_CONTRACT: &str = "teleportal.serialize.NullContract";
 
 pub struct DeserializerBuilder<'a> {
     base: rs::Baseline,
     inst_contracts: InstantiatedContracts,
     data: &'a [u8],
+    /// The buffer that `data` is, if it is shared
+    shared: Option<rs::Bytes>,
     base_t: fb::Baseline<'a>,
-    null_contract: NullContract,
+    null_contract: rs::DynContract,
     null_obj: rs::ObjectHandle,
 }
 impl<'a> DeserializerBuilder<'a> {
@@ -77,7 +88,9 @@ impl<'a> DeserializerBuilder<'a> {
             flatbuffers::root::<fb::Baseline>(data).wrap_err("Error while verifying flatbuffer")?;
 
         let mut base = rs::Baseline::new(kind);
-        let null_contract: NullContract = base.register_contract().unwrap();
+        let null_contract = base
+            .register_dyn_contract(&rs::ContractSchema::new(NULL_CONTRACT, (0, 0, 0)))
+            .unwrap();
         let null_obj = base
             .object_create(&null_contract, [].into_iter(), [].into_iter())
             .unwrap();
@@ -86,16 +99,25 @@ impl<'a> DeserializerBuilder<'a> {
             base,
             inst_contracts: InstantiatedContracts::new(),
             data,
+            shared: None,
             base_t,
             null_contract,
             null_obj,
         })
     }
 
+    /// Like [`Self::new`], but `Bytes` states share the buffer of `data`
+    /// instead of copying their data out of it.
+    pub fn new_shared(data: &'a rs::Bytes, kind: rs::BaselineKind) -> Result<Self> {
+        let mut builder = Self::new(data.as_slice(), kind)?;
+        builder.shared = Some(data.clone());
+        Ok(builder)
+    }
+
     /// Call this once for each contract.
     pub fn register_contract<C: rs::Contract>(&mut self) -> Result<C> {
         // Yes this is not super efficient. But who cares, this is the simplest to understand.
-        let idx = find_serialized_contract::<C>(self.base_t)
+        let idx = find_serialized_contract(self.base_t, &rs::ContractLayout::of::<C>())
             .wrap_err("Failed to find matching contract")?;
         let contract = self
             .base
@@ -106,6 +128,66 @@ impl<'a> DeserializerBuilder<'a> {
         Ok(contract)
     }
 
+    /// Like [`Self::register_contract`], but for a contract whose fields are only
+    /// known at runtime.
+    pub fn register_dyn_contract(
+        &mut self,
+        schema: &rs::ContractSchema,
+    ) -> Result<rs::DynContract> {
+        let layout = schema.layout()?;
+        let idx = find_serialized_contract(self.base_t, &layout)
+            .wrap_err("Failed to find matching contract")?;
+        let contract = self
+            .base
+            .register_dyn_contract(schema)
+            .wrap_err("Contract already existed")?;
+        self.inst_contracts.add_contract(idx, contract.handle());
+        Ok(contract)
+    }
+
+    /// The schemas of all the contracts in the flatbuffer, which can be passed to
+    /// [`Self::register_dyn_contract`] to deserialize contracts that aren't known
+    /// ahead of time.
+    ///
+    /// Only the names and types of states are serialized, so the schemas have no
+    /// channels and none of the attributes of their fields.
+    pub fn serialized_schemas(&self) -> Result<Vec<rs::ContractSchema>> {
+        self.base_t
+            .contracts()
+            .into_iter()
+            .flatten()
+            .map(|c| {
+                let id = c.id().ok_or_else(|| eyre!("Contract was missing its id"))?;
+                let name = id
+                    .name()
+                    .ok_or_else(|| eyre!("Contract was missing its name"))?;
+                let mut schema =
+                    rs::ContractSchema::new(name, (id.v_major(), id.v_minor(), id.v_patch()));
+                let Some(states_t) = c.states() else {
+                    return Ok(schema);
+                };
+                let names = states_t.names().into_iter().flatten();
+                let types = states_t.types().into_iter().flatten();
+                for (i, (name, typ)) in names.zip(types).enumerate() {
+                    let typ = rs::TpPrimitiveType::try_from(typ)?;
+                    let container = states_t
+                        .containers()
+                        .map_or(fb::TpContainerKind::Primitive, |containers| {
+                            containers.get(i)
+                        });
+                    let typ = match container {
+                        fb::TpContainerKind::Primitive => rs::TpPropertyType::Primitive(typ),
+                        fb::TpContainerKind::Option => rs::TpPropertyType::Option(typ),
+                        fb::TpContainerKind::Map => rs::TpPropertyType::Map(typ),
+                        _ => return Err(eyre!("Unknown container kind {:?}", container)),
+                    };
+                    schema = schema.with_state(name, typ);
+                }
+                Ok(schema)
+            })
+            .collect()
+    }
+
     pub fn finish(self) -> Deserializer<'a> {
         Deserializer::new(self)
     }
@@ -115,6 +197,8 @@ pub struct Deserializer<'a> {
     b: DeserializerBuilder<'a>,
     inst_states: InstantiatedStates,
     inst_objects: InstantiatedObjects,
+    /// The names of the deserialized prefabs
+    inst_prefabs: HashMap<PrefabsIdx, String>,
 }
 impl<'a> Deserializer<'a> {
     pub fn new(builder: DeserializerBuilder<'a>) -> Self {
@@ -122,28 +206,31 @@ impl<'a> Deserializer<'a> {
             b: builder,
             inst_states: InstantiatedStates::new(),
             inst_objects: InstantiatedObjects::new(),
+            inst_prefabs: HashMap::new(),
         }
     }
 
-    /// Deserializes all objects belonging to contract `c`.
+    /// Deserializes all objects and prefabs belonging to contract `c`.
     ///
     /// # Panics
     /// Will panic if the `contract` was not registered already.
-    pub fn deserialize_objects<C: rs::Contract>(&mut self, contract: &C) -> Result<()> {
+    pub fn deserialize_objects<C: rs::IContract>(&mut self, contract: &C) -> Result<()> {
         assert!(
             self.b
                 .inst_contracts
-                .is_registered_handle(contract.handle()),
+                .is_registered_handle(contract.contract_handle()),
             "Contract was not already registered"
         );
 
+        let contract_idx = self.b.inst_contracts.get_idx(contract.contract_handle());
+        self.deserialize_prefabs(contract_idx, contract)
+            .wrap_err("Failed to deserialize prefabs")?;
+
         let Some(objects_t) = self.b.base_t.objects() else {
             // No objects at all, so we are done.
             return Ok(());
         };
 
-        let contract_idx = self.b.inst_contracts.get_idx(contract.handle());
-
         for (obj_idx, _obj_t) in objects_with_contract_idx(contract_idx, objects_t) {
             let obj_valid: ValidatedObject = validate_obj_matches_contract(
                 obj_idx,
@@ -152,15 +239,21 @@ impl<'a> Deserializer<'a> {
                 self.b.base_t,
             )
             .wrap_err("Object did not conform to its contract")?;
-            self.deserialize_obj_with_null::<C>(obj_valid, contract)
+            self.deserialize_obj_with_null(obj_valid, contract)
                 .wrap_err("Failed to deserialize object")?;
         }
 
         Ok(())
     }
 
+    /// The handle of the object at `idx` in the flatbuffer, if it was
+    /// deserialized already. Objects keep their handles in the `Baseline`
+    /// returned by [`Deserializer::finish`].
+    pub fn deserialized_object(&self, idx: usize) -> Option<rs::ObjectHandle> {
+        self.inst_objects.get_handle(ObjectsIdx(idx)).ok()
+    }
+
     pub fn finish(mut self) -> Result<rs::Baseline> {
-        use rs::Contract;
         // Takes all deserialized states that hold the null object's handle, and sets them to their
         // correct target object based on what was originally in the flatbuffer.
         for (s_idx, s_handle, o_idx) in self.inst_states.iter() {
@@ -174,11 +267,34 @@ impl<'a> Deserializer<'a> {
             state_ref.value = o_handle;
         }
 
+        // Restores the hierarchy, which can span contracts.
+        let get_handle = |idx: u32| {
+            self.inst_objects
+                .get_handle(ObjectsIdx(idx as usize))
+                .wrap_err(
+                    "An object in the hierarchy didn't exist in the baseline. \
+                    Were all contracts' objects deserialized already?",
+                )
+        };
+        for children_t in self.b.base_t.children().into_iter().flatten() {
+            let parent_t = children_t
+                .parent()
+                .ok_or_else(|| eyre!("Children were missing their parent"))?;
+            let parent = get_handle(parent_t.idx())?;
+            for child_t in children_t.children().into_iter().flatten() {
+                let child = get_handle(child_t.idx())?;
+                self.b
+                    .base
+                    .object_reparent(child, Some(parent), None)
+                    .wrap_err("Failed to restore the hierarchy")?;
+            }
+        }
+
         // This should also remove the null object
         self.b
             .base
-            .unregister_contract::<NullContract>(self.b.null_contract.handle())
-            .wrap_err("Could not remove NullContract")?;
+            .unregister_dyn_contract(&self.b.null_contract)
+            .wrap_err("Could not remove the null contract")?;
 
         Ok(self.b.base)
     }
@@ -187,7 +303,10 @@ impl<'a> Deserializer<'a> {
 /// Check that the contract exists in the flatbuffer somewhere, and get its index.
 ///
 /// Validates that the stored contract's StateIds match.
-fn find_serialized_contract<C: rs::Contract>(baseline_t: fb::Baseline) -> Result<ContractsIdx> {
+fn find_serialized_contract(
+    baseline_t: fb::Baseline,
+    layout: &rs::ContractLayout,
+) -> Result<ContractsIdx> {
     let Some(contracts_t) = baseline_t.contracts() else {
         return Err(eyre!("There are no contracts to deserialize"));
     };
@@ -202,25 +321,42 @@ fn find_serialized_contract<C: rs::Contract>(baseline_t: fb::Baseline) -> Result
             // Using option to give us try operator.
             || -> Option<()> {
                 let id = c.id()?;
-                (id.name()? == C::ID.name
-                    && (id.v_major(), id.v_minor(), id.v_patch()) == C::ID.version)
+                (id.name()? == layout.id.name
+                    && (id.v_major(), id.v_minor(), id.v_patch()) == layout.id.version)
                     .then_some(())
             }()
             // Check that StateIds match
             .and_then(|_| {
                 let states_t = c.states()?;
-                let nfields = C::States::field_names().len();
+                let nfields = layout.state_names.len();
                 let names = states_t.names()?;
                 let types = states_t.types()?;
                 // Lengths match?
                 (names.len() == nfields && types.len() == nfields).then_some(())?;
                 // Names match?
-                std::iter::zip(C::States::field_names().into_iter(), names.iter())
+                std::iter::zip(layout.state_names.iter(), names.iter())
                     .all(|(a, b)| *a == b)
                     .then_some(())?;
                 // Types match?
-                std::iter::zip(C::States::enumerate_types().into_iter(), types.iter())
-                    .all(|(a, b)| *a == b)
+                std::iter::zip(layout.state_types.iter(), types.iter())
+                    .all(|(a, b)| rs::TpPropertyType::Primitive(a.primitive_type()) == b)
+                    .then_some(())?;
+                // Containers match? If absent, all states are primitives.
+                let containers = states_t.containers();
+                if let Some(containers) = containers {
+                    (containers.len() == nfields).then_some(())?;
+                }
+                layout
+                    .state_types
+                    .iter()
+                    .enumerate()
+                    .all(|(i, a)| {
+                        let container = containers
+                            .map_or(fb::TpContainerKind::Primitive, |containers| {
+                                containers.get(i)
+                            });
+                        fb::TpContainerKind::try_from(*a).ok() == Some(container)
+                    })
                     .then_some(())?;
                 Some(())
             })
@@ -258,7 +394,7 @@ struct ValidatedObject<'a> {
 /// Validates that the serialized object `obj_t` matches its `contract`.
 ///
 /// Return Err if they don't match.
-fn validate_obj_matches_contract<'a, C: rs::Contract>(
+fn validate_obj_matches_contract<'a, C: rs::IContract>(
     obj_idx: ObjectsIdx,
     contract: &C,
     inst_contracts: &InstantiatedContracts,
@@ -271,7 +407,7 @@ fn validate_obj_matches_contract<'a, C: rs::Contract>(
 
     // Validate contract field
     {
-        let contract_idx_expected = inst_contracts.get_idx(contract.handle());
+        let contract_idx_expected = inst_contracts.get_idx(contract.contract_handle());
         let contract_idx_found: ContractsIdx = {
             let c: fb::ContractDataHandle = obj_t
                 .contract()
@@ -283,9 +419,11 @@ fn validate_obj_matches_contract<'a, C: rs::Contract>(
         }
     }
 
+    let state_types = contract.layout().state_types;
+
     // Validate number of states matches
     {
-        let num_states_expected = C::States::enumerate_types().len();
+        let num_states_expected = state_types.len();
         let num_states_found = obj_t.states().map_or(0, |x| x.len());
         if num_states_found != num_states_expected {
             return Err(eyre!(
@@ -312,15 +450,40 @@ fn validate_obj_matches_contract<'a, C: rs::Contract>(
         .map(|s: fb::StateHandle| StatesIdx(usize::try_from(s.idx()).unwrap()))
         .map(|s_idx: StatesIdx| states_t.get(s_idx.0));
 
-    let zip_states_and_expected_types =
-        std::iter::zip(obj_states_t, C::States::enumerate_types().into_iter());
+    let zip_states_and_expected_types = std::iter::zip(obj_states_t, state_types.iter());
 
     // Check that state types match contract
     for (i, (obj_state_t, expected_typ)) in zip_states_and_expected_types.enumerate() {
-        if obj_state_t.p_type() != *expected_typ {
+        let expected_container = fb::TpContainerKind::try_from(*expected_typ)?;
+        if obj_state_t.container() != expected_container {
+            return Err(eyre!(
+                "state {i}'s container was {:?} but expected {:?}",
+                obj_state_t.container().variant_name().unwrap(),
+                *expected_typ,
+            ));
+        }
+        let expected_primitive = rs::TpPropertyType::Primitive(expected_typ.primitive_type());
+        let mismatched_primitive = match obj_state_t.container() {
+            fb::TpContainerKind::Option => {
+                // Empty options don't store their type
+                let p_type = obj_state_t.p_type();
+                (p_type != fb::TpPrimitive::NONE && p_type != expected_primitive).then_some(p_type)
+            }
+            fb::TpContainerKind::Map => obj_state_t
+                .entries()
+                .into_iter()
+                .flatten()
+                .map(|entry| entry.v_type())
+                .find(|v_type| *v_type != expected_primitive),
+            _ => {
+                let p_type = obj_state_t.p_type();
+                (p_type != expected_primitive).then_some(p_type)
+            }
+        };
+        if let Some(p_type) = mismatched_primitive {
             return Err(eyre!(
                 "state {i}'s type was {:?} but expected {:?}",
-                obj_state_t.p_type().variant_name().unwrap(),
+                p_type.variant_name().unwrap(),
                 *expected_typ,
             ));
         }
@@ -332,22 +495,153 @@ fn validate_obj_matches_contract<'a, C: rs::Contract>(
     })
 }
 
+/// The blob `v`, which was read from the buffer being deserialized. If that
+/// buffer is `shared`, the blob is a view into it, and otherwise it is copied.
+fn deserialize_bytes(shared: Option<&rs::Bytes>, v: &[u8]) -> rs::Bytes {
+    if let Some(shared) = shared {
+        let offset = (v.as_ptr() as usize).wrapping_sub(shared.as_ptr() as usize);
+        if offset <= shared.len() && v.len() <= shared.len() - offset {
+            return shared.slice(offset..offset + v.len());
+        }
+    }
+    rs::Bytes::from(v)
+}
+
+/// Deserializes the primitive stored in the union `$field` of the table `$t`.
+/// `$shared` is the buffer being deserialized, if it is shared.
+///
+/// Evaluates to `None` if the union is empty or holds a handle, since handles
+/// need to be resolved against the rest of the baseline.
+macro_rules! deserialize_primitive {
+    ($t:expr, $field:ident, $shared:expr) => {{
+        macro_rules! helper {
+            ($e:expr) => {{
+                Some(DynTpPrimitive::from($e.to_owned()))
+            }};
+        }
+        use fb::TpPrimitive as P;
+        paste! {
+            match $t.[<$field _type>]() {
+                P::U8 => helper!($t.[<$field _as_u8>]().unwrap().v()),
+                P::U16 => helper!($t.[<$field _as_u16>]().unwrap().v()),
+                P::U32 => helper!($t.[<$field _as_u32>]().unwrap().v()),
+                P::U64 => helper!($t.[<$field _as_u64>]().unwrap().v()),
+                P::I8 => helper!($t.[<$field _as_i8>]().unwrap().v()),
+                P::I16 => helper!($t.[<$field _as_i16>]().unwrap().v()),
+                P::I32 => helper!($t.[<$field _as_i32>]().unwrap().v()),
+                P::I64 => helper!($t.[<$field _as_i64>]().unwrap().v()),
+                P::Bool => helper!($t.[<$field _as_bool>]().unwrap().v()),
+                P::F32 => helper!($t.[<$field _as_f32>]().unwrap().v()),
+                P::F64 => helper!($t.[<$field _as_f64>]().unwrap().v()),
+                P::FbString => helper!($t.[<$field _as_fb_string>]().unwrap().v().unwrap()),
+                P::Vec2 => helper!(rs::Vec2::from($t.[<$field _as_vec2>]().unwrap().v().unwrap())),
+                P::Vec3 => helper!(rs::Vec3::from($t.[<$field _as_vec3>]().unwrap().v().unwrap())),
+                P::Quat => helper!(rs::Quat::from($t.[<$field _as_quat>]().unwrap().v().unwrap())),
+                P::Color => helper!(rs::Color::from($t.[<$field _as_color>]().unwrap().v().unwrap())),
+                P::Transform => helper!(rs::Transform::from(
+                    $t.[<$field _as_transform>]().unwrap().v().unwrap()
+                )),
+                P::Bytes => helper!(deserialize_bytes(
+                    $shared,
+                    $t.[<$field _as_bytes>]().unwrap().v().unwrap().bytes()
+                )),
+                _ => None,
+            }
+        }
+    }};
+}
+
+/// Deserializes the value of a state of type `typ`.
+///
+/// Evaluates to `None` if the state holds a handle, since handles need to be resolved
+/// against the rest of the baseline.
+fn deserialize_value(
+    state_t: fb::State,
+    typ: rs::TpPropertyType,
+    shared: Option<&rs::Bytes>,
+) -> Result<Option<DynTpProperty>> {
+    Ok(Some(match state_t.container() {
+        fb::TpContainerKind::Option => {
+            DynTpProperty::Option(match deserialize_primitive!(state_t, p, shared) {
+                Some(p) => DynTpOption::from(p),
+                None => DynTpOption::none(typ.primitive_type()),
+            })
+        }
+        fb::TpContainerKind::Map => {
+            let mut map = DynTpMap::new(typ.primitive_type());
+            for entry_t in state_t.entries().into_iter().flatten() {
+                let key = entry_t
+                    .key()
+                    .ok_or_else(|| eyre!("Map entry was missing its key"))?;
+                let value = deserialize_primitive!(entry_t, v, shared)
+                    .ok_or_else(|| eyre!("Map entry had an unsupported value"))?;
+                map.insert(key.to_owned(), value)?;
+            }
+            DynTpProperty::Map(map)
+        }
+        _ => match deserialize_primitive!(state_t, p, shared) {
+            Some(p) => DynTpProperty::Primitive(p),
+            None => return Ok(None),
+        },
+    }))
+}
+
 impl<'a> Deserializer<'a> {
+    /// Deserializes the prefabs of the contract at `contract_idx` into the baseline.
+    fn deserialize_prefabs<C: rs::IContract>(
+        &mut self,
+        contract_idx: ContractsIdx,
+        contract: &C,
+    ) -> Result<()> {
+        let layout = contract.layout();
+        let prefabs_t = self.b.base_t.prefabs().into_iter().flatten().enumerate();
+        for (prefab_idx, prefab_t) in prefabs_t {
+            let Some(c) = prefab_t.contract() else {
+                continue;
+            };
+            if usize::from(c.idx()) != contract_idx.0 {
+                continue;
+            }
+            let name = prefab_t
+                .name()
+                .ok_or_else(|| eyre!("Prefab was missing its name"))?;
+            let states_t = prefab_t
+                .states()
+                .ok_or_else(|| eyre!("Prefab `{name}` was missing its states"))?;
+            if states_t.len() != layout.state_types.len() {
+                return Err(eyre!(
+                    "Prefab `{name}` did not have a value for every state"
+                ));
+            }
+            let mut values = rs::Overrides::new();
+            let fields = std::iter::zip(layout.state_names, layout.state_types);
+            for (state_t, (state_name, typ)) in std::iter::zip(states_t, fields) {
+                let value = deserialize_value(state_t, *typ, self.b.shared.as_ref())?
+                    .ok_or_else(|| eyre!("Prefabs of handles are not yet supported"))?;
+                values = values.with_state(*state_name, value);
+            }
+            let prefab = rs::Prefab::new(name, contract, values)
+                .wrap_err_with(|| format!("Prefab `{name}` did not conform to its contract"))?;
+            self.b.base.prefab_insert(prefab)?;
+            self.inst_prefabs
+                .insert(PrefabsIdx(prefab_idx), name.to_owned());
+        }
+        Ok(())
+    }
+
     /// Deserializes `obj` into the baseline, but any `State<ObjectHandle`s are set to
     /// the null object handle.
-    fn deserialize_obj_with_null<C: rs::Contract>(
+    fn deserialize_obj_with_null<C: rs::IContract>(
         &mut self,
         obj: ValidatedObject,
         contract: &C,
     ) -> Result<()> {
+        let contract_handle = contract.contract_handle();
+        let layout = contract.layout();
         // TODO: This could be an array if we had a const for `C`'s number of states.
         let mut obj_states: Vec<StatesIdx> = Vec::new();
         if let Some(obj_states_t) = obj.t.states() {
-            assert_eq!(
-                obj_states_t.len(),
-                C::States::enumerate_types().len(),
-                "sanity check"
-            );
+            assert_eq!(obj_states_t.len(), layout.state_types.len(), "sanity check");
 
             obj_states.extend(
                 obj_states_t
@@ -356,79 +650,86 @@ impl<'a> Deserializer<'a> {
             );
         }
 
-        macro_rules! helper {
-            ($e:expr) => {{
-                DynTpProperty::Primitive(DynTpPrimitive::from($e.to_owned()))
-            }};
-        }
-
         let mut dyn_props: Vec<DynTpProperty> = Vec::new();
         // Used to track which states are null states temporarily. We don't have the
         // `rs::StateHandle` for the state until after we construct the object, so this
         // will be used after object construction to re-associate these `StatesIdx`
         // with the `rs::StateHandle`.
         let mut null_states: Vec<(rs::StateId<rs::ObjectHandle>, StatesIdx)> = Vec::new();
-        for (state_id, obj_state_idx) in contract.state_iter().zip(obj_states.into_iter()) {
+        let states_and_types = std::iter::zip(
+            layout
+                .state_iter(contract_handle)
+                .zip(obj_states.into_iter()),
+            layout.state_types.iter(),
+        );
+        for ((state_id, obj_state_idx), expected_typ) in states_and_types {
             let obj_state_t = self.b.base_t.states().unwrap().get(obj_state_idx.0);
             // Handle dynamic typing of union to access the property
             use fb::TpPrimitive as P;
 
-            let prop = match obj_state_t.p_type() {
-                P::U8 => helper!(obj_state_t.p_as_u8().unwrap().v()),
-                P::U16 => helper!(obj_state_t.p_as_u16().unwrap().v()),
-                P::U32 => helper!(obj_state_t.p_as_u32().unwrap().v()),
-                P::U64 => helper!(obj_state_t.p_as_u64().unwrap().v()),
-                P::I8 => helper!(obj_state_t.p_as_i8().unwrap().v()),
-                P::I16 => helper!(obj_state_t.p_as_i16().unwrap().v()),
-                P::I32 => helper!(obj_state_t.p_as_i32().unwrap().v()),
-                P::I64 => helper!(obj_state_t.p_as_i64().unwrap().v()),
-                P::Bool => helper!(obj_state_t.p_as_bool().unwrap().v()),
-                P::F32 => helper!(obj_state_t.p_as_f32().unwrap().v()),
-                P::F64 => helper!(obj_state_t.p_as_f64().unwrap().v()),
-                P::FbString => helper!(obj_state_t.p_as_fb_string().unwrap().v().unwrap()),
-                P::tp_serialize_object_ObjectHandle => {
-                    // Figure out what object was referenced in the state, and track it.
-                    let referenced_obj_handle_t: fb::ObjectHandle = obj_state_t
-                        .p_as_tp_serialize_object_object_handle()
-                        .unwrap();
-                    let referenced_obj_idx =
-                        ObjectsIdx(usize::try_from(referenced_obj_handle_t.idx()).unwrap());
-                    self.inst_states
-                        .track_obj_reference(obj_state_idx, referenced_obj_idx)?;
-
-                    let DynStateId::Primitive(DynStateIdPrimitive::ObjectHandle(state_id)) = state_id else {
-                        unreachable!("We already validated that the state type should match the contract");
-                    };
-                    // Mark our state as a null state.
-                    null_states.push((state_id, obj_state_idx));
-
-                    // Set to the null object
-                    DynTpProperty::Primitive(DynTpPrimitive::ObjectHandle(self.b.null_obj))
-                }
-                P::tp_serialize_contract_ContractDataHandle => {
-                    let contract_handle_t: fb::ContractDataHandle = obj_state_t
-                        .p_as_tp_serialize_contract_contract_data_handle()
-                        .unwrap();
-                    let contract_idx =
-                        ContractsIdx(usize::try_from(contract_handle_t.idx()).unwrap());
-                    let contract_handle: rs::ContractDataHandle = self
-                        .b
-                        .inst_contracts
-                        .get_handle(contract_idx)
-                        .ok_or_else(|| eyre!("Contract was missing from registry"))?;
-
-                    DynTpProperty::Primitive(DynTpPrimitive::ContractDataHandle(contract_handle))
-                }
-                _ => unimplemented!("Other types are not supported."),
+            let value = deserialize_value(obj_state_t, *expected_typ, self.b.shared.as_ref())?;
+            let prop = match value {
+                Some(prop) => prop,
+                None => match obj_state_t.p_type() {
+                    P::tp_serialize_object_ObjectHandle => {
+                        // Figure out what object was referenced in the state, and track it.
+                        let referenced_obj_handle_t: fb::ObjectHandle = obj_state_t
+                            .p_as_tp_serialize_object_object_handle()
+                            .unwrap();
+                        let referenced_obj_idx =
+                            ObjectsIdx(usize::try_from(referenced_obj_handle_t.idx()).unwrap());
+                        self.inst_states
+                            .track_obj_reference(obj_state_idx, referenced_obj_idx)?;
+
+                        let DynStateId::Primitive(DynStateIdPrimitive::ObjectHandle(state_id)) =
+                            state_id
+                        else {
+                            unreachable!("We already validated that the state type should match the contract");
+                        };
+                        // Mark our state as a null state.
+                        null_states.push((state_id, obj_state_idx));
+
+                        // Set to the null object
+                        DynTpProperty::Primitive(DynTpPrimitive::ObjectHandle(self.b.null_obj))
+                    }
+                    P::tp_serialize_contract_ContractDataHandle => {
+                        let contract_handle_t: fb::ContractDataHandle = obj_state_t
+                            .p_as_tp_serialize_contract_contract_data_handle()
+                            .unwrap();
+                        let contract_idx =
+                            ContractsIdx(usize::try_from(contract_handle_t.idx()).unwrap());
+                        let contract_handle: rs::ContractDataHandle = self
+                            .b
+                            .inst_contracts
+                            .get_handle(contract_idx)
+                            .ok_or_else(|| eyre!("Contract was missing from registry"))?;
+
+                        DynTpProperty::Primitive(DynTpPrimitive::ContractDataHandle(
+                            contract_handle,
+                        ))
+                    }
+                    _ => unimplemented!("Other types are not supported."),
+                },
             };
             dyn_props.push(prop);
         }
 
-        let new_obj_handle: rs::ObjectHandle = self
-            .b
-            .base
-            .object_create(contract, dyn_props.into_iter(), [].into_iter())
-            .wrap_err("failed to create object")?;
+        let new_obj_handle: rs::ObjectHandle = match obj.t.prefab() {
+            Some(prefab_t) => {
+                let name = self
+                    .inst_prefabs
+                    .get(&PrefabsIdx(prefab_t.idx() as usize))
+                    .ok_or_else(|| eyre!("The object's prefab was not deserialized"))?;
+                let overrides = std::iter::zip(layout.state_names, dyn_props)
+                    .fold(rs::Overrides::new(), |o, (n, p)| o.with_state(*n, p));
+                self.b.base.object_instantiate(name, overrides)
+            }
+            None => self
+                .b
+                .base
+                .object_create(contract, dyn_props.into_iter(), [].into_iter()),
+        }
+        .wrap_err("failed to create object")?;
 
         // Go back through all marked null states and actually associate their idx with
         // their handle.
@@ -29,28 +29,43 @@ pub use self::serializer::Serializer;
 
 mod types;
 
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
 /// The types related to the tp_client rust library
 mod rs {
     pub use tp_client::baseline::{Baseline, BaselineKind};
+    pub use tp_client::contract::properties::bytes::Bytes;
+    pub use tp_client::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
     pub use tp_client::contract::properties::dynamic::{TpPrimitiveType, TpPropertyType};
     pub use tp_client::contract::properties::states::{
         DynStateHandle, State, StateHandle, StateId,
     };
-    pub use tp_client::contract::{Contract, ContractData, ContractDataHandle, ContractId};
+    pub use tp_client::contract::{
+        Contract, ContractData, ContractDataHandle, ContractId, ContractLayout, ContractSchema,
+        DynContract, IContract,
+    };
     pub use tp_client::object::ObjectHandle;
+    pub use tp_client::prefab::{Overrides, Prefab};
 }
 
 /// The types related to the flatbuffer
 mod fb {
     pub use crate::baseline::Baseline;
     pub use crate::contract::{Contract, ContractDataHandle, ContractId, ContractStates};
-    pub use crate::object::{Object, ObjectHandle};
+    pub use crate::object::{Children, Object, ObjectHandle};
+    pub use crate::prefab::{Prefab, PrefabHandle};
+    pub use crate::primitive::TpContainerKind;
     pub use crate::primitive::TpPrimitive;
     pub use crate::primitive::TpPrimitiveKind;
-    pub use crate::state::{State, StateHandle};
+    pub use crate::state::{MapEntry, State, StateHandle};
     pub mod primitive {
         pub use crate::primitive::{
-            Bool, FbString, F32, F64, I16, I32, I64, I8, U16, U32, U64, U8,
+            Bool, Bytes, FbString, F32, F64, I16, I32, I64, I8, U16, U32, U64, U8,
+        };
+        pub use crate::primitive::{
+            Color, ColorValue, Quat, QuatValue, Transform, TransformValue, Vec2, Vec2Value, Vec3,
+            Vec3Value,
         };
     }
 }
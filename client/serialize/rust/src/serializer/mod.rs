@@ -1,21 +1,23 @@
 pub(crate) mod handle_map;
 
 use eyre::{eyre, Result, WrapErr};
-use flatbuffers::{FlatBufferBuilder, WIPOffset};
+use flatbuffers::{FlatBufferBuilder, UnionWIPOffset, WIPOffset};
 use paste::paste;
+use std::collections::HashMap;
 use tp_client::apply_to_state_id;
 use tp_client::contract::properties::dynamic::{DynTpPrimitiveRef, DynTpPropertyRef};
 use tp_client::contract::properties::states::dyn_handle::DynStateHandlePrimitive;
 use tp_client::contract::properties::states::dyn_state::DynStateRef;
-use tp_client::contract::properties::states::{DynStateHandle, IStates};
+use tp_client::contract::properties::states::DynStateHandle;
 
 use self::handle_map::HandleMap;
 use crate::baseline::BaselineArgs;
 use crate::contract::{ContractArgs, ContractDataHandleArgs, ContractIdArgs, ContractStatesArgs};
-use crate::object::{ObjectArgs, ObjectHandleArgs};
+use crate::object::{ChildrenArgs, ObjectArgs, ObjectHandleArgs};
+use crate::prefab::{PrefabArgs, PrefabHandleArgs};
 use crate::primitive::FbStringArgs;
-use crate::state::{StateArgs, StateHandleArgs};
-use crate::types::{ContractsIdx, ObjectsIdx, StatesIdx};
+use crate::state::{MapEntryArgs, StateArgs, StateHandleArgs};
+use crate::types::{ContractsIdx, ObjectsIdx, PrefabsIdx, StatesIdx};
 use crate::{fb, rs};
 
 /// The dummy value that will be used for temporary `WIPOffset` values.
@@ -27,6 +29,9 @@ pub struct Serializer<'b> {
     contracts: Vec<WIPOffset<fb::Contract<'static>>>,
     states: Vec<WIPOffset<fb::State<'static>>>,
     objects: Vec<WIPOffset<fb::Object<'static>>>,
+    prefabs: Vec<WIPOffset<fb::Prefab<'static>>>,
+    /// The prefabs serialized so far, by name
+    prefab_idxs: HashMap<String, PrefabsIdx>,
     handle_map: HandleMap,
 }
 impl<'b> Serializer<'b> {
@@ -38,28 +43,83 @@ impl<'b> Serializer<'b> {
             contracts: Vec::new(),
             states: Vec::new(),
             objects: Vec::new(),
+            prefabs: Vec::new(),
+            prefab_idxs: HashMap::new(),
             handle_map: Default::default(),
         }
     }
 
-    /// Serialize all objects related to contract `C`. Usually, this gets called once
-    /// per relevant contract.
-    pub fn serialize<C: rs::Contract>(&mut self, contract: &C) -> Result<()> {
+    /// Serialize all objects and prefabs related to `contract`, which is either a
+    /// `Contract` or a `DynContract`. Usually, this gets called once per relevant
+    /// contract.
+    pub fn serialize<C: rs::IContract>(&mut self, contract: &C) -> Result<()> {
+        self.serialize_filtered(contract, |_| true)
+    }
+
+    /// Like [`Self::serialize`], but only serializes the objects for which `filter`
+    /// returns true. The contract and its prefabs are always serialized.
+    ///
+    /// Any objects referenced by the states of those objects must be serialized
+    /// too, or [`Self::finish`] will panic. Children that aren't serialized are
+    /// left out of the hierarchy, and parents that aren't make their children
+    /// roots.
+    pub fn serialize_filtered<C: rs::IContract>(
+        &mut self,
+        contract: &C,
+        mut filter: impl FnMut(rs::ObjectHandle) -> bool,
+    ) -> Result<()> {
         let fbb = &mut self.fbb;
-        let contract_data: &rs::ContractData = self.baseline.contract_data(contract.handle())?;
+        let contract_handle = contract.contract_handle();
+        let layout = contract.layout();
+        let contract_data: &rs::ContractData = self.baseline.contract_data(contract_handle)?;
 
-        self.contracts.push(Self::serialize_contract::<C>(fbb)?);
+        self.contracts.push(Self::serialize_contract(fbb, &layout)?);
         self.handle_map
-            .insert_contract(contract.handle(), ContractsIdx(self.contracts.len() - 1));
+            .insert_contract(contract_handle, ContractsIdx(self.contracts.len() - 1));
 
         let contract_data_handle_t = {
             let idx = self.contracts.len() as u16 - 1;
             fb::ContractDataHandle::create(fbb, &ContractDataHandleArgs { idx })
         };
 
+        // Sorted, so that equal baselines serialize the same
+        let mut prefabs: Vec<&rs::Prefab> = self
+            .baseline
+            .iter_prefabs()
+            .filter(|p| p.contract().handle() == contract_handle)
+            .collect();
+        prefabs.sort_unstable_by(|a, b| a.name().cmp(b.name()));
+        for prefab in prefabs {
+            let states_t: Vec<_> = prefab
+                .states()
+                .iter()
+                .map(|s| {
+                    Self::serialize_state(fbb, s.into())?
+                        .ok_or_else(|| eyre!("Prefabs of handles are not yet supported"))
+                })
+                .collect::<Result<_>>()
+                .wrap_err_with(|| format!("Failed to serialize prefab `{}`", prefab.name()))?;
+            let states_t = fbb.create_vector(&states_t);
+            let name_t = fbb.create_string(prefab.name());
+            let prefab_t = fb::Prefab::create(
+                fbb,
+                &PrefabArgs {
+                    name: Some(name_t),
+                    contract: Some(contract_data_handle_t),
+                    states: Some(states_t),
+                },
+            );
+            self.prefabs.push(prefab_t);
+            self.prefab_idxs
+                .insert(prefab.name().to_owned(), PrefabsIdx(self.prefabs.len() - 1));
+        }
+
         for &obj_handle in contract_data.objects().iter() {
+            if !filter(obj_handle) {
+                continue;
+            }
             let mut state_handles = Vec::new();
-            for state_id in contract.state_iter() {
+            for state_id in layout.state_iter(contract_handle) {
                 let (state_handle, state) =
                     apply_to_state_id!(state_id, |state_id| -> eyre::Result<_> {
                         let state_handle = self
@@ -71,52 +131,10 @@ impl<'b> Serializer<'b> {
                         Ok((state_handle, state))
                     })?;
 
-                macro_rules! helper {
-                    ($t:ident, $v:expr) => {{
-                        paste! {
-                            let p = fb::primitive::$t::create(fbb, &$crate::primitive::[<$t Args>] { v: $v });
-                            fb::State::create(
-                                fbb,
-                                &StateArgs {
-                                    p_type: fb::TpPrimitive::$t,
-                                    p: Some(p.as_union_value()),
-                                },
-                            )
-                        }
-                    }};
-                }
-                let state_t: WIPOffset<fb::State> = match state.0 {
-                    DynTpPropertyRef::Primitive(p) => match p {
-                        DynTpPrimitiveRef::U8(&p) => helper!(U8, p),
-                        DynTpPrimitiveRef::U16(&p) => helper!(U16, p),
-                        DynTpPrimitiveRef::U32(&p) => helper!(U32, p),
-                        DynTpPrimitiveRef::U64(&p) => helper!(U64, p),
-                        DynTpPrimitiveRef::I8(&p) => helper!(I8, p),
-                        DynTpPrimitiveRef::I16(&p) => helper!(I16, p),
-                        DynTpPrimitiveRef::I32(&p) => helper!(I32, p),
-                        DynTpPrimitiveRef::I64(&p) => helper!(I64, p),
-                        DynTpPrimitiveRef::Bool(&p) => helper!(Bool, p),
-                        DynTpPrimitiveRef::F32(&p) => helper!(F32, p),
-                        DynTpPrimitiveRef::F64(&p) => helper!(F64, p),
-                        DynTpPrimitiveRef::String(s) => {
-                            let s = fbb.create_string(s.as_str());
-                            let p =
-                                fb::primitive::FbString::create(fbb, &FbStringArgs { v: Some(s) });
-                            fb::State::create(
-                                fbb,
-                                &StateArgs {
-                                    p_type: fb::TpPrimitive::FbString,
-                                    p: Some(p.as_union_value()),
-                                },
-                            )
-                        }
-                        // Handles will be serialized to a dummy value, and populated later
-                        DynTpPrimitiveRef::ObjectHandle(_)
-                        | DynTpPrimitiveRef::ContractDataHandle(_) => {
-                            WIPOffset::<fb::State>::new(WIP_DUMMY)
-                        }
-                    },
-                    DynTpPropertyRef::Vec(_v) => todo!("Vectors not supported yet"),
+                let state_t: WIPOffset<fb::State> = match Self::serialize_state(fbb, state.0)? {
+                    Some(state_t) => state_t,
+                    // Handles will be serialized to a dummy value, and populated later
+                    None => WIPOffset::<fb::State>::new(WIP_DUMMY),
                 };
                 self.states.push(state_t);
                 let idx = self.states.len() - 1;
@@ -137,11 +155,16 @@ impl<'b> Serializer<'b> {
             }
 
             let state_handles_t = fbb.create_vector_from_iter(state_handles.into_iter());
+            let prefab_t = self.baseline.object_prefab(obj_handle).map(|p| {
+                let idx = self.prefab_idxs[p.name()].0 as u32;
+                fb::PrefabHandle::create(fbb, &PrefabHandleArgs { idx })
+            });
             let obj_t = fb::Object::create(
                 fbb,
                 &ObjectArgs {
                     contract: Some(contract_data_handle_t),
                     states: Some(state_handles_t),
+                    prefab: prefab_t,
                 },
             );
             self.objects.push(obj_t);
@@ -151,12 +174,153 @@ impl<'b> Serializer<'b> {
         Ok(())
     }
 
-    fn serialize_contract<C: rs::Contract>(
+    /// The handles of the objects serialized so far, in the order that they
+    /// appear in the flatbuffer.
+    pub fn serialized_objects(&self) -> Vec<rs::ObjectHandle> {
+        (0..self.objects.len())
+            .map(|idx| self.handle_map[ObjectsIdx(idx)])
+            .collect()
+    }
+
+    /// Serializes the value of a state.
+    ///
+    /// Returns `None` for handles, since those can only be serialized once all the
+    /// objects and contracts have been serialized.
+    fn serialize_state(
         fbb: &mut FlatBufferBuilder<'static>,
+        state: DynTpPropertyRef,
+    ) -> Result<Option<WIPOffset<fb::State<'static>>>> {
+        let state_t = match state {
+            DynTpPropertyRef::Primitive(p) => match Self::serialize_primitive(fbb, p) {
+                Some((p_type, p)) => fb::State::create(
+                    fbb,
+                    &StateArgs {
+                        p_type,
+                        p: Some(p),
+                        ..Default::default()
+                    },
+                ),
+                None => return Ok(None),
+            },
+            DynTpPropertyRef::Vec(_v) => todo!("Vectors not supported yet"),
+            DynTpPropertyRef::Option(o) => {
+                let (p_type, p) = match o.get() {
+                    Some(p) => {
+                        let (p_type, p) = Self::serialize_primitive(fbb, p)
+                            .ok_or_else(|| eyre!("Options of handles are not yet supported"))?;
+                        (p_type, Some(p))
+                    }
+                    None => (fb::TpPrimitive::NONE, None),
+                };
+                fb::State::create(
+                    fbb,
+                    &StateArgs {
+                        p_type,
+                        p,
+                        container: fb::TpContainerKind::Option,
+                        ..Default::default()
+                    },
+                )
+            }
+            DynTpPropertyRef::Map(m) => {
+                let entries: Vec<_> = m
+                    .iter()
+                    .map(|(key, v)| {
+                        let (v_type, v) = Self::serialize_primitive(fbb, v)
+                            .ok_or_else(|| eyre!("Maps of handles are not yet supported"))?;
+                        let key = fbb.create_string(key);
+                        Ok(fb::MapEntry::create(
+                            fbb,
+                            &MapEntryArgs {
+                                key: Some(key),
+                                v_type,
+                                v: Some(v),
+                            },
+                        ))
+                    })
+                    .collect::<Result<_>>()?;
+                let entries = fbb.create_vector(&entries);
+                fb::State::create(
+                    fbb,
+                    &StateArgs {
+                        container: fb::TpContainerKind::Map,
+                        entries: Some(entries),
+                        ..Default::default()
+                    },
+                )
+            }
+        };
+        Ok(Some(state_t))
+    }
+
+    /// Serializes a primitive, returning the type and offset of the union value.
+    ///
+    /// Returns `None` for handles, since those can only be serialized once all the
+    /// objects and contracts have been serialized.
+    fn serialize_primitive(
+        fbb: &mut FlatBufferBuilder<'static>,
+        p: DynTpPrimitiveRef,
+    ) -> Option<(fb::TpPrimitive, WIPOffset<UnionWIPOffset>)> {
+        macro_rules! helper {
+            ($t:ident, $v:expr) => {{
+                paste! {
+                    let p = fb::primitive::$t::create(fbb, &$crate::primitive::[<$t Args>] { v: $v });
+                    (fb::TpPrimitive::$t, p.as_union_value())
+                }
+            }};
+        }
+        let result = match p {
+            DynTpPrimitiveRef::U8(&p) => helper!(U8, p),
+            DynTpPrimitiveRef::U16(&p) => helper!(U16, p),
+            DynTpPrimitiveRef::U32(&p) => helper!(U32, p),
+            DynTpPrimitiveRef::U64(&p) => helper!(U64, p),
+            DynTpPrimitiveRef::I8(&p) => helper!(I8, p),
+            DynTpPrimitiveRef::I16(&p) => helper!(I16, p),
+            DynTpPrimitiveRef::I32(&p) => helper!(I32, p),
+            DynTpPrimitiveRef::I64(&p) => helper!(I64, p),
+            DynTpPrimitiveRef::Bool(&p) => helper!(Bool, p),
+            DynTpPrimitiveRef::F32(&p) => helper!(F32, p),
+            DynTpPrimitiveRef::F64(&p) => helper!(F64, p),
+            DynTpPrimitiveRef::String(s) => {
+                let s = fbb.create_string(s.as_str());
+                let p = fb::primitive::FbString::create(fbb, &FbStringArgs { v: Some(s) });
+                (fb::TpPrimitive::FbString, p.as_union_value())
+            }
+            DynTpPrimitiveRef::Vec2(p) => {
+                helper!(Vec2, Some(&fb::primitive::Vec2Value::from(p)))
+            }
+            DynTpPrimitiveRef::Vec3(p) => {
+                helper!(Vec3, Some(&fb::primitive::Vec3Value::from(p)))
+            }
+            DynTpPrimitiveRef::Quat(p) => {
+                helper!(Quat, Some(&fb::primitive::QuatValue::from(p)))
+            }
+            DynTpPrimitiveRef::Color(p) => {
+                helper!(Color, Some(&fb::primitive::ColorValue::from(p)))
+            }
+            DynTpPrimitiveRef::Transform(p) => {
+                helper!(Transform, Some(&fb::primitive::TransformValue::from(p)))
+            }
+            DynTpPrimitiveRef::Bytes(b) => {
+                // The buffer has to hold the data, so this is the one copy that is
+                // made. `DeserializerBuilder::new_shared` reads it back without copying
+                let v = fbb.create_vector(b.as_slice());
+                helper!(Bytes, Some(v))
+            }
+            DynTpPrimitiveRef::ObjectHandle(_) | DynTpPrimitiveRef::ContractDataHandle(_) => {
+                return None
+            }
+        };
+        Some(result)
+    }
+
+    fn serialize_contract(
+        fbb: &mut FlatBufferBuilder<'static>,
+        layout: &rs::ContractLayout,
     ) -> Result<WIPOffset<fb::Contract<'static>>> {
         let cid_t = {
-            let (v_major, v_minor, v_patch) = C::ID.version;
-            let name_t = fbb.create_string(C::ID.name);
+            let (v_major, v_minor, v_patch) = layout.id.version;
+            let name_t = fbb.create_string(layout.id.name);
             fb::ContractId::create(
                 fbb,
                 &ContractIdArgs {
@@ -169,23 +333,28 @@ impl<'b> Serializer<'b> {
         };
         let cstates_t = {
             let names_t = {
-                let names_t: Vec<_> = C::States::field_names()
+                let names_t: Vec<_> = layout
+                    .state_names
                     .into_iter()
                     .map(|n| fbb.create_string(n))
                     .collect();
                 fbb.create_vector(&names_t)
             };
+            let containers_t = {
+                let containers_t: Result<Vec<_>> = layout
+                    .state_types
+                    .into_iter()
+                    .map(|t| fb::TpContainerKind::try_from(*t))
+                    .collect();
+                let containers_t = containers_t?;
+                fbb.create_vector(&containers_t)
+            };
             let types_t = {
-                let types_t: Result<Vec<_>> = C::States::enumerate_types()
+                let types_t: Vec<_> = layout
+                    .state_types
                     .into_iter()
-                    .map(|t| match t {
-                        rs::TpPropertyType::Primitive(p) => Ok(fb::TpPrimitiveKind::from(*p)),
-                        rs::TpPropertyType::Vec(_v) => {
-                            return Err(eyre!("Vectors are not yet supported"))
-                        }
-                    })
+                    .map(|t| fb::TpPrimitiveKind::from(t.primitive_type()))
                     .collect();
-                let types_t = types_t?;
                 fbb.create_vector(&types_t)
             };
             fb::ContractStates::create(
@@ -193,6 +362,7 @@ impl<'b> Serializer<'b> {
                 &ContractStatesArgs {
                     names: Some(names_t),
                     types: Some(types_t),
+                    containers: Some(containers_t),
                 },
             )
         };
@@ -233,6 +403,7 @@ impl<'b> Serializer<'b> {
                             &StateArgs {
                                 p_type: $handle_variant,
                                 p: Some(p.as_union_value()),
+                                ..Default::default()
                             },
                         )
                     }};
@@ -252,18 +423,56 @@ impl<'b> Serializer<'b> {
             fb::TpPrimitive::tp_serialize_object_ObjectHandle,
         );
 
+        // The hierarchy can span contracts, so it is only written once all the objects
+        // have been serialized.
+        let mut children = Vec::new();
+        for idx in 0..self.objects.len() {
+            let obj = self.handle_map[ObjectsIdx(idx)];
+            // Filtering may have skipped some of the children
+            let obj_children: Vec<u32> = self
+                .baseline
+                .object(obj)
+                .expect("Unexpectly had a missing handle")
+                .children()
+                .iter()
+                .filter_map(|child| self.handle_map.objects.get_by_left(child))
+                .map(|child_idx| child_idx.0 as u32)
+                .collect();
+            if obj_children.is_empty() {
+                continue;
+            }
+            let fbb = &mut self.fbb;
+            let parent_t = fb::ObjectHandle::create(fbb, &ObjectHandleArgs { idx: idx as u32 });
+            let children_t: Vec<_> = obj_children
+                .into_iter()
+                .map(|idx| fb::ObjectHandle::create(fbb, &ObjectHandleArgs { idx }))
+                .collect();
+            let children_t = fbb.create_vector(&children_t);
+            children.push(fb::Children::create(
+                fbb,
+                &ChildrenArgs {
+                    parent: Some(parent_t),
+                    children: Some(children_t),
+                },
+            ));
+        }
+
         // Now we need to actually serialize all of these vectors into the final buffer
         let fbb = &mut self.fbb;
         let baseline_t = {
             let contracts_t = fbb.create_vector_from_iter(self.contracts.into_iter());
             let states_t = fbb.create_vector_from_iter(self.states.into_iter());
             let objects_t = fbb.create_vector_from_iter(self.objects.into_iter());
+            let prefabs_t = fbb.create_vector_from_iter(self.prefabs.into_iter());
+            let children_t = fbb.create_vector_from_iter(children.into_iter());
             fb::Baseline::create(
                 fbb,
                 &BaselineArgs {
                     contracts: Some(contracts_t),
                     states: Some(states_t),
                     objects: Some(objects_t),
+                    prefabs: Some(prefabs_t),
+                    children: Some(children_t),
                 },
             )
         };
@@ -19,7 +19,13 @@ impl PartialEq<rs::TpPropertyType> for crate::primitive::TpPrimitiveKind {
             | (T::F64, Primitive(C::F64))
             | (T::String, Primitive(C::String))
             | (T::ObjectHandle, Primitive(C::ObjectHandle))
-            | (T::ContractDataHandle, Primitive(C::ContractDataHandle)) => true,
+            | (T::ContractDataHandle, Primitive(C::ContractDataHandle))
+            | (T::Vec2, Primitive(C::Vec2))
+            | (T::Vec3, Primitive(C::Vec3))
+            | (T::Quat, Primitive(C::Quat))
+            | (T::Color, Primitive(C::Color))
+            | (T::Transform, Primitive(C::Transform))
+            | (T::Bytes, Primitive(C::Bytes)) => true,
             _ => false,
         }
     }
@@ -48,9 +54,13 @@ impl PartialEq<rs::TpPropertyType> for crate::primitive::TpPrimitive {
             | (Self::F64, Primitive(C::F64))
             | (Self::FbString, Primitive(C::String))
             | (Self::tp_serialize_object_ObjectHandle, Primitive(C::ObjectHandle))
-            | (Self::tp_serialize_contract_ContractDataHandle, Primitive(C::ContractDataHandle)) => {
-                true
-            }
+            | (Self::tp_serialize_contract_ContractDataHandle, Primitive(C::ContractDataHandle))
+            | (Self::Vec2, Primitive(C::Vec2))
+            | (Self::Vec3, Primitive(C::Vec3))
+            | (Self::Quat, Primitive(C::Quat))
+            | (Self::Color, Primitive(C::Color))
+            | (Self::Transform, Primitive(C::Transform))
+            | (Self::Bytes, Primitive(C::Bytes)) => true,
             _ => false,
         }
     }
@@ -77,7 +87,13 @@ impl PartialEq<crate::primitive::TpPrimitiveKind> for crate::primitive::TpPrimit
             | (Self::F64, O::F64)
             | (Self::FbString, O::String)
             | (Self::tp_serialize_object_ObjectHandle, O::ObjectHandle)
-            | (Self::tp_serialize_contract_ContractDataHandle, O::ContractDataHandle) => true,
+            | (Self::tp_serialize_contract_ContractDataHandle, O::ContractDataHandle)
+            | (Self::Vec2, O::Vec2)
+            | (Self::Vec3, O::Vec3)
+            | (Self::Quat, O::Quat)
+            | (Self::Color, O::Color)
+            | (Self::Transform, O::Transform)
+            | (Self::Bytes, O::Bytes) => true,
             _ => false,
         }
     }
@@ -1,5 +1,7 @@
 use crate::{fb, rs};
 
+use eyre::{eyre, Report};
+
 impl From<rs::TpPrimitiveType> for fb::TpPrimitiveKind {
     fn from(other: rs::TpPrimitiveType) -> Self {
         use fb::TpPrimitiveKind as T;
@@ -19,6 +21,123 @@ impl From<rs::TpPrimitiveType> for fb::TpPrimitiveKind {
             C::String => T::String,
             C::ObjectHandle => T::ObjectHandle,
             C::ContractDataHandle => T::ContractDataHandle,
+            C::Vec2 => T::Vec2,
+            C::Vec3 => T::Vec3,
+            C::Quat => T::Quat,
+            C::Color => T::Color,
+            C::Transform => T::Transform,
+            C::Bytes => T::Bytes,
+        }
+    }
+}
+
+impl TryFrom<fb::TpPrimitiveKind> for rs::TpPrimitiveType {
+    type Error = Report;
+
+    fn try_from(other: fb::TpPrimitiveKind) -> Result<Self, Self::Error> {
+        use fb::TpPrimitiveKind as T;
+        use rs::TpPrimitiveType as C;
+        Ok(match other {
+            T::U8 => C::U8,
+            T::U16 => C::U16,
+            T::U32 => C::U32,
+            T::U64 => C::U64,
+            T::I8 => C::I8,
+            T::I16 => C::I16,
+            T::I32 => C::I32,
+            T::I64 => C::I64,
+            T::Bool => C::Bool,
+            T::F32 => C::F32,
+            T::F64 => C::F64,
+            T::String => C::String,
+            T::ObjectHandle => C::ObjectHandle,
+            T::ContractDataHandle => C::ContractDataHandle,
+            T::Vec2 => C::Vec2,
+            T::Vec3 => C::Vec3,
+            T::Quat => C::Quat,
+            T::Color => C::Color,
+            T::Transform => C::Transform,
+            T::Bytes => C::Bytes,
+            _ => return Err(eyre!("Unknown primitive kind {:?}", other)),
+        })
+    }
+}
+
+impl TryFrom<rs::TpPropertyType> for fb::TpContainerKind {
+    type Error = Report;
+
+    fn try_from(other: rs::TpPropertyType) -> Result<Self, Self::Error> {
+        use rs::TpPropertyType as C;
+        match other {
+            C::Primitive(_) => Ok(Self::Primitive),
+            C::Option(_) => Ok(Self::Option),
+            C::Map(_) => Ok(Self::Map),
+            C::Vec(_) => Err(eyre!("Vectors are not yet supported")),
         }
     }
 }
+
+// ---- Composite types ----
+
+impl From<&rs::Vec2> for fb::primitive::Vec2Value {
+    fn from(other: &rs::Vec2) -> Self {
+        Self::new(other.x, other.y)
+    }
+}
+impl From<&fb::primitive::Vec2Value> for rs::Vec2 {
+    fn from(other: &fb::primitive::Vec2Value) -> Self {
+        Self::new(other.x(), other.y())
+    }
+}
+
+impl From<&rs::Vec3> for fb::primitive::Vec3Value {
+    fn from(other: &rs::Vec3) -> Self {
+        Self::new(other.x, other.y, other.z)
+    }
+}
+impl From<&fb::primitive::Vec3Value> for rs::Vec3 {
+    fn from(other: &fb::primitive::Vec3Value) -> Self {
+        Self::new(other.x(), other.y(), other.z())
+    }
+}
+
+impl From<&rs::Quat> for fb::primitive::QuatValue {
+    fn from(other: &rs::Quat) -> Self {
+        Self::new(other.x, other.y, other.z, other.w)
+    }
+}
+impl From<&fb::primitive::QuatValue> for rs::Quat {
+    fn from(other: &fb::primitive::QuatValue) -> Self {
+        Self::new(other.x(), other.y(), other.z(), other.w())
+    }
+}
+
+impl From<&rs::Color> for fb::primitive::ColorValue {
+    fn from(other: &rs::Color) -> Self {
+        Self::new(other.r, other.g, other.b, other.a)
+    }
+}
+impl From<&fb::primitive::ColorValue> for rs::Color {
+    fn from(other: &fb::primitive::ColorValue) -> Self {
+        Self::new(other.r(), other.g(), other.b(), other.a())
+    }
+}
+
+impl From<&rs::Transform> for fb::primitive::TransformValue {
+    fn from(other: &rs::Transform) -> Self {
+        Self::new(
+            &(&other.translation).into(),
+            &(&other.rotation).into(),
+            &(&other.scale).into(),
+        )
+    }
+}
+impl From<&fb::primitive::TransformValue> for rs::Transform {
+    fn from(other: &fb::primitive::TransformValue) -> Self {
+        Self::new(
+            other.translation().into(),
+            other.rotation().into(),
+            other.scale().into(),
+        )
+    }
+}
@@ -12,3 +12,7 @@ pub struct ContractsIdx(pub usize);
 /// Index into `states` vec
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
 pub struct StatesIdx(pub usize);
+
+/// Index into `prefabs` vec
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub struct PrefabsIdx(pub usize);
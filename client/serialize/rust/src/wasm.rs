@@ -0,0 +1,79 @@
+//! Loading and saving baselines from JavaScript, via `wasm-bindgen`.
+//!
+//! These extend the bindings in `tp_client::wasm`, and live here because this
+//! crate depends on `tp_client`. Build with:
+//! ```sh
+//! wasm-pack build client/serialize/rust --target nodejs -- --features wasm
+//! ```
+//! and test with:
+//! ```sh
+//! wasm-pack test --node client/serialize/rust --features wasm
+//! ```
+
+use crate::{DeserializerBuilder, Serializer};
+
+use eyre::{Result, WrapErr};
+use flatbuffers::FlatBufferBuilder;
+use tp_client::baseline::BaselineKind;
+use tp_client::wasm::{Contract, Engine};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Contract[]")]
+    pub type ContractArray;
+}
+
+fn js_error(err: eyre::Report) -> JsError {
+    JsError::new(&format!("{err:#}"))
+}
+
+/// Serializes all contracts and objects in the baseline to a flatbuffer.
+#[wasm_bindgen(js_name = saveBaseline)]
+pub fn save_baseline(engine: &Engine, kind: BaselineKind) -> Result<Vec<u8>, JsError> {
+    let baseline = engine.inner().realm().baseline(kind);
+    let mut serializer = Serializer::new(FlatBufferBuilder::new(), baseline);
+    for contract in baseline.iter_contracts() {
+        serializer
+            .serialize(&contract)
+            .wrap_err_with(|| format!("Failed to serialize {:?}", contract.id()))
+            .map_err(js_error)?;
+    }
+    Ok(serializer.finish().finished_data().to_vec())
+}
+
+/// Replaces the baseline with the one serialized in `data`, by
+/// `saveBaseline()`. Returns the contracts in the new baseline. Handles into
+/// the old baseline are no longer valid.
+///
+/// Contracts are registered from the fields that were serialized, so they have
+/// no channels, and none of the attributes of their fields.
+#[wasm_bindgen(js_name = loadBaseline)]
+pub fn load_baseline(
+    engine: &mut Engine,
+    kind: BaselineKind,
+    data: &[u8],
+) -> Result<ContractArray, JsError> {
+    let load = || -> Result<_> {
+        let mut builder = DeserializerBuilder::new(data, kind)?;
+        let contracts = builder
+            .serialized_schemas()?
+            .iter()
+            .map(|schema| builder.register_dyn_contract(schema))
+            .collect::<Result<Vec<_>>>()?;
+        let mut deserializer = builder.finish();
+        for contract in &contracts {
+            deserializer.deserialize_objects(contract)?;
+        }
+        Ok((deserializer.finish()?, contracts))
+    };
+    let (baseline, contracts) = load().map_err(js_error)?;
+    *engine.inner_mut().realm_mut().baseline_mut(kind) = baseline;
+
+    let contracts: js_sys::Array = contracts
+        .into_iter()
+        .map(|c| JsValue::from(Contract::from(c)))
+        .collect();
+    Ok(contracts.unchecked_into())
+}
@@ -4,8 +4,15 @@ use tp_serialize::{DeserializerBuilder, Serializer};
 use eyre::WrapErr;
 use flatbuffers::FlatBufferBuilder;
 use tp_client::baseline::{Baseline, BaselineKind};
-use tp_client::contract::properties::dynamic::DynTpProperty;
-use tp_client::contract::{Contract, ContractData, ContractDataHandle, ContractId};
+use tp_client::contract::properties::bytes::Bytes;
+use tp_client::contract::properties::composite::{Color, Quat, Transform, Vec2, Vec3};
+use tp_client::contract::properties::dynamic::{DynTpProperty, TpPrimitiveType, TpPropertyType};
+use tp_client::contract::properties::states::StateId;
+use tp_client::contract::properties::traits::TpMap;
+use tp_client::contract::{
+    states, Contract, ContractData, ContractDataHandle, ContractId, ContractSchema,
+};
+use tp_client::prefab::{Overrides, Prefab};
 use tp_contract_example::ExampleContract;
 
 struct EmptyContract {
@@ -38,6 +45,131 @@ impl Contract for EmptyContract {
     }
 }
 
+#[states]
+struct CompositeStates {
+    v2: Vec2,
+    v3: Vec3,
+    q: Quat,
+    c: Color,
+    t: Transform,
+}
+
+struct CompositeContract {
+    handle: ContractDataHandle,
+    states: CompositeStates,
+}
+impl Contract for CompositeContract {
+    type States = CompositeStates;
+
+    type Channels = ();
+
+    const ID: ContractId = ContractId {
+        name: "composite",
+        version: (0, 0, 0),
+    };
+
+    fn new(handle: ContractDataHandle) -> Self {
+        Self {
+            handle,
+            states: CompositeStates::new(handle),
+        }
+    }
+
+    fn states(&self) -> &Self::States {
+        &self.states
+    }
+
+    fn channels(&self) -> &Self::Channels {
+        &()
+    }
+
+    fn handle(&self) -> ContractDataHandle {
+        self.handle
+    }
+}
+
+#[states]
+struct ContainerStates {
+    some: Option<u32>,
+    none: Option<String>,
+    map: TpMap<f32>,
+}
+
+struct ContainerContract {
+    handle: ContractDataHandle,
+    states: ContainerStates,
+}
+impl Contract for ContainerContract {
+    type States = ContainerStates;
+
+    type Channels = ();
+
+    const ID: ContractId = ContractId {
+        name: "container",
+        version: (0, 0, 0),
+    };
+
+    fn new(handle: ContractDataHandle) -> Self {
+        Self {
+            handle,
+            states: ContainerStates::new(handle),
+        }
+    }
+
+    fn states(&self) -> &Self::States {
+        &self.states
+    }
+
+    fn channels(&self) -> &Self::Channels {
+        &()
+    }
+
+    fn handle(&self) -> ContractDataHandle {
+        self.handle
+    }
+}
+
+#[states]
+struct BytesStates {
+    empty: Bytes,
+    blob: Bytes,
+    chunks: TpMap<Bytes>,
+}
+
+struct BytesContract {
+    handle: ContractDataHandle,
+    states: BytesStates,
+}
+impl Contract for BytesContract {
+    type States = BytesStates;
+
+    type Channels = ();
+
+    const ID: ContractId = ContractId {
+        name: "bytes",
+        version: (0, 0, 0),
+    };
+
+    fn new(handle: ContractDataHandle) -> Self {
+        Self {
+            handle,
+            states: BytesStates::new(handle),
+        }
+    }
+
+    fn states(&self) -> &Self::States {
+        &self.states
+    }
+
+    fn channels(&self) -> &Self::Channels {
+        &()
+    }
+
+    fn handle(&self) -> ContractDataHandle {
+        self.handle
+    }
+}
+
 #[derive(PartialEq, Debug, Clone)]
 struct Fields {
     u8_0: u8,
@@ -275,3 +407,428 @@ fn check_matches_fields(
 
     Ok(())
 }
+
+#[test]
+fn test_round_trip_composites() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let v2 = Vec2::new(1.0, -2.0);
+    let v3 = Vec3::new(3.0, 4.0, -5.0);
+    let q = Quat::new(
+        0.0,
+        0.0,
+        std::f32::consts::FRAC_1_SQRT_2,
+        std::f32::consts::FRAC_1_SQRT_2,
+    );
+    let c = Color::new(0.1, 0.2, 0.3, 0.4);
+    let t = Transform::new(v3, q, Vec3::ONE);
+
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract: CompositeContract = baseline.register_contract()?;
+    let states = [
+        DynTpProperty::from(v2),
+        DynTpProperty::from(v3),
+        DynTpProperty::from(q),
+        DynTpProperty::from(c),
+        DynTpProperty::from(t),
+    ];
+    baseline.object_create(&contract, states.into_iter(), [].into_iter())?;
+
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer
+            .serialize(&contract)
+            .wrap_err("Failed to serialize CompositeContract")?;
+        serializer.finish().finished_data().to_vec()
+    };
+
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)
+        .wrap_err("Failed to create DeserializerBuilder")?;
+    let de_contract: CompositeContract = builder
+        .register_contract()
+        .wrap_err("Failed to register CompositeContract")?;
+    let mut deserializer = builder.finish();
+    deserializer
+        .deserialize_objects(&de_contract)
+        .wrap_err("Failed to deserialize objects in CompositeContract")?;
+    let b = deserializer
+        .finish()
+        .wrap_err("Failed to finish deserialization")?;
+
+    let cd = b.contract_data(de_contract.handle())?;
+    assert_eq!(cd.objects().len(), 1);
+    let obj = *cd.objects().iter().next().unwrap();
+    let s = de_contract.states();
+    assert_eq!(b.state(b.bind_state(s.v2(), obj)?)?.value, v2);
+    assert_eq!(b.state(b.bind_state(s.v3(), obj)?)?.value, v3);
+    assert_eq!(b.state(b.bind_state(s.q(), obj)?)?.value, q);
+    assert_eq!(b.state(b.bind_state(s.c(), obj)?)?.value, c);
+    assert_eq!(b.state(b.bind_state(s.t(), obj)?)?.value, t);
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_containers() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let some = Some(1337u32);
+    let none: Option<String> = None;
+    let map: TpMap<f32> = [("a".to_string(), 1.0), ("b".to_string(), -2.5)]
+        .into_iter()
+        .collect();
+
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract: ContainerContract = baseline.register_contract()?;
+    let states = [
+        DynTpProperty::from(some),
+        DynTpProperty::from(none.clone()),
+        DynTpProperty::from(map.clone()),
+    ];
+    baseline.object_create(&contract, states.into_iter(), [].into_iter())?;
+
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer
+            .serialize(&contract)
+            .wrap_err("Failed to serialize ContainerContract")?;
+        serializer.finish().finished_data().to_vec()
+    };
+
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)
+        .wrap_err("Failed to create DeserializerBuilder")?;
+    let de_contract: ContainerContract = builder
+        .register_contract()
+        .wrap_err("Failed to register ContainerContract")?;
+    let mut deserializer = builder.finish();
+    deserializer
+        .deserialize_objects(&de_contract)
+        .wrap_err("Failed to deserialize objects in ContainerContract")?;
+    let b = deserializer
+        .finish()
+        .wrap_err("Failed to finish deserialization")?;
+
+    let cd = b.contract_data(de_contract.handle())?;
+    assert_eq!(cd.objects().len(), 1);
+    let obj = *cd.objects().iter().next().unwrap();
+    let s = de_contract.states();
+    assert_eq!(b.state(b.bind_state(s.some(), obj)?)?.value, some);
+    assert_eq!(b.state(b.bind_state(s.none(), obj)?)?.value, none);
+    assert_eq!(b.state(b.bind_state(s.map(), obj)?)?.value, map);
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_bytes() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let empty = Bytes::new();
+    let blob = Bytes::from((0..=255).collect::<Vec<u8>>());
+    let chunks: TpMap<Bytes> = [
+        ("a".to_string(), Bytes::from(vec![1, 2, 3])),
+        ("b".to_string(), Bytes::new()),
+    ]
+    .into_iter()
+    .collect();
+
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract: BytesContract = baseline.register_contract()?;
+    let states = [
+        DynTpProperty::from(empty.clone()),
+        DynTpProperty::from(blob.clone()),
+        DynTpProperty::from(chunks.clone()),
+    ];
+    baseline.object_create(&contract, states.into_iter(), [].into_iter())?;
+
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer
+            .serialize(&contract)
+            .wrap_err("Failed to serialize BytesContract")?;
+        Bytes::from(serializer.finish().finished_data())
+    };
+
+    let mut builder = DeserializerBuilder::new_shared(&bytes, BaselineKind::Main)
+        .wrap_err("Failed to create DeserializerBuilder")?;
+    let de_contract: BytesContract = builder
+        .register_contract()
+        .wrap_err("Failed to register BytesContract")?;
+    let mut deserializer = builder.finish();
+    deserializer
+        .deserialize_objects(&de_contract)
+        .wrap_err("Failed to deserialize objects in BytesContract")?;
+    let b = deserializer
+        .finish()
+        .wrap_err("Failed to finish deserialization")?;
+
+    let cd = b.contract_data(de_contract.handle())?;
+    assert_eq!(cd.objects().len(), 1);
+    let obj = *cd.objects().iter().next().unwrap();
+    let s = de_contract.states();
+    assert_eq!(b.state(b.bind_state(s.empty(), obj)?)?.value, empty);
+    assert_eq!(b.state(b.bind_state(s.blob(), obj)?)?.value, blob);
+    assert_eq!(b.state(b.bind_state(s.chunks(), obj)?)?.value, chunks);
+
+    // The blob was not copied out of the buffer
+    let de_blob = &b.state(b.bind_state(s.blob(), obj)?)?.value;
+    assert!(bytes.as_ptr_range().contains(&de_blob.as_ptr()));
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_dyn() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let schema = ContractSchema::new("teleportal.test.dyn-round-trip", (0, 1, 0))
+        .with_state("name", TpPropertyType::Primitive(TpPrimitiveType::String))
+        .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8))
+        .with_state("target", TpPropertyType::Option(TpPrimitiveType::Vec3));
+    let target = Some(Vec3::new(1.0, 2.0, 3.0));
+
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract = baseline.register_dyn_contract(&schema)?;
+    let states = [
+        DynTpProperty::from(String::from("bob")),
+        DynTpProperty::from(7u8),
+        DynTpProperty::from(target),
+    ];
+    let src_obj = baseline.object_create(&contract, states.into_iter(), [].into_iter())?;
+
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer
+            .serialize(&contract)
+            .wrap_err("Failed to serialize DynContract")?;
+        assert_eq!(serializer.serialized_objects(), [src_obj]);
+        serializer.finish().finished_data().to_vec()
+    };
+
+    // Deserialize without knowing the schema ahead of time
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)
+        .wrap_err("Failed to create DeserializerBuilder")?;
+    let schemas = builder.serialized_schemas()?;
+    assert_eq!(schemas, [schema.clone()]);
+    let de_contract = builder
+        .register_dyn_contract(&schemas[0])
+        .wrap_err("Failed to register DynContract")?;
+    let mut deserializer = builder.finish();
+    deserializer
+        .deserialize_objects(&de_contract)
+        .wrap_err("Failed to deserialize objects in DynContract")?;
+    let de_obj = deserializer.deserialized_object(0);
+    assert!(deserializer.deserialized_object(1).is_none());
+    let b = deserializer
+        .finish()
+        .wrap_err("Failed to finish deserialization")?;
+
+    let cd = b.contract_data(de_contract.handle())?;
+    assert_eq!(cd.id(), contract.id());
+    assert_eq!(cd.objects().len(), 1);
+    let obj = *cd.objects().iter().next().unwrap();
+    assert_eq!(de_obj, Some(obj));
+    let h = de_contract.handle();
+    let name: StateId<String> = StateId::new(0, h);
+    let hp: StateId<u8> = StateId::new(1, h);
+    let de_target: StateId<Option<Vec3>> = StateId::new(2, h);
+    assert_eq!(b.state(b.bind_state(name, obj)?)?.value, "bob");
+    assert_eq!(b.state(b.bind_state(hp, obj)?)?.value, 7);
+    assert_eq!(b.state(b.bind_state(de_target, obj)?)?.value, target);
+
+    // A schema that doesn't match the flatbuffer is rejected
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)?;
+    let wrong = ContractSchema::new("teleportal.test.dyn-round-trip", (0, 1, 0))
+        .with_state("name", TpPropertyType::Primitive(TpPrimitiveType::String));
+    assert!(builder.register_dyn_contract(&wrong).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_filtered() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let schema = ContractSchema::new("teleportal.test.filtered", (0, 1, 0))
+        .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8));
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract = baseline.register_dyn_contract(&schema)?;
+    let objs = (0..3u8)
+        .map(|hp| {
+            let states = [DynTpProperty::from(hp)];
+            baseline.object_create(&contract, states.into_iter(), [].into_iter())
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer.serialize_filtered(&contract, |obj| obj != objs[1])?;
+        assert_eq!(serializer.serialized_objects().len(), 2);
+        assert!(!serializer.serialized_objects().contains(&objs[1]));
+        serializer.finish().finished_data().to_vec()
+    };
+
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)?;
+    let de_contract = builder.register_dyn_contract(&schema)?;
+    let mut deserializer = builder.finish();
+    deserializer.deserialize_objects(&de_contract)?;
+    let b = deserializer.finish()?;
+
+    let hp: StateId<u8> = StateId::new(0, de_contract.handle());
+    let mut hps: Vec<u8> = b
+        .contract_data(de_contract.handle())?
+        .objects()
+        .iter()
+        .map(|obj| Ok(b.state(b.bind_state(hp, *obj)?)?.value))
+        .collect::<eyre::Result<_>>()?;
+    hps.sort_unstable();
+    assert_eq!(hps, [0, 2]);
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_prefabs() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let schema = ContractSchema::new("teleportal.test.prefabs", (0, 1, 0))
+        .with_state("name", TpPropertyType::Primitive(TpPrimitiveType::String))
+        .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8));
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract = baseline.register_dyn_contract(&schema)?;
+    let values = Overrides::new()
+        .with_state("name", String::from("goblin"))
+        .with_state("hp", 10u8);
+    baseline.prefab_insert(Prefab::new("goblin", &contract, values)?)?;
+    baseline.object_instantiate("goblin", Overrides::new())?;
+    baseline.object_instantiate("goblin", Overrides::new().with_state("hp", 50u8))?;
+    let states = [DynTpProperty::from(String::from("bob")), 10u8.into()];
+    baseline.object_create(&contract, states.into_iter(), [].into_iter())?;
+
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer.serialize(&contract)?;
+        serializer.finish().finished_data().to_vec()
+    };
+
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)?;
+    let de_contract = builder.register_dyn_contract(&schema)?;
+    let mut deserializer = builder.finish();
+    deserializer.deserialize_objects(&de_contract)?;
+    let mut b = deserializer.finish()?;
+
+    let prefab = b.prefab("goblin")?;
+    assert_eq!(prefab.contract().handle(), de_contract.handle());
+    assert_eq!(prefab.state("hp"), Some(&DynTpProperty::from(10u8)));
+    assert_eq!(prefab.instances().len(), 2);
+    assert_eq!(b.contract_data(de_contract.handle())?.objects().len(), 3);
+
+    // Edits still propagate to the instances that didn't override them
+    let hp: StateId<u8> = StateId::new(1, de_contract.handle());
+    let updated = b.prefab_update("goblin", Overrides::new().with_state("hp", 20u8))?;
+    assert_eq!(updated.len(), 1);
+    assert_eq!(b.state(b.bind_state(hp, updated[0])?)?.value, 20);
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_hierarchy() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let schema = ContractSchema::new("teleportal.test.hierarchy", (0, 1, 0))
+        .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8));
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract = baseline.register_dyn_contract(&schema)?;
+    let empty: EmptyContract = baseline.register_contract()?;
+    let objs = (0..3u8)
+        .map(|hp| {
+            let states = [DynTpProperty::from(hp)];
+            baseline.object_create(&contract, states.into_iter(), [].into_iter())
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    let leaf = baseline.object_create(&empty, [].into_iter(), [].into_iter())?;
+    baseline.object_reparent(objs[2], Some(objs[0]), None)?;
+    baseline.object_reparent(objs[1], Some(objs[0]), None)?;
+    // Across contracts
+    baseline.object_reparent(leaf, Some(objs[1]), None)?;
+
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer.serialize(&contract)?;
+        serializer.serialize(&empty)?;
+        serializer.finish().finished_data().to_vec()
+    };
+
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)?;
+    let de_contract = builder.register_dyn_contract(&schema)?;
+    let de_empty: EmptyContract = builder.register_contract()?;
+    let mut deserializer = builder.finish();
+    deserializer.deserialize_objects(&de_contract)?;
+    deserializer.deserialize_objects(&de_empty)?;
+    let b = deserializer.finish()?;
+
+    let hp: StateId<u8> = StateId::new(0, de_contract.handle());
+    let get_hp =
+        |obj: ObjectHandle| -> eyre::Result<u8> { Ok(b.state(b.bind_state(hp, obj)?)?.value) };
+    let roots: Vec<ObjectHandle> = b.iter_roots().collect();
+    assert_eq!(roots.len(), 1);
+    assert_eq!(get_hp(roots[0])?, 0);
+    let children = b.object(roots[0])?.children();
+    let hps = children
+        .iter()
+        .map(|c| get_hp(*c))
+        .collect::<eyre::Result<Vec<_>>>()?;
+    assert_eq!(hps, [2, 1]);
+    let de_leaf = b.object(children[1])?.children();
+    assert_eq!(de_leaf.len(), 1);
+    assert_eq!(b.object(de_leaf[0])?.contract(), de_empty.handle());
+    assert_eq!(b.iter_descendants(roots[0]).count(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_round_trip_hierarchy_filtered() -> eyre::Result<()> {
+    let _ = color_eyre::install();
+
+    let schema = ContractSchema::new("teleportal.test.hierarchy", (0, 1, 0))
+        .with_state("hp", TpPropertyType::Primitive(TpPrimitiveType::U8));
+    let mut baseline = Baseline::new(BaselineKind::Main);
+    let contract = baseline.register_dyn_contract(&schema)?;
+    let objs = (0..4u8)
+        .map(|hp| {
+            let states = [DynTpProperty::from(hp)];
+            baseline.object_create(&contract, states.into_iter(), [].into_iter())
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+    baseline.object_reparent(objs[1], Some(objs[0]), None)?;
+    baseline.object_reparent(objs[2], Some(objs[0]), None)?;
+    baseline.object_reparent(objs[3], Some(objs[1]), None)?;
+
+    // Leaves out an object whose parent and child are both serialized
+    let bytes = {
+        let mut serializer = Serializer::new(FlatBufferBuilder::new(), &baseline);
+        serializer.serialize_filtered(&contract, |obj| obj != objs[1])?;
+        serializer.finish().finished_data().to_vec()
+    };
+
+    let mut builder = DeserializerBuilder::new(&bytes, BaselineKind::Main)?;
+    let de_contract = builder.register_dyn_contract(&schema)?;
+    let mut deserializer = builder.finish();
+    deserializer.deserialize_objects(&de_contract)?;
+    let b = deserializer.finish()?;
+
+    let hp: StateId<u8> = StateId::new(0, de_contract.handle());
+    let get_hp =
+        |obj: ObjectHandle| -> eyre::Result<u8> { Ok(b.state(b.bind_state(hp, obj)?)?.value) };
+    let mut roots = b
+        .iter_roots()
+        .map(get_hp)
+        .collect::<eyre::Result<Vec<_>>>()?;
+    roots.sort_unstable();
+    // The orphaned grandchild becomes a root
+    assert_eq!(roots, [0, 3]);
+    let root = b.iter_roots().find(|r| get_hp(*r).ok() == Some(0)).unwrap();
+    let children = b.object(root)?.children();
+    assert_eq!(children.len(), 1);
+    assert_eq!(get_hp(children[0])?, 2);
+    Ok(())
+}
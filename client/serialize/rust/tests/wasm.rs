@@ -0,0 +1,62 @@
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use tp_client::baseline::BaselineKind;
+use tp_client::wasm::{ContractSchema, Engine, Realm};
+use tp_serialize::wasm::{load_baseline, save_baseline};
+
+use js_sys::Array;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_save_load() {
+    let mut engine = Engine::new(Realm::new("test".into()), None);
+    let mut schema = ContractSchema::new("teleportal.test.ball".into(), 0, 0, 0);
+    schema
+        .add_state("name".into(), "String")
+        .map_err(JsValue::from)
+        .unwrap();
+    schema
+        .add_state("radius".into(), "f32")
+        .map_err(JsValue::from)
+        .unwrap();
+    let contract = engine
+        .register_contract(BaselineKind::Main, &schema)
+        .map_err(JsValue::from)
+        .unwrap();
+    let states = Array::of2(&"ball".into(), &1.5.into());
+    engine
+        .object_create(BaselineKind::Main, &contract, states.unchecked_into())
+        .map_err(JsValue::from)
+        .unwrap();
+
+    let data = save_baseline(&engine, BaselineKind::Main)
+        .map_err(JsValue::from)
+        .unwrap();
+    let contracts: Array = load_baseline(&mut engine, BaselineKind::Fork, &data)
+        .map_err(JsValue::from)
+        .unwrap()
+        .unchecked_into();
+    assert_eq!(contracts.length(), 1);
+
+    let objects: Array = engine.objects(BaselineKind::Fork).unchecked_into();
+    assert_eq!(objects.length(), 1);
+    let radius = engine
+        .bind_state(
+            BaselineKind::Fork,
+            objects.get(0).unchecked_into(),
+            1,
+            "f32",
+        )
+        .map_err(JsValue::from)
+        .unwrap();
+    let value: JsValue = engine
+        .state(BaselineKind::Fork, &radius)
+        .map_err(JsValue::from)
+        .unwrap()
+        .into();
+    assert_eq!(value.as_f64(), Some(1.5));
+
+    // Garbage is an error rather than a panic
+    assert!(load_baseline(&mut engine, BaselineKind::Fork, &[1, 2, 3]).is_err());
+}
@@ -1,34 +1,36 @@
+// This file was generated by `rsharp_codegen`. Do not edit it by hand.
 using System;
 using System.Runtime.InteropServices;
+using RSharp;
 
-namespace RSharp
+namespace {{namespace}}
 {
 
-    public class RBox_{{type_platform}} : OpaqueWrapper<{{type_cs}}>
+    public class {{rbox_ident}} : OpaqueWrapper<{{value_cs}}>
     {
-        public unsafe RBox_{{type_platform}}({{type_cs}} value) : base(new Ptr<{{type_cs}}>((IntPtr)rsharp__Box_{{type_platform}}__new(value)), OwnershipSemantics.Owned)
+        public unsafe {{rbox_ident}}({{value_cs}} value) : base(new Ptr<{{value_cs}}>((IntPtr){{symbol_prefix}}__Box_{{type_camel}}__new(value)), OwnershipSemantics.Owned)
         { }
 
-        public unsafe RBox_{{type_platform}}(Ptr<{{type_cs}}> inner, OwnershipSemantics ownershipSemantics) : base(inner, ownershipSemantics)
+        public unsafe {{rbox_ident}}(Ptr<{{value_cs}}> inner, OwnershipSemantics ownershipSemantics) : base(inner, ownershipSemantics)
         { }
 
-        protected override unsafe void NativeDrop(Ptr<{{type_cs}}> inner)
+        protected override unsafe void NativeDrop(Ptr<{{value_cs}}> inner)
         {
-            rsharp__Box_{{type_platform}}__drop(({{type_cs}}{{ptr_literal}})inner.p);
+            {{symbol_prefix}}__Box_{{type_camel}}__drop(({{value_cs}}*)inner.p);
         }
 
-        public unsafe {{type_cs}} Value {
-            get => *({{type_cs}}{{ptr_literal}})this.Inner.Value.p;
+        public unsafe {{value_cs}} Value {
+            get => *({{value_cs}}*)this.Inner.Value.p;
         }
 
 
         // -- C interop
 
         [DllImport(Metadata.LIBRARY_NAME)]
-        private static extern unsafe {{type_cs}}{{ptr_literal}} rsharp__Box_{{type_platform}}__new({{type_cs}} value);
+        private static extern unsafe {{value_cs}}* {{symbol_prefix}}__Box_{{type_camel}}__new({{value_cs}} value);
 
         [DllImport(Metadata.LIBRARY_NAME)]
-        private static extern unsafe void rsharp__Box_{{type_platform}}__drop({{type_cs}}{{ptr_literal}} value);
+        private static extern unsafe void {{symbol_prefix}}__Box_{{type_camel}}__drop({{value_cs}}* value);
     }
 
 }
@@ -0,0 +1,65 @@
+// This file was generated by `rsharp_codegen`. Do not edit it by hand.
+using System;
+using System.Runtime.InteropServices;
+using RSharp;
+
+namespace {{namespace}}
+{
+
+    public sealed class {{rcallback_ident}} : SharedWrapper<RawCallback>
+    {
+        public {{rcallback_ident}}(RawCallback inner) : base(inner, OwnershipSemantics.Owned)
+        { }
+
+        /// `action` is called on the thread that the callback is invoked on, or
+        /// on the thread of the `Dispatcher` that it is registered with.
+        public {{rcallback_ident}}(Action<{{arg_cs}}> action) : base(RawCallback.New(action, CallPtr), OwnershipSemantics.Owned)
+        { }
+
+        override protected void NativeDrop(RawCallback inner)
+        {
+            {{symbol_prefix}}__RCallback_{{type_camel}}__drop(inner);
+        }
+
+        /// Calls the callback on the current thread.{{#if is_opaque}} Takes ownership of `arg`.{{/if}}
+        public unsafe void Invoke({{arg_cs}} arg)
+        {
+{{#if is_opaque}}
+            if (arg.OwnershipSemantics != OwnershipSemantics.Owned)
+            {
+                throw new OwnershipException("`arg` must be owned");
+            }
+{{/if}}
+            var inner = this.Inner.Value;
+            {{symbol_prefix}}__RCallback_{{type_camel}}__call(&inner, {{arg_to_raw}});
+        }
+
+        private delegate void CallFn(IntPtr userData, {{arg_marshal}}{{arg_raw}} arg);
+        private static readonly CallFn callFn = Call;
+        private static readonly IntPtr CallPtr = Marshal.GetFunctionPointerForDelegate(callFn);
+
+        [MonoPInvokeCallback(typeof(CallFn))]
+        private static void Call(IntPtr userData, {{arg_marshal}}{{arg_raw}} arg)
+        {
+            try
+            {
+                RawCallback.Target<Action<{{arg_cs}}>>(userData)({{arg_from_raw}});
+            }
+            catch (Exception e)
+            {
+                // Exceptions can't unwind into rust
+                Callbacks.UnhandledException(e);
+            }
+        }
+
+
+        // -- C interop
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern unsafe void {{symbol_prefix}}__RCallback_{{type_camel}}__call(RawCallback* callback, {{arg_marshal}}{{arg_raw}} arg);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern void {{symbol_prefix}}__RCallback_{{type_camel}}__drop(RawCallback callback);
+    }
+
+}
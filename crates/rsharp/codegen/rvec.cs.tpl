@@ -0,0 +1,93 @@
+// This file was generated by `rsharp_codegen`. Do not edit it by hand.
+using System;
+using System.Runtime.InteropServices;
+using RSharp;
+
+namespace {{namespace}}
+{
+
+    public sealed class {{rvec_ident}} : RVec<{{item_cs}}, RawVec>, IRList<{{item_cs}}>
+    {
+        public {{rvec_ident}}(RawVec inner) : base(inner)
+        { }
+
+        public {{rvec_ident}}() : base({{symbol_prefix}}__RVec_{{type_camel}}__new())
+        { }
+
+        override protected void NativeDrop(RawVec inner)
+        {
+            {{symbol_prefix}}__RVec_{{type_camel}}__drop(inner);
+        }
+
+        public int Count
+        {
+            get => (int)this.Inner.Value.len;
+        }
+
+        /// Takes ownership of `e`.
+        public unsafe override void push({{item_cs}} e)
+        {
+            if (e.OwnershipSemantics != OwnershipSemantics.Owned)
+            {
+                throw new OwnershipException("`e` must be owned");
+            }
+            var inner = this.Inner.Value;
+            {{symbol_prefix}}__RVec_{{type_camel}}__push(&inner, e.StealInner().p);
+            // Pushing may reallocate
+            this.Inner = inner;
+        }
+
+        /// Getting borrows the item, setting takes ownership of `value`.
+        public unsafe override {{item_cs}} this[int index]
+        {
+            get
+            {
+                if (index < 0 || index >= this.Count)
+                {
+                    throw new IndexOutOfRangeException();
+                }
+                var inner = this.Inner.Value;
+                var p = new Ptr<{{item_inner}}>({{symbol_prefix}}__RVec_{{type_camel}}__get(&inner, (UIntPtr)index));
+                return new {{item_cs}}(p, OwnershipSemantics.SharedRef);
+            }
+
+            set
+            {
+                if (index < 0 || index >= this.Count)
+                {
+                    throw new IndexOutOfRangeException();
+                }
+                if (value.OwnershipSemantics != OwnershipSemantics.Owned)
+                {
+                    throw new OwnershipException("`value` must be owned");
+                }
+                var inner = this.Inner.Value;
+                {{symbol_prefix}}__RVec_{{type_camel}}__set(&inner, (UIntPtr)index, value.StealInner().p);
+            }
+        }
+
+        public RListEnumerator<{{rvec_ident}}, {{item_cs}}> GetEnumerator()
+        {
+            return new RListEnumerator<{{rvec_ident}}, {{item_cs}}>(this);
+        }
+
+
+        // -- C interop
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern RawVec {{symbol_prefix}}__RVec_{{type_camel}}__new();
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern void {{symbol_prefix}}__RVec_{{type_camel}}__drop(RawVec vec);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern unsafe void {{symbol_prefix}}__RVec_{{type_camel}}__push(RawVec* vec, IntPtr item);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern unsafe IntPtr {{symbol_prefix}}__RVec_{{type_camel}}__get(RawVec* vec, UIntPtr idx);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern unsafe void {{symbol_prefix}}__RVec_{{type_camel}}__set(RawVec* vec, UIntPtr idx, IntPtr value);
+    }
+
+}
@@ -1,28 +1,54 @@
 use eyre::{eyre, Result, WrapErr};
 use handlebars::Handlebars;
-use lazy_static::lazy_static;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::{
+    collections::HashMap,
     fs::File,
     path::{Path, PathBuf},
 };
 
-const TPL_NAME: &'static str = "tpl";
+const RBOX_TPL: &str = "rbox";
+const RVEC_TPL: &str = "rvec";
+const RCALLBACK_TPL: &str = "rcallback";
+
+/// Mirrors `rsharp::registry::Kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Kind {
+    RVec,
+    RBox,
+    RCallback,
+}
+
+/// Mirrors `rsharp::registry::Entry`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Entry {
+    pub kind: Kind,
+    pub path: String,
+    pub ty: String,
+}
+
+/// Reads the entries written by `rsharp::registry::to_file`.
+pub fn read_registry(path: &Path) -> Result<Vec<Entry>> {
+    let file = File::open(path).wrap_err_with(|| {
+        format!("Failed to open registry {path:?}. Did you run `cargo test` first?")
+    })?;
+    serde_json::from_reader(file).wrap_err("Failed to parse registry")
+}
 
 pub struct Codegen {
     reg: Handlebars<'static>,
     output_dir: PathBuf,
+    /// Maps the root of a rust path (`tp_client`) to a C# namespace
+    /// (`Teleportal.Client`).
+    namespaces: HashMap<String, String>,
 }
 
 impl Codegen {
-    pub fn new(overwrite: bool) -> Result<Self> {
-        let tpl_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("rbox.cs.tpl");
-        println!("{}", tpl_path.display());
-        let output_dir = Path::new(env!("CARGO_MANIFEST_DIR"))
-            .parent()
-            .unwrap()
-            .join("cs/src/generated");
-
+    pub fn new(
+        output_dir: PathBuf,
+        namespaces: HashMap<String, String>,
+        overwrite: bool,
+    ) -> Result<Self> {
         if !overwrite && output_dir.exists() && output_dir.read_dir()?.next().is_some() {
             return Err(eyre!(format!(
                 "`output_dir` is not empty! Please delete it, or rerun with `-f`. (output_dir={output_dir:?})"
@@ -39,65 +65,269 @@ impl Codegen {
         // Don't escape characters
         reg.register_escape_fn(|s| s.to_string());
 
-        reg.register_template_file(TPL_NAME, &tpl_path)?;
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        reg.register_template_file(RBOX_TPL, manifest_dir.join("rbox.cs.tpl"))?;
+        reg.register_template_file(RVEC_TPL, manifest_dir.join("rvec.cs.tpl"))?;
+        reg.register_template_file(RCALLBACK_TPL, manifest_dir.join("rcallback.cs.tpl"))?;
 
-        std::fs::read_to_string(&tpl_path).wrap_err("Failed to read template file")?;
-
-        Ok(Self { reg, output_dir })
+        Ok(Self {
+            reg,
+            output_dir,
+            namespaces,
+        })
     }
 
-    pub fn render_to_file(&self, type_info: &TypeInfo) -> Result<()> {
-        let output_path = self
-            .output_dir
-            .join(format!("RBox_{}.cs", type_info.type_platform));
-        let output_file = File::create(&output_path)
-            .wrap_err_with(|| format!("Failed to create output file for RBox."))?;
-
-        self.reg
-            .render_to_write(TPL_NAME, type_info, output_file)
-            .wrap_err("Failed to render to file")
-    }
-
-    pub fn render_all(&self) -> Result<()> {
-        TYPES_INFO.iter().for_each(|type_info| {
-            self.render_to_file(type_info)
-                .expect("Failed to render to file");
-        });
+    /// Renders every entry whose path has a root in `namespaces`. Other entries
+    /// belong to other crates, and are skipped.
+    pub fn render_all(&self, entries: &[Entry]) -> Result<()> {
+        for entry in entries {
+            let info = match TypeInfo::new(entry, &self.namespaces)? {
+                Some(info) => info,
+                None => continue,
+            };
+            let (tpl, class_ident) = match entry.kind {
+                Kind::RBox => (RBOX_TPL, &info.rbox_ident),
+                Kind::RVec => (RVEC_TPL, &info.rvec_ident),
+                Kind::RCallback => (RCALLBACK_TPL, &info.rcallback_ident),
+            };
+            let output_path = self.output_dir.join(format!("{class_ident}.cs"));
+            let output_file = File::create(&output_path)
+                .wrap_err_with(|| format!("Failed to create output file {output_path:?}"))?;
+            self.reg
+                .render_to_write(tpl, &info, output_file)
+                .wrap_err_with(|| format!("Failed to render {class_ident}"))?;
+        }
         Ok(())
     }
 }
 
-lazy_static! {
-    // Platform Type | C# Type
-    static ref TYPES_INFO: Vec<TypeInfo> = Vec::from([
-        TypeInfo::new("U8", "byte", "*"),
-        TypeInfo::new("U16", "ushort", "*"),
-        TypeInfo::new("U32", "uint", "*"),
-        TypeInfo::new("U64", "ulong", "*"),
-        TypeInfo::new("I8", "sbyte", "*"),
-        TypeInfo::new("I16", "short", "*"),
-        TypeInfo::new("I32", "int", "*"),
-        TypeInfo::new("I64", "long", "*"),
-        TypeInfo::new("Bool", "bool", "*"),
-        TypeInfo::new("F32", "float", "*"),
-        TypeInfo::new("F64", "double", "*"),
-        // TypeInfo::new("ObjectHandle", "IntPtr", ""),
-        // TypeInfo::new("ContractDataHandle", "IntPtr", ""),
-    ]);
-}
+/// Rust value types, and their C# equivalent.
+const VALUE_TYPES: &[(&str, &str)] = &[
+    ("u8", "byte"),
+    ("u16", "ushort"),
+    ("u32", "uint"),
+    ("u64", "ulong"),
+    ("i8", "sbyte"),
+    ("i16", "short"),
+    ("i32", "int"),
+    ("i64", "long"),
+    ("bool", "bool"),
+    ("f32", "float"),
+    ("f64", "double"),
+];
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize)]
 pub struct TypeInfo {
-    type_platform: &'static str,
-    type_cs: &'static str,
-    ptr_literal: &'static str,
+    /// The C# namespace of the generated class.
+    namespace: String,
+    /// The prefix of the exported functions (`tp_client__contract`).
+    symbol_prefix: String,
+    /// The type, as mangled by `paste`'s `:camel` (`U8`, `KeyframeU8`).
+    type_camel: String,
+    /// The C# type that the items are exposed as (`RBox_U8`, `Keyframe_U8`).
+    item_cs: String,
+    /// The `T` of the `Ptr<T>` that `item_cs` wraps (`byte`, `Keyframe_U8`).
+    item_inner: String,
+    /// The C# primitive (`byte`). Only set for value types.
+    value_cs: Option<&'static str>,
+    rbox_ident: String,
+    rvec_ident: String,
+    rcallback_ident: String,
+    /// The C# type that callbacks are called with (`byte`, `Keyframe_U8`).
+    arg_cs: String,
+    /// The C# type that callbacks are called with across FFI (`byte`, `IntPtr`).
+    arg_raw: String,
+    /// The `MarshalAs` attribute of the argument, if it needs one.
+    arg_marshal: &'static str,
+    /// Converts `arg` from `arg_raw` to `arg_cs`.
+    arg_from_raw: String,
+    /// Converts `arg` from `arg_cs` to `arg_raw`.
+    arg_to_raw: String,
+    /// Whether the type is opaque, i.e. passed behind a pointer.
+    is_opaque: bool,
 }
 impl TypeInfo {
-    fn new(type_platform: &'static str, type_cs: &'static str, ptr_literal: &'static str) -> Self {
-        Self {
-            type_platform,
-            type_cs,
-            ptr_literal,
+    /// Returns `None` if the root of the entry's path has no namespace.
+    pub fn new(entry: &Entry, namespaces: &HashMap<String, String>) -> Result<Option<Self>> {
+        let mut segments = entry.path.split("::");
+        let root = segments.next().unwrap_or_default();
+        let namespace_root = match namespaces.get(root) {
+            Some(ns) => ns,
+            None => return Ok(None),
+        };
+        let namespace = std::iter::once(namespace_root.clone())
+            .chain(segments.map(pascal_case))
+            .collect::<Vec<_>>()
+            .join(".");
+
+        let value_cs = VALUE_TYPES
+            .iter()
+            .find(|(rust, _)| *rust == entry.ty)
+            .map(|(_, cs)| *cs);
+        if entry.kind == Kind::RBox && value_cs.is_none() {
+            return Err(eyre!(
+                "`RBox` is only supported for value types, but got `{}`",
+                entry.ty
+            ));
+        }
+
+        let type_camel = camel_case(&entry.ty);
+        // Value types are wrapped by the `RBox` of rsharp, opaque types by their
+        // own class, which is named after the rust type.
+        let item_cs = match value_cs {
+            Some(_) => format!("RSharp.RBox_{type_camel}"),
+            None => entry.ty.clone(),
+        };
+        let (elem_ident, item_inner) = match value_cs {
+            Some(cs) => (type_camel.clone(), cs.to_string()),
+            None => (entry.ty.clone(), entry.ty.clone()),
+        };
+
+        // Value types are passed to callbacks as is, opaque types as an owned
+        // `repr_c::Box`.
+        let (arg_cs, arg_raw, arg_from_raw, arg_to_raw) = match value_cs {
+            Some(cs) => (
+                cs.to_string(),
+                cs.to_string(),
+                "arg".to_string(),
+                "arg".to_string(),
+            ),
+            None => (
+                entry.ty.clone(),
+                "IntPtr".to_string(),
+                format!(
+                    "new {0}(new Ptr<{0}>(arg), OwnershipSemantics.Owned)",
+                    entry.ty
+                ),
+                "arg.StealInner().p".to_string(),
+            ),
+        };
+        let arg_marshal = match value_cs {
+            Some("bool") => "[MarshalAs(UnmanagedType.U1)] ",
+            _ => "",
+        };
+
+        Ok(Some(Self {
+            namespace,
+            symbol_prefix: entry.path.replace("::", "__"),
+            rbox_ident: format!("RBox_{type_camel}"),
+            rvec_ident: format!("RVec_{elem_ident}"),
+            rcallback_ident: format!("RCallback_{elem_ident}"),
+            arg_cs,
+            arg_raw,
+            arg_marshal,
+            arg_from_raw,
+            arg_to_raw,
+            is_opaque: value_cs.is_none(),
+            type_camel,
+            item_cs,
+            item_inner,
+            value_cs,
+        }))
+    }
+}
+
+/// Matches the `:camel` modifier of `paste`, so that the generated C# uses the
+/// same symbols as the rust macros (`Keyframe_U8` -> `KeyframeU8`).
+fn camel_case(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut prev = '_';
+    for c in s.chars() {
+        if c != '_' {
+            if prev == '_' {
+                result.extend(c.to_uppercase());
+            } else if prev.is_uppercase() {
+                result.extend(c.to_lowercase());
+            } else {
+                result.push(c);
+            }
         }
+        prev = c;
+    }
+    result
+}
+
+/// `contract_data` -> `ContractData`
+fn pascal_case(s: &str) -> String {
+    s.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camel_case() {
+        assert_eq!(camel_case("u8"), "U8");
+        assert_eq!(camel_case("bool"), "Bool");
+        assert_eq!(camel_case("Keyframe_U8"), "KeyframeU8");
+        assert_eq!(camel_case("Keyframe_ObjectHandle"), "KeyframeObjectHandle");
+    }
+
+    #[test]
+    fn test_type_info() -> Result<()> {
+        let namespaces =
+            HashMap::from([("tp_client".to_string(), "Teleportal.Client".to_string())]);
+        let entry = Entry {
+            kind: Kind::RVec,
+            path: "tp_client::contract::properties::channels".to_string(),
+            ty: "Keyframe_U8".to_string(),
+        };
+        let info = TypeInfo::new(&entry, &namespaces)?.unwrap();
+        assert_eq!(
+            info.namespace,
+            "Teleportal.Client.Contract.Properties.Channels"
+        );
+        assert_eq!(
+            info.symbol_prefix,
+            "tp_client__contract__properties__channels"
+        );
+        assert_eq!(info.type_camel, "KeyframeU8");
+        assert_eq!(info.item_cs, "Keyframe_U8");
+        assert_eq!(info.rvec_ident, "RVec_Keyframe_U8");
+
+        let entry = Entry {
+            kind: Kind::RBox,
+            path: "rsharp".to_string(),
+            ty: "u8".to_string(),
+        };
+        assert!(TypeInfo::new(&entry, &namespaces)?.is_none());
+
+        let entry = Entry {
+            kind: Kind::RBox,
+            path: "tp_client".to_string(),
+            ty: "Keyframe_U8".to_string(),
+        };
+        assert!(TypeInfo::new(&entry, &namespaces).is_err());
+
+        let entry = Entry {
+            kind: Kind::RCallback,
+            path: "tp_client".to_string(),
+            ty: "ObjectHandle".to_string(),
+        };
+        let info = TypeInfo::new(&entry, &namespaces)?.unwrap();
+        assert_eq!(info.rcallback_ident, "RCallback_ObjectHandle");
+        assert_eq!(info.arg_raw, "IntPtr");
+        assert!(info.is_opaque);
+
+        let entry = Entry {
+            kind: Kind::RCallback,
+            path: "tp_client".to_string(),
+            ty: "bool".to_string(),
+        };
+        let info = TypeInfo::new(&entry, &namespaces)?.unwrap();
+        assert_eq!(info.rcallback_ident, "RCallback_Bool");
+        assert_eq!(info.arg_cs, "bool");
+        assert_eq!(info.arg_marshal, "[MarshalAs(UnmanagedType.U1)] ");
+        assert!(!info.is_opaque);
+        Ok(())
     }
 }
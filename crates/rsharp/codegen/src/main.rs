@@ -1,19 +1,55 @@
 mod codegen;
 
 use clap::Parser;
-use eyre::{Result, WrapErr};
+use eyre::{eyre, Result, WrapErr};
+use std::{collections::HashMap, path::PathBuf};
 
-use crate::codegen::Codegen;
+use crate::codegen::{read_registry, Codegen};
 
 #[derive(Parser)]
 struct Args {
     #[clap(short)]
     /// Forcibly overwrites existing files
     force: bool,
+
+    /// The registry written by `rsharp::registry::to_file`. Defaults to the one
+    /// of `rsharp` itself.
+    #[clap(long)]
+    registry: Option<PathBuf>,
+
+    /// Maps the root of a rust path to a C# namespace, like
+    /// `tp_client=Teleportal.Client`. Only entries with a mapped root are
+    /// generated.
+    #[clap(long = "namespace", default_value = "rsharp=RSharp")]
+    namespaces: Vec<String>,
+
+    /// The directory to write the classes to. Defaults to the generated
+    /// directory of `rsharp`.
+    #[clap(long)]
+    out: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    let codegen = Codegen::new(args.force).wrap_err("Failed to create `Codegen`")?;
-    codegen.render_all()
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    let registry = args
+        .registry
+        .unwrap_or_else(|| manifest_dir.join("../rust/rsharp_registry.json"));
+    let out = args
+        .out
+        .unwrap_or_else(|| manifest_dir.join("../cs/src/generated"));
+    let namespaces = args
+        .namespaces
+        .iter()
+        .map(|s| match s.split_once('=') {
+            Some((root, ns)) => Ok((root.to_string(), ns.to_string())),
+            None => Err(eyre!("Expected `root=Namespace`, but got `{s}`")),
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let entries = read_registry(&registry)?;
+    let codegen =
+        Codegen::new(out, namespaces, args.force).wrap_err("Failed to create `Codegen`")?;
+    codegen.render_all(&entries)
 }
@@ -0,0 +1,91 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace RSharp
+{
+    /// The layout of an `rsharp::callback::RCallback<A>` in rust, for any `A`.
+    ///
+    /// `userData` is a `GCHandle` to the managed delegate, which is freed when
+    /// rust drops the callback.
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RawCallback
+    {
+        public IntPtr userData;
+        public IntPtr call;
+        public IntPtr release;
+
+        /// Pins `target` until rust drops the callback. `call` must be a
+        /// function pointer that takes the handle of `target` as its first
+        /// argument.
+        public static RawCallback New(object target, IntPtr call)
+        {
+            return new RawCallback
+            {
+                userData = GCHandle.ToIntPtr(GCHandle.Alloc(target)),
+                call = call,
+                release = ReleasePtr,
+            };
+        }
+
+        /// Gets the `target` that the callback was created with.
+        public static T Target<T>(IntPtr userData) where T : class
+        {
+            return (T)GCHandle.FromIntPtr(userData).Target;
+        }
+
+        private delegate void ReleaseFn(IntPtr userData);
+        private static readonly ReleaseFn releaseFn = Release;
+        private static readonly IntPtr ReleasePtr = Marshal.GetFunctionPointerForDelegate(releaseFn);
+
+        [MonoPInvokeCallback(typeof(ReleaseFn))]
+        private static void Release(IntPtr userData)
+        {
+            GCHandle.FromIntPtr(userData).Free();
+        }
+    }
+
+    public static class Callbacks
+    {
+        /// Called with exceptions thrown by callbacks. They can't be thrown
+        /// into rust, so by default they are written to stderr.
+        public static Action<Exception> UnhandledException = (e) => Console.Error.WriteLine(e);
+    }
+
+    /// Marks a static method that is called from native code, which IL2CPP
+    /// (i.e. Unity) requires. IL2CPP matches this attribute by name, so it is
+    /// defined here rather than depending on Unity.
+    [AttributeUsage(AttributeTargets.Method)]
+    public sealed class MonoPInvokeCallbackAttribute : Attribute
+    {
+        public MonoPInvokeCallbackAttribute(Type delegateType) { }
+    }
+
+    /// Runs queued callbacks on the thread that created it. Callbacks created
+    /// with a dispatcher are only ever called on that thread, so create it on
+    /// the main thread, and call `RunPending()` once per frame.
+    public sealed class Dispatcher : OpaqueWrapper<Dispatcher>
+    {
+        public Dispatcher(Ptr<Dispatcher> inner, OwnershipSemantics ownershipSemantics) : base(inner, ownershipSemantics) { }
+        public Dispatcher() : this(new Ptr<Dispatcher>(rsharp__Dispatcher__new()), OwnershipSemantics.Owned) { }
+
+        /// Runs the queued callbacks, and returns how many were run.
+        public int RunPending()
+        {
+            return (int)rsharp__Dispatcher__run_pending(this.Inner.Value.p).Unwrap();
+        }
+
+        override protected void NativeDrop(Ptr<Dispatcher> inner)
+        {
+            rsharp__Dispatcher__drop(inner.p);
+        }
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern IntPtr rsharp__Dispatcher__new();
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern void rsharp__Dispatcher__drop(IntPtr dispatcher);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern RResult_USize rsharp__Dispatcher__run_pending(IntPtr dispatcher);
+    }
+}
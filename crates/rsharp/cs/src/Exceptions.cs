@@ -23,4 +23,32 @@ namespace RSharp
             System.Runtime.Serialization.SerializationInfo info,
             System.Runtime.Serialization.StreamingContext context) : base(info, context) { }
     }
+
+    /// Thrown when a rust function returns an error that has no more specific
+    /// exception
+    [System.Serializable]
+    public class RustException : System.Exception
+    {
+        public RErrorKind Kind { get; } = RErrorKind.Other;
+
+        public RustException() { }
+        public RustException(string message) : base(message) { }
+        public RustException(RErrorKind kind, string message) : base(message) { this.Kind = kind; }
+        public RustException(string message, System.Exception inner) : base(message, inner) { }
+        protected RustException(
+            System.Runtime.Serialization.SerializationInfo info,
+            System.Runtime.Serialization.StreamingContext context) : base(info, context) { }
+    }
+
+    /// Thrown when a rust function panics
+    [System.Serializable]
+    public class RustPanicException : RustException
+    {
+        public RustPanicException() : base(RErrorKind.Panic, "A rust function panicked") { }
+        public RustPanicException(string message) : base(RErrorKind.Panic, message) { }
+        public RustPanicException(string message, System.Exception inner) : base(message, inner) { }
+        protected RustPanicException(
+            System.Runtime.Serialization.SerializationInfo info,
+            System.Runtime.Serialization.StreamingContext context) : base(info, context) { }
+    }
 }
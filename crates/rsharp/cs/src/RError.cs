@@ -0,0 +1,83 @@
+using System;
+using System.Runtime.InteropServices;
+using System.Text;
+
+namespace RSharp
+{
+    /// Mirrors `rsharp::result::RErrorKind`.
+    public enum RErrorKind : byte
+    {
+        Other = 0,
+        Panic = 1,
+        NullPointer = 2,
+        InvalidArgument = 3,
+        InvalidOperation = 4,
+        IndexOutOfRange = 5,
+        Mutability = 6,
+        Ownership = 7,
+    }
+
+    /// Converts the errors returned by fallible rust functions into exceptions.
+    public static class RError
+    {
+        /// Throws the exception that matches `error`, taking ownership of it.
+        /// Does nothing if `error` is null.
+        public static void Check(IntPtr error)
+        {
+            if (error == IntPtr.Zero)
+            {
+                return;
+            }
+
+            RErrorKind kind = rsharp__RError__kind(error);
+            string message;
+            unsafe
+            {
+                SliceU8 slice = rsharp__RError__message(error);
+                message = new UTF8Encoding().GetString(slice.ptr, (int)slice.len);
+            }
+            rsharp__RError__drop(error);
+
+            throw ToException(kind, message);
+        }
+
+        private static Exception ToException(RErrorKind kind, string message)
+        {
+            switch (kind)
+            {
+                case RErrorKind.Panic:
+                    return new RustPanicException(message);
+                case RErrorKind.NullPointer:
+                    return new ArgumentNullException(paramName: null, message: message);
+                case RErrorKind.InvalidArgument:
+                    return new ArgumentException(message);
+                case RErrorKind.InvalidOperation:
+                    return new InvalidOperationException(message);
+                case RErrorKind.IndexOutOfRange:
+                    return new IndexOutOfRangeException(message);
+                case RErrorKind.Mutability:
+                    return new MutabilityException(message);
+                case RErrorKind.Ownership:
+                    return new OwnershipException(message);
+                default:
+                    return new RustException(kind, message);
+            }
+        }
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern RErrorKind rsharp__RError__kind(IntPtr err);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern SliceU8 rsharp__RError__message(IntPtr err);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern void rsharp__RError__drop(IntPtr err);
+
+        [StructLayout(LayoutKind.Sequential)]
+        private unsafe struct SliceU8
+        {
+            public byte* ptr;
+            public ulong len;
+        }
+    }
+}
@@ -0,0 +1,45 @@
+namespace RSharp
+{
+    /// A collection owned by rust that can be indexed without copying it.
+    ///
+    /// Items are fetched from rust one at a time, so reading a single item never
+    /// allocates a copy of the whole collection.
+    public interface IRList<T>
+    {
+        int Count { get; }
+
+        T this[int index] { get; }
+    }
+
+    /// Enumerates an `IRList<T>` by index. Because this is a struct, `foreach`
+    /// loops over it don't allocate, unlike `IEnumerable<T>`.
+    public struct RListEnumerator<L, T> where L : IRList<T>
+    {
+        private readonly L list;
+        private readonly int count;
+        private int index;
+
+        public RListEnumerator(L list)
+        {
+            this.list = list;
+            this.count = list.Count;
+            this.index = -1;
+        }
+
+        public T Current
+        {
+            get => this.list[this.index];
+        }
+
+        public bool MoveNext()
+        {
+            this.index++;
+            return this.index < this.count;
+        }
+
+        public void Reset()
+        {
+            this.index = -1;
+        }
+    }
+}
@@ -0,0 +1,142 @@
+using System;
+using System.Runtime.InteropServices;
+
+namespace RSharp
+{
+    // The layouts of `rsharp::result::RResult<T>`. P/Invoke can't marshal generic
+    // structs, so there is one per type of value.
+
+    /// `RResult<T>` for any pointer, like `Option<repr_c::Box<T>>` or `Option<&T>`.
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_Ptr
+    {
+        public IntPtr value;
+        public IntPtr error;
+
+        /// Throws if there was an error, otherwise returns the value.
+        public IntPtr Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_Bool
+    {
+        [MarshalAs(UnmanagedType.U1)]
+        public bool value;
+        public IntPtr error;
+
+        public bool Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_U8
+    {
+        public byte value;
+        public IntPtr error;
+
+        public byte Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_U32
+    {
+        public uint value;
+        public IntPtr error;
+
+        public uint Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_U64
+    {
+        public ulong value;
+        public IntPtr error;
+
+        public ulong Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    /// `RResult<usize>`
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_USize
+    {
+        public UIntPtr value;
+        public IntPtr error;
+
+        public UIntPtr Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_I32
+    {
+        public int value;
+        public IntPtr error;
+
+        public int Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_I64
+    {
+        public long value;
+        public IntPtr error;
+
+        public long Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_F32
+    {
+        public float value;
+        public IntPtr error;
+
+        public float Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RResult_F64
+    {
+        public double value;
+        public IntPtr error;
+
+        public double Unwrap()
+        {
+            RError.Check(this.error);
+            return this.value;
+        }
+    }
+}
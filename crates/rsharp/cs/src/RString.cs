@@ -1,4 +1,5 @@
 using System;
+using System.Collections.Generic;
 using System.Runtime.InteropServices;
 using System.Text;
 
@@ -39,6 +40,67 @@ namespace RSharp
             }
         }
 
+        /// Borrows the UTF-8 bytes, without decoding them.
+        public RStr View
+        {
+            get
+            {
+                SliceU8 slice = rsharp__String__value(this.Inner.Value.p);
+                unsafe
+                {
+                    return new RStr(new ReadOnlySpan<byte>(slice.ptr, (int)slice.len));
+                }
+            }
+        }
+
+        /// The number of chars that `CopyTo()` writes.
+        public int Utf16Length
+        {
+            get => (int)rsharp__String__utf16_len(this.Inner.Value.p);
+        }
+
+        /// Encodes the string directly into `destination`, and returns the
+        /// number of chars written. Throws if `destination` is shorter than
+        /// `Utf16Length`.
+        public unsafe int CopyTo(Span<char> destination)
+        {
+            fixed (char* ptr = destination)
+            {
+                var slice = new SliceU16((ushort*)ptr, (ulong)destination.Length);
+                return (int)rsharp__String__write_utf16(this.Inner.Value.p, slice).Unwrap();
+            }
+        }
+
+        /// Like `Value`, but each distinct string is only decoded once, and the
+        /// same managed string is returned every time. Interned strings are
+        /// never freed, so only use this for strings with few distinct values,
+        /// like enum-like states.
+        public string Interned
+        {
+            get
+            {
+                uint id = rsharp__String__intern(this.Inner.Value.p);
+                lock (interned)
+                {
+                    if (interned.TryGetValue(id, out string? cached))
+                    {
+                        return cached;
+                    }
+                    SliceU8 slice = rsharp__String__interned(id).Unwrap();
+                    string result;
+                    unsafe
+                    {
+                        result = new UTF8Encoding().GetString(slice.ptr, (int)slice.len);
+                    }
+                    interned.Add(id, result);
+                    return result;
+                }
+            }
+        }
+
+        /// Maps the ids of interned strings to their decoded value.
+        private static readonly Dictionary<uint, string> interned = new Dictionary<uint, string>();
+
         override protected void NativeDrop(Ptr<RString> inner)
         {
             rsharp__String__drop(inner.p);
@@ -56,6 +118,18 @@ namespace RSharp
         [DllImport(Metadata.LIBRARY_NAME)]
         private static extern unsafe IntPtr rsharp__String__copy_utf16(SliceU16 slice);
 
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern UIntPtr rsharp__String__utf16_len(IntPtr s);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern RResult_USize rsharp__String__write_utf16(IntPtr s, SliceU16 output);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern uint rsharp__String__intern(IntPtr s);
+
+        [DllImport(Metadata.LIBRARY_NAME)]
+        private static extern RResult_SliceU8 rsharp__String__interned(uint id);
+
         [StructLayout(LayoutKind.Sequential)]
         private unsafe struct SliceU8
         {
@@ -69,6 +143,19 @@ namespace RSharp
             }
         }
 
+        [StructLayout(LayoutKind.Sequential)]
+        private struct RResult_SliceU8
+        {
+            public SliceU8 value;
+            public IntPtr error;
+
+            public SliceU8 Unwrap()
+            {
+                RError.Check(this.error);
+                return this.value;
+            }
+        }
+
         [StructLayout(LayoutKind.Sequential)]
         private unsafe struct SliceU16
         {
@@ -82,4 +169,47 @@ namespace RSharp
             }
         }
     }
+
+    /// A borrowed view of the UTF-8 bytes of an `RString`. Only valid until the
+    /// `RString` is mutated or disposed, so don't keep it past the current scope.
+    public readonly ref struct RStr
+    {
+        private readonly ReadOnlySpan<byte> utf8;
+
+        internal RStr(ReadOnlySpan<byte> utf8)
+        {
+            this.utf8 = utf8;
+        }
+
+        public ReadOnlySpan<byte> Utf8
+        {
+            get => this.utf8;
+        }
+
+        /// The length in bytes.
+        public int Length
+        {
+            get => this.utf8.Length;
+        }
+
+        /// Compares with `other` without decoding the view.
+        public bool Equals(string other)
+        {
+            var encoding = new UTF8Encoding();
+            int count = encoding.GetByteCount(other);
+            if (count != this.utf8.Length)
+            {
+                return false;
+            }
+            Span<byte> bytes = count <= 256 ? stackalloc byte[count] : new byte[count];
+            encoding.GetBytes(other, bytes);
+            return this.utf8.SequenceEqual(bytes);
+        }
+
+        /// Decodes the view into a managed string.
+        public override string ToString()
+        {
+            return new UTF8Encoding().GetString(this.utf8);
+        }
+    }
 }
@@ -1,7 +1,16 @@
 using System;
+using System.Runtime.InteropServices;
 
 namespace RSharp
 {
+    /// The layout of a `repr_c::Vec<T>` in rust, for any `T`.
+    [StructLayout(LayoutKind.Sequential)]
+    public struct RawVec
+    {
+        public IntPtr ptr;
+        public UIntPtr len;
+        public UIntPtr cap;
+    }
 
     /// T is the c# wrapped data type stored *inside* the RVec, V is the C#
     /// shared struct of the monomorphized RVec
@@ -0,0 +1,78 @@
+using Xunit;
+using RSharp;
+using InvalidOperationException = System.InvalidOperationException;
+using Thread = System.Threading.Thread;
+
+namespace test
+{
+
+    public class TestCallback
+    {
+        [Fact]
+        public void TestInvoke()
+        {
+            int sum = 0;
+            var callback = new RCallback_U8((arg) => sum += arg);
+            callback.Invoke(1);
+            callback.Invoke(2);
+            Assert.Equal(3, sum);
+
+            callback.Dispose();
+            Assert.Throws<InvalidOperationException>(() => callback.Invoke(3));
+        }
+
+        [Fact]
+        public void TestBool()
+        {
+            bool? received = null;
+            var callback = new RCallback_Bool((arg) => received = arg);
+            callback.Invoke(true);
+            Assert.True(received);
+            callback.Dispose();
+        }
+
+        [Fact]
+        public void TestExceptionsAreCaught()
+        {
+            System.Exception? caught = null;
+            var previous = Callbacks.UnhandledException;
+            Callbacks.UnhandledException = (e) => caught = e;
+            try
+            {
+                var callback = new RCallback_U8((arg) => throw new System.ArgumentException("oops"));
+                callback.Invoke(1);
+                Assert.IsType<System.ArgumentException>(caught);
+                callback.Dispose();
+            }
+            finally
+            {
+                Callbacks.UnhandledException = previous;
+            }
+        }
+
+        [Fact]
+        public void TestRunPendingOnWrongThread()
+        {
+            var dispatcher = new Dispatcher();
+            Assert.Equal(0, dispatcher.RunPending());
+
+            System.Exception? caught = null;
+            var thread = new Thread(() =>
+            {
+                try
+                {
+                    dispatcher.RunPending();
+                }
+                catch (System.Exception e)
+                {
+                    caught = e;
+                }
+            });
+            thread.Start();
+            thread.Join();
+            Assert.IsType<InvalidOperationException>(caught);
+            dispatcher.Dispose();
+        }
+    }
+
+}
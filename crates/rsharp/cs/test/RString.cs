@@ -22,4 +22,40 @@ public class TestString
         rs.Dispose();
         Assert.Throws<Sys.InvalidOperationException>(() => rs.Value);
     }
+
+    [Fact]
+    public void TestView()
+    {
+        var rs = new RString("héllo");
+        Assert.Equal(6, rs.View.Length);
+        Assert.True(rs.View.Equals("héllo"));
+        Assert.False(rs.View.Equals("hello"));
+        Assert.Equal("héllo", rs.View.ToString());
+        rs.Dispose();
+    }
+
+    [Fact]
+    public void TestCopyTo()
+    {
+        var rs = new RString("héllo \U0001F600");
+        Assert.Equal(8, rs.Utf16Length);
+
+        var buffer = new char[16];
+        int written = rs.CopyTo(buffer);
+        Assert.Equal("héllo \U0001F600", new string(buffer, 0, written));
+
+        Assert.Throws<Sys.ArgumentException>(() => rs.CopyTo(new char[4]));
+        rs.Dispose();
+    }
+
+    [Fact]
+    public void TestInterned()
+    {
+        var a = new RString("idle");
+        var b = new RString("idle");
+        Assert.Equal("idle", a.Interned);
+        Assert.Same(a.Interned, b.Interned);
+        a.Dispose();
+        b.Dispose();
+    }
 }
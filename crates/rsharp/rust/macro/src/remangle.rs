@@ -1,5 +1,5 @@
 use proc_macro2::TokenStream;
-use quote::quote_spanned;
+use quote::{format_ident, quote, quote_spanned};
 use syn::spanned::Spanned;
 use syn::{self, Error, Result};
 
@@ -28,7 +28,10 @@ pub fn remangle(path: syn::LitStr, mut item: syn::Item) -> Result<TokenStream> {
     use syn::Item::*;
     let (original_ident, mangled_ident) = match item {
         Struct(ref mut i) => mangle(&mut i.ident),
-        Fn(ref mut f) => mangle(&mut f.sig.ident),
+        Fn(ref mut f) => {
+            catch_unwind(f);
+            mangle(&mut f.sig.ident)
+        }
         Enum(ref mut e) => mangle(&mut e.ident),
         _ => return Err(Error::new(item.span(), "This item type is not supported")),
     }?;
@@ -43,9 +46,53 @@ pub fn remangle(path: syn::LitStr, mut item: syn::Item) -> Result<TokenStream> {
     Ok(output)
 }
 
+/// Whether `ty` is an `RResult<T>` or `RStatus` from `rsharp::result`.
+fn is_fallible(ty: &syn::Type) -> bool {
+    match ty {
+        syn::Type::Path(p) => p
+            .path
+            .segments
+            .last()
+            .map_or(false, |s| s.ident == "RResult" || s.ident == "RStatus"),
+        _ => false,
+    }
+}
+
+/// The path to the `rsharp` crate, as seen by the crate being compiled.
+fn rsharp_path() -> TokenStream {
+    match find_crate::find_crate(|s| s == "rsharp") {
+        Ok(package) => {
+            let ident = format_ident!("{}", package.name);
+            quote! { ::#ident }
+        }
+        // Only `rsharp` itself doesn't depend on `rsharp`.
+        Err(_) => quote! { crate },
+    }
+}
+
+/// Wraps the body of fallible functions in `rsharp::result::catch_unwind`, so
+/// that neither errors nor panics unwind into C#.
+fn catch_unwind(f: &mut syn::ItemFn) {
+    let ty = match &f.sig.output {
+        syn::ReturnType::Type(_, ty) if is_fallible(ty) => ty,
+        _ => return,
+    };
+    let rsharp = rsharp_path();
+    let block = &f.block;
+    f.block = syn::parse_quote! {{
+        #rsharp::result::catch_unwind::<#ty>(
+            move || -> ::std::result::Result<
+                <#ty as #rsharp::result::FfiResult>::Value,
+                #rsharp::result::Report,
+            > #block
+        )
+    }};
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use quote::ToTokens;
     #[test]
     fn test_mangle_path() -> Result<()> {
         let inputs = [
@@ -59,4 +106,38 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_catch_unwind() {
+        let mut fallible: syn::ItemFn = syn::parse_quote! {
+            pub fn f(x: u8) -> rsharp::result::RResult<u8> {
+                Ok(x)
+            }
+        };
+        catch_unwind(&mut fallible);
+        let body = fallible.block.to_token_stream().to_string();
+        assert!(body.contains("catch_unwind"));
+        assert!(body.contains("Ok (x)"));
+
+        let mut status: syn::ItemFn = syn::parse_quote! {
+            pub fn f() -> RStatus {
+                Ok(())
+            }
+        };
+        catch_unwind(&mut status);
+        assert!(status
+            .block
+            .to_token_stream()
+            .to_string()
+            .contains("catch_unwind"));
+
+        for mut infallible in [
+            syn::parse_quote! { pub fn f(x: u8) -> u8 { x } },
+            syn::parse_quote! { pub fn f() {} },
+        ] {
+            let before = infallible.to_token_stream().to_string();
+            catch_unwind(&mut infallible);
+            assert_eq!(infallible.to_token_stream().to_string(), before);
+        }
+    }
 }
@@ -0,0 +1,313 @@
+//! Callbacks from rust into another language, like C#.
+//!
+//! An [`RCallback<A>`] is a C function pointer, and an opaque handle to the
+//! user data that it is called with (in C#, a `GCHandle` to a delegate). The
+//! handle is released when the callback is dropped.
+//!
+//! Many hosts only allow calling into them from a particular thread (such as
+//! the main thread in Unity), while rust events may happen on any thread. A
+//! [`Callback<A>`] created with a [`Dispatcher`] is only ever called on the
+//! thread that created the dispatcher. Calls from any other thread are queued
+//! until that thread calls [`Dispatcher::run_pending()`].
+//!
+//! Every argument type needs an instantiation of [`callback_fns!`](crate::callback_fns)
+//! so that `rsharp_codegen` generates the matching C# class. Arguments are
+//! passed by value, so opaque types are passed as `repr_c::Box<T>` and owned by
+//! the callee.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::remangle;
+use crate::result::{RError, RErrorKind, RResult};
+use crate::value_types::value_types;
+
+use eyre::Result;
+use safer_ffi::prelude::*;
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread::{self, ThreadId};
+
+/// A C function pointer, and the user data that it is called with.
+///
+/// `release` is called exactly once, when the callback is dropped. It may be
+/// called from any thread.
+#[remangle("rsharp")]
+#[derive_ReprC]
+#[repr(C)]
+pub struct RCallback<A> {
+    user_data: usize,
+    call: unsafe extern "C" fn(usize, A),
+    release: unsafe extern "C" fn(usize),
+}
+impl<A> RCallback<A> {
+    /// # Safety
+    /// `call` and `release` must be safe to call with `user_data`, until
+    /// `release` is called.
+    pub unsafe fn new(
+        user_data: usize,
+        call: unsafe extern "C" fn(usize, A),
+        release: unsafe extern "C" fn(usize),
+    ) -> Self {
+        Self {
+            user_data,
+            call,
+            release,
+        }
+    }
+
+    /// Calls the callback on the current thread. Prefer [`Callback::call()`],
+    /// which respects the thread affinity of the callback.
+    pub fn call(&self, arg: A) {
+        unsafe { (self.call)(self.user_data, arg) }
+    }
+}
+impl<A> Drop for RCallback<A> {
+    fn drop(&mut self) {
+        unsafe { (self.release)(self.user_data) }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Runs queued callbacks on the thread that created it.
+#[remangle("rsharp")]
+#[derive_ReprC]
+#[ReprC::opaque]
+pub struct Dispatcher {
+    thread: ThreadId,
+    sender: mpsc::Sender<Job>,
+    receiver: mpsc::Receiver<Job>,
+}
+impl Dispatcher {
+    /// Creates a dispatcher for the current thread.
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            thread: thread::current().id(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Runs all callbacks that were queued for this thread, and returns how
+    /// many were run. Errors if called from any other thread.
+    pub fn run_pending(&self) -> Result<usize> {
+        if thread::current().id() != self.thread {
+            return Err(RError::new(
+                RErrorKind::InvalidOperation,
+                "`run_pending()` must be called on the thread that created the dispatcher",
+            )
+            .into());
+        }
+        let mut count = 0;
+        for job in self.receiver.try_iter() {
+            job();
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+impl Default for Dispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where a [`Callback`] is allowed to be called.
+#[derive(Clone)]
+enum Affinity {
+    /// Any thread.
+    Any,
+    /// Only `thread`, and calls from other threads are sent to its dispatcher.
+    Thread {
+        thread: ThreadId,
+        sender: mpsc::Sender<Job>,
+    },
+}
+
+/// An [`RCallback`] that is only called on the threads that it allows.
+pub struct Callback<A> {
+    inner: Arc<RCallback<A>>,
+    affinity: Affinity,
+}
+impl<A: Send + 'static> Callback<A> {
+    /// If `dispatcher` is `None`, the callback may be called on any thread.
+    pub fn new(inner: RCallback<A>, dispatcher: Option<&Dispatcher>) -> Self {
+        let affinity = match dispatcher {
+            Some(d) => Affinity::Thread {
+                thread: d.thread,
+                sender: d.sender.clone(),
+            },
+            None => Affinity::Any,
+        };
+        Self {
+            inner: Arc::new(inner),
+            affinity,
+        }
+    }
+
+    /// Calls the callback, or queues the call if this is the wrong thread. If
+    /// the dispatcher no longer exists, the call is dropped.
+    pub fn call(&self, arg: A) {
+        match &self.affinity {
+            Affinity::Thread { thread, sender } if *thread != thread::current().id() => {
+                let inner = Arc::clone(&self.inner);
+                let _ = sender.send(Box::new(move || inner.call(arg)));
+            }
+            _ => self.inner.call(arg),
+        }
+    }
+}
+impl<A> Clone for Callback<A> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            affinity: self.affinity.clone(),
+        }
+    }
+}
+
+/// Generates the C API for callbacks that take an argument of type `$t`, and
+/// registers it for `rsharp_codegen`. Use `Box<T>` for opaque types.
+#[macro_export]
+macro_rules! callback_fns {
+    (@impl $path:literal, $t:ty, $arg:ty) => {
+        $crate::__register!(RCallback, $path, $t);
+
+        paste::paste! {
+            /// Calls the callback on the current thread.
+            #[$crate::remangle($path)]
+            #[::safer_ffi::ffi_export]
+            pub fn [<RCallback_ $t:camel __call>](callback: &$crate::callback::RCallback<$arg>, arg: $arg) {
+                callback.call(arg)
+            }
+
+            #[$crate::remangle($path)]
+            #[::safer_ffi::ffi_export]
+            pub fn [<RCallback_ $t:camel __drop>](callback: $crate::callback::RCallback<$arg>) {
+                drop(callback)
+            }
+        }
+    };
+    ($path:literal, Box<$t:ty>) => {
+        $crate::callback_fns!(@impl $path, $t, ::safer_ffi::boxed::Box<$t>);
+    };
+    ($path:literal, $t:ty) => {
+        $crate::callback_fns!(@impl $path, $t, $t);
+    };
+}
+
+macro_rules! callbacks {
+    ($path:literal, $($t:ty),+ $(,)?) => {
+        $(crate::callback_fns!($path, $t);)+
+    };
+}
+value_types!(; types, callbacks, "rsharp");
+
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn Dispatcher__new() -> repr_c::Box<Dispatcher> {
+    repr_c::Box::new(Dispatcher::new())
+}
+
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn Dispatcher__drop(dispatcher: repr_c::Box<Dispatcher>) {
+    drop(dispatcher)
+}
+
+/// Errors if called from a thread other than the one that created `dispatcher`.
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn Dispatcher__run_pending(dispatcher: &Dispatcher) -> RResult<usize> {
+    dispatcher.run_pending()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Counters {
+        calls: AtomicUsize,
+        sum: AtomicUsize,
+        releases: AtomicUsize,
+    }
+
+    unsafe extern "C" fn call(user_data: usize, arg: u8) {
+        let counters = &*(user_data as *const Counters);
+        counters.calls.fetch_add(1, Ordering::SeqCst);
+        counters.sum.fetch_add(arg.into(), Ordering::SeqCst);
+    }
+
+    unsafe extern "C" fn release(user_data: usize) {
+        let counters = &*(user_data as *const Counters);
+        counters.releases.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn counters() -> &'static Counters {
+        Box::leak(Box::new(Counters {
+            calls: AtomicUsize::new(0),
+            sum: AtomicUsize::new(0),
+            releases: AtomicUsize::new(0),
+        }))
+    }
+
+    fn raw(counters: &'static Counters) -> RCallback<u8> {
+        unsafe { RCallback::new(counters as *const _ as usize, call, release) }
+    }
+
+    #[test]
+    fn test_any_thread() {
+        let counters = counters();
+        let cb = Callback::new(raw(counters), None);
+        cb.call(1);
+        let cb2 = cb.clone();
+        thread::spawn(move || cb2.call(2)).join().unwrap();
+        assert_eq!(counters.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(counters.sum.load(Ordering::SeqCst), 3);
+
+        assert_eq!(counters.releases.load(Ordering::SeqCst), 0);
+        drop(cb);
+        assert_eq!(counters.releases.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_dispatcher() -> Result<()> {
+        let counters = counters();
+        let dispatcher = Dispatcher::new();
+        let cb = Callback::new(raw(counters), Some(&dispatcher));
+
+        // Calls on the dispatcher's thread are immediate
+        cb.call(1);
+        assert_eq!(counters.calls.load(Ordering::SeqCst), 1);
+
+        // Calls from other threads are queued
+        let cb2 = cb.clone();
+        thread::spawn(move || {
+            cb2.call(2);
+            cb2.call(3);
+        })
+        .join()
+        .unwrap();
+        assert_eq!(counters.calls.load(Ordering::SeqCst), 1);
+
+        assert_eq!(dispatcher.run_pending()?, 2);
+        assert_eq!(counters.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(counters.sum.load(Ordering::SeqCst), 6);
+        assert_eq!(dispatcher.run_pending()?, 0);
+
+        drop(cb);
+        assert_eq!(counters.releases.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_pending_on_wrong_thread() {
+        let dispatcher = Dispatcher::new();
+        let err = thread::spawn(move || dispatcher.run_pending().unwrap_err().to_string())
+            .join()
+            .unwrap();
+        assert!(err.contains("thread"));
+    }
+}
@@ -14,6 +14,9 @@
     while_true
 )]
 mod rbox;
+pub mod callback;
+pub mod registry;
+pub mod result;
 mod rvec;
 pub mod string;
 mod types;
@@ -30,16 +33,31 @@ where
     /// Equivalent to `self.expect("unexpected null pointer!")`.
     /// Useful for conversion from Option<&T> to &T in ffi signatures.
     fn expect_not_null(self) -> T;
+
+    /// Like [`Self::expect_not_null()`], but errors instead of panicking. Use
+    /// this in functions that return a [`result::RResult`].
+    fn try_not_null(self) -> Result<T, result::RError>;
 }
 impl<'a, T> OptionExt<&'a T> for Option<&'a T> {
     fn expect_not_null(self) -> &'a T {
         self.expect("unexpected null pointer!")
     }
+
+    fn try_not_null(self) -> Result<&'a T, result::RError> {
+        self.ok_or_else(null_pointer_error)
+    }
 }
 impl<'a, T> OptionExt<&'a mut T> for Option<&'a mut T> {
     fn expect_not_null(self) -> &'a mut T {
         self.expect("unexpected null pointer!")
     }
+
+    fn try_not_null(self) -> Result<&'a mut T, result::RError> {
+        self.ok_or_else(null_pointer_error)
+    }
+}
+fn null_pointer_error() -> result::RError {
+    result::RError::new(result::RErrorKind::NullPointer, "unexpected null pointer!")
 }
 mod private {
     pub trait Sealed {}
@@ -50,6 +68,7 @@ mod private {
 #[safer_ffi::cfg_headers]
 #[test]
 fn generate_headers() -> ::std::io::Result<()> {
+    registry::to_file("rsharp_registry.json")?;
     let builder = ::safer_ffi::headers::builder();
     if ::std::env::var("HEADERS_TO_STDOUT")
         .ok()
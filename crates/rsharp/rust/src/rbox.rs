@@ -7,6 +7,8 @@ use safer_ffi::prelude::*;
 macro_rules! boxes {
     // Base case
     ($path:literal, $t:ty $(,)?) => {
+        crate::__register!(RBox, $path, $t);
+
         paste::paste! {
 
             #[remangle($path)]
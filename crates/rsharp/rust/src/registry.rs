@@ -0,0 +1,99 @@
+//! A registry of every instantiation of [`rvec_fns!`](crate::rvec_fns),
+//! [`callback_fns!`](crate::callback_fns) and `boxes!`, so that `rsharp_codegen` can generate the matching C# classes.
+//!
+//! Entries are collected from every crate that is linked into the binary. Write
+//! them to a file with [`to_file`], typically right next to the C headers:
+//!
+//! ```ignore
+//! #[safer_ffi::cfg_headers]
+//! #[test]
+//! fn generate_headers() -> ::std::io::Result<()> {
+//!     rsharp::registry::to_file("rsharp_registry.json")?;
+//!     // ...
+//! }
+//! ```
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum Kind {
+    RVec,
+    RBox,
+    RCallback,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub kind: Kind,
+    /// The path that the functions were remangled with (e.g.
+    /// `tp_client::contract::properties::channels`).
+    pub path: &'static str,
+    /// The name of the rust type, as written in the macro (e.g. `u8` or
+    /// `Keyframe_U8`).
+    pub ty: &'static str,
+}
+impl Entry {
+    pub const fn new(kind: Kind, path: &'static str, ty: &'static str) -> Self {
+        Self { kind, path, ty }
+    }
+}
+
+inventory::collect!(Entry);
+
+#[doc(hidden)]
+pub use inventory as __inventory;
+
+/// Registers an [`Entry`]. Only intended for use by the macros of this crate.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register {
+    ($kind:ident, $path:literal, $t:ty) => {
+        $crate::registry::__inventory::submit! {
+            #![crate = $crate::registry::__inventory]
+            $crate::registry::Entry::new(
+                $crate::registry::Kind::$kind,
+                $path,
+                ::std::stringify!($t),
+            )
+        }
+    };
+}
+
+/// All registered entries, sorted so that the output is deterministic.
+pub fn entries() -> Vec<&'static Entry> {
+    let mut result: Vec<_> = inventory::iter::<Entry>.into_iter().collect();
+    result.sort_by_key(|e| (e.path, e.ty, e.kind));
+    result
+}
+
+/// Writes all registered entries to `path` as JSON.
+pub fn to_file(path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer_pretty(file, &entries())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_boxes_are_registered() {
+        let entries = entries();
+        let boxes: Vec<_> = entries
+            .iter()
+            .filter(|e| e.kind == Kind::RBox && e.path == "rsharp")
+            .map(|e| e.ty)
+            .collect();
+        assert!(boxes.contains(&"u8"));
+        assert!(boxes.contains(&"f64"));
+        assert!(boxes.contains(&"bool"));
+    }
+
+    #[test]
+    fn test_callbacks_are_registered() {
+        assert!(entries()
+            .iter()
+            .any(|e| e.kind == Kind::RCallback && e.path == "rsharp" && e.ty == "bool"));
+    }
+}
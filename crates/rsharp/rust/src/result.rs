@@ -0,0 +1,252 @@
+//! Errors that cross the FFI boundary, instead of panicking (which aborts the
+//! process when unwinding into C#).
+//!
+//! Fallible functions return an [`RResult<T>`], or an [`RStatus`] if they have
+//! no value. `#[remangle]` detects those return types and runs the body inside
+//! [`catch_unwind`], so the body returns an `eyre::Result` and can use `?`:
+//!
+//! ```ignore
+//! #[remangle("my_crate")]
+//! #[ffi_export]
+//! pub fn Foo__new(x: u8) -> RResult<Option<repr_c::Box<Foo>>> {
+//!     let foo = Foo::try_from(x)?;
+//!     Ok(Some(repr_c::Box::new(foo)))
+//! }
+//! ```
+//!
+//! On the C# side, the error is converted to an exception by `RError.Check`.
+
+#![allow(non_camel_case_types, non_snake_case)]
+
+use crate::remangle;
+
+use safer_ffi::prelude::*;
+use std::any::Any;
+use std::panic::AssertUnwindSafe;
+
+pub use eyre::Report;
+
+/// The kind of an [`RError`], which determines the exception that is thrown
+/// in C#.
+#[remangle("rsharp")]
+#[derive_ReprC]
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RErrorKind {
+    /// Any error without a more specific kind.
+    Other = 0,
+    /// The function panicked.
+    Panic = 1,
+    NullPointer = 2,
+    InvalidArgument = 3,
+    InvalidOperation = 4,
+    IndexOutOfRange = 5,
+    Mutability = 6,
+    Ownership = 7,
+}
+
+#[remangle("rsharp")]
+#[derive_ReprC]
+#[ReprC::opaque]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RError {
+    kind: RErrorKind,
+    message: String,
+}
+impl RError {
+    pub fn new(kind: RErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn kind(&self) -> RErrorKind {
+        self.kind
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = if let Some(s) = payload.downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = payload.downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+        Self::new(RErrorKind::Panic, message)
+    }
+}
+impl std::fmt::Display for RError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}: {}", self.kind, self.message)
+    }
+}
+impl std::error::Error for RError {}
+impl From<Report> for RError {
+    /// Keeps the kind if the report was created from an [`RError`].
+    fn from(report: Report) -> Self {
+        match report.downcast_ref::<RError>() {
+            Some(err) if report.chain().count() == 1 => err.clone(),
+            Some(err) => Self::new(err.kind, format!("{report:#}")),
+            None => Self::new(RErrorKind::Other, format!("{report:#}")),
+        }
+    }
+}
+
+/// The value returned by a fallible function when it errors. Only valid when
+/// the error is null.
+pub trait FfiDefault {
+    fn ffi_default() -> Self;
+}
+macro_rules! ffi_default {
+    ($($t:ty => $value:expr),+ $(,)?) => {
+        $(
+            impl FfiDefault for $t {
+                fn ffi_default() -> Self {
+                    $value
+                }
+            }
+        )+
+    };
+}
+ffi_default!(
+    u8 => 0, u16 => 0, u32 => 0, u64 => 0, usize => 0,
+    i8 => 0, i16 => 0, i32 => 0, i64 => 0, isize => 0,
+    f32 => 0.0, f64 => 0.0, bool => false,
+);
+impl<T> FfiDefault for Option<T> {
+    fn ffi_default() -> Self {
+        None
+    }
+}
+impl<T> FfiDefault for repr_c::Vec<T> {
+    fn ffi_default() -> Self {
+        repr_c::Vec::EMPTY
+    }
+}
+impl<T> FfiDefault for c_slice::Ref<'_, T> {
+    fn ffi_default() -> Self {
+        (&[][..]).into()
+    }
+}
+
+/// The result of a fallible function. `value` is only valid if `error` is null.
+#[remangle("rsharp")]
+#[derive_ReprC]
+#[repr(C)]
+pub struct RResult<T> {
+    pub value: T,
+    pub error: Option<repr_c::Box<RError>>,
+}
+impl<T> RResult<T> {
+    pub fn into_result(self) -> Result<T, RError> {
+        match self.error {
+            Some(err) => Err(*err.into()),
+            None => Ok(self.value),
+        }
+    }
+}
+
+/// The result of a fallible function that has no value. Null on success.
+pub type RStatus = Option<repr_c::Box<RError>>;
+
+/// Implemented by the types that fallible functions can return.
+pub trait FfiResult {
+    type Value;
+
+    fn from_result(result: Result<Self::Value, RError>) -> Self;
+}
+impl<T: FfiDefault> FfiResult for RResult<T> {
+    type Value = T;
+
+    fn from_result(result: Result<T, RError>) -> Self {
+        match result {
+            Ok(value) => Self { value, error: None },
+            Err(err) => Self {
+                value: T::ffi_default(),
+                error: Some(repr_c::Box::new(err)),
+            },
+        }
+    }
+}
+impl FfiResult for RStatus {
+    type Value = ();
+
+    fn from_result(result: Result<(), RError>) -> Self {
+        result.err().map(repr_c::Box::new)
+    }
+}
+
+/// Runs `f`, converting both errors and panics to an [`FfiResult`]. This is
+/// used by `#[remangle]`, and rarely needs to be called directly.
+pub fn catch_unwind<R: FfiResult>(f: impl FnOnce() -> Result<R::Value, Report>) -> R {
+    let result = match std::panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(result) => result.map_err(RError::from),
+        Err(payload) => Err(RError::from_panic(payload)),
+    };
+    R::from_result(result)
+}
+
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn RError__kind(err: &RError) -> RErrorKind {
+    err.kind
+}
+
+/// Borrows the UTF-8 message. Only valid for as long as `err` is.
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn RError__message(err: &RError) -> c_slice::Ref<'_, u8> {
+    err.message.as_bytes().into()
+}
+
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn RError__drop(err: repr_c::Box<RError>) {
+    drop(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::{eyre, WrapErr};
+
+    #[test]
+    fn test_catch_unwind() {
+        let ok: RResult<u8> = catch_unwind(|| Ok(1));
+        assert_eq!(ok.into_result(), Ok(1));
+
+        let err: RResult<u8> = catch_unwind(|| Err(eyre!("oops")));
+        let err = err.into_result().unwrap_err();
+        assert_eq!(err.kind(), RErrorKind::Other);
+        assert_eq!(err.message(), "oops");
+
+        let panicked: RResult<Option<repr_c::Box<u8>>> = catch_unwind(|| panic!("boom"));
+        let err = panicked.into_result().unwrap_err();
+        assert_eq!(err.kind(), RErrorKind::Panic);
+        assert_eq!(err.message(), "boom");
+
+        let status: RStatus = catch_unwind(|| Ok(()));
+        assert!(status.is_none());
+    }
+
+    #[test]
+    fn test_kind_is_kept() {
+        let err: RResult<bool> =
+            catch_unwind(|| Err(RError::new(RErrorKind::IndexOutOfRange, "too big").into()));
+        let err = err.into_result().unwrap_err();
+        assert_eq!(err.kind(), RErrorKind::IndexOutOfRange);
+        assert_eq!(err.message(), "too big");
+
+        let err: RStatus = catch_unwind(|| {
+            Err(RError::new(RErrorKind::Ownership, "not owned")).wrap_err("context")
+        });
+        let err = *err.unwrap().into();
+        assert_eq!(err.kind(), RErrorKind::Ownership);
+        assert_eq!(err.message(), "context: not owned");
+    }
+}
@@ -1,6 +1,8 @@
 #[macro_export]
 macro_rules! rvec_fns {
     ($path:literal, $t:ty) => {
+        ::rsharp::__register!(RVec, $path, $t);
+
         paste::paste! {
             #[::rsharp::remangle($path)]
             #[::safer_ffi::ffi_export]
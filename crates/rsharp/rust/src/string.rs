@@ -1,10 +1,24 @@
+//! Strings that are owned by rust.
+//!
+//! C# can read them in three ways, from most to least expensive:
+//! - Decoding the UTF-8 bytes, which are borrowed with [`String__value`].
+//! - Encoding directly into a UTF-16 buffer that C# owns, with
+//!   [`String__write_utf16`].
+//! - Interning them with [`intern`], so that C# only decodes each distinct
+//!   string once. This is meant for strings with few distinct values, like
+//!   enum-like states, since interned strings are never freed.
+
 #![allow(non_snake_case)]
 
 use crate::remangle;
+use crate::result::{RError, RErrorKind, RResult};
 
 use derive_more::{From, Into};
+use lazy_static::lazy_static;
 use ref_cast::RefCast;
 use safer_ffi::prelude::*;
+use std::collections::HashMap;
+use std::sync::RwLock;
 
 #[derive_ReprC]
 #[ReprC::opaque]
@@ -14,12 +28,61 @@ pub struct String {
     pub inner: std::string::String,
 }
 
+/// Borrows the UTF-8 bytes. Only valid for as long as `s` is, and until `s`
+/// is mutated.
 #[remangle("rsharp")]
 #[ffi_export]
 pub fn String__value(s: &String) -> c_slice::Ref<u8> {
     s.inner.as_bytes().into()
 }
 
+/// The number of UTF-16 code units that `s` encodes to.
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn String__utf16_len(s: &String) -> usize {
+    s.inner.encode_utf16().count()
+}
+
+/// Encodes `s` as UTF-16 into `out`, and returns the number of code units
+/// written. Errors if `out` is shorter than [`String__utf16_len`].
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn String__write_utf16(s: &String, mut out: c_slice::Mut<u16>) -> RResult<usize> {
+    let mut len = 0;
+    for unit in s.inner.encode_utf16() {
+        let slot = out.get_mut(len).ok_or_else(|| {
+            RError::new(
+                RErrorKind::InvalidArgument,
+                "`out` is too short for the string",
+            )
+        })?;
+        *slot = unit;
+        len += 1;
+    }
+    Ok(len)
+}
+
+/// Interns `s`, and returns its id. See [`intern`].
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn String__intern(s: &String) -> u32 {
+    intern(&s.inner)
+}
+
+/// Borrows the UTF-8 bytes of the interned string with id `id`, which are
+/// valid forever.
+#[remangle("rsharp")]
+#[ffi_export]
+pub fn String__interned(id: u32) -> RResult<c_slice::Ref<'static, u8>> {
+    let s = resolve(id).ok_or_else(|| {
+        RError::new(
+            RErrorKind::InvalidArgument,
+            format!("{id} is not an interned string"),
+        )
+    })?;
+    Ok(s.as_bytes().into())
+}
+
 #[remangle("rsharp")]
 #[ffi_export]
 pub fn String__drop(s: repr_c::Box<String>) {
@@ -48,6 +111,42 @@ pub fn String__copy_utf16(utf16: c_slice::Ref<u16>) -> repr_c::Box<String> {
     )
 }
 
+// ---- Interning ----
+
+#[derive(Default)]
+struct Interner {
+    ids: HashMap<&'static str, u32>,
+    strings: Vec<&'static str>,
+}
+
+lazy_static! {
+    static ref INTERNER: RwLock<Interner> = RwLock::default();
+}
+
+/// Returns the id of `s`, which is the same for every equal string. Interned
+/// strings are never freed, so only intern strings with few distinct values.
+pub fn intern(s: &str) -> u32 {
+    if let Some(id) = INTERNER.read().unwrap().ids.get(s) {
+        return *id;
+    }
+    let mut interner = INTERNER.write().unwrap();
+    // Another thread may have interned it in the meantime
+    if let Some(id) = interner.ids.get(s) {
+        return *id;
+    }
+    let id = u32::try_from(interner.strings.len()).expect("Too many interned strings");
+    let s: &'static str = Box::leak(s.into());
+    interner.strings.push(s);
+    interner.ids.insert(s, id);
+    id
+}
+
+/// The string that was interned with id `id`.
+pub fn resolve(id: u32) -> Option<&'static str> {
+    let index = usize::try_from(id).ok()?;
+    INTERNER.read().unwrap().strings.get(index).copied()
+}
+
 // ---- From trait impls ----
 
 impl<'a> From<&'a std::string::String> for &'a String {
@@ -70,3 +169,44 @@ impl<'a> From<&'a mut String> for &'a mut std::string::String {
         &mut from.inner
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_utf16() {
+        let s = String::from("h\u{e9}llo \u{1f600}".to_string());
+        let expected: Vec<u16> = s.inner.encode_utf16().collect();
+        assert_eq!(String__utf16_len(&s), expected.len());
+
+        let mut out = vec![0u16; expected.len()];
+        let len = String__write_utf16(&s, out.as_mut_slice().into())
+            .into_result()
+            .unwrap();
+        assert_eq!(len, expected.len());
+        assert_eq!(out, expected);
+
+        let mut short = vec![0u16; expected.len() - 1];
+        let err = String__write_utf16(&s, short.as_mut_slice().into())
+            .into_result()
+            .unwrap_err();
+        assert_eq!(err.kind(), RErrorKind::InvalidArgument);
+    }
+
+    #[test]
+    fn test_intern() {
+        let a = intern("test_intern_a");
+        let b = intern("test_intern_b");
+        assert_ne!(a, b);
+        assert_eq!(intern("test_intern_a"), a);
+        assert_eq!(resolve(a), Some("test_intern_a"));
+        assert_eq!(resolve(u32::MAX), None);
+
+        let s = String::from("test_intern_b".to_string());
+        assert_eq!(String__intern(&s), b);
+        let bytes = String__interned(b).into_result().unwrap();
+        assert_eq!(bytes.as_slice(), b"test_intern_b");
+        assert!(String__interned(u32::MAX).into_result().is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use tp_client::contract::{states, Contract, ContractDataHandle, ContractId};
+use tp_client::spatial::PositionStates;
 
 #[derive(Debug)]
 pub struct Circle {
@@ -42,4 +43,8 @@ impl Contract for Circle {
     fn handle(&self) -> ContractDataHandle {
         self.handle
     }
+
+    fn position(&self) -> Option<PositionStates> {
+        Some(PositionStates::new_2d(self.states.x(), self.states.y()))
+    }
 }